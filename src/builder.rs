@@ -1,14 +1,36 @@
+#[cfg(feature = "json")]
+use crate::wiremock::load_stubs;
+#[cfg(feature = "recording")]
+use crate::Fixture;
+#[cfg(feature = "gzip")]
+use crate::Gzip;
+#[cfg(feature = "sigv4")]
+use crate::SigV4Credentials;
 use crate::{
-    case::Case,
+    case::{AddCase, Case, CaseOptions},
+    clock::SharedClock,
     connector::InnerConnector,
-    handler::{DefaultWith, Returning, With, WithHandler},
-    Connector, Error, Level, Report,
+    diagnostics::Diagnostics,
+    handler::{DefaultWith, Multipart, Returning, ReturningCtx, With, WithAsync, WithHandler},
+    state::ReturningState,
+    Backoff, BodyStream, CaseContext, Chunked, Clock, ColorChoice, Connector, Error,
+    ExpectContinue, FaultProfile, Forward, HostScope, IntoResponse, IntoResponseFuture, Level,
+    MockResponse, OnUnmatched, Report, Scenario, Sequence, SoapFault, Sse, SseEvent, State, Tls,
+    Upgrade, UriNormalization, UriParts,
 };
+#[cfg(feature = "json")]
+use crate::{handler::JsonDe, ArrayMatching, Json, JsonApi};
 use hyper::{
+    body::Bytes,
+    client::connect::{Connect, Connected},
+    header::LOCATION,
     http::{HeaderName, HeaderValue},
-    Method, Request, Uri,
+    Method, Request, Response, StatusCode, Uri,
 };
-use std::error::Error as StdError;
+use std::{
+    convert::Infallible, error::Error as StdError, future::Future, io, sync::Mutex, time::Duration,
+};
+use tokio::io::DuplexStream;
 
 /// Builder for [`Connector`]
 #[derive(Default)]
@@ -27,471 +49,3122 @@ impl Builder {
         self.inner.level = level;
     }
 
-    /// Create a new expected case
-    pub fn expect(&mut self) -> CaseBuilder<'_> {
-        CaseBuilder::new(&mut self.inner)
+    /// Write diagnostic reports into `writer` instead of stdout
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.diagnostics_writer(std::io::stderr());
+    /// ```
+    pub fn diagnostics_writer<W>(&mut self, writer: W)
+    where
+        W: io::Write + Send + 'static,
+    {
+        self.inner.diagnostics = Diagnostics::Writer(Mutex::new(Box::new(writer)));
     }
-}
 
-/// Builder for specific mock cases
-///
-/// ## Example
-///
-/// ```rust
-/// # use mock_http_connector::Connector;
-/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let mut builder = Connector::builder();
-/// let mut case_builder = builder.expect();
-///
-/// case_builder
-///     .with_uri("https://test.example/some/path")
-///     .times(3)
-///     .returning("Some response")?;
-/// # Ok(())
-/// # }
-/// ```
-#[must_use = "case builders do nothing until you call the `returning` method"]
-pub struct CaseBuilder<'c, W = DefaultWith> {
-    connector: &'c mut InnerConnector,
-    with: Result<W, Error>,
-    count: Option<usize>,
-}
+    /// Emit diagnostic reports as `tracing` events instead of printing them to stdout
+    ///
+    /// Reports are emitted as `WARN` events, with the `mock_http_connector` target.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.diagnostics_tracing();
+    /// ```
+    #[cfg(feature = "tracing")]
+    pub fn diagnostics_tracing(&mut self) {
+        self.inner.diagnostics = Diagnostics::Tracing;
+    }
 
-impl<'c> CaseBuilder<'c> {
-    pub(crate) fn new(connector: &'c mut InnerConnector) -> Self {
-        Self {
-            connector,
-            with: Ok(DefaultWith),
-            count: None,
-        }
+    /// Set whether diagnostic reports contain colors
+    ///
+    /// See [`ColorChoice`] for the possible values.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{ColorChoice, Connector};
+    /// let mut builder = Connector::builder();
+    /// builder.color(ColorChoice::Never);
+    /// ```
+    pub fn color(&mut self, color: ColorChoice) {
+        self.inner.color = color;
     }
 
-    /// Pass a function or closure to check if the incoming payload matches this mock case
+    /// Set the simulated TLS behavior for `https` URIs
     ///
-    /// If you only need to validate the [`Uri`], [`Method`], headers, or incoming payload, you
-    /// should use one of the other `with_*` methods. You also cannot combine this validator with
-    /// the other `with` methods.
+    /// See [`Tls`] for the possible behaviors.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::{Response, Request};
-    /// # use mock_http_connector::{Connector, Error};
-    /// # use std::convert::Infallible;
-    /// # || {
+    /// # use mock_http_connector::{Connector, Tls};
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with(|req: &Request<String>| Ok::<_, Infallible>(req.body().contains("hello")))
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
-    /// # };
+    /// builder.tls(Tls::Fail("UnknownIssuer".to_string()));
     /// ```
-    pub fn with<W, E, R>(self, with: W) -> CaseBuilder<'c, W>
-    where
-        for<'r> W: Fn(&'r Request<String>) -> Result<R, E>,
-        R: Into<Report>,
-        E: StdError + Send + Sync + 'static,
-    {
-        CaseBuilder {
-            connector: self.connector,
-            with: Ok(with),
-            count: self.count,
-        }
+    pub fn tls(&mut self, tls: Tls) {
+        self.inner.tls = tls;
     }
 
-    /// Match requests with the specified [`Uri`]
+    /// Set the behavior of the connector for requests with an `expect: 100-continue` header
+    ///
+    /// See [`ExpectContinue`] for the possible behaviors.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::Response;
-    /// # use mock_http_connector::{Connector, Error};
-    /// # || {
+    /// # use hyper::StatusCode;
+    /// # use mock_http_connector::{Connector, ExpectContinue};
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with_uri("https://example.test/hello")
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
-    /// # };
+    /// builder.expect_continue(ExpectContinue::Reject(StatusCode::EXPECTATION_FAILED));
     /// ```
+    pub fn expect_continue(&mut self, expect_continue: ExpectContinue) {
+        self.inner.expect_continue = expect_continue;
+    }
+
+    /// Close connections after they served `count` requests
     ///
-    /// ## Remark
+    /// The last response on each connection has a `connection: close` header, so the client
+    /// doesn't reuse the connection for further requests. This can be used to test connection
+    /// pooling behaviors, such as pool eviction.
     ///
-    /// You can combine this with other validators, such as `with_header`, but not with `with`.
-    pub fn with_uri<U>(self, uri: U) -> CaseBuilder<'c, WithHandler>
-    where
-        U: TryInto<Uri>,
-        U::Error: Into<hyper::http::Error>,
-    {
-        CaseBuilder {
-            connector: self.connector,
-            with: WithHandler::default().with_uri(uri),
-            count: self.count,
-        }
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.max_requests_per_connection(1);
+    /// ```
+    pub fn max_requests_per_connection(&mut self, count: usize) {
+        self.inner.max_requests = Some(count);
     }
 
-    /// Match requests with the specified [`Method`]
+    /// Pass request payloads to [`BodyStream`] matchers as they arrive, instead of buffering them
+    ///
+    /// This keeps memory usage low for very large uploads. Payloads are only passed to the
+    /// matchers added with [`CaseBuilder::with_body_stream`], and are then discarded: other
+    /// matchers and the `returning` handlers receive requests with an empty body.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::Response;
-    /// # use mock_http_connector::{Connector, Error};
-    /// # || {
+    /// # use mock_http_connector::Connector;
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with_method("GET")
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
-    /// # };
+    /// builder.stream_request_bodies();
     /// ```
     ///
     /// ## Remark
     ///
-    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
-    pub fn with_method<M>(self, method: M) -> CaseBuilder<'c, WithHandler>
-    where
-        M: TryInto<Method>,
-        M::Error: Into<hyper::http::Error>,
-    {
-        CaseBuilder {
-            connector: self.connector,
-            with: WithHandler::default().with_method(method),
-            count: self.count,
-        }
+    /// Payloads with chunked transfer-encoding are still buffered, so they can be decoded. Payloads
+    /// are streamed as sent on the wire, without decompressing them.
+    pub fn stream_request_bodies(&mut self) {
+        self.inner.stream_bodies = true;
     }
 
-    /// Match requests that contains the specific header
+    /// Check that all mock cases were called the right number of times when the connector is
+    /// dropped
     ///
-    /// An HTTP request can contain multiple headers with the same key, but different values. This
-    /// checks that there is at least one value matching. If you want to ensure that there is only
-    /// one entry for this key, consider using `with_header_once`.
+    /// When enabled, dropping the last clone of the [`Connector`] runs [`Connector::checkpoint`],
+    /// so tests that forget to call it still fail on unmet expectations.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::Response;
-    /// # use mock_http_connector::{Connector, Error};
-    /// # || {
+    /// # use mock_http_connector::Connector;
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with_header("content-type", "application/json")
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
-    /// # };
+    /// builder.verify_on_drop(true);
     /// ```
     ///
-    /// ## Remark
+    /// ## Panics
     ///
-    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
-    pub fn with_header<K, V>(self, key: K, value: V) -> CaseBuilder<'c, WithHandler>
-    where
-        K: TryInto<HeaderName>,
-        K::Error: Into<hyper::http::Error>,
-        V: TryInto<HeaderValue>,
-        V::Error: Into<hyper::http::Error>,
-    {
-        CaseBuilder {
-            connector: self.connector,
-            with: WithHandler::default().with_header(key, value),
-            count: self.count,
-        }
+    /// Dropping the last clone of the connector panics if the checkpoint fails, unless the
+    /// current thread is already panicking.
+    pub fn verify_on_drop(&mut self, enabled: bool) {
+        self.inner.verify_on_drop = enabled;
     }
 
-    /// Match requests that contains the specific header
+    /// Add an `x-mock-case` header to the responses of mock cases
     ///
-    /// An HTTP request can contain multiple headers with the same key, but different values. This
-    /// checks that there is only one value for the given header.
+    /// The header contains the name of the case that returned the response, set with
+    /// [`CaseBuilder::name`], or its position in [`Connector::report`] if it doesn't have a
+    /// name. This makes it easier to find which case produced an unexpected response.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::Response;
-    /// # use mock_http_connector::{Connector, Error};
-    /// # || {
+    /// # use mock_http_connector::Connector;
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with_header_once("content-type", "application/json")
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
-    /// # };
+    /// builder.case_header(true);
     /// ```
-    ///
-    /// ## Remark
-    ///
-    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
-    pub fn with_header_once<K, V>(self, key: K, value: V) -> CaseBuilder<'c, WithHandler>
-    where
-        K: TryInto<HeaderName>,
-        K::Error: Into<hyper::http::Error>,
-        V: TryInto<HeaderValue>,
-        V::Error: Into<hyper::http::Error>,
-    {
-        CaseBuilder {
-            connector: self.connector,
-            with: WithHandler::default().with_header_once(key, value),
-            count: self.count,
-        }
+    pub fn case_header(&mut self, enabled: bool) {
+        self.inner.case_header = enabled;
     }
 
-    /// Match requests that contains the specific header
+    /// Reject requests to mock cases that already received all their expected calls
     ///
-    /// An HTTP request can contain multiple headers with the same key, but different values. This
-    /// checks that all entries correspond to the given set of values.
+    /// By default, [`CaseBuilder::times`] is only checked by [`Connector::checkpoint`]. In strict
+    /// mode, extra requests don't match the case, and are handled like any other request that
+    /// doesn't match, so tests fail at the offending request. This applies to all cases, see
+    /// [`CaseBuilder::strict`] to enable it for a single case.
     ///
-    /// If you want to check that a header name has multiple values, but do not mind if there are
-    /// additional values, you can use `with_header` multiple times instead.
+    /// ## Example
     ///
-    /// If you want to ensure that a header name only has one value, you can use `with_header_once`
-    /// instead.
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.strict(true);
+    /// ```
+    pub fn strict(&mut self, enabled: bool) {
+        self.inner.strict = enabled;
+    }
+
+    /// Inject random faults into the responses of all mock cases
+    ///
+    /// Cases with their own profile, set with [`CaseBuilder::faults`], use it instead. See
+    /// [`FaultProfile`] for the available faults.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::Response;
-    /// # use mock_http_connector::{Connector, Error};
-    /// # || {
+    /// # use hyper::StatusCode;
+    /// # use mock_http_connector::{Connector, FaultProfile};
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with_header_all("content-type", ["application/json", "text/html"])
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
-    /// # };
+    /// builder.faults(FaultProfile::new(42).status(0.1, StatusCode::BAD_GATEWAY));
     /// ```
+    pub fn faults(&mut self, faults: FaultProfile) {
+        self.inner.faults = Some(faults);
+    }
+
+    /// Set the [`Clock`] used for delays and time windows
     ///
-    /// ## Remark
+    /// By default, the connector uses [`crate::TokioClock`], which follows `tokio::time::pause`. See
+    /// [`Clock`] to inject a custom clock in tests.
     ///
-    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
-    pub fn with_header_all<K, IV, V>(self, key: K, values: IV) -> CaseBuilder<'c, WithHandler>
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, TokioClock};
+    /// let mut builder = Connector::builder();
+    /// builder.clock(TokioClock);
+    /// ```
+    pub fn clock<C>(&mut self, clock: C)
     where
-        K: TryInto<HeaderName>,
-        K::Error: Into<hyper::http::Error>,
-        IV: IntoIterator<Item = V>,
-        V: TryInto<HeaderValue>,
-        V::Error: Into<hyper::http::Error>,
+        C: Clock + 'static,
     {
-        CaseBuilder {
-            connector: self.connector,
-            with: WithHandler::default().with_header_all(key, values),
-            count: self.count,
-        }
+        self.inner.clock = SharedClock::new(clock);
     }
 
-    /// Match requests that contains the provided payload
+    /// Collect metrics about the requests received by the mock cases
+    ///
+    /// The metrics include the number of requests, the time spent matching them, and the size of
+    /// their bodies for each case, and can be retrieved with [`Connector::metrics`]. This can be
+    /// used to check how a client batches its requests.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::Response;
-    /// # use mock_http_connector::{Connector, Error};
-    /// # || {
+    /// # use mock_http_connector::Connector;
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with_body("some body")
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
-    /// # };
+    /// builder.metrics(true);
     /// ```
+    pub fn metrics(&mut self, enabled: bool) {
+        self.inner.metrics = enabled;
+    }
+
+    /// Call `hook` for every request received by the connector, before looking for a mock case
     ///
-    /// ## Remark
+    /// This runs regardless of which case matches the request, or if none does, and can be used
+    /// to log or collect requests. Hooks run in the order they were added.
     ///
-    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    /// ## Example
     ///
-    /// A mock case only supports `with_body`, `with_json`, or `with_json_value`, but not multiple
-    /// ones at the same time.
-    pub fn with_body<B>(self, body: B) -> CaseBuilder<'c, WithHandler>
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.on_request(|req: &Request<Bytes>| println!("{} {}", req.method(), req.uri()));
+    /// ```
+    pub fn on_request<F>(&mut self, hook: F)
     where
-        B: ToString,
+        F: Fn(&Request<Bytes>) + Send + Sync + 'static,
     {
-        CaseBuilder {
-            connector: self.connector,
-            with: Ok(WithHandler::default().with_body(body)),
-            count: self.count,
-        }
+        self.inner.on_request.push(Box::new(hook));
     }
 
-    /// Match requests with a body that exactly matches the provided JSON payload
+    /// Call `hook` for every response returned by the connector
+    ///
+    /// The hook receives the method, URI, version, and headers of the request, and can modify
+    /// the response, for example to add headers common to all responses. This runs for the
+    /// responses of all mock cases, as well as the fallback and [`OnUnmatched::Status`]
+    /// responses, but not for requests that fail. Hooks run in the order they were added.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use hyper::Response;
-    /// # use mock_http_connector::{Connector, Error};
-    /// # || {
+    /// # use hyper::{body::Bytes, Request, Response};
+    /// # use mock_http_connector::Connector;
+    /// // Echo the request ID in all responses
     /// let mut builder = Connector::builder();
-    /// builder
-    ///     .expect()
-    ///     .with_json(serde_json::json!({"status": "OK"}))
-    ///     .returning("OK")?;
-    /// # Ok::<_, Error>(())
+    /// builder.on_response(|req: &Request<()>, res: &mut Response<Bytes>| {
+    ///     if let Some(id) = req.headers().get("x-request-id") {
+    ///         res.headers_mut().insert("x-request-id", id.clone());
+    ///     }
+    /// });
+    /// ```
+    pub fn on_response<F>(&mut self, hook: F)
+    where
+        F: Fn(&Request<()>, &mut Response<Bytes>) + Send + Sync + 'static,
+    {
+        self.inner.on_response.push(Box::new(hook));
+    }
+
+    /// Call `hook` to customize the connection metadata of every new connection
+    ///
+    /// The hook receives the URI of the connection and its default metadata, and returns the
+    /// metadata that the client sees, for example to mark the connection as proxied or to attach
+    /// extra information with [`Connected::extra`]. Hooks run in the order they were added.
+    ///
+    /// The metadata is set when the connection is opened, before any request is sent, so it
+    /// applies to all the requests on that connection, regardless of which mock case they match.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{client::connect::Connected, Uri};
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.on_connect(|_uri: &Uri, connected: Connected| connected.proxy(true));
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// The connector only speaks HTTP/1.1, so marking a connection with
+    /// [`Connected::negotiated_h2`] makes requests sent through [`hyper::Client`] fail.
+    pub fn on_connect<F>(&mut self, hook: F)
+    where
+        F: Fn(&Uri, Connected) -> Connected + Send + Sync + 'static,
+    {
+        self.inner.on_connect.push(Box::new(hook));
+    }
+
+    /// Wait for `delay` before establishing each connection
+    ///
+    /// Connecting through the connector only completes after the delay, which can be used to
+    /// test connect timeouts and connection pool warm-up. The delay uses the clock of the
+    /// connector, see [`Builder::clock`]. Use [`Builder::connect_delay_for`] to set a different
+    /// delay for some hosts.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// # use std::time::Duration;
+    /// let mut builder = Connector::builder();
+    /// builder.connect_delay(Duration::from_millis(200));
+    /// ```
+    pub fn connect_delay(&mut self, delay: Duration) {
+        self.inner.connect_delay = Some(delay);
+    }
+
+    /// Wait for `delay` before establishing each connection to `host`
+    ///
+    /// This overrides [`Builder::connect_delay`] for that host. Hosts are compared
+    /// case-insensitively.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// # use std::time::Duration;
+    /// let mut builder = Connector::builder();
+    /// builder.connect_delay(Duration::from_millis(10));
+    /// builder.connect_delay_for("slow.example", Duration::from_secs(5));
+    /// ```
+    pub fn connect_delay_for<H>(&mut self, host: H, delay: Duration)
+    where
+        H: Into<String>,
+    {
+        self.inner.connect_delays.push((host.into(), delay));
+    }
+
+    /// Refuse connections to `authority`, as if the server was down
+    ///
+    /// Connecting to this authority returns an [`io::ErrorKind::ConnectionRefused`] error,
+    /// before any request is sent, so the client reports a connect error. This can be used to
+    /// test connection retries and failover separately from HTTP errors.
+    ///
+    /// The authority is compared case-insensitively with the host of the URI, or with both its
+    /// host and port if `authority` contains a port.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.refuse_connection_to("api.down.example");
+    /// builder.refuse_connection_to("api.example:8443");
+    /// ```
+    pub fn refuse_connection_to<A>(&mut self, authority: A)
+    where
+        A: Into<String>,
+    {
+        self.inner.refused.push(authority.into());
+    }
+
+    /// Refuse connections to hosts that no mock case can handle
+    ///
+    /// This is only useful when all mock cases are restricted to a host with
+    /// [`Builder::for_host`], as other cases can handle requests sent to any host. Connections to
+    /// other hosts fail as with [`Builder::refuse_connection_to`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.refuse_unexpected_hosts(true);
+    /// builder
+    ///     .for_host("primary.example")
+    ///     .expect()
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn refuse_unexpected_hosts(&mut self, enabled: bool) {
+        self.inner.refuse_unexpected_hosts = enabled;
+    }
+
+    /// Set the behavior of the connector for requests that don't match any mock case
+    ///
+    /// See [`OnUnmatched`] for the possible behaviors.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, OnUnmatched};
+    /// # use hyper::StatusCode;
+    /// let mut builder = Connector::builder();
+    /// builder.on_unmatched(OnUnmatched::Status(StatusCode::NOT_IMPLEMENTED));
+    /// ```
+    pub fn on_unmatched(&mut self, on_unmatched: OnUnmatched) {
+        self.inner.on_unmatched = on_unmatched;
+    }
+
+    /// Record the request/response pairs for matched requests
+    ///
+    /// The recorded pairs can be retrieved with [`Connector::recording`] and saved into a fixture
+    /// file with [`Fixture::save`].
+    #[cfg(feature = "recording")]
+    pub fn record(&mut self) {
+        self.inner.recording = Some(Default::default());
+    }
+
+    /// Load the request/response pairs from a fixture file as mock cases
+    ///
+    /// Each entry becomes a case matching the recorded method, URI, and payload, and returning the
+    /// recorded response. See [`Fixture::load`] for the supported formats.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.load_fixture("tests/fixtures/dynamodb.json")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    #[cfg(feature = "recording")]
+    pub fn load_fixture<P>(&mut self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        for entry in Fixture::load(path)?.entries {
+            let request = entry.request;
+            self.expect()
+                .with_method(request.method.as_str())
+                .with_uri(request.uri.as_str())
+                .with_body(request.body)
+                .returning(entry.response)?;
+        }
+
+        Ok(())
+    }
+
+    /// Load WireMock stub mappings as mock cases
+    ///
+    /// `path` can either be a single JSON file, or a directory, in which case all the `.json`
+    /// files it contains are loaded in alphabetical order. Each file can contain a single stub
+    /// mapping, or an object with a `mappings` array.
+    ///
+    /// The following parts of the mappings are supported:
+    ///
+    /// * Request: `method`, `url`, `urlPath`, `queryParameters` and `headers` (with `equalTo`,
+    ///   `caseInsensitive`, `contains` and `absent`), and `bodyPatterns` (with `equalTo`,
+    ///   `equalToJson`, `ignoreExtraElements` and `contains`).
+    /// * Response: `status`, `headers`, `body` and `jsonBody`.
+    /// * Stub: `name` and `priority`.
+    ///
+    /// Loading fails on request matchers that are not supported, such as regular expressions, as
+    /// ignoring them would make the cases match more requests than expected. Other response
+    /// options, such as delays, are ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.load_wiremock_stubs("tests/wiremock/mappings")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn load_wiremock_stubs<P>(&mut self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        for stub in load_stubs(path.as_ref())? {
+            let mut case = self.expect().with_matcher(stub.with);
+            if let Some(name) = stub.name {
+                case = case.name(name);
+            }
+            if let Some(priority) = stub.priority {
+                case = case.priority(priority);
+            }
+            case.returning(stub.response)?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a new expected case
+    pub fn expect(&mut self) -> CaseBuilder<'_> {
+        CaseBuilder::new(&self.inner)
+    }
+
+    /// Create a [`HostScope`] to group mock cases for a single host
+    ///
+    /// See [`HostScope`] for more information.
+    pub fn for_host<H>(&mut self, host: H) -> HostScope<'_>
+    where
+        H: Into<String>,
+    {
+        HostScope::new(&self.inner, host.into())
+    }
+
+    /// Create mock cases for a chain of redirects, ending with `returning`
+    ///
+    /// Each URI in `uris` gets a case that redirects to the next one with a `302 Found`
+    /// response, see [`CaseBuilder::returning_redirect`], and the case for the last URI returns
+    /// `returning`. This can be used to test clients that follow redirects. If `uris` is empty, no
+    /// case is created.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.redirect_chain(
+    ///     [
+    ///         "http://test.example/a",
+    ///         "http://test.example/b",
+    ///         "http://test.example/c",
+    ///     ],
+    ///     "final response",
+    /// )?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// This will fail if any of the URIs is invalid.
+    pub fn redirect_chain<I, U, R>(&mut self, uris: I, returning: R) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: AsRef<str>,
+        R: Returning + 'static,
+    {
+        let uris = uris
+            .into_iter()
+            .map(|uri| uri.as_ref().to_string())
+            .collect::<Vec<_>>();
+        let Some((last, redirects)) = uris.split_last() else {
+            return Ok(());
+        };
+
+        for (uri, next) in redirects.iter().zip(&uris[1..]) {
+            self.expect()
+                .with_uri(uri.as_str())
+                .returning_redirect(next.as_str())?;
+        }
+        self.expect().with_uri(last.as_str()).returning(returning)
+    }
+
+    /// Create a [`JsonApi`] to group mock cases for a JSON API at `base_url`
+    ///
+    /// This also creates a case returning `404 Not Found` for requests sent to the host of the
+    /// API that don't match any of its cases. See [`JsonApi`] for more information.
+    ///
+    /// ## Errors
+    ///
+    /// This will fail if `base_url` is not a valid URI, or doesn't have a host.
+    #[cfg(feature = "json")]
+    pub fn expect_json_api<U>(&mut self, base_url: U) -> Result<JsonApi<'_>, Error>
+    where
+        U: TryInto<Uri>,
+        U::Error: Into<hyper::http::Error>,
+    {
+        JsonApi::new(&self.inner, base_url.try_into().map_err(Into::into)?)
+    }
+
+    /// Set a fallback response for requests that don't match any mock case
+    ///
+    /// Without a fallback, the connector returns an error for those requests. The fallback is not
+    /// a mock case: it is only used when no other case matches, and it is never taken into account
+    /// by `checkpoint`.
+    ///
+    /// See the documentation for [`Returning`] to see the full list of what is accepted by this
+    /// method.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.fallback((404, "NOT FOUND"));
+    /// builder
+    ///     .expect()
+    ///     .with_uri("https://test.example/hello")
+    ///     .returning("hello")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn fallback<R>(&mut self, returning: R)
+    where
+        R: Returning + 'static,
+    {
+        self.inner.fallback = Some(Box::new(returning));
+    }
+}
+
+/// Builder for specific mock cases
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::Connector;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut builder = Connector::builder();
+/// let mut case_builder = builder.expect();
+///
+/// case_builder
+///     .with_uri("https://test.example/some/path")
+///     .times(3)
+///     .returning("Some response")?;
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "case builders do nothing until you call the `returning` method"]
+pub struct CaseBuilder<'c, W = DefaultWith> {
+    connector: &'c dyn AddCase,
+    with: Result<W, Error>,
+    options: CaseOptions,
+}
+
+impl<'c> CaseBuilder<'c> {
+    pub(crate) fn new(connector: &'c dyn AddCase) -> Self {
+        Self {
+            connector,
+            with: Ok(DefaultWith),
+            options: CaseOptions::default(),
+        }
+    }
+
+    /// Pass a function or closure to check if the incoming payload matches this mock case
+    ///
+    /// If you only need to validate the [`Uri`], [`Method`], headers, or incoming payload, you
+    /// should use one of the other `with_*` methods. You also cannot combine this validator with
+    /// the other `with` methods.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::{Connector, Error};
+    /// # use std::convert::Infallible;
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with(|req: &Request<Bytes>| Ok::<_, Infallible>(req.body().starts_with(b"hello")))
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn with<W, E, R>(self, with: W) -> CaseBuilder<'c, W>
+    where
+        for<'r> W: Fn(&'r Request<Bytes>) -> Result<R, E>,
+        R: Into<Report>,
+        E: StdError + Send + Sync + 'static,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(with),
+            options: self.options,
+        }
+    }
+
+    /// Pass a [`With`] matcher to check if the incoming payload matches this mock case
+    ///
+    /// This can be used with matchers built from [`crate::WithHandler`] or combinators such as
+    /// [`crate::any_of`], [`crate::all_of`], or [`crate::not`]. You cannot combine this validator
+    /// with the other `with` methods.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{any_of, Connector, Error, WithHandler};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_matcher(any_of([
+    ///         WithHandler::default().with_uri("https://test.example/a")?,
+    ///         WithHandler::default().with_uri("https://test.example/b")?,
+    ///     ]))
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn with_matcher<W>(self, matcher: W) -> CaseBuilder<'c, W>
+    where
+        W: With,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(matcher),
+            options: self.options,
+        }
+    }
+
+    /// Pass a function or closure that returns a future to check if the incoming payload matches
+    /// this mock case
+    ///
+    /// This works like [`CaseBuilder::with`], but can await asynchronous state, such as a
+    /// `tokio::sync::RwLock`. The closure receives a copy of the incoming request. You cannot
+    /// combine this validator with the other `with` methods.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::{Connector, Error};
+    /// # use std::{convert::Infallible, sync::Arc};
+    /// # use tokio::sync::RwLock;
+    /// # || {
+    /// let allowed = Arc::new(RwLock::new(vec!["/a".to_string()]));
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_async(move |req: Request<Bytes>| {
+    ///         let allowed = allowed.clone();
+    ///         async move {
+    ///             let path = req.uri().path().to_string();
+    ///             Ok::<_, Infallible>(allowed.read().await.contains(&path))
+    ///         }
+    ///     })
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn with_async<F, Fut, R, E>(self, with: F) -> CaseBuilder<'c, WithAsync<F>>
+    where
+        F: Fn(Request<Bytes>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + Sync + 'static,
+        R: Into<Report> + 'static,
+        E: StdError + Send + Sync + 'static,
+    {
+        self.with_matcher(WithAsync::new(with))
+    }
+
+    /// Pass a function or closure with access to a shared [`State`] to check if the incoming
+    /// payload matches this mock case
+    ///
+    /// The closure receives a mutable reference to the value of the state, which stays locked
+    /// while the closure runs. You cannot combine this validator with the other `with` methods.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::{Connector, Error, State};
+    /// # || {
+    /// // Only match the first 3 requests
+    /// let calls = State::new(0);
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_state(&calls, |calls: &mut usize, _req: &Request<Bytes>| {
+    ///         *calls += 1;
+    ///         *calls <= 3
+    ///     })
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn with_state<T, F, R>(
+        self,
+        state: &State<T>,
+        with: F,
+    ) -> CaseBuilder<'c, impl With + 'static>
+    where
+        T: Send + 'static,
+        F: Fn(&mut T, &Request<Bytes>) -> R + Send + Sync + 'static,
+        R: Into<Report> + Send + Sync + 'static,
+    {
+        let state = state.clone();
+        self.with(move |req: &Request<Bytes>| Ok::<_, Infallible>(with(&mut state.lock(), req)))
+    }
+
+    /// Match requests with the specified [`Uri`]
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_uri("https://example.test/hello")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_header`, but not with `with`.
+    pub fn with_uri<U>(self, uri: U) -> CaseBuilder<'c, WithHandler>
+    where
+        U: TryInto<Uri>,
+        U::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_uri(uri),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with the specified [`Uri`], ignoring cosmetic differences
+    ///
+    /// See [`UriNormalization`] for the differences that can be ignored, such as trailing
+    /// slashes, default ports, percent-encoding, or the order of query parameters.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error, UriNormalization};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_uri_normalized("https://example.test/hello", UriNormalization::all())
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_header`, but not with `with`.
+    pub fn with_uri_normalized<U>(
+        self,
+        uri: U,
+        normalization: UriNormalization,
+    ) -> CaseBuilder<'c, WithHandler>
+    where
+        U: TryInto<Uri>,
+        U::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_uri_normalized(uri, normalization),
+            options: self.options,
+        }
+    }
+
+    /// Match requests where the URI contains the expected components
+    ///
+    /// Only the components set on [`UriParts`] are checked, and mismatches report which
+    /// component differs.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error, UriParts};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_uri_parts(
+    ///         UriParts::new()
+    ///             .path("/search")
+    ///             .query_param("q", "hello world")
+    ///             .query_param_missing("page"),
+    ///     )
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_method`, but not with `with`.
+    pub fn with_uri_parts(self, parts: UriParts) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_uri_parts(parts)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a path matching the template, such as `/users/{id}/orders/{order_id}`
+    ///
+    /// Each `{name}` segment matches one non-empty path segment. The captured parameters are
+    /// added to the request extensions as [`crate::PathParams`], so the `returning` closure can
+    /// read them.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::{Connector, Error, PathParams};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_path_template("/users/{id}/orders/{order_id}")
+    ///     .returning(|req: Request<Bytes>| async move {
+    ///         let params = req.extensions().get::<PathParams>().cloned().unwrap_or_default();
+    ///         format!(
+    ///             r#"{{"user": "{}", "order": "{}"}}"#,
+    ///             params.get("id").unwrap_or_default(),
+    ///             params.get("order_id").unwrap_or_default(),
+    ///         )
+    ///     })?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_method`, but not with `with`.
+    pub fn with_path_template(self, template: &str) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_path_template(template),
+            options: self.options,
+        }
+    }
+
+    /// Match requests sent to the specified host, regardless of the path
+    ///
+    /// This is useful when the same client sends requests to multiple services.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_host("api.example.com")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_host<H>(self, host: H) -> CaseBuilder<'c, WithHandler>
+    where
+        H: Into<String>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_host(host)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests using the specified scheme, such as `http` or `https`
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_scheme("https")
+    ///     .with_host("api.example.com")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_scheme<S>(self, scheme: S) -> CaseBuilder<'c, WithHandler>
+    where
+        S: Into<String>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_scheme(scheme)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with the specified [`Method`]
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_method("GET")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_method<M>(self, method: M) -> CaseBuilder<'c, WithHandler>
+    where
+        M: TryInto<Method>,
+        M::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_method(method),
+            options: self.options,
+        }
+    }
+
+    /// Match requests using any of the specified [`Method`]s
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Method;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_method_in([Method::PUT, Method::PATCH])
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    ///
+    /// Registering the case fails if `methods` is empty, as no requests could match.
+    pub fn with_method_in<IM, M>(self, methods: IM) -> CaseBuilder<'c, WithHandler>
+    where
+        IM: IntoIterator<Item = M>,
+        M: TryInto<Method>,
+        M::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_method_in(methods),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the specific header
+    ///
+    /// An HTTP request can contain multiple headers with the same key, but different values. This
+    /// checks that there is at least one value matching. If you want to ensure that there is only
+    /// one entry for this key, consider using `with_header_once`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header("content-type", "application/json")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header<K, V>(self, key: K, value: V) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header(key, value),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the specific header
+    ///
+    /// An HTTP request can contain multiple headers with the same key, but different values. This
+    /// checks that there is only one value for the given header.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header_once("content-type", "application/json")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header_once<K, V>(self, key: K, value: V) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header_once(key, value),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the specific header
+    ///
+    /// An HTTP request can contain multiple headers with the same key, but different values. This
+    /// checks that all entries correspond to the given set of values.
+    ///
+    /// If you want to check that a header name has multiple values, but do not mind if there are
+    /// additional values, you can use `with_header` multiple times instead.
+    ///
+    /// If you want to ensure that a header name only has one value, you can use `with_header_once`
+    /// instead.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header_all("content-type", ["application/json", "text/html"])
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header_all<K, IV, V>(self, key: K, values: IV) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        IV: IntoIterator<Item = V>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header_all(key, values),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contain the specific header, with an equivalent value
+    ///
+    /// Unlike [`CaseBuilder::with_header`], values are compared case-insensitively and ignoring
+    /// whitespace around `;`, `,` and `=`. For example, `application/json; charset=UTF-8` matches
+    /// `application/json;charset=utf-8`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header_loose("content-type", "application/json; charset=utf-8")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header_loose<K, V>(self, key: K, value: V) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header_loose(key, value),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contain the specific header, with a value for which `f` returns `true`
+    ///
+    /// This can be used to validate the structure of header values, such as tokens or request
+    /// IDs, instead of their exact value. Header values that are not valid UTF-8 never match.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header_fn("authorization", |value| value.starts_with("Bearer "))
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header_fn<K, F>(self, key: K, f: F) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header_fn(key, f),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contain the specific header, with a value that matches the regular
+    /// expression `pattern`
+    ///
+    /// The pattern matches if it matches any part of the value, unless it is anchored with `^` or
+    /// `$`. This supports a subset of the usual regular expression syntax: character classes,
+    /// groups, alternations, quantifiers and anchors, but no backreferences or lookarounds. Header
+    /// values that are not valid UTF-8 never match.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header_matches("x-request-id", "^[0-9a-f]{8}(-[0-9a-f]{4}){3}-[0-9a-f]{12}$")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header_matches<K>(self, key: K, pattern: &str) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header_matches(key, pattern),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contain exactly `count` entries for this header, with any value
+    ///
+    /// This catches duplicate headers, such as a `content-type` header added twice by
+    /// middlewares. See [`CaseBuilder::with_header_once`] to also check the value.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header_count("content-type", 1)
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header_count<K>(self, key: K, count: usize) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header_count(key, count),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that don't contain the specific header
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_header_missing("authorization")
+    ///     .returning((401, "UNAUTHORIZED"))?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_header_missing<K>(self, key: K) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_header_missing(key),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with HTTP Basic credentials for `user` and `password`
+    ///
+    /// The credentials are base64-encoded and compared with the `authorization` header.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_basic_auth("user", "password")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_basic_auth(self, user: &str, password: &str) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_basic_auth(user, password),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with this bearer token in the `authorization` header
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_bearer_token("some-token")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_bearer_token(self, token: &str) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_bearer_token(token),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a bearer token for which `f` returns `true`
+    ///
+    /// The `Bearer` scheme is matched case-insensitively, and `f` receives the token alone.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_bearer_token_matching(|token| token.starts_with("eyJ"))
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_bearer_token_matching<F>(self, f: F) -> CaseBuilder<'c, WithHandler>
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_bearer_token_matching(f),
+            options: self.options,
+        }
+    }
+
+    /// Match SOAP requests for this action
+    ///
+    /// The action is read from the `SOAPAction` header, without the surrounding quotes, or from
+    /// the `action` parameter of the `content-type` header for SOAP 1.2.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_soap_action("http://example.com/GetStockPrice")
+    ///     .returning("<soap:Envelope>...</soap:Envelope>")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_soap_action<A>(self, action: A) -> CaseBuilder<'c, WithHandler>
+    where
+        A: Into<String>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_soap_action(action)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contain the specific trailer, sent after a chunked payload
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_trailer("grpc-status", "0")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_trailer<K, V>(self, key: K, value: V) -> CaseBuilder<'c, WithHandler>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_trailer(key, value),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the provided payload
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_body("some body")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    ///
+    /// A mock case only supports `with_body`, `with_json`, or `with_json_value`, but not multiple
+    /// ones at the same time.
+    pub fn with_body<B>(self, body: B) -> CaseBuilder<'c, WithHandler>
+    where
+        B: ToString,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_body(body)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a valid AWS Signature Version 4 for these credentials
+    ///
+    /// This recomputes the signature of the incoming request, from its method, URI, signed
+    /// headers, and payload, and compares it with the one in the `authorization` header. The
+    /// credential scope must also match `region` and `service`. This can be used to verify that
+    /// code signing requests, such as the AWS SDK, produce valid signatures. When the signature
+    /// isn't valid, the reason is reported with [`crate::Reason::Signature`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error, SigV4Credentials};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_sigv4(
+    ///         SigV4Credentials::new("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+    ///         "us-east-1",
+    ///         "dynamodb",
+    ///     )
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// This doesn't check that the `x-amz-date` header is recent, and trusts the payload hash in
+    /// the `x-amz-content-sha256` header when there is one. Payloads decompressed by the connector
+    /// are hashed after decompression, so compressed requests should send that header.
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    #[cfg(feature = "sigv4")]
+    pub fn with_sigv4(
+        self,
+        credentials: SigV4Credentials,
+        region: &str,
+        service: &str,
+    ) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_sigv4(credentials, region, service)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a `multipart/form-data` body that contains the expected parts
+    ///
+    /// The boundary is read from the `content-type` header, so the test doesn't need to know
+    /// which one the client generates.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error, Multipart, MultipartPart};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_multipart(
+    ///         Multipart::new().part(
+    ///             MultipartPart::new("file")
+    ///                 .filename("image.png")
+    ///                 .content_type("image/png"),
+    ///         ),
+    ///     )
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_multipart(self, multipart: Multipart) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_multipart(multipart)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the provided binary payload
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_body_bytes([0x08, 0x96, 0x01])
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    ///
+    /// A mock case only supports one of `with_body`, `with_body_bytes`, `with_json`, or
+    /// `with_json_partial` at the same time.
+    pub fn with_body_bytes<B>(self, body: B) -> CaseBuilder<'c, WithHandler>
+    where
+        B: AsRef<[u8]>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_body_bytes(body)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the provided payload, as sent on the wire
+    ///
+    /// Unlike `with_body`, this compares the payload before any decoding, such as chunked
+    /// transfer-encoding framing.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_raw_body("5\r\nhello\r\n0\r\n\r\n")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    ///
+    /// A mock case only supports one of `with_body`, `with_raw_body`, `with_body_bytes`,
+    /// `with_json`, or `with_json_partial` at the same time.
+    pub fn with_raw_body<B>(self, body: B) -> CaseBuilder<'c, WithHandler>
+    where
+        B: AsRef<[u8]>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_raw_body(body)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests for which a [`BodyStream`] matches the payload
+    ///
+    /// `f` is called for each incoming request to create a new [`BodyStream`]. This is useful
+    /// to check large payloads incrementally, for example by hashing them, especially with
+    /// [`Builder::stream_request_bodies`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{BodyStream, Connector, Error};
+    /// # || {
+    /// #[derive(Default)]
+    /// struct NoNulBytes(bool);
+    ///
+    /// impl BodyStream for NoNulBytes {
+    ///     fn update(&mut self, chunk: &[u8]) {
+    ///         self.0 |= chunk.contains(&0);
+    ///     }
+    ///
+    ///     fn finish(&mut self) -> bool {
+    ///         !self.0
+    ///     }
+    /// }
+    ///
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_body_stream(NoNulBytes::default)
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_body_stream<F, S>(self, f: F) -> CaseBuilder<'c, WithHandler>
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+        S: BodyStream + 'static,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_body_stream(f)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a payload of exactly `len` bytes
+    ///
+    /// The payload is only counted, so this can be used to check very large uploads, especially
+    /// with [`Builder::stream_request_bodies`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_method("PUT")
+    ///     .with_body_len(512 * 1024 * 1024)
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_body_len(self, len: usize) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_body_len(len)),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a payload whose SHA-256 digest is the provided hex-encoded value
+    ///
+    /// The payload is hashed incrementally, so this can be used to check very large or binary
+    /// uploads without storing the expected payload, especially with
+    /// [`Builder::stream_request_bodies`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_body_sha256("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    pub fn with_body_sha256(self, hex: &str) -> CaseBuilder<'c, WithHandler> {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_body_sha256(hex),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a body that exactly matches the provided JSON payload
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_json(serde_json::json!({"status": "OK"}))
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    ///
+    /// A mock case only supports `with_body`, `with_json`, or `with_json_value`, but not multiple
+    /// ones at the same time.
+    #[cfg(feature = "json")]
+    pub fn with_json<V>(self, value: V) -> CaseBuilder<'c, WithHandler>
+    where
+        V: serde::Serialize,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_json(value),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a body that exactly matches the provided JSON payload, except for the
+    /// values selected by `paths`
+    ///
+    /// This is useful to ignore generated values, such as timestamps or UUIDs, while still
+    /// checking the rest of the payload. Each path is either a JSONPath expression starting with
+    /// `$`, with the same syntax as [`CaseBuilder::with_json_path`], or a JSON Pointer such as
+    /// `/request_id`. Ignored values are removed from both the expected and the actual payloads
+    /// before comparing them.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_json_ignoring(
+    ///         serde_json::json!({"name": "some event", "timestamp": 0}),
+    ///         ["$.timestamp", "/request_id"],
+    ///     )
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    #[cfg(feature = "json")]
+    pub fn with_json_ignoring<V, I, P>(self, value: V, paths: I) -> CaseBuilder<'c, WithHandler>
+    where
+        V: serde::Serialize,
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_json_ignoring(value, paths),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the provided JSON payload, but may contain other properties
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    #[cfg(feature = "json")]
+    pub fn with_json_partial<V>(self, value: V) -> CaseBuilder<'c, WithHandler>
+    where
+        V: serde::Serialize,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_json_partial(value),
+            options: self.options,
+        }
+    }
+
+    /// Match requests that contains the provided JSON payload, but may contain other properties,
+    /// comparing arrays with the provided [`ArrayMatching`] mode
+    ///
+    /// [`CaseBuilder::with_json_partial`] uses [`ArrayMatching::Subset`], where the request can
+    /// contain extra array items in any order. Other modes can check the order or the length of
+    /// arrays, while still ignoring extra object properties.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{ArrayMatching, Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_json_partial_arrays(
+    ///         serde_json::json!({"steps": [{"name": "build"}, {"name": "test"}]}),
+    ///         ArrayMatching::Exact,
+    ///     )
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    #[cfg(feature = "json")]
+    pub fn with_json_partial_arrays<V>(
+        self,
+        value: V,
+        arrays: ArrayMatching,
+    ) -> CaseBuilder<'c, WithHandler>
+    where
+        V: serde::Serialize,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_json_partial_arrays(value, arrays),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a JSON body that is valid against the provided JSON Schema
+    ///
+    /// This can be used to check the shape of the payload rather than its exact content. When
+    /// the payload is not valid, the failed schema keywords are listed in the diagnostics report
+    /// and in [`crate::Reason::Body`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_json_schema(serde_json::json!({
+    ///         "type": "object",
+    ///         "required": ["id"],
+    ///         "properties": {"id": {"type": "integer"}},
+    ///     }))
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// This supports a subset of JSON Schema: `type`, `enum`, `const`, `required`, `properties`,
+    /// `additionalProperties`, `minProperties`, `maxProperties`, `items`, `minItems`, `maxItems`,
+    /// `uniqueItems`, `minLength`, `maxLength`, `minimum`, `maximum`, `exclusiveMinimum`,
+    /// `exclusiveMaximum`, `multipleOf`, `allOf`, `anyOf`, `oneOf`, and `not`. Annotations such as
+    /// `title` or `format` are ignored, and other keywords, such as `$ref` or `pattern`, make the
+    /// case fail to register.
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    #[cfg(feature = "json")]
+    pub fn with_json_schema<V>(self, schema: V) -> CaseBuilder<'c, WithHandler>
+    where
+        V: serde::Serialize,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_json_schema(schema),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a JSON body where the JSONPath expression selects the provided value
+    ///
+    /// This supports a subset of JSONPath: the root `$`, child members with `.name` or
+    /// `['name']`, array indices with `[0]`, and wildcards with `.*` or `[*]`. If the expression
+    /// selects multiple values, the request matches if any of them is equal to `value`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::Response;
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_json_path("$.Item.Host.S", "some_host")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri` or `with_json`, but not
+    /// with `with`.
+    #[cfg(feature = "json")]
+    pub fn with_json_path<V>(self, path: &str, value: V) -> CaseBuilder<'c, WithHandler>
+    where
+        V: serde::Serialize,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: WithHandler::default().with_json_path(path, value),
+            options: self.options,
+        }
+    }
+
+    /// Match requests with a JSON body that deserializes into `T` and for which the closure
+    /// returns `true`
+    ///
+    /// Requests with a body that cannot be deserialized into `T` do not match.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// #[derive(serde::Deserialize)]
+    /// struct Payload {
+    ///     id: u32,
+    /// }
+    ///
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_json_de(|payload: Payload| payload.id == 42)
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    #[cfg(feature = "json")]
+    pub fn with_json_de<T, F>(self, with: F) -> CaseBuilder<'c, WithHandler>
+    where
+        T: serde::de::DeserializeOwned,
+        F: Fn(T) -> bool + Send + Sync + 'static,
+    {
+        CaseBuilder {
+            connector: self.connector,
+            with: Ok(WithHandler::default().with_json_de(with)),
+            options: self.options,
+        }
+    }
+}
+
+impl<'c> CaseBuilder<'c, WithHandler> {
+    #[doc(hidden)]
+    pub fn with_uri<U>(mut self, uri: U) -> Self
+    where
+        U: TryInto<Uri>,
+        U::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_uri(uri));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_uri_normalized<U>(mut self, uri: U, normalization: UriNormalization) -> Self
+    where
+        U: TryInto<Uri>,
+        U::Error: Into<hyper::http::Error>,
+    {
+        self.with = self
+            .with
+            .and_then(|w| w.with_uri_normalized(uri, normalization));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_uri_parts(mut self, parts: UriParts) -> Self {
+        self.with = self.with.map(|w| w.with_uri_parts(parts));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_path_template(mut self, template: &str) -> Self {
+        self.with = self.with.and_then(|w| w.with_path_template(template));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_host<H>(mut self, host: H) -> Self
+    where
+        H: Into<String>,
+    {
+        self.with = self.with.map(|w| w.with_host(host));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_scheme<S>(mut self, scheme: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.with = self.with.map(|w| w.with_scheme(scheme));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_method<M>(mut self, method: M) -> Self
+    where
+        M: TryInto<Method>,
+        M::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_method(method));
+        self
+    }
+
+    /// Update the matcher with a closure that can fail
+    #[cfg(feature = "json")]
+    pub(crate) fn and_then_with<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(WithHandler) -> Result<WithHandler, Error>,
+    {
+        self.with = self.with.and_then(f);
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_method_in<IM, M>(mut self, methods: IM) -> Self
+    where
+        IM: IntoIterator<Item = M>,
+        M: TryInto<Method>,
+        M::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_method_in(methods));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_header(key, value));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header_once<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_header_once(key, value));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header_all<K, IV, V>(mut self, key: K, values: IV) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        IV: IntoIterator<Item = V>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_header_all(key, values));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header_loose<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_header_loose(key, value));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header_fn<K, F>(mut self, key: K, f: F) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.with = self.with.and_then(|w| w.with_header_fn(key, f));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header_matches<K>(mut self, key: K, pattern: &str) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_header_matches(key, pattern));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header_count<K>(mut self, key: K, count: usize) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_header_count(key, count));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_header_missing<K>(mut self, key: K) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_header_missing(key));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_basic_auth(mut self, user: &str, password: &str) -> Self {
+        self.with = self.with.and_then(|w| w.with_basic_auth(user, password));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_bearer_token(mut self, token: &str) -> Self {
+        self.with = self.with.and_then(|w| w.with_bearer_token(token));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_bearer_token_matching<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.with = self.with.and_then(|w| w.with_bearer_token_matching(f));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_soap_action<A>(mut self, action: A) -> Self
+    where
+        A: Into<String>,
+    {
+        self.with = self.with.map(|w| w.with_soap_action(action));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_trailer<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.with = self.with.and_then(|w| w.with_trailer(key, value));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_body<B>(mut self, body: B) -> Self
+    where
+        B: ToString,
+    {
+        self.with = self.with.map(|w| w.with_body(body));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "sigv4")]
+    pub fn with_sigv4(
+        mut self,
+        credentials: SigV4Credentials,
+        region: &str,
+        service: &str,
+    ) -> Self {
+        self.with = self
+            .with
+            .map(|w| w.with_sigv4(credentials, region, service));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_multipart(mut self, multipart: Multipart) -> Self {
+        self.with = self.with.map(|w| w.with_multipart(multipart));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_raw_body<B>(mut self, body: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.with = self.with.map(|w| w.with_raw_body(body));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_body_bytes<B>(mut self, body: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.with = self.with.map(|w| w.with_body_bytes(body));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_body_stream<F, S>(mut self, f: F) -> Self
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+        S: BodyStream + 'static,
+    {
+        self.with = self.with.map(|w| w.with_body_stream(f));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_body_len(mut self, len: usize) -> Self {
+        self.with = self.with.map(|w| w.with_body_len(len));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_body_sha256(mut self, hex: &str) -> Self {
+        self.with = self.with.and_then(|w| w.with_body_sha256(hex));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "json")]
+    pub fn with_json<V>(mut self, value: V) -> Self
+    where
+        V: serde::Serialize,
+    {
+        self.with = self.with.and_then(|w| w.with_json(value));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "json")]
+    pub fn with_json_ignoring<V, I, P>(mut self, value: V, paths: I) -> Self
+    where
+        V: serde::Serialize,
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        self.with = self.with.and_then(|w| w.with_json_ignoring(value, paths));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "json")]
+    pub fn with_json_partial<V>(mut self, value: V) -> Self
+    where
+        V: serde::Serialize,
+    {
+        self.with = self.with.and_then(|w| w.with_json_partial(value));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "json")]
+    pub fn with_json_partial_arrays<V>(mut self, value: V, arrays: ArrayMatching) -> Self
+    where
+        V: serde::Serialize,
+    {
+        self.with = self
+            .with
+            .and_then(|w| w.with_json_partial_arrays(value, arrays));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "json")]
+    pub fn with_json_schema<V>(mut self, schema: V) -> Self
+    where
+        V: serde::Serialize,
+    {
+        self.with = self.with.and_then(|w| w.with_json_schema(schema));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "json")]
+    pub fn with_json_path<V>(mut self, path: &str, value: V) -> Self
+    where
+        V: serde::Serialize,
+    {
+        self.with = self.with.and_then(|w| w.with_json_path(path, value));
+        self
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "json")]
+    pub fn with_json_de<T, F>(mut self, with: F) -> Self
+    where
+        T: serde::de::DeserializeOwned,
+        F: Fn(T) -> bool + Send + Sync + 'static,
+    {
+        self.with = self.with.map(|w| w.with_json_de(with));
+        self
+    }
+}
+
+impl<'c> CaseBuilder<'c, WithHandler> {
+    /// Pass a function or closure to check if the incoming payload matches this mock case, in
+    /// addition to the other `with_*` methods
+    ///
+    /// Unlike `with`, this can be combined with the other `with_*` methods, so the structured
+    /// validators handle the common attributes and the closure validates the rest.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::{Connector, Error};
+    /// # use std::convert::Infallible;
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_uri("https://example.test/hello")
+    ///     .and_with(|req: &Request<Bytes>| {
+    ///         Ok::<_, Infallible>(req.headers().contains_key("x-signature"))
+    ///     })
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn and_with<W, E, R>(mut self, with: W) -> Self
+    where
+        for<'r> W: Fn(&'r Request<Bytes>) -> Result<R, E> + Send + Sync + 'static,
+        R: Into<Report> + Send + Sync + 'static,
+        E: StdError + Send + Sync + 'static,
+    {
+        self.with = self.with.map(|w| w.and_with(with));
+        self
+    }
+}
+
+impl<'c, W> CaseBuilder<'c, W> {
+    /// Mark how many times this mock case can be called
+    ///
+    /// Nothing enforces how many times a mock case is called, but you can use the `checkpoint`
+    /// method on the [`Connector`] to ensure all methods were called the right amount of times.
+    pub fn times(mut self, count: usize) -> Self {
+        self.options.count = Some(count);
+        self
+    }
+
+    /// Reject requests once this mock case received all the calls expected with
+    /// [`CaseBuilder::times`]
+    ///
+    /// Extra requests don't match the case, and are handled like any other request that doesn't
+    /// match. See [`Builder::strict`] to enable this for all cases.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .times(1)
+    ///     .strict()
+    ///     .with_uri("https://test.example/token")
+    ///     .returning("token")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn strict(mut self) -> Self {
+        self.options.strict = true;
+        self
+    }
+
+    /// Stop matching requests once this mock case received all the calls expected with
+    /// [`CaseBuilder::times`]
+    ///
+    /// Once the case is consumed, the next mock case that matches takes over. Unlike
+    /// [`CaseBuilder::strict`], extra requests are not counted, so they don't fail the
+    /// checkpoint.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// // The first request fails, and the following ones succeed
+    /// let mut builder = Connector::builder();
+    /// builder.expect().times(1).consumed().returning(503)?;
+    /// builder.expect().returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn consumed(mut self) -> Self {
+        self.options.consumed = true;
+        self
+    }
+
+    /// Set a human-readable name for this mock case
+    ///
+    /// The name is used to identify the case in checkpoint errors and diagnostic reports. By
+    /// default, cases are identified by the name of their `with` matcher.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .name("auth token request")
+    ///     .with_uri("https://test.example/token")
+    ///     .returning("token")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.options.name = Some(name.into());
+        self
+    }
+
+    /// Set the priority of this mock case
+    ///
+    /// Incoming requests are matched against cases with a higher priority first. Cases with the
+    /// same priority are matched in the order they were registered. By default, all cases have a
+    /// priority of `0`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// // Catch-all fallback, registered first
+    /// builder.expect().priority(-1).returning((404, "NOT FOUND"))?;
+    /// builder
+    ///     .expect()
+    ///     .with_uri("https://test.example/hello")
+    ///     .returning("hello")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.options.priority = priority;
+        self
+    }
+
+    /// Add this mock case to a [`Sequence`]
+    ///
+    /// All cases in a [`Sequence`] must be called in the order they were added to it. Calling
+    /// them out of order will make the `checkpoint` method on the [`Connector`] return an error.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error, Sequence};
+    /// # || {
+    /// let mut seq = Sequence::new();
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_uri("https://test.example/first")
+    ///     .in_sequence(&mut seq)
+    ///     .returning("first")?;
+    /// builder
+    ///     .expect()
+    ///     .with_uri("https://test.example/second")
+    ///     .in_sequence(&mut seq)
+    ///     .returning("second")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn in_sequence(mut self, sequence: &mut Sequence) -> Self {
+        self.options.sequence = Some(sequence.next_entry());
+        self
+    }
+
+    /// Attach this mock case to a [`Scenario`]
+    ///
+    /// Use [`CaseBuilder::in_state`] and [`CaseBuilder::will_set_state`] to set the states
+    /// that this case requires and sets. See [`Scenario`] for more information.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error, Scenario};
+    /// # || {
+    /// let login = Scenario::new("anonymous");
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_uri("https://test.example/login")
+    ///     .in_scenario(&login)
+    ///     .will_set_state("authorized")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn in_scenario(mut self, scenario: &Scenario) -> Self {
+        self.options.scenario = Some(scenario.clone());
+        self
+    }
+
+    /// Only match requests when the scenario of this mock case is in `state`
+    ///
+    /// This requires [`CaseBuilder::in_scenario`]. Requests received in other states are
+    /// reported with [`crate::Reason::Scenario`].
+    pub fn in_state(mut self, state: impl Into<String>) -> Self {
+        self.options.scenario_state = Some(state.into());
+        self
+    }
+
+    /// Move the scenario of this mock case to `state` when the case handles a request
+    ///
+    /// This requires [`CaseBuilder::in_scenario`].
+    pub fn will_set_state(mut self, state: impl Into<String>) -> Self {
+        self.options.scenario_next = Some(state.into());
+        self
+    }
+
+    /// Ask the client to close the connection after this mock case's response
+    ///
+    /// This adds a `connection: close` header to the response, unless it already has a
+    /// `connection` header.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.expect().close_connection().returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn close_connection(mut self) -> Self {
+        self.options.close_connection = true;
+        self
+    }
+
+    /// Abort the connection after sending `bytes` bytes of the response body
+    ///
+    /// The client receives the response head and the start of the body, then a connection reset
+    /// error. This can be used to test how clients handle connections dropped mid-response.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.expect().abort_after(5).returning("hello world")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn abort_after(mut self, bytes: usize) -> Self {
+        self.options.abort_after = Some(bytes);
+        self
+    }
+
+    /// Limit how many requests matching this mock case can be in flight at the same time
+    ///
+    /// A request is in flight from the moment it matches until its response is fully sent. Once
+    /// `count` requests are in flight, further requests matching this case receive a
+    /// `429 Too Many Requests` response, and the `checkpoint` method on the [`Connector`] returns
+    /// an error. This can be used to check that a client honors its concurrency limits.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # use std::time::Duration;
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .max_concurrent(2)
+    ///     .returning(|_| async {
+    ///         tokio::time::sleep(Duration::from_millis(100)).await;
+    ///         "OK"
+    ///     })?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Remark
+    ///
+    /// Requests over the limit are rejected, rather than queued until another request completes.
+    pub fn max_concurrent(mut self, count: usize) -> Self {
+        self.options.max_concurrent = Some(count);
+        self
+    }
+
+    /// Inject random faults into the responses of this mock case
+    ///
+    /// This replaces the profile set for the whole connector with [`Builder::faults`], if any.
+    /// See [`FaultProfile`] for the available faults.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error, FaultProfile};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .faults(FaultProfile::new(42).drop_connection(0.5))
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
     /// # };
     /// ```
+    pub fn faults(mut self, faults: FaultProfile) -> Self {
+        self.options.faults = Some(faults);
+        self
+    }
+}
+
+impl<'c, W> CaseBuilder<'c, W>
+where
+    W: With + 'static,
+{
+    /// Mark what will generate the response for a given mock case
+    ///
+    /// You can either pass a static value, or a function or closure that takes a `Request<Bytes>`
+    /// as an input.
+    ///
+    /// See the documentation for [`Returning`] to see the full list of what is accepted by this
+    /// method.
+    ///
+    /// ## Errors
+    ///
+    /// This will fail if any of the previous steps in [`CaseBuilder`] failed, if it uses
+    /// [`CaseBuilder::in_state`] or [`CaseBuilder::will_set_state`] without a scenario, or if it
+    /// fails to store the case into the connector.
+    pub fn returning<R>(self, returning: R) -> Result<(), Error>
+    where
+        R: Returning + 'static,
+    {
+        if self.options.scenario.is_none()
+            && (self.options.scenario_state.is_some() || self.options.scenario_next.is_some())
+        {
+            return Err(Error::Scenario(
+                "`in_state` and `will_set_state` require `in_scenario`".to_string(),
+            ));
+        }
+
+        let case = Case::new(self.with?, returning, self.options);
+        self.connector.add_case(case);
+
+        Ok(())
+    }
+
+    /// Send the response body as multiple chunks, with chunked transfer-encoding
     ///
-    /// ## Remark
+    /// This is a shortcut for `returning(Chunked::new(chunks))`. See [`crate::Chunked`] to
+    /// customize the response further or add delays between chunks.
     ///
-    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
+    /// ## Example
     ///
-    /// A mock case only supports `with_body`, `with_json`, or `with_json_value`, but not multiple
-    /// ones at the same time.
-    #[cfg(feature = "json")]
-    pub fn with_json<V>(self, value: V) -> CaseBuilder<'c, WithHandler>
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .returning_chunked(["part1", "part2"])?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn returning_chunked<I, B>(self, chunks: I) -> Result<(), Error>
     where
-        V: serde::Serialize,
+        I: IntoIterator<Item = B>,
+        B: Into<Bytes>,
     {
-        CaseBuilder {
-            connector: self.connector,
-            with: WithHandler::default().with_json(value),
-            count: self.count,
-        }
+        self.returning(Chunked::new(chunks))
     }
 
-    /// Match requests that contains the provided JSON payload, but may contain other properties
+    /// Stream the given events as Server-Sent Events
     ///
-    /// You can combine this with other validators, such as `with_uri`, but not with `with`.
-    pub fn with_json_partial<V>(self, value: V) -> CaseBuilder<'c, WithHandler>
+    /// This is a shortcut for `returning(Sse::new(events))`. See [`crate::Sse`] to add delays
+    /// between events or keep the connection open after the last event.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .returning_sse([r#"{"delta": "Hello"}"#, r#"{"delta": " world"}"#, "[DONE]"])?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn returning_sse<I, E>(self, events: I) -> Result<(), Error>
     where
-        V: serde::Serialize,
+        I: IntoIterator<Item = E>,
+        E: Into<SseEvent>,
     {
-        CaseBuilder {
-            connector: self.connector,
-            with: WithHandler::default().with_json_partial(value),
-            count: self.count,
-        }
+        self.returning(Sse::new(events))
     }
-}
 
-impl<'c> CaseBuilder<'c, WithHandler> {
-    #[doc(hidden)]
-    pub fn with_uri<U>(mut self, uri: U) -> Self
+    /// Upgrade the connection, and hand the raw byte stream to `handler`
+    ///
+    /// This is a shortcut for `returning(Upgrade::new(handler))`, and can be used to mock `CONNECT`
+    /// tunnels or custom upgrade protocols. See [`crate::Upgrade`] for more information.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # use tokio::io::AsyncWriteExt;
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_method("CONNECT")
+    ///     .returning_upgrade(|mut io| async move {
+    ///         let _ = io.write_all(b"SSH-2.0-mock\r\n").await;
+    ///     })?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn returning_upgrade<F, Fut>(self, handler: F) -> Result<(), Error>
     where
-        U: TryInto<Uri>,
-        U::Error: Into<hyper::http::Error>,
+        F: Fn(DuplexStream) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
     {
-        self.with = self.with.and_then(|w| w.with_uri(uri));
-        self
+        self.returning(Upgrade::new(handler))
     }
 
-    #[doc(hidden)]
-    pub fn with_method<M>(mut self, method: M) -> Self
+    /// Mark a function or closure with access to a shared [`State`] to generate the response
+    ///
+    /// The closure receives a mutable reference to the value of the state, which stays locked
+    /// while the closure runs, and can return anything that implements [`crate::IntoResponse`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::{Connector, Error, State};
+    /// # || {
+    /// // Capture the token from the request, and return how many requests were made
+    /// let tokens = State::new(Vec::new());
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .returning_state(&tokens, |tokens: &mut Vec<String>, req: Request<Bytes>| {
+    ///         tokens.push(String::from_utf8_lossy(req.body()).into_owned());
+    ///         tokens.len().to_string()
+    ///     })?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn returning_state<T, F, R>(self, state: &State<T>, returning: F) -> Result<(), Error>
     where
-        M: TryInto<Method>,
-        M::Error: Into<hyper::http::Error>,
+        T: Send + 'static,
+        F: Fn(&mut T, Request<Bytes>) -> R + Send + Sync + 'static,
+        R: IntoResponse + 'static,
     {
-        self.with = self.with.and_then(|w| w.with_method(method));
-        self
+        self.returning(ReturningState::new(state.clone(), returning))
     }
 
-    #[doc(hidden)]
-    pub fn with_header<K, V>(mut self, key: K, value: V) -> Self
+    /// Mark a function or closure with access to the [`CaseContext`] of the call to generate the
+    /// response
+    ///
+    /// The closure receives the position of the call, how many requests the case received so far,
+    /// and its name, so responses can vary between calls without an external counter.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, Request};
+    /// # use mock_http_connector::{CaseContext, Connector, Error};
+    /// # || {
+    /// // Fail the first call, then succeed
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .returning_with_ctx(|ctx: CaseContext, _req: Request<Bytes>| async move {
+    ///         if ctx.call() == 0 {
+    ///             (503, "try again")
+    ///         } else {
+    ///             (200, "OK")
+    ///         }
+    ///     })?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn returning_with_ctx<F, R>(self, returning: F) -> Result<(), Error>
     where
-        K: TryInto<HeaderName>,
-        K::Error: Into<hyper::http::Error>,
-        V: TryInto<HeaderValue>,
-        V::Error: Into<hyper::http::Error>,
+        F: Fn(CaseContext, Request<Bytes>) -> R + Send + Sync + 'static,
+        R: IntoResponseFuture + 'static,
     {
-        self.with = self.with.and_then(|w| w.with_header(key, value));
-        self
+        self.returning(ReturningCtx::new(returning))
     }
 
-    #[doc(hidden)]
-    pub fn with_header_once<K, V>(mut self, key: K, value: V) -> Self
+    /// Fail the first requests, then return a regular response
+    ///
+    /// This is a shortcut for `times(n).returning(backoff)`, where `n` is the number of failures
+    /// plus one, so [`Connector::checkpoint`] verifies that the client retried until it
+    /// succeeded, and didn't send any more requests. See [`crate::Backoff`] for more details.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Backoff, Connector, Error};
+    /// # use std::time::Duration;
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .returning_backoff(Backoff::new(2).retry_after(Duration::from_secs(1)))?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn returning_backoff<R>(self, backoff: Backoff<R>) -> Result<(), Error>
     where
-        K: TryInto<HeaderName>,
-        K::Error: Into<hyper::http::Error>,
-        V: TryInto<HeaderValue>,
-        V::Error: Into<hyper::http::Error>,
+        R: Returning + 'static,
     {
-        self.with = self.with.and_then(|w| w.with_header_once(key, value));
-        self
+        let attempts = backoff.attempts();
+        self.times(attempts).returning(backoff)
     }
 
-    #[doc(hidden)]
-    pub fn with_header_all<K, IV, V>(mut self, key: K, values: IV) -> Self
+    /// Redirect matching requests to `location`, with a `302 Found` response
+    ///
+    /// See [`Builder::redirect_chain`] to create the cases for multiple redirects at once.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_uri("http://test.example/old")
+    ///     .returning_redirect("http://test.example/new")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// This will fail if `location` is not a valid header value.
+    pub fn returning_redirect<L>(self, location: L) -> Result<(), Error>
     where
-        K: TryInto<HeaderName>,
-        K::Error: Into<hyper::http::Error>,
-        IV: IntoIterator<Item = V>,
-        V: TryInto<HeaderValue>,
-        V::Error: Into<hyper::http::Error>,
+        L: TryInto<HeaderValue>,
+        L::Error: Into<hyper::http::Error>,
     {
-        self.with = self.with.and_then(|w| w.with_header_all(key, values));
-        self
+        let response = MockResponse::new(StatusCode::FOUND).header(LOCATION, location)?;
+        self.returning(response)
     }
 
-    #[doc(hidden)]
-    pub fn with_body<B>(mut self, body: B) -> Self
+    /// Forward matching requests to a real [`hyper::Client`]
+    ///
+    /// This is a shortcut for `returning(Forward::new(client))`. See [`crate::Forward`] for more
+    /// details.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_host("localhost")
+    ///     .passthrough(hyper::Client::new())?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn passthrough<C>(self, client: hyper::Client<C, hyper::Body>) -> Result<(), Error>
     where
-        B: ToString,
+        C: Connect + Clone + Send + Sync + 'static,
     {
-        self.with = self.with.map(|w| w.with_body(body));
-        self
+        self.returning(Forward::new(client))
     }
 
-    #[doc(hidden)]
-    #[cfg(feature = "json")]
-    pub fn with_json<V>(mut self, value: V) -> Self
+    /// Compress the response body with gzip
+    ///
+    /// This is a shortcut for `returning(Gzip::new(returning))`. See [`crate::Gzip`] for more
+    /// details.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .returning_gzip("some compressed payload")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    #[cfg(feature = "gzip")]
+    pub fn returning_gzip<R>(self, returning: R) -> Result<(), Error>
     where
-        V: serde::Serialize,
+        R: Returning + 'static,
     {
-        self.with = self.with.and_then(|w| w.with_json(value));
-        self
+        self.returning(Gzip::new(returning))
     }
 
-    #[doc(hidden)]
+    /// Serialize `value` as the JSON body of the response
+    ///
+    /// This is a shortcut for `returning(Json(value))`, and sets the `content-type:
+    /// application/json` header. To set another status code, use
+    /// `returning((status, Json(value)))` instead. See [`crate::Json`] for more details.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// #[derive(serde::Serialize)]
+    /// struct Item {
+    ///     id: u64,
+    /// }
+    ///
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.expect().returning_json(Item { id: 42 })?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
     #[cfg(feature = "json")]
-    pub fn with_json_partial<V>(mut self, value: V) -> Self
+    pub fn returning_json<V>(self, value: V) -> Result<(), Error>
     where
-        V: serde::Serialize,
+        V: serde::Serialize + Send + Sync + 'static,
     {
-        self.with = self.with.and_then(|w| w.with_json_partial(value));
-        self
+        self.returning(Json(value))
     }
-}
 
-impl<'c, W> CaseBuilder<'c, W> {
-    /// Mark how many times this mock case can be called
+    /// Return a SOAP 1.1 fault with this fault code and description
     ///
-    /// Nothing enforces how many times a mock case is called, but you can use the `checkpoint`
-    /// method on the [`Connector`] to ensure all methods were called the right amount of times.
-    pub fn times(self, count: usize) -> Self {
-        Self {
-            count: Some(count),
-            ..self
-        }
+    /// This is a shortcut for `returning(SoapFault::new(code, string))`. See
+    /// [`crate::SoapFault`] for more details.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .with_soap_action("http://example.com/GetStockPrice")
+    ///     .returning_soap_fault("Server", "Service unavailable")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn returning_soap_fault<C, S>(self, code: C, string: S) -> Result<(), Error>
+    where
+        C: Into<String>,
+        S: Into<String>,
+    {
+        self.returning(SoapFault::new(code, string))
     }
-}
 
-impl<'c, W> CaseBuilder<'c, W>
-where
-    W: With + 'static,
-{
-    /// Mark what will generate the response for a given mock case
+    /// Mark a function or closure that takes the deserialized JSON body of the request to
+    /// generate the response
     ///
-    /// You can either pass a static value, or a function or closure that takes a `Request<String>`
-    /// as an input.
+    /// The closure can return anything that implements [`crate::IntoResponseFuture`]. If the
+    /// request body cannot be deserialized into `T`, the request will fail.
     ///
-    /// See the documentation for [`Returning`] to see the full list of what is accepted by this
-    /// method.
+    /// ## Example
     ///
-    /// ## Errors
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// #[derive(serde::Deserialize)]
+    /// struct Payload {
+    ///     name: String,
+    /// }
     ///
-    /// This will fail if any of the previous steps in [`CaseBuilder`] failed, or if it fails to
-    /// store the case into the connector.
-    pub fn returning<R>(self, returning: R) -> Result<(), Error>
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder
+    ///     .expect()
+    ///     .returning_json_de(|payload: Payload| async move { format!("hello {}", payload.name) })?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn returning_json_de<T, F, R>(self, returning: F) -> Result<(), Error>
     where
-        R: Returning + 'static,
+        T: serde::de::DeserializeOwned + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+        R: IntoResponseFuture,
     {
-        let case = Case::new(self.with?, returning, self.count);
-        self.connector.cases.push(case);
-
-        Ok(())
+        self.returning(JsonDe::new(returning))
     }
 }
 
@@ -508,7 +3181,7 @@ mod tests {
         let mut connector = Connector::builder();
         connector
             .expect()
-            .with(|req: &Request<String>| Ok::<_, Infallible>(req.body().contains("hello")))
+            .with(|req: &Request<Bytes>| Ok::<_, Infallible>(req.body().starts_with(b"hello")))
             .returning("OK")
             .unwrap();
     }
@@ -1,20 +1,56 @@
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
-use crate::handler::{Returning, With};
+use crate::{
+    handler::{Reason, Returning, With},
+    metrics::CaseRecorder,
+    sequence::SequenceEntry,
+    stream::ConnectionOptions,
+    CaseReport, Error, FaultProfile, Scenario,
+};
+
+/// Options shared by all mock cases, regardless of their `with` and `returning` handlers
+#[derive(Debug, Default, Clone)]
+pub(crate) struct CaseOptions {
+    pub abort_after: Option<usize>,
+    pub close_connection: bool,
+    pub consumed: bool,
+    pub count: Option<usize>,
+    pub faults: Option<FaultProfile>,
+    pub host: Option<String>,
+    pub max_concurrent: Option<usize>,
+    pub name: Option<String>,
+    pub priority: i32,
+    pub scenario: Option<Scenario>,
+    pub scenario_state: Option<String>,
+    pub scenario_next: Option<String>,
+    pub sequence: Option<SequenceEntry>,
+    pub strict: bool,
+}
+
+/// Destination for mock cases created through [`crate::CaseBuilder`]
+pub(crate) trait AddCase {
+    fn add_case(&self, case: Case);
+}
 
 #[derive(Clone)]
 pub(crate) struct Case {
     pub(crate) with: Arc<Box<dyn With + Send + Sync>>,
     pub(crate) returning: Arc<Box<dyn Returning + Send + Sync>>,
-    count: Option<usize>,
+    options: CaseOptions,
     pub(crate) seen: Arc<AtomicUsize>,
+    pub(crate) metrics: CaseRecorder,
+    concurrency: Arc<Concurrency>,
 }
 
 impl Case {
-    pub fn new<W, R>(with: W, returning: R, count: Option<usize>) -> Self
+    pub fn new<W, R>(with: W, returning: R, options: CaseOptions) -> Self
     where
         W: With + Send + Sync + 'static,
         R: Returning + Send + Sync + 'static,
@@ -22,35 +58,397 @@ impl Case {
         Self {
             with: Arc::new(Box::new(with)),
             returning: Arc::new(Box::new(returning)),
-            count,
+            options,
             seen: Arc::new(AtomicUsize::new(0)),
+            metrics: CaseRecorder::default(),
+            concurrency: Arc::default(),
+        }
+    }
+
+    /// Record that an incoming request matched this case
+    ///
+    /// This returns the position of the call for this case, starting at 0, or the reason why the
+    /// case shouldn't handle the request:
+    ///
+    /// * [`Reason::Times`] if the case already received all the expected calls and is consumed or
+    ///   strict. For strict cases, the call is still counted, so the checkpoint reports it.
+    /// * [`Reason::Scenario`] if its scenario isn't in the expected state.
+    ///
+    /// If the case handles the request, its scenario moves to the next state.
+    pub fn record(&self, strict: bool) -> Result<usize, Reason> {
+        // Hold the scenario lock until the transition, so concurrent requests see it in order
+        let mut scenario = self.options.scenario.as_ref().map(Scenario::lock);
+        if let (Some(current), Some(state)) = (&scenario, &self.options.scenario_state) {
+            if **current != *state {
+                return Err(Reason::Scenario);
+            }
+        }
+
+        let count = self.options.count;
+        let call = if self.options.consumed {
+            self.seen
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |seen| {
+                    count.map_or(true, |count| seen < count).then_some(seen + 1)
+                })
+                .map_err(|_| Reason::Times)?
+        } else {
+            let seen = self.seen.fetch_add(1, Ordering::AcqRel);
+            if (strict || self.options.strict) && count.is_some_and(|count| seen >= count) {
+                return Err(Reason::Times);
+            }
+            seen
+        };
+
+        if let (Some(current), Some(next)) = (&mut scenario, &self.options.scenario_next) {
+            **current = next.clone();
+        }
+        if let Some(sequence) = &self.options.sequence {
+            sequence.record();
+        }
+        Ok(call)
+    }
+
+    /// Check if this case would handle a request, without counting it as a call
+    ///
+    /// This returns the same errors as [`Case::record`], but with strict cases, the call isn't
+    /// counted.
+    pub fn accepts(&self, strict: bool) -> Result<(), Reason> {
+        if let (Some(scenario), Some(state)) =
+            (&self.options.scenario, &self.options.scenario_state)
+        {
+            if *scenario.lock() != *state {
+                return Err(Reason::Scenario);
+            }
+        }
+
+        let exhausted = self
+            .options
+            .count
+            .is_some_and(|count| self.seen.load(Ordering::Acquire) >= count);
+        if exhausted && (self.options.consumed || strict || self.options.strict) {
+            return Err(Reason::Times);
+        }
+        Ok(())
+    }
+
+    /// State required by this case, and current state of its scenario
+    pub fn scenario_states(&self) -> Option<(Option<&str>, String)> {
+        self.options
+            .scenario
+            .as_ref()
+            .map(|scenario| (self.options.scenario_state.as_deref(), scenario.state()))
+    }
+
+    /// Context passed to the [`Returning`] implementation for the call at position `call`
+    pub fn context(&self, call: usize) -> CaseContext {
+        CaseContext {
+            call,
+            seen: self.seen.load(Ordering::Acquire),
+            name: self.name(),
         }
     }
 
-    pub fn checkpoint(&self) -> Option<Checkpoint> {
-        self.count
-            .and_then(|count| Checkpoint::check(count, self.seen.load(Ordering::Acquire)))
+    /// Restrict this case to requests sent to `host`
+    pub fn with_host(mut self, host: String) -> Self {
+        self.options.host = Some(host);
+        self
     }
+
+    /// Returns `true` if this case can handle requests sent to `host`
+    pub fn accepts_host(&self, host: Option<&str>) -> bool {
+        match &self.options.host {
+            Some(case_host) => host.is_some_and(|host| host.eq_ignore_ascii_case(case_host)),
+            None => true,
+        }
+    }
+
+    /// Options for the connection once this case returns a response
+    pub fn connection(&self) -> ConnectionOptions {
+        ConnectionOptions {
+            close: self.options.close_connection,
+            abort_after: self.options.abort_after,
+            ..Default::default()
+        }
+    }
+
+    /// Faults to inject into the responses of this case, if it overrides the connector ones
+    pub fn faults(&self) -> Option<&FaultProfile> {
+        self.options.faults.as_ref()
+    }
+
+    /// Start tracking a request handled by this case, if it has a concurrency limit
+    ///
+    /// The request is in flight until the returned [`InFlight`] is dropped. It only counts towards
+    /// the peak concurrency of the case once [`InFlight::commit`] is called.
+    pub fn in_flight(&self) -> Option<InFlight> {
+        let max = self.options.max_concurrent?;
+        let count = self.concurrency.in_flight.fetch_add(1, Ordering::AcqRel) + 1;
+
+        Some(InFlight {
+            concurrency: self.concurrency.clone(),
+            count,
+            exceeded: count > max,
+        })
+    }
+
+    /// Priority of this case over other cases when matching requests
+    pub fn priority(&self) -> i32 {
+        self.options.priority
+    }
+
+    /// Human-readable name for this case
+    ///
+    /// This falls back to the name of the `with` matcher if no name was set.
+    pub fn name(&self) -> String {
+        match &self.options.name {
+            Some(name) => name.clone(),
+            None => self.with.print_pretty(&HashSet::new()).name.into_owned(),
+        }
+    }
+
+    /// Name set with [`crate::CaseBuilder::name`], if any
+    pub fn custom_name(&self) -> Option<&str> {
+        self.options.name.as_deref()
+    }
+
+    /// Returns `true` if both values refer to the same mock case
+    pub fn is_same(&self, other: &Case) -> bool {
+        Arc::ptr_eq(&self.seen, &other.seen)
+    }
+
+    /// Statistics for this case so far
+    pub fn report(&self) -> CaseReport {
+        CaseReport {
+            name: self.name(),
+            times: self.options.count,
+            seen: self.seen.load(Ordering::Acquire),
+        }
+    }
+
+    pub fn checkpoint(&self) -> Vec<Checkpoint> {
+        let count = self.options.count.and_then(|expected| {
+            let got = self.seen.load(Ordering::Acquire);
+            (expected != got).then_some(CheckpointKind::Count { expected, got })
+        });
+        let sequence = self.options.sequence.as_ref().and_then(|sequence| {
+            sequence
+                .violation()
+                .map(|after| CheckpointKind::OutOfOrder {
+                    position: sequence.position(),
+                    after,
+                })
+        });
+        let concurrency = self.options.max_concurrent.and_then(|max| {
+            let got = self.concurrency.peak.load(Ordering::Acquire);
+            (got > max).then_some(CheckpointKind::Concurrency { max, got })
+        });
+
+        count
+            .into_iter()
+            .chain(sequence)
+            .chain(concurrency)
+            .map(|kind| Checkpoint {
+                case: self.name(),
+                matcher: self
+                    .with
+                    .print_pretty(&HashSet::new())
+                    .body
+                    .map(Cow::into_owned),
+                kind,
+            })
+            .collect()
+    }
+}
+
+/// Number of requests in flight for a mock case
+#[derive(Debug, Default)]
+struct Concurrency {
+    in_flight: AtomicUsize,
+    /// Highest number of requests in flight at the same time
+    peak: AtomicUsize,
 }
 
+/// Request in flight for a mock case with a concurrency limit
+///
+/// This is stored in the [`hyper::Response`] extensions, and dropped once the response is sent.
 #[derive(Debug)]
+pub(crate) struct InFlight {
+    concurrency: Arc<Concurrency>,
+    /// Number of requests in flight when this one started
+    count: usize,
+    exceeded: bool,
+}
+
+impl InFlight {
+    /// Returns `true` if this request exceeds the concurrency limit of the case
+    pub fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Count this request towards the peak concurrency of the case
+    pub fn commit(&self) {
+        self.concurrency
+            .peak
+            .fetch_max(self.count, Ordering::AcqRel);
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.concurrency.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Check if all the given mock cases were called the right amount of time
+pub(crate) fn checkpoint<'c>(cases: impl IntoIterator<Item = &'c Case>) -> Result<(), Error> {
+    let checkpoints = cases
+        .into_iter()
+        .flat_map(|case| case.checkpoint())
+        .collect::<Vec<_>>();
+
+    if checkpoints.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Checkpoint(checkpoints))
+    }
+}
+
+/// Mock case that failed a [`crate::Connector::checkpoint`]
+///
+/// This is returned in [`Error::Checkpoint`], so test frameworks can render their own failure
+/// messages.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder.expect().times(1).name("token").returning("OK")?;
+/// let connector = builder.build();
+///
+/// let err = connector.checkpoint().unwrap_err();
+/// let checkpoint = &err.checkpoints()[0];
+/// assert_eq!(checkpoint.case(), "token");
+/// assert_eq!(checkpoint.expected(), Some(1));
+/// assert_eq!(checkpoint.got(), 0);
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Checkpoint {
-    expected: usize,
-    got: usize,
+    case: String,
+    matcher: Option<String>,
+    kind: CheckpointKind,
 }
 
-impl std::fmt::Display for Checkpoint {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "expected {}, got {}", self.expected, self.got)
+/// Reason why a mock case failed a checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointKind {
+    /// The case wasn't called the expected number of times
+    Count {
+        /// Number of calls expected with [`crate::CaseBuilder::times`]
+        expected: usize,
+        /// Number of calls received
+        got: usize,
+    },
+    /// The case was called after a case that comes later in the same sequence
+    OutOfOrder {
+        /// Position of the case in its sequence
+        position: usize,
+        /// Position of the case that was called before it
+        after: usize,
+    },
+    /// The case received more concurrent requests than allowed
+    Concurrency {
+        /// Limit set with [`crate::CaseBuilder::max_concurrent`]
+        max: usize,
+        /// Highest number of concurrent requests received
+        got: usize,
+    },
+}
+
+/// Information about the mock case that matched a request
+///
+/// This is passed to the closures given to [`crate::CaseBuilder::returning_with_ctx`], so
+/// responses can vary between calls without keeping a counter around.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaseContext {
+    call: usize,
+    seen: usize,
+    name: String,
+}
+
+impl CaseContext {
+    /// Position of this call for the mock case, starting at 0
+    pub fn call(&self) -> usize {
+        self.call
+    }
+
+    /// Number of requests the mock case received so far, including this one
+    ///
+    /// This can be higher than `call() + 1` if other requests matched the case concurrently.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Name of the mock case, or of its matcher if no name was set
+    pub fn name(&self) -> &str {
+        &self.name
     }
 }
 
 impl Checkpoint {
-    pub fn check(expected: usize, got: usize) -> Option<Self> {
-        if expected == got {
-            None
-        } else {
-            Some(Self { expected, got })
+    /// Name of the mock case, or of its matcher if no name was set
+    pub fn case(&self) -> &str {
+        &self.case
+    }
+
+    /// Description of what the matcher of the case expects, if it has one
+    pub fn matcher(&self) -> Option<&str> {
+        self.matcher.as_deref()
+    }
+
+    /// Reason why the case failed the checkpoint
+    pub fn kind(&self) -> CheckpointKind {
+        self.kind
+    }
+
+    /// Expected number of calls, if the case wasn't called the expected number of times
+    pub fn expected(&self) -> Option<usize> {
+        match self.kind {
+            CheckpointKind::Count { expected, .. } => Some(expected),
+            _ => None,
+        }
+    }
+
+    /// Actual number of calls, or of concurrent requests for [`CheckpointKind::Concurrency`]
+    ///
+    /// This returns the position of the case called before this one for
+    /// [`CheckpointKind::OutOfOrder`].
+    pub fn got(&self) -> usize {
+        match self.kind {
+            CheckpointKind::Count { got, .. } | CheckpointKind::Concurrency { got, .. } => got,
+            CheckpointKind::OutOfOrder { after, .. } => after,
+        }
+    }
+}
+
+impl std::fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let case = &self.case;
+        match self.kind {
+            CheckpointKind::Count { expected, got } => {
+                write!(f, "case `{case}`: expected {expected}, got {got}")
+            }
+            CheckpointKind::OutOfOrder { position, after } => write!(
+                f,
+                "case `{case}`: sequence position {position} was called after position {after}"
+            ),
+            CheckpointKind::Concurrency { max, got } => write!(
+                f,
+                "case `{case}`: expected at most {max} concurrent requests, got {got}"
+            ),
         }
     }
 }
@@ -59,16 +457,16 @@ impl Checkpoint {
 mod tests {
     use std::convert::Infallible;
 
-    use hyper::{Request, Response, StatusCode};
+    use hyper::{body::Bytes, Request, Response, StatusCode};
 
     use super::*;
 
     #[test]
     fn case_new() {
         let _case = Case::new(
-            |_req: &Request<String>| Ok::<_, Infallible>(true),
+            |_req: &Request<Bytes>| Ok::<_, Infallible>(true),
             |_| async { Response::builder().status(StatusCode::OK).body("") },
-            None,
+            CaseOptions::default(),
         );
     }
 }
@@ -0,0 +1,98 @@
+use hyper::Request;
+use std::{fmt, future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+/// Future returned by [`Clock::sleep`]
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send + Sync>>;
+
+/// Source of time for the delays and time windows of the connector
+///
+/// This is used for the delays between chunks with [`crate::Chunked`] and [`crate::Sse`], for
+/// delays injected with [`crate::FaultProfile`], for connection delays set with
+/// [`crate::Builder::connect_delay`], and for the time windows of [`crate::RateLimited`].
+///
+/// By default, the connector uses [`TokioClock`], which follows `tokio::time::pause` and
+/// `tokio::time::advance`: with a paused clock, tests can check delays and timeouts without
+/// waiting for them. Tests that need more control can set their own implementation with
+/// [`crate::Builder::clock`].
+///
+/// ## Example
+///
+/// ```rust
+/// use mock_http_connector::{Clock, SleepFuture};
+/// use std::{sync::Mutex, time::Duration};
+/// use tokio::time::Instant;
+///
+/// /// Clock that never waits, and moves forward by the duration of each sleep instead
+/// #[derive(Debug)]
+/// struct InstantClock(Mutex<Instant>);
+///
+/// impl Clock for InstantClock {
+///     fn now(&self) -> Instant {
+///         *self.0.lock().unwrap()
+///     }
+///
+///     fn sleep(&self, duration: Duration) -> SleepFuture {
+///         *self.0.lock().unwrap() += duration;
+///         Box::pin(async {})
+///     }
+/// }
+/// ```
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current instant
+    fn now(&self) -> Instant;
+
+    /// Returns a future that completes once `duration` has elapsed
+    fn sleep(&self, duration: Duration) -> SleepFuture;
+}
+
+/// [`Clock`] backed by [`tokio::time`]
+///
+/// This requires the time driver to be enabled on the tokio runtime.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// [`Clock`] of the connector
+///
+/// This is stored in the [`Request`] extensions, so [`crate::Returning`] implementations use the
+/// same clock as the connector.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new<C>(clock: C) -> Self
+    where
+        C: Clock + 'static,
+    {
+        Self(Arc::new(clock))
+    }
+
+    /// Returns the clock of the connector that received `req`
+    pub fn of<B>(req: &Request<B>) -> Self {
+        req.extensions().get::<Self>().cloned().unwrap_or_default()
+    }
+
+    pub fn now(&self) -> Instant {
+        self.0.now()
+    }
+
+    pub fn sleep(&self, duration: Duration) -> SleepFuture {
+        self.0.sleep(duration)
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(TokioClock)
+    }
+}
@@ -0,0 +1,48 @@
+//! Minimal base64 encoding (RFC 4648), with the standard alphabet and padding
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode `data` as base64
+pub(crate) fn encode_base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity((data.len() + 2) / 3 * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case(b"", "")]
+    #[case(b"f", "Zg==")]
+    #[case(b"fo", "Zm8=")]
+    #[case(b"foo", "Zm9v")]
+    #[case(b"foob", "Zm9vYg==")]
+    #[case(b"fooba", "Zm9vYmE=")]
+    #[case(b"foobar", "Zm9vYmFy")]
+    #[case(b"user:pass", "dXNlcjpwYXNz")]
+    #[case(b"\xff\xfe\xfd", "//79")]
+    fn base64_encode(#[case] data: &[u8], #[case] expected: &str) {
+        assert_that!(encode_base64(data)).is_equal_to(expected.to_string());
+    }
+}
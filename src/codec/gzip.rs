@@ -0,0 +1,409 @@
+//! Minimal gzip, zlib, and deflate codec (RFC 1950, 1951, and 1952)
+
+/// Compress a payload into a gzip payload
+///
+/// This only uses stored deflate blocks: the output is larger than the input, but any gzip
+/// decoder can read it.
+pub(crate) fn encode_gzip(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+
+    let mut output = vec![0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff];
+
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        output.extend([0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        output.push(blocks.peek().is_none() as u8);
+        output.extend(len.to_le_bytes());
+        output.extend((!len).to_le_bytes());
+        output.extend(block);
+    }
+
+    output.extend(crc32(data).to_le_bytes());
+    output.extend((data.len() as u32).to_le_bytes());
+    output
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Decompress a gzip payload
+pub(crate) fn decode_gzip(data: &[u8]) -> Option<Vec<u8>> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    if data.len() < 18 || data[0..3] != [0x1f, 0x8b, 0x08] {
+        return None;
+    }
+    let flags = data[3];
+    let mut pos = 10;
+
+    if flags & FEXTRA != 0 {
+        let len = u16::from_le_bytes([*data.get(pos)?, *data.get(pos + 1)?]) as usize;
+        pos += 2 + len;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            pos += data.get(pos..)?.iter().position(|b| *b == 0)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+
+    inflate(data.get(pos..)?)
+}
+
+/// Decompress a zlib payload, or a raw deflate payload as some clients send for `deflate`
+pub(crate) fn decode_deflate(data: &[u8]) -> Option<Vec<u8>> {
+    let is_zlib = data.len() >= 2
+        && data[0] & 0x0f == 8
+        && data[1] & 0x20 == 0
+        && u16::from_be_bytes([data[0], data[1]]) % 31 == 0;
+
+    if is_zlib {
+        inflate(&data[2..])
+    } else {
+        inflate(data)
+    }
+}
+
+/// Decompress a raw deflate stream
+pub(crate) fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = BitReader::new(data);
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored(&mut reader, &mut output)?,
+            1 => {
+                let (lengths, distances) = fixed_tables();
+                codes(&mut reader, &mut output, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic_tables(&mut reader)?;
+                codes(&mut reader, &mut output, &lengths, &distances)?;
+            }
+            _ => return None,
+        }
+
+        if last {
+            return Some(output);
+        }
+    }
+}
+
+struct BitReader<'d> {
+    data: &'d [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl<'d> BitReader<'d> {
+    fn new(data: &'d [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buf: 0,
+            count: 0,
+        }
+    }
+
+    /// Read `n` bits, least significant bit first
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        while self.count < n {
+            self.buf |= (*self.data.get(self.pos)? as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+
+        let value = self.buf & ((1 << n) - 1);
+        self.buf >>= n;
+        self.count -= n;
+        Some(value)
+    }
+
+    /// Discard the remaining bits of the current byte
+    fn align(&mut self) {
+        self.buf = 0;
+        self.count = 0;
+    }
+}
+
+/// Canonical Huffman code, as a count of codes per length and the symbols ordered by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader<'_>) -> Option<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+
+        None
+    }
+}
+
+fn stored(reader: &mut BitReader<'_>, output: &mut Vec<u8>) -> Option<()> {
+    reader.align();
+    let header = reader.data.get(reader.pos..reader.pos + 4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen {
+        return None;
+    }
+    reader.pos += 4;
+
+    output.extend(reader.data.get(reader.pos..reader.pos + len as usize)?);
+    reader.pos += len as usize;
+    Some(())
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_tables(reader: &mut BitReader<'_>) -> Option<(Huffman, Huffman)> {
+    const ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+
+    let nlen = reader.bits(5)? as usize + 257;
+    let ndist = reader.bits(5)? as usize + 1;
+    let ncode = reader.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return None;
+    }
+
+    let mut code_lengths = [0; 19];
+    for &index in &ORDER[..ncode] {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(nlen + ndist);
+    while lengths.len() < nlen + ndist {
+        let (value, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last()?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            18 => (0, 11 + reader.bits(7)?),
+            _ => return None,
+        };
+        lengths.extend(std::iter::repeat(value).take(repeat as usize));
+    }
+    if lengths.len() != nlen + ndist {
+        return None;
+    }
+
+    Some((
+        Huffman::new(&lengths[..nlen]),
+        Huffman::new(&lengths[nlen..]),
+    ))
+}
+
+fn codes(
+    reader: &mut BitReader<'_>,
+    output: &mut Vec<u8>,
+    lengths: &Huffman,
+    distances: &Huffman,
+) -> Option<()> {
+    const LENGTH_BASE: [u16; 29] = [
+        3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+        131, 163, 195, 227, 258,
+    ];
+    const LENGTH_EXTRA: [u8; 29] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+    ];
+    const DIST_BASE: [u16; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+        2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    const DIST_EXTRA: [u8; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12,
+        13, 13,
+    ];
+
+    loop {
+        let symbol = lengths.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Some(()),
+            _ => {
+                let index = symbol - 257;
+                let length = *LENGTH_BASE.get(index)? as usize
+                    + reader.bits(*LENGTH_EXTRA.get(index)? as u32)? as usize;
+
+                let index = distances.decode(reader)? as usize;
+                let distance = *DIST_BASE.get(index)? as usize
+                    + reader.bits(*DIST_EXTRA.get(index)? as u32)? as usize;
+                if distance > output.len() {
+                    return None;
+                }
+
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn gzip_fixed() {
+        let data = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x57, 0x28, 0xcf, 0x2f, 0xca, 0x49, 0x01, 0x00, 0x85, 0x11, 0x4a, 0x0d, 0x0b,
+            0x00, 0x00, 0x00,
+        ];
+
+        assert_that!(decode_gzip(&data))
+            .is_some()
+            .is_equal_to(b"hello world".to_vec());
+    }
+
+    #[test]
+    fn gzip_dynamic() {
+        let data = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x65, 0xd0, 0x4d, 0x0a,
+            0x83, 0x40, 0x0c, 0x86, 0xe1, 0xab, 0x0c, 0x59, 0xbb, 0xf0, 0xf3, 0xdf, 0x5e, 0xa5,
+            0x74, 0x21, 0x38, 0xb4, 0xc2, 0x8c, 0x82, 0x53, 0xe8, 0x42, 0xbc, 0xbb, 0xd3, 0x52,
+            0x48, 0x48, 0x56, 0x81, 0xbc, 0xd9, 0xe4, 0x39, 0x28, 0xfa, 0x94, 0xa6, 0xa7, 0xa7,
+            0x9b, 0xa3, 0x97, 0x0f, 0x61, 0x73, 0x9f, 0x6d, 0x0f, 0x33, 0x15, 0x8e, 0x96, 0xb7,
+            0x8f, 0x29, 0xef, 0xef, 0x07, 0x2d, 0x73, 0x9e, 0x65, 0xde, 0xad, 0x53, 0xfc, 0x9d,
+            0x7e, 0x9b, 0x2b, 0xe9, 0x2c, 0xfe, 0x0d, 0xba, 0x81, 0x5b, 0xa5, 0x5b, 0xc5, 0xad,
+            0xd6, 0xad, 0xe6, 0xd6, 0xe8, 0xd6, 0x70, 0x6b, 0x75, 0x6b, 0xb9, 0x75, 0xba, 0x75,
+            0xdc, 0x7a, 0xdd, 0x7a, 0x6e, 0x83, 0x6e, 0x03, 0xb7, 0x51, 0xb7, 0x51, 0xfc, 0x6e,
+            0x60, 0x20, 0x65, 0x2c, 0x8d, 0xb0, 0x81, 0xc1, 0x81, 0xd0, 0x81, 0xe1, 0x81, 0xf0,
+            0x81, 0x01, 0x82, 0x10, 0x82, 0x21, 0x82, 0x30, 0x82, 0x41, 0x82, 0x50, 0x82, 0x61,
+            0x82, 0x70, 0x82, 0x81, 0x82, 0x90, 0x82, 0xa1, 0x42, 0xb6, 0x7a, 0x9c, 0x17, 0x50,
+            0x8c, 0x06, 0x0a, 0x6a, 0x02, 0x00, 0x00,
+        ];
+        let items = (0..20)
+            .map(|i| format!(r#"{{"id": {i}, "name": "item {i}"}}"#))
+            .collect::<Vec<_>>()
+            .join(",");
+        let expected = format!(r#"{{"message": "hello world", "items": [{items}]}}"#);
+
+        assert_that!(decode_gzip(&data))
+            .is_some()
+            .is_equal_to(expected.into_bytes());
+    }
+
+    #[test]
+    fn deflate_zlib() {
+        let data = [
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0x28, 0xcf, 0x2f, 0xca, 0x49, 0x01,
+            0x00, 0x1a, 0x0b, 0x04, 0x5d,
+        ];
+
+        assert_that!(decode_deflate(&data))
+            .is_some()
+            .is_equal_to(b"hello world".to_vec());
+    }
+
+    #[test]
+    fn deflate_raw_stored() {
+        let data = [
+            0x01, 0x06, 0x00, 0xf9, 0xff, 0x73, 0x74, 0x6f, 0x72, 0x65, 0x64,
+        ];
+
+        assert_that!(decode_deflate(&data))
+            .is_some()
+            .is_equal_to(b"stored".to_vec());
+    }
+
+    #[rstest]
+    #[case(b"".to_vec())]
+    #[case(b"hello world".to_vec())]
+    #[case(vec![0x42; 70_000])]
+    fn gzip_roundtrip(#[case] data: Vec<u8>) {
+        let encoded = encode_gzip(&data);
+
+        assert_that!(decode_gzip(&encoded))
+            .is_some()
+            .is_equal_to(data);
+    }
+
+    #[test]
+    fn gzip_encode() {
+        let encoded = encode_gzip(b"hello world");
+
+        // CRC32 and size of the uncompressed payload
+        assert_that!(encoded[encoded.len() - 8..].to_vec())
+            .is_equal_to(vec![0x85, 0x11, 0x4a, 0x0d, 0x0b, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn gzip_invalid() {
+        assert_that!(decode_gzip(b"hello world, not gzip")).is_none();
+    }
+}
@@ -0,0 +1,264 @@
+//! Decoders for request bodies
+
+use hyper::{
+    body::Bytes,
+    header::{HeaderName, CONTENT_LENGTH, TRANSFER_ENCODING},
+    HeaderMap,
+};
+
+use crate::Error;
+
+mod base64;
+pub(crate) use base64::encode_base64;
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(feature = "gzip")]
+pub(crate) use gzip::{decode_deflate, decode_gzip, encode_gzip};
+mod multipart;
+pub(crate) use multipart::{parse_boundary, parse_multipart, parse_params, FormPart};
+mod sha256;
+#[cfg(feature = "sigv4")]
+pub(crate) use sha256::{encode_hex, hmac_sha256};
+pub(crate) use sha256::{parse_digest, Sha256};
+
+/// Request body, as sent on the wire before any decoding
+///
+/// This is stored in the request extensions when the body passed to matchers differs from
+/// what the client sent, such as for chunked transfer-encoding.
+#[derive(Debug, Clone)]
+pub(crate) struct RawBody(pub Bytes);
+
+/// Trailers sent after a body with chunked transfer-encoding
+///
+/// This is stored in the request extensions, as [`hyper::Request`] doesn't have a place for
+/// trailers.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Trailers(pub HeaderMap);
+
+/// Decoded body with chunked transfer-encoding
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Chunks {
+    pub body: Vec<u8>,
+    pub trailers: Vec<(String, String)>,
+    /// Length of the encoded body, including the framing and trailers
+    pub len: usize,
+}
+
+/// Decoder for bodies with chunked transfer-encoding, as they are received
+///
+/// Each call to [`ChunkedDecoder::decode`] resumes after the last complete chunk or trailer, so
+/// a body received in many parts is only decoded once.
+#[derive(Debug, Default)]
+pub(crate) struct ChunkedDecoder {
+    /// Length of the data already decoded
+    pos: usize,
+    /// The last chunk was received, and the rest of the body is trailers
+    trailers: bool,
+    chunks: Chunks,
+}
+
+impl ChunkedDecoder {
+    /// Decode the data received since the previous call
+    ///
+    /// `encoded` must start with the data passed to previous calls. This returns the length of the
+    /// encoded body once it is complete, or an error if the framing is invalid.
+    pub fn decode(&mut self, encoded: &[u8]) -> Result<Option<usize>, Error> {
+        let malformed = |reason: &str| Error::Chunked(reason.to_string());
+
+        loop {
+            let data = &encoded[self.pos..];
+            let Some((line, rest)) = split_line(data) else {
+                return Ok(None);
+            };
+            let line_len = data.len() - rest.len();
+
+            // Trailers, until an empty line
+            if self.trailers {
+                self.pos += line_len;
+                if line.is_empty() {
+                    self.chunks.len = self.pos;
+                    return Ok(Some(self.pos));
+                }
+
+                let (name, value) = std::str::from_utf8(line)
+                    .ok()
+                    .and_then(|line| line.split_once(':'))
+                    .ok_or_else(|| malformed("invalid trailer"))?;
+                self.chunks
+                    .trailers
+                    .push((name.trim().to_string(), value.trim().to_string()));
+                continue;
+            }
+
+            let size = line.split(|b| *b == b';').next().unwrap_or_default();
+            let size = std::str::from_utf8(size)
+                .ok()
+                .and_then(|size| usize::from_str_radix(size.trim(), 16).ok())
+                .ok_or_else(|| malformed("invalid chunk size"))?;
+
+            if size == 0 {
+                self.pos += line_len;
+                self.trailers = true;
+                continue;
+            }
+
+            let chunk_len = size
+                .checked_add(2)
+                .ok_or_else(|| malformed("invalid chunk size"))?;
+            if rest.len() < chunk_len {
+                return Ok(None);
+            }
+            if &rest[size..chunk_len] != b"\r\n" {
+                return Err(malformed("missing CRLF after chunk"));
+            }
+            self.chunks.body.extend(&rest[..size]);
+            self.pos += line_len + chunk_len;
+        }
+    }
+
+    /// Return the decoded body and trailers, once [`ChunkedDecoder::decode`] returned a length
+    pub fn finish(self) -> Chunks {
+        self.chunks
+    }
+}
+
+/// Length of a complete request, including the head and the payload
+///
+/// `head_len` is the length of the request line and headers. This returns `None` if the payload
+/// is incomplete, based on the `content-length` or `transfer-encoding` headers, or an error if
+/// the chunked framing is invalid. `chunked` keeps track of the chunks already received, so it
+/// must be reset for each request.
+pub(crate) fn request_len(
+    headers: &[httparse::Header],
+    head_len: usize,
+    data: &[u8],
+    chunked: &mut ChunkedDecoder,
+) -> Result<Option<usize>, Error> {
+    if is_chunked(headers) {
+        return Ok(chunked.decode(&data[head_len..])?.map(|len| head_len + len));
+    }
+
+    // Requests without framing information don't have a payload
+    let body_len = content_length(headers).unwrap_or(0);
+    Ok((data.len() >= head_len + body_len).then_some(head_len + body_len))
+}
+
+/// Returns `true` if the payload uses chunked transfer-encoding
+pub(crate) fn is_chunked(headers: &[httparse::Header]) -> bool {
+    find_header(headers, &TRANSFER_ENCODING)
+        .is_some_and(|value| value.eq_ignore_ascii_case(b"chunked"))
+}
+
+/// Length of the payload from the `content-length` header, if there is a valid one
+pub(crate) fn content_length(headers: &[httparse::Header]) -> Option<usize> {
+    std::str::from_utf8(find_header(headers, &CONTENT_LENGTH)?)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn find_header<'h>(headers: &[httparse::Header<'h>], name: &HeaderName) -> Option<&'h [u8]> {
+    headers
+        .iter()
+        .find(|header| header.name.eq_ignore_ascii_case(name.as_str()))
+        .map(|header| header.value)
+}
+
+/// Split the data at the first CRLF
+fn split_line(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let pos = data.windows(2).position(|w| w == b"\r\n")?;
+    Some((&data[..pos], &data[pos + 2..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    fn decode_chunked(encoded: &[u8]) -> Option<Chunks> {
+        let mut decoder = ChunkedDecoder::default();
+        decoder.decode(encoded).ok()??;
+        Some(decoder.finish())
+    }
+
+    #[rstest]
+    #[case(b"0\r\n\r\n", Some(""))]
+    #[case(b"6\r\nhello \r\n6\r\nworld!\r\n0\r\n\r\n", Some("hello world!"))]
+    #[case(
+        b"6;ext=1\r\nhello \r\nC\r\nworld!world!\r\n0\r\n\r\n",
+        Some("hello world!world!")
+    )]
+    #[case(b"6\r\nhello \r\n", None)]
+    #[case(b"6\r\nhello \r\n0\r\n", None)]
+    #[case(b"5\r\nhello \r\n0\r\n\r\n", None)]
+    #[case(b"zz\r\nhello \r\n0\r\n\r\n", None)]
+    #[case(b"ffffffffffffffff\r\nhello \r\n0\r\n\r\n", None)]
+    fn chunked_decode(#[case] data: &[u8], #[case] expected: Option<&str>) {
+        let decoded = decode_chunked(data).map(|c| String::from_utf8(c.body).unwrap());
+        assert_that!(decoded).is_equal_to(expected.map(ToString::to_string));
+    }
+
+    #[test]
+    fn chunked_decode_trailers() {
+        let decoded = decode_chunked(b"5\r\nhello\r\n0\r\nx-checksum: abc\r\n\r\n");
+        assert_that!(decoded).is_some().is_equal_to(Chunks {
+            body: b"hello".to_vec(),
+            trailers: vec![("x-checksum".to_string(), "abc".to_string())],
+            len: 32,
+        });
+    }
+
+    #[rstest]
+    #[case(b"GET / HTTP/1.1\r\n\r\n", Some(18))]
+    #[case(b"GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n", Some(18))]
+    #[case(b"POST / HTTP/1.1\r\ncontent-length: 5\r\n\r\nhello", Some(43))]
+    #[case(b"POST / HTTP/1.1\r\ncontent-length: 5\r\n\r\nhel", None)]
+    #[case(
+        b"POST / HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+        Some(62)
+    )]
+    #[case(
+        b"POST / HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n5\r\nhello\r\n",
+        None
+    )]
+    fn test_request_len(#[case] data: &[u8], #[case] expected: Option<usize>) {
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let mut req = httparse::Request::new(&mut headers);
+        let head_len = req.parse(data).unwrap().unwrap();
+
+        let len = request_len(req.headers, head_len, data, &mut ChunkedDecoder::default());
+        assert_that!(len.ok()).is_equal_to(Some(expected));
+    }
+
+    #[rstest]
+    #[case(b"5\r\nhello!\r\n0\r\n\r\n", "missing CRLF after chunk")]
+    #[case(b"zz\r\nhello\r\n0\r\n\r\n", "invalid chunk size")]
+    #[case(b"ffffffffffffffff\r\nhello\r\n", "invalid chunk size")]
+    #[case(b"0\r\nx-checksum\r\n\r\n", "invalid trailer")]
+    fn chunked_decoder_malformed(#[case] data: &[u8], #[case] expected: &str) {
+        let err = ChunkedDecoder::default().decode(data).unwrap_err();
+        assert_that!(err.to_string())
+            .is_equal_to(format!("invalid chunked transfer-encoding: {expected}"));
+    }
+
+    #[test]
+    fn chunked_decoder_resume() {
+        // GIVEN a body received one byte at a time
+        let data = b"5\r\nhello\r\n6\r\n world\r\n0\r\nx-checksum: abc\r\n\r\n";
+        let mut decoder = ChunkedDecoder::default();
+
+        // WHEN decoding it after each byte
+        let lens = (1..=data.len())
+            .map(|end| decoder.decode(&data[..end]).unwrap())
+            .collect::<Vec<_>>();
+
+        // THEN it is only complete once the last byte is received
+        assert_that!(lens[..data.len() - 1].iter().all(Option::is_none)).is_true();
+        assert_that!(lens[data.len() - 1]).is_equal_to(Some(data.len()));
+        assert_that!(decoder.chunks.body).is_equal_to(b"hello world".to_vec());
+        assert_that!(decoder.chunks.trailers)
+            .is_equal_to(vec![("x-checksum".to_string(), "abc".to_string())]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Minimal `multipart/*` body parser (RFC 7578)
+
+/// Part of a `multipart/form-data` body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FormPart<'b> {
+    /// Field name, from the `content-disposition` header
+    pub name: Option<String>,
+    /// File name, from the `content-disposition` header
+    pub filename: Option<String>,
+    /// Value of the `content-type` header of the part
+    pub content_type: Option<String>,
+    /// Content of the part
+    pub content: &'b [u8],
+}
+
+/// Parse the boundary from a `multipart/*` content type
+pub(crate) fn parse_boundary(content_type: &str) -> Option<String> {
+    let (essence, params) = content_type.split_once(';')?;
+    if !essence
+        .trim()
+        .get(..10)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("multipart/"))
+    {
+        return None;
+    }
+
+    parse_params(params)
+        .into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value)
+        .filter(|boundary| !boundary.is_empty())
+}
+
+/// Split a multipart body into its parts
+///
+/// This returns `None` if the body doesn't contain the boundary or isn't terminated.
+pub(crate) fn parse_multipart<'b>(boundary: &str, body: &'b [u8]) -> Option<Vec<FormPart<'b>>> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+
+    // Anything before the first delimiter is a preamble, and is ignored
+    let mut rest = &body[find(body, delimiter)? + delimiter.len()..];
+    let mut parts = Vec::new();
+    loop {
+        if rest.starts_with(b"--") {
+            return Some(parts);
+        }
+        // Transport padding is allowed after the delimiter
+        let start = find(rest, b"\r\n")?;
+        rest = &rest[start + 2..];
+
+        let end = find(rest, &[b"\r\n", delimiter].concat())?;
+        parts.push(parse_part(&rest[..end])?);
+        rest = &rest[end + 2 + delimiter.len()..];
+    }
+}
+
+/// Parse the headers and content of a single part
+fn parse_part(part: &[u8]) -> Option<FormPart<'_>> {
+    let (head, content) = if part.starts_with(b"\r\n") {
+        ("", &part[2..])
+    } else {
+        let end = find(part, b"\r\n\r\n")?;
+        (std::str::from_utf8(&part[..end]).ok()?, &part[end + 4..])
+    };
+
+    let mut form_part = FormPart {
+        name: None,
+        filename: None,
+        content_type: None,
+        content,
+    };
+    for line in head.split("\r\n").filter(|line| !line.is_empty()) {
+        let (key, value) = line.split_once(':')?;
+        let value = value.trim();
+        if key.trim().eq_ignore_ascii_case("content-disposition") {
+            let params = value.split_once(';').map_or("", |(_, params)| params);
+            for (key, value) in parse_params(params) {
+                if key.eq_ignore_ascii_case("name") {
+                    form_part.name = Some(value);
+                } else if key.eq_ignore_ascii_case("filename") {
+                    form_part.filename = Some(value);
+                }
+            }
+        } else if key.trim().eq_ignore_ascii_case("content-type") {
+            form_part.content_type = Some(value.to_string());
+        }
+    }
+
+    Some(form_part)
+}
+
+/// Parse `key=value` parameters separated by `;`, where values may be quoted
+pub(crate) fn parse_params(params: &str) -> Vec<(String, String)> {
+    let mut parsed = Vec::new();
+    let mut chars = params.chars().peekable();
+
+    loop {
+        let key = chars
+            .by_ref()
+            .take_while(|&c| c != '=')
+            .collect::<String>()
+            .trim_start_matches([';', ' ', '\t'])
+            .trim()
+            .to_string();
+        if key.is_empty() {
+            return parsed;
+        }
+
+        while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+            chars.by_ref().take_while(|&c| c != ';').for_each(drop);
+        } else {
+            value = chars.by_ref().take_while(|&c| c != ';').collect();
+            value = value.trim().to_string();
+        }
+
+        parsed.push((key, value));
+    }
+}
+
+/// Position of the first occurrence of `needle` in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case("multipart/form-data; boundary=abc", Some("abc"))]
+    #[case(
+        "Multipart/Form-Data; charset=utf-8; Boundary=\"a;b c\"",
+        Some("a;b c")
+    )]
+    #[case("multipart/mixed;boundary=abc", Some("abc"))]
+    #[case("application/json; boundary=abc", None)]
+    #[case("multipart/form-data", None)]
+    #[case("multipart/form-data; boundary=", None)]
+    fn multipart_boundary(#[case] content_type: &str, #[case] expected: Option<&str>) {
+        assert_that!(parse_boundary(content_type)).is_equal_to(expected.map(ToString::to_string));
+    }
+
+    #[rstest]
+    fn multipart_parts() {
+        let body = concat!(
+            "preamble\r\n",
+            "--abc\r\n",
+            "Content-Disposition: form-data; name=\"title\"\r\n",
+            "\r\n",
+            "Hello\r\n",
+            "--abc\r\n",
+            "Content-Disposition: form-data; name=\"file\"; filename=\"a \\\"b\\\".txt\"\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "line 1\r\nline 2\r\n",
+            "--abc\r\n",
+            "\r\n",
+            "anonymous\r\n",
+            "--abc--\r\n",
+        );
+
+        let parts = parse_multipart("abc", body.as_bytes()).unwrap();
+
+        assert_that!(parts).is_equal_to(vec![
+            FormPart {
+                name: Some("title".to_string()),
+                filename: None,
+                content_type: None,
+                content: b"Hello",
+            },
+            FormPart {
+                name: Some("file".to_string()),
+                filename: Some("a \"b\".txt".to_string()),
+                content_type: Some("text/plain".to_string()),
+                content: b"line 1\r\nline 2",
+            },
+            FormPart {
+                name: None,
+                filename: None,
+                content_type: None,
+                content: b"anonymous",
+            },
+        ]);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("--abc\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nvalue")]
+    #[case("--xyz\r\n\r\nvalue\r\n--xyz--")]
+    fn multipart_invalid(#[case] body: &str) {
+        assert_that!(parse_multipart("abc", body.as_bytes())).is_none();
+    }
+}
@@ -1,26 +1,63 @@
 use colored::Colorize;
-use hyper::{service::Service, Request, Uri};
+use hyper::{
+    body::Bytes,
+    client::connect::Connected,
+    http::{Extensions, HeaderName, HeaderValue},
+    service::Service,
+    Request, Response, StatusCode, Uri,
+};
 use std::{
     cmp::max,
-    collections::{BinaryHeap, HashSet},
-    future::{ready, Ready},
-    io,
-    str::from_utf8,
-    sync::{atomic::Ordering, Arc},
+    collections::HashSet,
+    fmt,
+    future::Future,
+    io::{self, IsTerminal},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 
+#[cfg(feature = "gzip")]
+use crate::codec::{decode_deflate, decode_gzip};
 use crate::{
-    builder::Builder, error::BoxError, response::ResponseFuture, stream::MockStream, Case, Error,
-    Level, Reason, Report,
+    builder::Builder,
+    case::{checkpoint, AddCase, InFlight},
+    clock::SharedClock,
+    codec::{Chunks, RawBody, Trailers},
+    diagnostics::Diagnostics,
+    error::BoxError,
+    handler::{BodyStreams, StreamedBody},
+    mismatch::{diff_lines, diff_position, DiffLine, Mismatch},
+    response::ResponseFuture,
+    stream::{ConnectionOptions, MockStream},
+    Case, CaseBuilder, CaseReport, ColorChoice, Error, ExpectContinue, FaultProfile, Level,
+    Metrics, MismatchReport, MockGuard, MockService, OnUnmatched, Reason, Report, Returning, Tls,
 };
+#[cfg(feature = "recording")]
+use crate::{Fixture, FixtureEntry, RecordedRequest, RecordedResponse};
+#[cfg(feature = "gzip")]
+use hyper::header::CONTENT_ENCODING;
+
+/// Header with the mock case that returned a response, see [`Builder::case_header`]
+const MOCK_CASE: HeaderName = HeaderName::from_static("x-mock-case");
 
 /// Mock connector for [`hyper::Client`]
 ///
 /// See the crate documentation for how to configure the connector.
+///
+/// ## Remark
+///
+/// The connector only speaks HTTP/1.1. Clients configured with `http2_only(true)` won't be able
+/// to send requests through it.
 #[derive(Default, Clone)]
 pub struct Connector {
     inner: Arc<InnerConnector>,
+    /// Checks the mock cases once the last clone of the connector is dropped
+    _verify: Option<Arc<VerifyOnDrop>>,
 }
 
 impl Connector {
@@ -32,169 +69,924 @@ impl Connector {
     /// Check if all the mock cases were called the right amount of time
     ///
     /// If not, this will return an error with all the mock cases that failed.
+    ///
+    /// ## Panics
+    ///
+    /// With [`Level::Strict`] or [`OnUnmatched::Panic`], this panics with the diagnostic report
+    /// of the first request that didn't match any mock case, if there was one.
     pub fn checkpoint(&self) -> Result<(), Error> {
+        if let Some(report) = self.inner.take_strict_report() {
+            panic!("{report}");
+        }
         self.inner.checkpoint()
     }
 
+    /// Return statistics for all the mock cases, in the order they are matched
+    ///
+    /// Unlike [`Connector::checkpoint`], this doesn't fail if some cases weren't called the right
+    /// number of times yet. See [`CaseReport`] for more information.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let mut builder = Connector::builder();
+    /// builder.expect().times(1).returning("OK")?;
+    /// let connector = builder.build();
+    ///
+    /// assert!(connector.report().iter().all(|case| case.seen == 0));
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn report(&self) -> Vec<CaseReport> {
+        self.inner.cases().iter().map(Case::report).collect()
+    }
+
+    /// Create a new expected case on an already-built connector
+    ///
+    /// This works the same way as [`Builder::expect`], but can be used after the connector was
+    /// built, for example by test fixtures that share a single connector.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::{Connector, Error};
+    /// # || {
+    /// let connector = Connector::builder().build();
+    /// connector
+    ///     .expect()
+    ///     .with_uri("https://test.example/added-later")
+    ///     .returning("OK")?;
+    /// # Ok::<_, Error>(())
+    /// # };
+    /// ```
+    pub fn expect(&self) -> CaseBuilder<'_> {
+        CaseBuilder::new(self.inner.as_ref())
+    }
+
+    /// Return the report for the last request that didn't match any mock case
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let connector = Connector::builder().build();
+    /// assert!(connector.last_report().is_none());
+    /// ```
+    pub fn last_report(&self) -> Option<MismatchReport> {
+        self.inner
+            .last_report
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            .map(|last| last.report.clone())
+    }
+
+    /// Render the diagnostic report for the last request that didn't match any mock case
+    ///
+    /// This is the same report the connector prints for unmatched requests, without colors, so it
+    /// can be used in snapshot tests. Headers and the attributes that didn't match are sorted, so
+    /// the output is the same between runs.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let connector = Connector::builder().build();
+    /// assert!(connector.render_report_plain().is_none());
+    /// ```
+    pub fn render_report_plain(&self) -> Option<String> {
+        self.inner
+            .last_report
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            .map(|last| ColorChoice::Never.apply(last.rendered.clone(), false))
+    }
+
+    /// Return the request/response pairs recorded by this connector
+    ///
+    /// This returns `None` if recording wasn't enabled with [`Builder::record`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use mock_http_connector::Connector;
+    /// let mut builder = Connector::builder();
+    /// builder.record();
+    /// let connector = builder.build();
+    ///
+    /// // Send requests through the connector...
+    ///
+    /// let fixture = connector.recording().unwrap();
+    /// # assert!(fixture.entries.is_empty());
+    /// ```
+    #[cfg(feature = "recording")]
+    pub fn recording(&self) -> Option<Fixture> {
+        self.inner.recording.as_ref().map(|recording| {
+            recording
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .clone()
+        })
+    }
+
+    /// Return the metrics collected for all the mock cases, in the order they are matched
+    ///
+    /// This returns `None` if metrics weren't enabled with [`Builder::metrics`]. See [`Metrics`]
+    /// for more information.
+    pub fn metrics(&self) -> Option<Metrics> {
+        if !self.inner.metrics {
+            return None;
+        }
+
+        Some(Metrics {
+            cases: self
+                .inner
+                .cases()
+                .iter()
+                .map(|case| case.metrics.metrics(case.name()))
+                .collect(),
+            unmatched: self.inner.unmatched.load(Ordering::Acquire),
+        })
+    }
+
+    /// Remove all mock cases from the connector
+    ///
+    /// This also removes the call counters for those cases, the reports and metrics of unmatched
+    /// requests, and the recorded requests, so a connector shared between tests can start again
+    /// with a clean slate.
+    pub fn reset(&self) {
+        self.inner.cases_mut().clear();
+        self.inner.take_strict_report();
+        *self
+            .inner
+            .last_report
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = None;
+        self.inner.unmatched.store(0, Ordering::Release);
+        #[cfg(feature = "recording")]
+        if let Some(recording) = &self.inner.recording {
+            recording
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .entries
+                .clear();
+        }
+    }
+
+    /// Create a new [`MockGuard`] to register mock cases for a limited scope
+    ///
+    /// Cases registered through the guard are checked and removed from the connector when the
+    /// guard is dropped. See [`MockGuard`] for more information.
+    pub fn scope(&self) -> MockGuard {
+        MockGuard::new(self.clone())
+    }
+
+    /// Create a new [`MockService`] that answers requests with the mock cases of this connector
+    ///
+    /// See [`MockService`] to mock at the service layer instead of the connector layer.
+    pub fn service(&self) -> MockService {
+        MockService::new(self.inner.clone())
+    }
+
+    /// Open a new [`MockStream`] to the given URI
+    ///
+    /// This is the connection that [`hyper::Client`] receives when it connects through the
+    /// connector, so it can be used with any code that expects an [`AsyncRead`] and
+    /// [`AsyncWrite`] transport. See [`MockStream`] for more information.
+    ///
+    /// The stream is opened immediately, without waiting for [`Builder::connect_delay`].
+    ///
+    /// This returns an error if the connector refuses connections to the URI, see
+    /// [`Builder::refuse_connection_to`], or if it is configured with [`Tls::Fail`] and the URI
+    /// uses the `https` scheme.
+    ///
+    /// [`AsyncRead`]: tokio::io::AsyncRead
+    /// [`AsyncWrite`]: tokio::io::AsyncWrite
+    pub fn stream(&self, uri: Uri) -> io::Result<MockStream> {
+        if self.inner.refuses(&uri) {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!(
+                    "connection refused: {}",
+                    uri.authority().map(|a| a.as_str()).unwrap_or_default()
+                ),
+            ));
+        }
+        if let (Tls::Fail(reason), Some("https")) = (&self.inner.tls, uri.scheme_str()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid peer certificate: {reason}"),
+            ));
+        }
+
+        Ok(MockStream::new(self.inner.clone(), uri))
+    }
+
+    pub(crate) fn inner(&self) -> &InnerConnector {
+        &self.inner
+    }
+
     pub(crate) fn from_inner(inner: InnerConnector) -> Self {
+        let inner = Arc::new(inner);
+        let verify = Some(Arc::new(VerifyOnDrop(inner.clone())));
         Self {
-            inner: Arc::new(inner),
+            inner,
+            _verify: verify,
+        }
+    }
+}
+
+/// Check the connector once the last clone is dropped
+///
+/// This panics with the report of a request that didn't match with [`Level::Strict`], and runs
+/// the checkpoint of the connector with [`Builder::verify_on_drop`].
+struct VerifyOnDrop(Arc<InnerConnector>);
+
+impl Drop for VerifyOnDrop {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+
+        if let Some(report) = self.0.take_strict_report() {
+            panic!("{report}");
+        }
+        if !self.0.verify_on_drop {
+            return;
+        }
+        if let Err(err) = self.0.checkpoint() {
+            panic!("{err}");
         }
     }
 }
 
+/// Future returned when connecting through the [`Connector`]
+pub type ConnectFuture = Pin<Box<dyn Future<Output = io::Result<MockStream>> + Send>>;
+
+/// Hook called for every request, see [`Builder::on_request`]
+pub(crate) type RequestHook = Box<dyn Fn(&Request<Bytes>) + Send + Sync>;
+
+/// Hook called for every response, see [`Builder::on_response`]
+pub(crate) type ResponseHook = Box<dyn Fn(&Request<()>, &mut Response<Bytes>) + Send + Sync>;
+
+/// Hook called for every new connection, see [`Builder::on_connect`]
+pub(crate) type ConnectHook = Box<dyn Fn(&Uri, Connected) -> Connected + Send + Sync>;
+
+/// Report for the last request that didn't match any mock case
+pub(crate) struct LastReport {
+    report: MismatchReport,
+    /// Diagnostic report, with colors
+    rendered: String,
+}
+
 #[derive(Default)]
 pub(crate) struct InnerConnector {
     pub level: Level,
-    pub cases: Vec<Case>,
+    pub cases: RwLock<Vec<Case>>,
+    pub fallback: Option<Box<dyn Returning + Send + Sync>>,
+    pub on_unmatched: OnUnmatched,
+    pub diagnostics: Diagnostics,
+    pub color: ColorChoice,
+    pub tls: Tls,
+    pub expect_continue: ExpectContinue,
+    pub max_requests: Option<usize>,
+    pub stream_bodies: bool,
+    pub verify_on_drop: bool,
+    pub case_header: bool,
+    pub strict: bool,
+    pub faults: Option<FaultProfile>,
+    pub clock: SharedClock,
+    pub last_report: Mutex<Option<LastReport>>,
+    /// Report of the first unmatched request with [`Level::Strict`], until it panics the test
+    pub strict_report: Mutex<Option<String>>,
+    pub metrics: bool,
+    pub unmatched: AtomicUsize,
+    pub on_request: Vec<RequestHook>,
+    pub on_response: Vec<ResponseHook>,
+    pub on_connect: Vec<ConnectHook>,
+    pub refused: Vec<String>,
+    pub refuse_unexpected_hosts: bool,
+    pub connect_delay: Option<Duration>,
+    pub connect_delays: Vec<(String, Duration)>,
+    #[cfg(feature = "recording")]
+    pub recording: Option<Arc<Mutex<Fixture>>>,
 }
 
 impl InnerConnector {
+    pub fn cases(&self) -> RwLockReadGuard<'_, Vec<Case>> {
+        self.cases.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn cases_mut(&self) -> RwLockWriteGuard<'_, Vec<Case>> {
+        self.cases.write().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Delay before connections to `uri` are established
+    pub fn connect_delay(&self, uri: &Uri) -> Option<Duration> {
+        uri.host()
+            .and_then(|host| {
+                self.connect_delays
+                    .iter()
+                    .find(|(delay_host, _)| delay_host.eq_ignore_ascii_case(host))
+            })
+            .map(|(_, delay)| *delay)
+            .or(self.connect_delay)
+    }
+
+    /// Returns `true` if connections to `uri` should be refused
+    pub fn refuses(&self, uri: &Uri) -> bool {
+        let authority = uri.authority().map(|a| a.as_str());
+        let host = uri.host();
+        if self.refused.iter().any(|refused| {
+            [authority, host]
+                .into_iter()
+                .flatten()
+                .any(|value| value.eq_ignore_ascii_case(refused))
+        }) {
+            return true;
+        }
+
+        self.refuse_unexpected_hosts && !self.cases().iter().any(|case| case.accepts_host(host))
+    }
+
     pub fn checkpoint(&self) -> Result<(), Error> {
-        let checkpoints = self
-            .cases
-            .iter()
-            .filter_map(|case| case.checkpoint())
-            .collect::<Vec<_>>();
+        checkpoint(self.cases().iter())
+    }
 
-        if checkpoints.is_empty() {
-            Ok(())
-        } else {
-            Err(Error::Checkpoint(checkpoints))
+    /// Take the report of an unmatched request with [`Level::Strict`], if there is one
+    pub fn take_strict_report(&self) -> Option<String> {
+        self.strict_report
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+    }
+
+    /// Create the [`BodyStreams`] of all the cases that can handle requests sent to `host`
+    pub(crate) fn body_streams(&self, host: Option<&str>) -> BodyStreams {
+        let mut streams = BodyStreams::default();
+        for case in self.cases().iter().filter(|case| case.accepts_host(host)) {
+            case.with.body_streams(&mut streams);
         }
+        streams
     }
 
     pub(crate) fn matches(
-        &self,
+        self: &Arc<Self>,
         req: httparse::Request,
         body: &[u8],
+        chunks: Option<Chunks>,
+        streamed: Option<StreamedBody>,
         uri: &Uri,
     ) -> Result<ResponseFuture, Error> {
-        let req = into_request(req, body, uri)?;
+        let mut req = into_request(req, body, chunks, uri)?;
+        if let Some(streamed) = streamed {
+            req.extensions_mut().insert(streamed);
+        }
+
+        // Matchers can be asynchronous, so the cases are evaluated as part of the response future
+        let connector = self.clone();
+        Ok(Box::pin(async move { connector.respond(req).await }))
+    }
+
+    /// Return the response of the first mock case matching `req`
+    pub(crate) async fn respond(&self, req: Request<Bytes>) -> Result<Response<Bytes>, BoxError> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            target: "mock_http_connector",
+            "request",
+            method = %req.method(),
+            uri = %req.uri(),
+        );
+
+        let fut = self.respond_inner(req);
+        #[cfg(feature = "tracing")]
+        let fut = tracing::Instrument::instrument(fut, span);
+        fut.await
+    }
+
+    async fn respond_inner(&self, mut req: Request<Bytes>) -> Result<Response<Bytes>, BoxError> {
+        req.extensions_mut().insert(self.clock.clone());
+        for hook in &self.on_request {
+            hook(&req);
+        }
+
+        // The request is consumed by the mock case, so keep its head for the response hooks
+        let head = (!self.on_response.is_empty()).then(|| request_head(&req));
+
+        let cases = self.cases().clone();
+        let mut res = self.match_cases(&cases, req).await?.await?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "mock_http_connector",
+            status = res.status().as_u16(),
+            "returning response",
+        );
+        if let Some(head) = head {
+            for hook in &self.on_response {
+                hook(&head, &mut res);
+            }
+        }
+        Ok(res)
+    }
 
+    async fn match_cases(
+        &self,
+        cases: &[Case],
+        mut req: Request<Bytes>,
+    ) -> Result<ResponseFuture, BoxError> {
+        let start = Instant::now();
         let mut reports = Vec::new();
 
-        for case in self.cases.iter() {
-            match case.with.with(&req)? {
+        // Route by host first, so cases for other hosts don't show up in the report
+        let host = req.uri().host().map(str::to_owned);
+        for (index, case) in cases
+            .iter()
+            .enumerate()
+            .filter(|(_, case)| case.accepts_host(host.as_deref()))
+        {
+            let mut extensions = Extensions::new();
+            match case.with.with_extensions(&req, &mut extensions).await? {
                 Report::Match => {
-                    case.seen.fetch_add(1, Ordering::Release);
-                    return Ok(case.returning.returning(req));
+                    // Requests over the concurrency limit are rejected without counting them as a
+                    // call to the case
+                    let in_flight = case.in_flight();
+                    let recorded = if in_flight.as_ref().is_some_and(InFlight::exceeded) {
+                        case.accepts(self.strict).map(|()| None)
+                    } else {
+                        case.record(self.strict).map(Some)
+                    };
+
+                    let call = match recorded {
+                        Ok(call) => call,
+                        Err(reason) => {
+                            #[cfg(feature = "tracing")]
+                            match reason {
+                                Reason::Scenario => tracing::trace!(
+                                    target: "mock_http_connector",
+                                    index,
+                                    case = %case.name(),
+                                    "mock case scenario isn't in the expected state",
+                                ),
+                                _ => tracing::trace!(
+                                    target: "mock_http_connector",
+                                    index,
+                                    case = %case.name(),
+                                    "mock case already received all its calls",
+                                ),
+                            }
+                            reports.push((case, [reason].into()));
+                            continue;
+                        }
+                    };
+
+                    // Only requests handled by the case count towards its peak concurrency
+                    if let Some(in_flight) = &in_flight {
+                        in_flight.commit();
+                    }
+                    let Some(call) = call else {
+                        let res = Response::builder()
+                            .status(StatusCode::TOO_MANY_REQUESTS)
+                            .body(Bytes::new());
+                        return Ok(Box::pin(async move { Ok(res?) }));
+                    };
+
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        target: "mock_http_connector",
+                        index,
+                        case = %case.name(),
+                        call,
+                        "matched mock case",
+                    );
+                    if self.metrics {
+                        case.metrics.record(start.elapsed(), req.body().len());
+                    }
+                    req.extensions_mut().extend(extensions);
+                    req.extensions_mut().insert(case.context(call));
+
+                    #[cfg(feature = "recording")]
+                    let fut = match &self.recording {
+                        Some(recording) => record(recording.clone(), case, req),
+                        None => case.returning.returning(req),
+                    };
+                    #[cfg(not(feature = "recording"))]
+                    let fut = case.returning.returning(req);
+
+                    let mut connection = case.connection();
+                    let fut = match case.faults().or(self.faults.as_ref()) {
+                        Some(faults) => faults.apply(fut, &mut connection, &self.clock),
+                        None => fut,
+                    };
+                    let fut = with_connection(fut, connection, in_flight);
+                    if self.case_header {
+                        return Ok(with_case_header(fut, case.custom_name(), index));
+                    }
+                    return Ok(fut);
                 }
                 Report::Mismatch(reasons) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(
+                        target: "mock_http_connector",
+                        index,
+                        case = %case.name(),
+                        reasons = ?reasons.iter().map(Reason::as_str).collect::<Vec<_>>(),
+                        "mock case didn't match",
+                    );
                     reports.push((case, reasons));
                 }
             }
         }
 
-        // Couldn't find a match, log the error
+        // Couldn't find a match, store the report and use the fallback if there is one
+        if self.metrics {
+            self.unmatched.fetch_add(1, Ordering::AcqRel);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "mock_http_connector", "no mock case matched");
+        let rendered = format_report(&req, &reports);
+        *self
+            .last_report
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(LastReport {
+            report: MismatchReport::new(&req, &reports),
+            rendered: rendered.clone(),
+        });
+
+        if let Some(fallback) = &self.fallback {
+            return Ok(fallback.returning(req));
+        }
+
+        // Otherwise, log the error
+        if self.on_unmatched == OnUnmatched::Panic || self.level >= Level::Strict {
+            // Panicking here would only abort the connection task, so the report is returned to
+            // the client, and the test panics on the next checkpoint or when the connector is
+            // dropped
+            self.strict_report
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .get_or_insert_with(|| {
+                    self.color
+                        .apply(rendered.clone(), io::stderr().is_terminal())
+                });
+            return Err(Error::Unmatched(self.color.apply(rendered, false)).into());
+        }
         if self.level >= Level::Missing {
-            print_report(&req, reports);
+            self.diagnostics
+                .emit(&self.color.apply(rendered, self.diagnostics.is_terminal()));
+        }
+
+        match self.on_unmatched {
+            OnUnmatched::Status(status) => {
+                let res = Response::builder().status(status).body(Bytes::new());
+                Ok(Box::pin(async move { Ok(res?) }))
+            }
+            _ => Err(Error::NotFound(Box::new(req)).into()),
         }
-        Err(Error::NotFound(req))
+    }
+}
+
+impl AddCase for InnerConnector {
+    fn add_case(&self, case: Case) {
+        let mut cases = self.cases_mut();
+        // Keep cases sorted by descending priority, then by registration order
+        let index = cases
+            .iter()
+            .position(|c| c.priority() < case.priority())
+            .unwrap_or(cases.len());
+        cases.insert(index, case);
     }
 }
 
 impl Service<Uri> for Connector {
     type Response = MockStream;
     type Error = io::Error;
-    type Future = Ready<Result<Self::Response, Self::Error>>;
+    type Future = ConnectFuture;
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         Poll::Ready(Ok(()))
     }
 
     fn call(&mut self, req: Uri) -> Self::Future {
-        ready(Ok(MockStream::new(self.inner.clone(), req)))
+        let delay = self.inner.connect_delay(&req);
+        let connector = self.clone();
+        Box::pin(async move {
+            if let Some(delay) = delay {
+                connector.inner.clock.sleep(delay).await;
+            }
+            connector.stream(req)
+        })
+    }
+}
+
+/// Attach the connection options of a case to its response, and keep track of the request until
+/// the response is sent
+fn with_connection(
+    fut: ResponseFuture,
+    options: ConnectionOptions,
+    in_flight: Option<InFlight>,
+) -> ResponseFuture {
+    if options == ConnectionOptions::default() && in_flight.is_none() {
+        return fut;
     }
+
+    Box::pin(async move {
+        let mut res = fut.await?;
+        res.extensions_mut().insert(options);
+        if let Some(in_flight) = in_flight {
+            res.extensions_mut().insert(in_flight);
+        }
+        Ok(res)
+    })
 }
 
+/// Add the `x-mock-case` header to the response of a mock case
+///
+/// This uses the name of the case if it has one, or its position in [`Connector::report`].
+fn with_case_header(fut: ResponseFuture, name: Option<&str>, index: usize) -> ResponseFuture {
+    let value = name
+        .and_then(|name| HeaderValue::try_from(name).ok())
+        .unwrap_or_else(|| index.into());
+
+    Box::pin(async move {
+        let mut res = fut.await?;
+        res.headers_mut().insert(MOCK_CASE, value);
+        Ok(res)
+    })
+}
+
+/// Return the response for `case`, and store the request/response pair once it resolves
+#[cfg(feature = "recording")]
+fn record(recording: Arc<Mutex<Fixture>>, case: &Case, req: Request<Bytes>) -> ResponseFuture {
+    let request = RecordedRequest::new(&req);
+    let fut = case.returning.returning(req);
+
+    Box::pin(async move {
+        let res = fut.await?;
+        recording
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entries
+            .push(FixtureEntry {
+                request,
+                response: RecordedResponse::new(&res),
+            });
+        Ok(res)
+    })
+}
+
+/// Copy the method, URI, version, and headers of `req`
+fn request_head(req: &Request<Bytes>) -> Request<()> {
+    let mut head = Request::new(());
+    *head.method_mut() = req.method().clone();
+    *head.uri_mut() = req.uri().clone();
+    *head.version_mut() = req.version();
+    *head.headers_mut() = req.headers().clone();
+    head
+}
+
+/// Build the request passed to matchers
+///
+/// `body` is the payload as sent on the wire, and `chunks` its decoded content when it uses
+/// chunked transfer-encoding.
 fn into_request(
     req: httparse::Request,
     body: &[u8],
+    chunks: Option<Chunks>,
     uri: &Uri,
-) -> Result<Request<String>, BoxError> {
-    let body = from_utf8(body)?.to_string();
-
+) -> Result<Request<Bytes>, BoxError> {
     let mut builder = Request::builder().uri(uri);
 
     if let Some(path) = req.path {
         // TODO: handle errors
-        let mut parts = uri.clone().into_parts();
-        parts.path_and_query = Some(path.parse()?);
-        builder = builder.uri(Uri::from_parts(parts)?);
+        let target = path.parse::<Uri>()?;
+        // Clients send the request target in absolute form on proxied connections
+        if target.scheme().is_some() {
+            builder = builder.uri(target);
+        } else {
+            let mut parts = uri.clone().into_parts();
+            parts.path_and_query = Some(path.parse()?);
+            builder = builder.uri(Uri::from_parts(parts)?);
+        }
     }
     if let Some(method) = req.method {
         builder = builder.method(method);
     }
+    #[cfg(feature = "gzip")]
+    let mut content_encoding = None;
     for header in req.headers {
         if !header.name.is_empty() {
+            #[cfg(feature = "gzip")]
+            if header.name.eq_ignore_ascii_case(CONTENT_ENCODING.as_str()) {
+                content_encoding = Some(header.value.to_ascii_lowercase());
+            }
             builder = builder.header(header.name, header.value);
         }
     }
 
-    Ok(builder.body(body)?)
+    // Pass decoded chunked payloads, so matchers don't need to know about the framing
+    let raw = Bytes::copy_from_slice(body);
+    let mut trailers = Trailers::default();
+    let body = match chunks {
+        Some(chunks) => {
+            for (name, value) in chunks.trailers {
+                trailers
+                    .0
+                    .append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+            }
+            Bytes::from(chunks.body)
+        }
+        None => raw.clone(),
+    };
+
+    // Decompress payloads, so matchers can compare the actual content
+    #[cfg(feature = "gzip")]
+    let body = decompress(content_encoding.as_deref(), body);
+
+    Ok(builder
+        .extension(RawBody(raw))
+        .extension(trailers)
+        .body(body)?)
+}
+
+/// Decompress `body` according to the value of its `content-encoding` header
+#[cfg(feature = "gzip")]
+pub(crate) fn decompress(content_encoding: Option<&[u8]>, body: Bytes) -> Bytes {
+    match content_encoding {
+        Some(b"gzip" | b"x-gzip") => decode_gzip(&body).map(Bytes::from).unwrap_or(body),
+        Some(b"deflate") => decode_deflate(&body).map(Bytes::from).unwrap_or(body),
+        _ => body,
+    }
+}
+
+/// Write a line-by-line diff between two bodies
+///
+/// When a line is replaced by another, the first byte that differs is underlined.
+fn write_body_diff(
+    out: &mut impl fmt::Write,
+    bar: &impl fmt::Display,
+    expected: &str,
+    actual: &str,
+) -> fmt::Result {
+    let mut removed = None;
+    for line in diff_lines(expected, actual) {
+        match line {
+            DiffLine::Same(line) => writeln!(out, "{bar}  {line}")?,
+            DiffLine::Removed(line) => {
+                writeln!(out, "{bar}{}", format!("- {line}").red())?;
+                removed = Some(line);
+                continue;
+            }
+            DiffLine::Added(line) => {
+                writeln!(out, "{bar}{}", format!("+ {line}").green())?;
+                if let Some(position) = removed.and_then(|removed| diff_position(removed, line)) {
+                    let column = line
+                        .get(..position)
+                        .map_or(position, |prefix| prefix.chars().count());
+                    writeln!(out, "{bar}  {: <column$}{}", "", "^".yellow())?;
+                }
+            }
+        }
+        removed = None;
+    }
+    Ok(())
+}
+
+fn format_report(req: &Request<Bytes>, reports: &[(&Case, HashSet<Reason>)]) -> String {
+    let mut out = String::new();
+    // Writing into a `String` cannot fail
+    let _ = write_report(&mut out, req, reports);
+    out
 }
 
-fn print_report(req: &Request<String>, reports: Vec<(&Case, HashSet<Reason>)>) {
+fn write_report(
+    out: &mut impl fmt::Write,
+    req: &Request<Bytes>,
+    reports: &[(&Case, HashSet<Reason>)],
+) -> fmt::Result {
     let req_note = " = ".red().bold();
     let req_bar = " | ".red().bold();
     let case_note = " = ".blue().bold();
     let case_bar = " | ".blue().bold();
 
-    println!("{}", "--> no matching case for request".red().bold());
-    println!("{req_bar}");
-    println!("{req_note}the incoming request did not match any know cases.");
-    println!("{req_note}incoming request:");
-    println!("{req_bar}");
-    println!("{req_bar}method:   {}", req.method());
-    println!("{req_bar}uri:      {}", req.uri());
+    writeln!(out, "{}", "--> no matching case for request".red().bold())?;
+    writeln!(out, "{req_bar}")?;
+    writeln!(
+        out,
+        "{req_note}the incoming request did not match any know cases."
+    )?;
+    writeln!(out, "{req_note}incoming request:")?;
+    writeln!(out, "{req_bar}")?;
+    writeln!(out, "{req_bar}method:   {}", req.method())?;
+    writeln!(out, "{req_bar}uri:      {}", req.uri())?;
     if !req.headers().is_empty() {
         let key_length = req
             .headers()
             .iter()
             .fold(0, |acc, (key, _)| max(acc, key.to_string().len()));
-        println!("{req_bar}headers:");
-        for (key, value) in req.headers() {
+        writeln!(out, "{req_bar}headers:")?;
+        // Sort headers by name, keeping the order of the values for each header
+        let mut headers = req.headers().iter().collect::<Vec<_>>();
+        headers.sort_by_key(|(key, _)| key.as_str());
+        for (key, value) in headers {
             let value = if let Ok(value) = value.to_str() {
                 value.into()
             } else {
                 format!("{value:?}")
             };
-            println!("{req_bar}  {key: <key_length$}: {value}");
+            writeln!(out, "{req_bar}  {key: <key_length$}: {value}")?;
         }
     }
-    println!("{req_bar}");
+    writeln!(out, "{req_bar}")?;
 
     if !req.body().is_empty() {
-        println!("{req_bar}{}:", "body".bold());
-        for line in req.body().split('\n') {
-            println!("{req_bar}{line}");
+        writeln!(out, "{req_bar}{}:", "body".bold())?;
+        for line in String::from_utf8_lossy(req.body()).split('\n') {
+            writeln!(out, "{req_bar}{line}")?;
         }
-        println!("{req_bar}");
+        writeln!(out, "{req_bar}")?;
     }
 
     for (id, (case, report)) in reports.iter().enumerate() {
         let with_print = case.with.print_pretty(report);
-        println!(
+        writeln!(
+            out,
             "{}",
-            format!("--> case {id} `{}`", with_print.name).blue().bold(),
-        );
+            format!("--> case {id} `{}`", case.name()).blue().bold(),
+        )?;
         if let Some(body) = with_print.body {
-            println!("{case_bar}");
+            writeln!(out, "{case_bar}")?;
             for line in body.split('\n') {
-                println!("{case_bar}{line}");
+                writeln!(out, "{case_bar}{line}")?;
             }
-            println!("{case_bar}");
+            writeln!(out, "{case_bar}")?;
         }
         if !report.is_empty() {
-            let cases = report.iter().map(|r| r.as_str()).collect::<BinaryHeap<_>>();
-            println!("{case_note}this case doesn't match the request on the following attributes:");
+            let mut reasons = report.iter().collect::<Vec<_>>();
+            reasons.sort();
+            let cases = reasons
+                .into_iter()
+                .map(|reason| match reason {
+                    // Show the actual values next to the expected ones, as headers are easy to miss
+                    // in the request dump
+                    Reason::Header { check, .. } => {
+                        let mismatch = Mismatch::new(reason.clone(), case, req);
+                        let actual = mismatch
+                            .actual
+                            .map(|actual| format!("`{actual}`"))
+                            .unwrap_or_else(|| "nothing".to_string());
+                        match mismatch.expected {
+                            Some(expected) => format!(
+                                "{} ({}): expected `{expected}`, got {actual}",
+                                reason.as_str(),
+                                check.as_str(),
+                            ),
+                            None => {
+                                format!("{} ({}): got {actual}", reason.as_str(), check.as_str())
+                            }
+                        }
+                        .into()
+                    }
+                    Reason::Trailer(_) => {
+                        let mismatch = Mismatch::new(reason.clone(), case, req);
+                        match mismatch.expected {
+                            Some(expected) => format!(
+                                "{}: expected `{expected}`, got {}",
+                                reason.as_str(),
+                                mismatch
+                                    .actual
+                                    .map(|actual| format!("`{actual}`"))
+                                    .unwrap_or_else(|| "nothing".to_string()),
+                            )
+                            .into(),
+                            None => reason.as_str(),
+                        }
+                    }
+                    _ => reason.as_str(),
+                })
+                .collect::<Vec<_>>();
+            writeln!(
+                out,
+                "{case_note}this case doesn't match the request on the following attributes:"
+            )?;
             for case in cases {
-                println!("{case_bar}- {case}");
+                writeln!(out, "{case_bar}- {case}")?;
+            }
+            writeln!(out, "{case_bar}")?;
+        }
+
+        // JSON mismatches already list their differences, so only diff other bodies
+        if report.contains(&Reason::Body(Vec::new())) {
+            let mismatch = Mismatch::new(Reason::Body(Vec::new()), case, req);
+            if let (Some(expected), Some(actual)) = (mismatch.expected, mismatch.actual) {
+                writeln!(out, "{case_note}body diff (- expected, + actual):")?;
+                write_body_diff(out, &case_bar, &expected, &actual)?;
+                writeln!(out, "{case_bar}")?;
             }
-            println!("{case_bar}");
         }
     }
 
-    println!();
+    writeln!(out)
 }
@@ -0,0 +1,125 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::Mutex,
+};
+
+/// Whether diagnostic reports contain colors
+///
+/// ## Default
+///
+/// [`ColorChoice`] implements [`Default`], which will return `ColorChoice::Auto`.
+///
+/// ```rust
+/// use mock_http_connector::ColorChoice;
+///
+/// assert_eq!(ColorChoice::default(), ColorChoice::Auto);
+/// ```
+///
+/// ## Remark
+///
+/// Colors are generated by the [`colored`] crate, which can also disable them for the whole
+/// process, for example when `CLICOLOR=0` is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Use colors only if `NO_COLOR` is not set and reports are written to a terminal
+    #[default]
+    Auto,
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+}
+
+impl ColorChoice {
+    /// Remove colors from `report` if they should not be displayed
+    pub(crate) fn apply(self, report: String, is_terminal: bool) -> String {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        match self {
+            Self::Always => report,
+            Self::Auto if is_terminal && !no_color => report,
+            _ => strip_ansi(&report),
+        }
+    }
+}
+
+/// Remove ANSI escape sequences from a string
+fn strip_ansi(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip the control sequence up to its final byte
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+/// Destination for diagnostic reports
+#[derive(Default)]
+pub(crate) enum Diagnostics {
+    /// Print reports to stdout
+    #[default]
+    Stdout,
+    /// Write reports into a custom writer
+    Writer(Mutex<Box<dyn io::Write + Send>>),
+    /// Emit reports as `tracing` events
+    #[cfg(feature = "tracing")]
+    Tracing,
+}
+
+impl Diagnostics {
+    /// Returns `true` if reports are written to a terminal
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Self::Stdout => io::stdout().is_terminal(),
+            _ => false,
+        }
+    }
+
+    pub fn emit(&self, report: &str) {
+        match self {
+            Self::Stdout => print!("{report}"),
+            Self::Writer(writer) => {
+                let mut writer = writer.lock().unwrap_or_else(|err| err.into_inner());
+                // Diagnostics are best-effort, and shouldn't fail the request
+                let _ = writer.write_all(report.as_bytes());
+                let _ = writer.flush();
+            }
+            #[cfg(feature = "tracing")]
+            Self::Tracing => tracing::warn!(target: "mock_http_connector", "{report}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case(ColorChoice::Never, true, false)]
+    #[case(ColorChoice::Auto, false, false)]
+    fn color_choice_strip(#[case] choice: ColorChoice, #[case] terminal: bool, #[case] ansi: bool) {
+        let report = "\x1b[1;31msome\x1b[0m report".to_string();
+
+        let report = choice.apply(report, terminal);
+
+        assert_that!(report.contains('\x1b')).is_equal_to(ansi);
+        assert_that!(report.ends_with("some report")).is_true();
+    }
+
+    #[test]
+    fn strip_ansi_sequences() {
+        assert_that!(strip_ansi("\x1b[1;31m --> \x1b[0mhello"))
+            .is_equal_to(" --> hello".to_string());
+    }
+}
@@ -1,6 +1,6 @@
 use std::error::Error as StdError;
 
-use hyper::Request;
+use hyper::{body::Bytes, Request};
 
 use crate::case::Checkpoint;
 
@@ -8,7 +8,7 @@ use crate::case::Checkpoint;
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     /// Errors while checking if all mock cases were called the right number of times
-    #[error("checkpoint error: {0:?}")]
+    #[error("checkpoint error: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
     Checkpoint(Vec<Checkpoint>),
 
     /// Error from the [`hyper`] crate
@@ -28,13 +28,79 @@ pub enum Error {
     #[error("JSON serde error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// Invalid JSONPath expression
+    #[cfg(feature = "json")]
+    #[error("invalid JSON path: {0}")]
+    JsonPath(String),
+
+    /// Invalid path template
+    #[error("invalid path template: {0}")]
+    PathTemplate(String),
+
+    /// I/O error while reading or writing a file
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Invalid or unsupported JSON Schema document
+    #[cfg(feature = "json")]
+    #[error("invalid JSON schema: {0}")]
+    JsonSchema(String),
+
+    /// Invalid base URL for a [`crate::JsonApi`]
+    #[cfg(feature = "json")]
+    #[error("invalid JSON API base URL: {0}")]
+    JsonApi(String),
+
+    /// Invalid or unsupported WireMock stub mapping
+    #[cfg(feature = "json")]
+    #[error("invalid WireMock stub: {0}")]
+    WireMock(String),
+
+    /// Invalid use of a [`crate::Scenario`] in a mock case
+    #[error("invalid scenario: {0}")]
+    Scenario(String),
+
+    /// Invalid hex-encoded SHA-256 digest
+    #[error("invalid SHA-256 digest: {0}")]
+    Digest(String),
+
+    /// Invalid or unsupported regular expression
+    #[error("invalid pattern: {0}")]
+    Pattern(String),
+
+    /// Invalid method matcher, such as an empty list of methods
+    #[error("invalid method matcher: {0}")]
+    Method(String),
+
+    /// Invalid framing for a request body with chunked transfer-encoding
+    #[error("invalid chunked transfer-encoding: {0}")]
+    Chunked(String),
+
     /// No match found for the incoming [`Request`]
     #[error("no cases matched the request: {0:?}")]
-    NotFound(Request<String>),
+    NotFound(Box<Request<Bytes>>),
+
+    /// No match found for the incoming request, with [`crate::Level::Strict`]
+    ///
+    /// This contains the diagnostic report for the request.
+    #[error("{0}")]
+    Unmatched(String),
 
     /// Runtime errors
     #[error("transparent")]
     Runtime(#[from] BoxError),
 }
 
+impl Error {
+    /// Mock cases that failed the checkpoint for an [`Error::Checkpoint`], or an empty slice for
+    /// other errors
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        match self {
+            Self::Checkpoint(checkpoints) => checkpoints,
+            _ => &[],
+        }
+    }
+}
+
+/// Boxed error, returned by [`crate::With`] matchers
 pub type BoxError = Box<dyn StdError + Send + Sync>;
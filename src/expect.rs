@@ -0,0 +1,32 @@
+use hyper::StatusCode;
+
+/// Behavior of the connector for requests with an `expect: 100-continue` header
+///
+/// With [`ExpectContinue::Continue`], the connector sends an interim `100 Continue` response
+/// before the final response, so clients waiting for it before sending the payload don't stall.
+/// With [`ExpectContinue::Reject`], the connector sends a final response with the given status
+/// code instead, without matching the request against any mock case. The payload is discarded if
+/// the client sends it anyway.
+///
+/// ## Default
+///
+/// [`ExpectContinue`] implements [`Default`], which will return `ExpectContinue::Continue`.
+///
+/// ```rust
+/// use mock_http_connector::ExpectContinue;
+///
+/// assert_eq!(ExpectContinue::default(), ExpectContinue::Continue);
+/// ```
+///
+/// ## Remark
+///
+/// [`hyper::Client`] doesn't wait for the interim response, and skips it when reading the final
+/// response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExpectContinue {
+    /// Send an interim `100 Continue` response, then the final response
+    #[default]
+    Continue,
+    /// Reject the request with the given status code, such as `417 Expectation Failed`
+    Reject(StatusCode),
+}
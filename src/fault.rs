@@ -0,0 +1,190 @@
+use hyper::{body::Bytes, Response, StatusCode};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{clock::SharedClock, response::ResponseFuture, stream::ConnectionOptions};
+
+/// Random faults injected into the responses of mock cases, to simulate a flaky server
+///
+/// Each fault has a probability between `0.0` and `1.0`. For each request, the connector picks
+/// at most one fault, in the order they were added: with two faults of probability `0.1`, 10% of
+/// the requests get the first fault, 10% get the second one, and the other 80% are unaffected.
+///
+/// Faults are picked with a pseudo-random generator initialized from `seed`, so the same
+/// sequence of requests always gets the same faults.
+///
+/// A profile can be set for the whole connector with [`crate::Builder::faults`], or for a single
+/// mock case with [`crate::CaseBuilder::faults`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::StatusCode;
+/// # use mock_http_connector::{Connector, Error, FaultProfile};
+/// # use std::time::Duration;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder.faults(
+///     FaultProfile::new(42)
+///         .status(0.1, StatusCode::SERVICE_UNAVAILABLE)
+///         .drop_connection(0.05)
+///         .truncate_body(0.05)
+///         .delay(0.2, Duration::from_millis(100), Duration::from_millis(50)),
+/// );
+/// builder.expect().returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone)]
+pub struct FaultProfile {
+    faults: Vec<(f64, Fault)>,
+    rng: Arc<Mutex<SplitMix64>>,
+}
+
+/// Fault injected into a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    Status(StatusCode),
+    DropConnection,
+    TruncateBody,
+    Delay { base: Duration, jitter: Duration },
+}
+
+impl FaultProfile {
+    /// Create a new [`FaultProfile`] without any fault, using `seed` to pick the faults
+    pub fn new(seed: u64) -> Self {
+        Self {
+            faults: Vec::new(),
+            rng: Arc::new(Mutex::new(SplitMix64(seed))),
+        }
+    }
+
+    /// Return an empty response with the given `status`, such as `503 Service Unavailable`,
+    /// instead of the response of the mock case
+    pub fn status(mut self, probability: f64, status: StatusCode) -> Self {
+        self.faults.push((probability, Fault::Status(status)));
+        self
+    }
+
+    /// Drop the connection before sending the response
+    pub fn drop_connection(mut self, probability: f64) -> Self {
+        self.faults.push((probability, Fault::DropConnection));
+        self
+    }
+
+    /// Drop the connection after sending half of the response body
+    pub fn truncate_body(mut self, probability: f64) -> Self {
+        self.faults.push((probability, Fault::TruncateBody));
+        self
+    }
+
+    /// Delay the response by `base`, plus a random duration up to `jitter`
+    pub fn delay(mut self, probability: f64, base: Duration, jitter: Duration) -> Self {
+        self.faults
+            .push((probability, Fault::Delay { base, jitter }));
+        self
+    }
+
+    /// Pick a fault for the next response, and apply it
+    pub(crate) fn apply(
+        &self,
+        fut: ResponseFuture,
+        options: &mut ConnectionOptions,
+        clock: &SharedClock,
+    ) -> ResponseFuture {
+        let mut rng = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+
+        let mut roll = rng.next_f64();
+        let fault = self.faults.iter().find_map(|(probability, fault)| {
+            roll -= probability;
+            (roll < 0.0).then_some(*fault)
+        });
+
+        match fault {
+            Some(Fault::Status(status)) => {
+                let res = Response::builder().status(status).body(Bytes::new());
+                Box::pin(async move { Ok(res?) })
+            }
+            Some(Fault::DropConnection) => {
+                options.reset = true;
+                fut
+            }
+            Some(Fault::TruncateBody) => {
+                options.truncate = true;
+                fut
+            }
+            Some(Fault::Delay { base, jitter }) => {
+                let sleep = clock.sleep(base + jitter.mul_f64(rng.next_f64()));
+                Box::pin(async move {
+                    sleep.await;
+                    fut.await
+                })
+            }
+            None => fut,
+        }
+    }
+}
+
+/// Pseudo-random number generator for [`FaultProfile`]
+///
+/// See <https://prng.di.unimi.it/splitmix64.c>.
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Return a number in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn splitmix64_sequence() {
+        let mut rng = SplitMix64(1234567);
+        let values = (0..3).map(|_| rng.next_u64()).collect::<Vec<_>>();
+
+        assert_that!(values).is_equal_to(vec![
+            6457827717110365317,
+            3203168211198807973,
+            9817491932198370423,
+        ]);
+    }
+
+    #[rstest]
+    #[case(0.0, 0)]
+    #[case(0.25, 250)]
+    #[case(1.0, 1000)]
+    fn fault_profile_probability(#[case] probability: f64, #[case] expected: usize) {
+        let profile = FaultProfile::new(42).drop_connection(probability);
+
+        let faults = (0..1000)
+            .filter(|_| {
+                let mut options = ConnectionOptions::default();
+                drop(profile.apply(
+                    Box::pin(async { unreachable!() }),
+                    &mut options,
+                    &SharedClock::default(),
+                ));
+                options.reset
+            })
+            .count();
+
+        assert_that!(faults.abs_diff(expected)).is_less_than(50);
+    }
+}
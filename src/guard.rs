@@ -0,0 +1,93 @@
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{
+    case::{checkpoint, AddCase, Case},
+    CaseBuilder, Connector, Error,
+};
+
+/// Guard for mock cases registered on an existing [`Connector`] for a limited scope
+///
+/// Mock cases created through [`MockGuard::expect`] are added to the [`Connector`] right away.
+/// When the guard is dropped, those cases are checked the same way as [`Connector::checkpoint`]
+/// and removed from the connector, so they cannot leak into other tests sharing the same
+/// connector.
+///
+/// ## Panics
+///
+/// Dropping the guard panics if any of its mock cases wasn't called the right number of times,
+/// unless the current thread is already panicking.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error};
+/// # || {
+/// let connector = Connector::builder().build();
+///
+/// {
+///     let mut guard = connector.scope();
+///     guard
+///         .expect()
+///         .with_uri("https://test.example/scoped")
+///         .returning("OK")?;
+///
+///     // The case is available until the end of the scope
+/// }
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[must_use = "mock cases are removed as soon as the guard is dropped"]
+pub struct MockGuard {
+    connector: Connector,
+    cases: Mutex<Vec<Case>>,
+}
+
+impl MockGuard {
+    pub(crate) fn new(connector: Connector) -> Self {
+        Self {
+            connector,
+            cases: Mutex::default(),
+        }
+    }
+
+    /// Create a new expected case for the duration of this guard
+    pub fn expect(&mut self) -> CaseBuilder<'_> {
+        CaseBuilder::new(self)
+    }
+
+    fn cases(&self) -> MutexGuard<'_, Vec<Case>> {
+        self.cases.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Check if all the mock cases registered through this guard were called the right amount
+    /// of time
+    pub fn checkpoint(&self) -> Result<(), Error> {
+        checkpoint(self.cases().iter())
+    }
+}
+
+impl AddCase for MockGuard {
+    fn add_case(&self, case: Case) {
+        self.connector.inner().add_case(case.clone());
+        self.cases().push(case);
+    }
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        let cases = self.cases();
+        self.connector
+            .inner()
+            .cases_mut()
+            .retain(|case| !cases.iter().any(|c| c.is_same(case)));
+        drop(cases);
+
+        if std::thread::panicking() {
+            return;
+        }
+
+        if let Err(err) = self.checkpoint() {
+            panic!("{err}");
+        }
+    }
+}
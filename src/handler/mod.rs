@@ -1,5 +1,22 @@
 mod returning;
+mod template;
 mod with;
 
+#[cfg(feature = "json")]
+pub(crate) use returning::JsonDe;
 pub use returning::Returning;
-pub use with::{DefaultWith, Reason, Report, With, WithHandler};
+pub(crate) use returning::ReturningCtx;
+pub(crate) use returning::Sealed;
+pub use template::Template;
+#[cfg(feature = "json")]
+pub use with::ArrayMatching;
+#[cfg(feature = "json")]
+pub(crate) use with::JsonEq;
+#[cfg(feature = "sigv4")]
+pub use with::SigV4Credentials;
+pub use with::{
+    all_of, any_of, not, AllOf, AnyOf, BodyStream, DefaultWith, HeaderCheckKind, JsonDiff,
+    JsonDiffKind, Multipart, MultipartPart, Not, PathParams, Reason, Report, UriComponent,
+    UriNormalization, UriParts, With, WithAsync, WithFuture, WithHandler, WithPrint,
+};
+pub(crate) use with::{form_parts, soap_action, uri_component, BodyStreams, StreamedBody};
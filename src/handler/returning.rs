@@ -1,11 +1,17 @@
-use crate::{error::BoxError, response::ResponseFuture, IntoResponseFuture};
-use hyper::{Request, Response, StatusCode};
+use crate::{error::BoxError, response::ResponseFuture, CaseContext, IntoResponseFuture};
+use hyper::{
+    body::Bytes,
+    http::{HeaderName, HeaderValue},
+    HeaderMap, Request, Response, StatusCode,
+};
+#[cfg(feature = "json")]
+use std::marker::PhantomData;
 use std::{borrow::Cow, convert::Infallible, error::Error as StdError};
 
 /// Trait for responses matching mock cases
 pub trait Returning: Send + Sync + Sealed {
     /// Return a [`Response`] based on the incoming [`Request`]
-    fn returning(&self, req: Request<String>) -> ResponseFuture;
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture;
 }
 
 /// Sealed trait to avoid additional implementations of [`Returning`]
@@ -31,9 +37,9 @@ macro_rules! returning {
     ($type:ty, $body:expr, $status:expr, $headers:expr, $($lt:lifetime),+) => {
         impl<$($lt),+> Returning for $type {
             #[allow(clippy::redundant_closure_call)]
-            fn returning(&self, _req: ::hyper::Request<String>) -> ResponseFuture {
+            fn returning(&self, _req: ::hyper::Request<Bytes>) -> ResponseFuture {
                 #[allow(clippy::ptr_arg)]
-                fn response<$($lt),+>(s: &$type) -> Result<Response<String>, BoxError> {
+                fn response<$($lt),+>(s: &$type) -> Result<Response<Bytes>, BoxError> {
                     let mut res = ::hyper::Response::builder();
 
                     for (k, v) in ($headers)(s)?.iter() {
@@ -42,10 +48,10 @@ macro_rules! returning {
 
                     Ok(res
                         .status(($status)(s)?)
-                        .body(($body)(s)?)?)
+                        .body(Bytes::from(($body)(s)?))?)
                 }
 
-                let res: Result<Response<String>, BoxError> = response(self);
+                let res: Result<Response<Bytes>, BoxError> = response(self);
                 Box::pin(async move {
                     res
                 })
@@ -57,8 +63,8 @@ macro_rules! returning {
     ($type:ty, $body:expr, $status:expr, $headers:expr) => {
         impl Returning for $type {
             #[allow(clippy::redundant_closure_call)]
-            fn returning(&self, _req: ::hyper::Request<String>) -> ResponseFuture {
-                fn response(s: &$type) -> Result<Response<String>, BoxError> {
+            fn returning(&self, _req: ::hyper::Request<Bytes>) -> ResponseFuture {
+                fn response(s: &$type) -> Result<Response<Bytes>, BoxError> {
                     let mut res = ::hyper::Response::builder();
 
                     for (k, v) in ($headers)(s)?.iter() {
@@ -67,10 +73,10 @@ macro_rules! returning {
 
                     Ok(res
                         .status(($status)(s)?)
-                        .body(($body)(s)?)?)
+                        .body(Bytes::from(($body)(s)?))?)
                 }
 
-                let res: Result<Response<String>, BoxError> = response(self);
+                let res: Result<Response<Bytes>, BoxError> = response(self);
                 Box::pin(async move {
                     res
                 })
@@ -92,6 +98,9 @@ returning!(
 returning!(u16, |_| Ok::<_, Infallible>(String::new()), |v: &u16| {
     StatusCode::try_from(*v)
 });
+returning!(Bytes, |v: &Bytes| { Ok::<_, Infallible>(v.clone()) });
+returning!(Vec<u8>, |v: &Vec<u8>| { Ok::<_, Infallible>(v.clone()) });
+returning!((), |_| Ok::<_, Infallible>(Bytes::new()));
 #[cfg(feature = "json")]
 returning!(serde_json::Value, |v: &serde_json::Value| {
     Ok::<_, Infallible>(v.to_string())
@@ -104,10 +113,10 @@ where
     S::Error: StdError + Send + Sync + 'static,
     B: ToString + 'static,
 {
-    fn returning(&self, _req: Request<String>) -> ResponseFuture {
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
         let status = self.0.clone().try_into();
         let body = self.1.to_string();
-        Box::pin(async { Ok(Response::builder().status(status?).body(body)?) })
+        Box::pin(async { Ok(Response::builder().status(status?).body(body.into())?) })
     }
 }
 
@@ -119,19 +128,140 @@ where
 {
 }
 
+impl<S, B> Returning for (S, HeaderMap, B)
+where
+    (S, HeaderMap, B): Send + Sync,
+    S: TryInto<StatusCode> + Clone,
+    S::Error: StdError + Send + Sync + 'static,
+    B: ToString + 'static,
+{
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let status = self.0.clone().try_into();
+        let headers = self.1.clone();
+        let body = self.2.to_string();
+        Box::pin(async {
+            let mut res = Response::builder().status(status?);
+            if let Some(map) = res.headers_mut() {
+                map.extend(headers);
+            }
+            Ok(res.body(body.into())?)
+        })
+    }
+}
+
+impl<S, B> Sealed for (S, HeaderMap, B)
+where
+    S: TryInto<StatusCode> + Clone,
+    S::Error: StdError + Send + Sync + 'static,
+    B: ToString,
+{
+}
+
+impl<S, B> Returning for (S, Vec<(HeaderName, HeaderValue)>, B)
+where
+    (S, Vec<(HeaderName, HeaderValue)>, B): Send + Sync,
+    S: TryInto<StatusCode> + Clone,
+    S::Error: StdError + Send + Sync + 'static,
+    B: ToString + 'static,
+{
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let status = self.0.clone().try_into();
+        let headers = self.1.clone();
+        let body = self.2.to_string();
+        Box::pin(async {
+            let mut res = Response::builder().status(status?);
+            for (name, value) in headers {
+                res = res.header(name, value);
+            }
+            Ok(res.body(body.into())?)
+        })
+    }
+}
+
+impl<S, B> Sealed for (S, Vec<(HeaderName, HeaderValue)>, B)
+where
+    S: TryInto<StatusCode> + Clone,
+    S::Error: StdError + Send + Sync + 'static,
+    B: ToString,
+{
+}
+
 impl<F, R> Returning for F
 where
-    F: Fn(Request<String>) -> R + Send + Sync,
+    F: Fn(Request<Bytes>) -> R + Send + Sync,
     R: IntoResponseFuture,
 {
-    fn returning(&self, req: Request<String>) -> ResponseFuture {
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
         (self)(req).into_response_future()
     }
 }
 
 impl<F, R> Sealed for F
 where
-    F: Fn(Request<String>) -> R,
+    F: Fn(Request<Bytes>) -> R,
+    R: IntoResponseFuture,
+{
+}
+
+/// [`Returning`] implementation that passes the [`CaseContext`] of the call to a closure
+pub(crate) struct ReturningCtx<F> {
+    f: F,
+}
+
+impl<F> ReturningCtx<F> {
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<F, R> Returning for ReturningCtx<F>
+where
+    F: Fn(CaseContext, Request<Bytes>) -> R + Send + Sync,
+    R: IntoResponseFuture,
+{
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let ctx = req
+            .extensions()
+            .get::<CaseContext>()
+            .cloned()
+            .unwrap_or_default();
+        (self.f)(ctx, req).into_response_future()
+    }
+}
+
+impl<F> Sealed for ReturningCtx<F> {}
+
+/// [`Returning`] implementation that deserializes the request body before calling a closure
+#[cfg(feature = "json")]
+pub(crate) struct JsonDe<F, T> {
+    f: F,
+    _payload: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "json")]
+impl<F, T> JsonDe<F, T> {
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            _payload: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<F, T, R> Returning for JsonDe<F, T>
+where
+    F: Fn(T) -> R + Send + Sync,
+    T: serde::de::DeserializeOwned,
     R: IntoResponseFuture,
 {
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        match serde_json::from_slice::<T>(req.body()) {
+            Ok(payload) => (self.f)(payload).into_response_future(),
+            Err(err) => Box::pin(async move { Err(err.into()) }),
+        }
+    }
 }
+
+#[cfg(feature = "json")]
+impl<F, T> Sealed for JsonDe<F, T> {}
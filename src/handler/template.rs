@@ -0,0 +1,167 @@
+use hyper::{body::Bytes, http::HeaderName, Request, Response, StatusCode};
+
+use crate::{handler::Sealed, response::ResponseFuture, Error, Returning};
+
+/// [`Returning`] implementation that interpolates values from the request into the response
+///
+/// Placeholders use the `{{name}}` syntax and are replaced in both the body and header values:
+///
+/// * `{{body}}`: the request body
+/// * `{{method}}`: the request method
+/// * `{{uri}}`: the full request URI
+/// * `{{uri.path}}`, `{{uri.query}}`, `{{uri.host}}`: parts of the request URI
+/// * `{{headers.<name>}}`: the first value for the request header `<name>`
+///
+/// Placeholders for values that are not present in the request are replaced with an empty
+/// string, and unknown placeholders are left as-is.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Template};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .returning(
+///         Template::new(r#"{ "echo": "{{body}}", "path": "{{uri.path}}" }"#)
+///             .status(201)?
+///             .header("x-request-id", "{{headers.x-request-id}}")?,
+///     )?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template {
+    status: StatusCode,
+    headers: Vec<(HeaderName, String)>,
+    body: String,
+}
+
+impl Template {
+    /// Create a new [`Template`] for the response body
+    pub fn new<B>(body: B) -> Self
+    where
+        B: ToString,
+    {
+        Self {
+            status: StatusCode::OK,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Set the status code of the response
+    pub fn status<S>(mut self, status: S) -> Result<Self, Error>
+    where
+        S: TryInto<StatusCode>,
+        S::Error: Into<hyper::http::Error>,
+    {
+        self.status = status.try_into().map_err(Into::into)?;
+        Ok(self)
+    }
+
+    /// Add a header to the response, with a templated value
+    pub fn header<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: ToString,
+    {
+        self.headers
+            .push((key.try_into().map_err(Into::into)?, value.to_string()));
+        Ok(self)
+    }
+}
+
+impl Returning for Template {
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let mut res = Response::builder().status(self.status);
+        for (key, value) in &self.headers {
+            res = res.header(key, render(value, &req));
+        }
+        let res = res.body(render(&self.body, &req).into());
+
+        Box::pin(async move { Ok(res?) })
+    }
+}
+
+impl Sealed for Template {}
+
+/// Replace all placeholders in `template` with the values from `req`
+fn render(template: &str, req: &Request<Bytes>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let key = after[..end].trim();
+        match lookup(key, req) {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Find the value for a placeholder
+///
+/// This returns `None` if the placeholder is unknown.
+fn lookup(key: &str, req: &Request<Bytes>) -> Option<String> {
+    let value = match key {
+        "body" => String::from_utf8_lossy(req.body()).into_owned(),
+        "method" => req.method().to_string(),
+        "uri" => req.uri().to_string(),
+        "uri.path" => req.uri().path().to_string(),
+        "uri.query" => req.uri().query().unwrap_or_default().to_string(),
+        "uri.host" => req.uri().host().unwrap_or_default().to_string(),
+        key => {
+            let name = key.strip_prefix("headers.")?;
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        }
+    };
+
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case("static", "static")]
+    #[case("{{body}}", "some body")]
+    #[case("{{ method }} {{uri}}", "POST http://test.example/path?a=b")]
+    #[case("{{uri.path}}?{{uri.query}}", "/path?a=b")]
+    #[case("{{uri.host}}", "test.example")]
+    #[case("{{headers.x-request-id}}", "1234")]
+    #[case("[{{headers.x-missing}}]", "[]")]
+    #[case("{{unknown}}", "{{unknown}}")]
+    #[case("{{body", "{{body")]
+    fn template_render(#[case] template: &str, #[case] expected: &str) {
+        let req = Request::builder()
+            .method("POST")
+            .uri("http://test.example/path?a=b")
+            .header("x-request-id", "1234")
+            .body(Bytes::from("some body"))
+            .unwrap();
+
+        assert_that!(render(template, &req)).is_equal_to(expected.to_string());
+    }
+}
@@ -0,0 +1,62 @@
+use std::{any::Any, collections::HashSet, error::Error as StdError, future::Future};
+
+use hyper::{body::Bytes, Request};
+
+use super::{With, WithFuture, WithPrint};
+use crate::{codec::RawBody, error::BoxError, Reason, Report};
+
+/// Matcher that awaits a future to check if the incoming request matches
+///
+/// See [`crate::CaseBuilder::with_async`].
+pub struct WithAsync<F> {
+    f: F,
+}
+
+impl<F> WithAsync<F> {
+    /// Create a new [`WithAsync`] matcher from a function or closure that returns a future
+    pub fn new<Fut, R, E>(f: F) -> Self
+    where
+        F: Fn(Request<Bytes>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + Sync + 'static,
+        R: Into<Report>,
+        E: StdError + Send + Sync + 'static,
+    {
+        Self { f }
+    }
+}
+
+impl<F, Fut, R, E> With for WithAsync<F>
+where
+    F: Fn(Request<Bytes>) -> Fut + Any + Send + Sync,
+    Fut: Future<Output = Result<R, E>> + Send + Sync + 'static,
+    R: Into<Report>,
+    E: StdError + Send + Sync + 'static,
+{
+    fn with(&self, _req: &Request<Bytes>) -> Result<Report, BoxError> {
+        Err("asynchronous matchers can only be evaluated with `With::with_async`".into())
+    }
+
+    fn with_async<'w>(&'w self, req: &'w Request<Bytes>) -> WithFuture<'w> {
+        let fut = (self.f)(clone_request(req));
+        Box::pin(async move { fut.await.map(Into::into).map_err(Into::into) })
+    }
+
+    fn print_pretty(&self, _report: &HashSet<Reason>) -> WithPrint<'_> {
+        let name = format!("async closure {}", std::any::type_name::<F>()).into();
+
+        WithPrint { name, body: None }
+    }
+}
+
+/// Copy a request, so it can be moved into the future
+fn clone_request(req: &Request<Bytes>) -> Request<Bytes> {
+    let mut clone = Request::new(req.body().clone());
+    *clone.method_mut() = req.method().clone();
+    *clone.uri_mut() = req.uri().clone();
+    *clone.version_mut() = req.version();
+    *clone.headers_mut() = req.headers().clone();
+    if let Some(raw) = req.extensions().get::<RawBody>() {
+        clone.extensions_mut().insert(raw.clone());
+    }
+    clone
+}
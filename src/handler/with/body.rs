@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use hyper::{body::Bytes, Request};
+
+use crate::codec::Sha256;
+
+/// Matcher that consumes the request body incrementally
+///
+/// This is used with [`crate::CaseBuilder::with_body_stream`] to check large payloads without
+/// comparing them as a whole, for example by hashing them. A new instance is created for each
+/// incoming request.
+///
+/// ## Example
+///
+/// ```rust
+/// use mock_http_connector::BodyStream;
+///
+/// /// Check that the payload starts with a given prefix
+/// struct Prefix {
+///     prefix: &'static [u8],
+///     seen: Vec<u8>,
+/// }
+///
+/// impl BodyStream for Prefix {
+///     fn update(&mut self, chunk: &[u8]) {
+///         let missing = self.prefix.len().saturating_sub(self.seen.len());
+///         self.seen.extend(&chunk[..missing.min(chunk.len())]);
+///     }
+///
+///     fn finish(&mut self) -> bool {
+///         self.seen == self.prefix
+///     }
+/// }
+/// ```
+pub trait BodyStream: Send {
+    /// Consume the next chunk of the request body
+    fn update(&mut self, chunk: &[u8]);
+
+    /// Returns `true` if the request body matches, once all the chunks were consumed
+    fn finish(&mut self) -> bool;
+}
+
+/// Identifier for the next [`BodyStreamFactory`]
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Constructor for the [`BodyStream`] of a matcher
+#[derive(Clone)]
+pub(crate) struct BodyStreamFactory {
+    id: usize,
+    /// Description of the expected payload, for diagnostics
+    label: String,
+    new: Arc<dyn Fn() -> Box<dyn BodyStream> + Send + Sync>,
+}
+
+impl BodyStreamFactory {
+    pub fn new<F, S>(f: F) -> Self
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+        S: BodyStream + 'static,
+    {
+        Self::with_label(format!("body stream: {}", std::any::type_name::<S>()), f)
+    }
+
+    pub fn with_label<F, S>(label: String, f: F) -> Self
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+        S: BodyStream + 'static,
+    {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            label,
+            new: Arc::new(move || Box::new(f())),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns `true` if the body of `req` matches
+    ///
+    /// When the connector streamed the body, this returns the result it stored in the request.
+    /// Otherwise, the body is passed to a new [`BodyStream`] in a single chunk.
+    pub fn matches(&self, req: &Request<Bytes>) -> bool {
+        match req.extensions().get::<StreamedBody>() {
+            Some(streamed) => streamed.0.get(&self.id).copied().unwrap_or(false),
+            None => {
+                let mut stream = (self.new)();
+                stream.update(req.body());
+                stream.finish()
+            }
+        }
+    }
+}
+
+impl fmt::Debug for BodyStreamFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyStreamFactory")
+            .field("id", &self.id)
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+/// [`BodyStream`] checking the length of the payload
+pub(crate) struct BodyLen {
+    expected: usize,
+    len: usize,
+}
+
+impl BodyLen {
+    pub fn new(expected: usize) -> Self {
+        Self { expected, len: 0 }
+    }
+}
+
+impl BodyStream for BodyLen {
+    fn update(&mut self, chunk: &[u8]) {
+        self.len += chunk.len();
+    }
+
+    fn finish(&mut self) -> bool {
+        self.len == self.expected
+    }
+}
+
+/// [`BodyStream`] checking the SHA-256 digest of the payload
+pub(crate) struct BodySha256 {
+    expected: [u8; 32],
+    hasher: Option<Sha256>,
+}
+
+impl BodySha256 {
+    pub fn new(expected: [u8; 32]) -> Self {
+        Self {
+            expected,
+            hasher: Some(Sha256::default()),
+        }
+    }
+}
+
+impl BodyStream for BodySha256 {
+    fn update(&mut self, chunk: &[u8]) {
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(chunk);
+        }
+    }
+
+    fn finish(&mut self) -> bool {
+        self.hasher
+            .take()
+            .is_some_and(|hasher| hasher.finish() == self.expected)
+    }
+}
+
+/// [`BodyStream`]s of the mock cases for a request, while the connector streams its body
+///
+/// See [`crate::With::body_streams`].
+#[derive(Default)]
+pub struct BodyStreams(Vec<(usize, Box<dyn BodyStream>)>);
+
+impl BodyStreams {
+    /// Add a new stream for `factory`
+    pub(crate) fn push(&mut self, factory: &BodyStreamFactory) {
+        self.0.push((factory.id, (factory.new)()));
+    }
+
+    /// Pass the next chunk of the request body to all the streams
+    pub(crate) fn update(&mut self, chunk: &[u8]) {
+        for (_, stream) in &mut self.0 {
+            stream.update(chunk);
+        }
+    }
+
+    /// Collect the results of all the streams, once the request body is complete
+    pub(crate) fn finish(self) -> StreamedBody {
+        StreamedBody(
+            self.0
+                .into_iter()
+                .map(|(id, mut stream)| (id, stream.finish()))
+                .collect(),
+        )
+    }
+}
+
+/// Results of the [`BodyStream`]s for a request whose body was streamed
+///
+/// This is stored in the [`Request`] extensions, like [`crate::codec::RawBody`].
+#[derive(Debug, Clone)]
+pub(crate) struct StreamedBody(HashMap<usize, bool>);
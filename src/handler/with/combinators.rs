@@ -0,0 +1,416 @@
+use std::collections::HashSet;
+
+use hyper::{body::Bytes, http::Extensions, Request};
+
+use super::{BodyStreams, With, WithFuture, WithPrint};
+use crate::{error::BoxError, Reason, Report};
+
+/// Matcher that matches if any of the inner matchers match
+///
+/// See [`any_of`].
+pub struct AnyOf<W> {
+    matchers: Vec<W>,
+}
+
+/// Match requests that match at least one of the provided matchers
+///
+/// To combine matchers of different types, you can use [`With::boxed`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::{body::Bytes, Request};
+/// # use mock_http_connector::{any_of, Connector, Error, With, WithHandler};
+/// # use std::convert::Infallible;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_matcher(any_of([
+///         WithHandler::default().with_method("PUT")?.boxed(),
+///         (|req: &Request<Bytes>| Ok::<_, Infallible>(req.body().is_empty())).boxed(),
+///     ]))
+///     .returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+pub fn any_of<I>(matchers: I) -> AnyOf<I::Item>
+where
+    I: IntoIterator,
+    I::Item: With,
+{
+    AnyOf {
+        matchers: matchers.into_iter().collect(),
+    }
+}
+
+impl<W> With for AnyOf<W>
+where
+    W: With,
+{
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
+        #[allow(clippy::mutable_key_type)]
+        let mut reasons = HashSet::new();
+
+        for matcher in &self.matchers {
+            match matcher.with(req)? {
+                Report::Match => return Ok(Report::Match),
+                Report::Mismatch(inner) => reasons.extend(inner),
+            }
+        }
+
+        Ok(Report::Mismatch(reasons))
+    }
+
+    fn with_async<'w>(&'w self, req: &'w Request<Bytes>) -> WithFuture<'w> {
+        Box::pin(async move {
+            #[allow(clippy::mutable_key_type)]
+            let mut reasons = HashSet::new();
+
+            for matcher in &self.matchers {
+                match matcher.with_async(req).await? {
+                    Report::Match => return Ok(Report::Match),
+                    Report::Mismatch(inner) => reasons.extend(inner),
+                }
+            }
+
+            Ok(Report::Mismatch(reasons))
+        })
+    }
+
+    fn with_extensions<'w>(
+        &'w self,
+        req: &'w Request<Bytes>,
+        extensions: &'w mut Extensions,
+    ) -> WithFuture<'w> {
+        Box::pin(async move {
+            #[allow(clippy::mutable_key_type)]
+            let mut reasons = HashSet::new();
+
+            // Only keep the extensions from the matcher that matched
+            for matcher in &self.matchers {
+                let mut inner_extensions = Extensions::new();
+                match matcher.with_extensions(req, &mut inner_extensions).await? {
+                    Report::Match => {
+                        extensions.extend(inner_extensions);
+                        return Ok(Report::Match);
+                    }
+                    Report::Mismatch(inner) => reasons.extend(inner),
+                }
+            }
+
+            Ok(Report::Mismatch(reasons))
+        })
+    }
+
+    fn print_pretty(&self, report: &HashSet<Reason>) -> WithPrint<'_> {
+        print_nested("any of", &self.matchers, report)
+    }
+
+    fn expected(&self, reason: &Reason) -> Option<String> {
+        self.matchers
+            .iter()
+            .find_map(|matcher| matcher.expected(reason))
+    }
+
+    fn actual(&self, reason: &Reason, req: &Request<Bytes>) -> Option<String> {
+        self.matchers
+            .iter()
+            .find_map(|matcher| matcher.actual(reason, req))
+    }
+
+    fn body_streams(&self, streams: &mut BodyStreams) {
+        for matcher in &self.matchers {
+            matcher.body_streams(streams);
+        }
+    }
+}
+
+/// Matcher that matches if all of the inner matchers match
+///
+/// See [`all_of`].
+pub struct AllOf<W> {
+    matchers: Vec<W>,
+}
+
+/// Match requests that match all the provided matchers
+///
+/// To combine matchers of different types, you can use [`With::boxed`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::{body::Bytes, Request};
+/// # use mock_http_connector::{all_of, Connector, Error, With, WithHandler};
+/// # use std::convert::Infallible;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_matcher(all_of([
+///         WithHandler::default().with_method("POST")?.boxed(),
+///         (|req: &Request<Bytes>| Ok::<_, Infallible>(req.body().starts_with(b"hello"))).boxed(),
+///     ]))
+///     .returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+pub fn all_of<I>(matchers: I) -> AllOf<I::Item>
+where
+    I: IntoIterator,
+    I::Item: With,
+{
+    AllOf {
+        matchers: matchers.into_iter().collect(),
+    }
+}
+
+impl<W> With for AllOf<W>
+where
+    W: With,
+{
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
+        #[allow(clippy::mutable_key_type)]
+        let mut reasons = HashSet::new();
+        let mut matched = true;
+
+        for matcher in &self.matchers {
+            if let Report::Mismatch(inner) = matcher.with(req)? {
+                matched = false;
+                reasons.extend(inner);
+            }
+        }
+
+        if matched {
+            Ok(Report::Match)
+        } else {
+            Ok(Report::Mismatch(reasons))
+        }
+    }
+
+    fn with_async<'w>(&'w self, req: &'w Request<Bytes>) -> WithFuture<'w> {
+        Box::pin(async move {
+            #[allow(clippy::mutable_key_type)]
+            let mut reasons = HashSet::new();
+            let mut matched = true;
+
+            for matcher in &self.matchers {
+                if let Report::Mismatch(inner) = matcher.with_async(req).await? {
+                    matched = false;
+                    reasons.extend(inner);
+                }
+            }
+
+            if matched {
+                Ok(Report::Match)
+            } else {
+                Ok(Report::Mismatch(reasons))
+            }
+        })
+    }
+
+    fn with_extensions<'w>(
+        &'w self,
+        req: &'w Request<Bytes>,
+        extensions: &'w mut Extensions,
+    ) -> WithFuture<'w> {
+        Box::pin(async move {
+            #[allow(clippy::mutable_key_type)]
+            let mut reasons = HashSet::new();
+            let mut matched = true;
+
+            for matcher in &self.matchers {
+                if let Report::Mismatch(inner) = matcher.with_extensions(req, extensions).await? {
+                    matched = false;
+                    reasons.extend(inner);
+                }
+            }
+
+            if matched {
+                Ok(Report::Match)
+            } else {
+                Ok(Report::Mismatch(reasons))
+            }
+        })
+    }
+
+    fn print_pretty(&self, report: &HashSet<Reason>) -> WithPrint<'_> {
+        print_nested("all of", &self.matchers, report)
+    }
+
+    fn expected(&self, reason: &Reason) -> Option<String> {
+        self.matchers
+            .iter()
+            .find_map(|matcher| matcher.expected(reason))
+    }
+
+    fn actual(&self, reason: &Reason, req: &Request<Bytes>) -> Option<String> {
+        self.matchers
+            .iter()
+            .find_map(|matcher| matcher.actual(reason, req))
+    }
+
+    fn body_streams(&self, streams: &mut BodyStreams) {
+        for matcher in &self.matchers {
+            matcher.body_streams(streams);
+        }
+    }
+}
+
+/// Matcher that inverts the result of the inner matcher
+///
+/// See [`not`].
+pub struct Not<W> {
+    matcher: W,
+}
+
+/// Match requests that do not match the provided matcher
+///
+/// Requests that match the inner matcher are reported with a [`Reason::Custom`] naming it, such
+/// as `not WithHandler`.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{not, Connector, Error, WithHandler};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_matcher(not(WithHandler::default().with_method("GET")?))
+///     .returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+pub fn not<W>(matcher: W) -> Not<W>
+where
+    W: With,
+{
+    Not { matcher }
+}
+
+impl<W> Not<W>
+where
+    W: With,
+{
+    /// Report for requests that match the inner matcher, naming it as the reason
+    fn mismatch(&self) -> Report {
+        let name = self.matcher.print_pretty(&HashSet::default()).name;
+        Report::Mismatch([Reason::Custom(format!("not {name}"))].into())
+    }
+}
+
+impl<W> With for Not<W>
+where
+    W: With,
+{
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
+        Ok(match self.matcher.with(req)? {
+            Report::Match => self.mismatch(),
+            Report::Mismatch(_) => Report::Match,
+        })
+    }
+
+    fn with_async<'w>(&'w self, req: &'w Request<Bytes>) -> WithFuture<'w> {
+        Box::pin(async move {
+            Ok(match self.matcher.with_async(req).await? {
+                Report::Match => self.mismatch(),
+                Report::Mismatch(_) => Report::Match,
+            })
+        })
+    }
+
+    fn print_pretty(&self, report: &HashSet<Reason>) -> WithPrint<'_> {
+        print_nested("not", std::slice::from_ref(&self.matcher), report)
+    }
+
+    fn body_streams(&self, streams: &mut BodyStreams) {
+        self.matcher.body_streams(streams)
+    }
+}
+
+#[allow(clippy::mutable_key_type)]
+fn print_nested<'w, W>(name: &str, matchers: &'w [W], report: &HashSet<Reason>) -> WithPrint<'w>
+where
+    W: With,
+{
+    let mut print_body = Vec::new();
+
+    for matcher in matchers {
+        let print = matcher.print_pretty(report);
+        print_body.push(format!("- {}", print.name));
+        if let Some(body) = print.body {
+            for line in body.split('\n') {
+                print_body.push(format!("    {line}"));
+            }
+        }
+    }
+
+    WithPrint {
+        name: name.to_string().into(),
+        body: Some(print_body.join("\n").into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WithHandler;
+    use speculoos::prelude::*;
+
+    fn request(method: &str) -> Request<Bytes> {
+        Request::builder()
+            .method(method)
+            .uri("http://test.example")
+            .body(Bytes::new())
+            .unwrap()
+    }
+
+    fn method(method: &str) -> WithHandler {
+        WithHandler::default().with_method(method).unwrap()
+    }
+
+    #[test]
+    fn any_of_matches() {
+        let matcher = any_of([method("PUT"), method("PATCH")]);
+
+        assert_that!(matcher.with(&request("PATCH")))
+            .is_ok()
+            .matches(|r| matches!(r, Report::Match));
+        assert_that!(matcher.with(&request("GET")))
+            .is_ok()
+            .matches(|r| matches!(r, Report::Mismatch(..)));
+    }
+
+    #[test]
+    fn all_of_matches() {
+        let matcher = all_of([
+            method("POST"),
+            WithHandler::default()
+                .with_uri("http://test.example")
+                .unwrap(),
+        ]);
+
+        assert_that!(matcher.with(&request("POST")))
+            .is_ok()
+            .matches(|r| matches!(r, Report::Match));
+        assert_that!(matcher.with(&request("GET")))
+            .is_ok()
+            .matches(|r| matches!(r, Report::Mismatch(..)));
+    }
+
+    #[test]
+    fn not_matches() {
+        let matcher = not(any_of([method("GET"), method("HEAD")]));
+
+        assert_that!(matcher.with(&request("POST")))
+            .is_ok()
+            .matches(|r| matches!(r, Report::Match));
+        assert_that!(matcher.with(&request("HEAD")))
+            .is_ok()
+            .matches(|r| matches!(
+                r,
+                Report::Mismatch(reasons) if reasons.contains(&Reason::Custom("not any of".to_string()))
+            ));
+    }
+}
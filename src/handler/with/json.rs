@@ -1,22 +1,210 @@
 use serde_json::{Map, Value};
 
+use super::{JsonDiff, JsonDiffKind};
+
+/// Parse the payload of a request, or describe why it isn't valid JSON
+pub fn parse_payload(body: &[u8]) -> Result<Value, JsonDiff> {
+    serde_json::from_slice(body).map_err(|err| {
+        let message = match std::str::from_utf8(body) {
+            Ok(_) => format!("invalid JSON payload: {err}"),
+            Err(_) => "body is not valid UTF-8".to_string(),
+        };
+        JsonDiff {
+            pointer: String::new(),
+            kind: JsonDiffKind::Invalid { message },
+        }
+    })
+}
+
+/// How arrays are compared in partial JSON matches
+///
+/// Items are compared with the same rules as the rest of the partial match, so an item of the
+/// expected array can match an item of the request payload that has extra properties. Nested arrays
+/// use the same mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ArrayMatching {
+    /// The arrays must have the same length, with matching items in the same order
+    Exact,
+    /// The arrays must have the same length, with matching items in any order
+    AnyOrder,
+    /// Each item of the expected array must match a distinct item of the actual array, in any
+    /// order, and the actual array can contain other items
+    #[default]
+    Subset,
+    /// The expected array must match the first items of the actual array, in the same order
+    Prefix,
+}
+
+/// Compute the differences between an `expected` and an `actual` JSON value
+///
+/// When `partial` is set, this follows the same rules as [`JsonEq`]: extra properties in `actual`
+/// are not differences, and arrays are compared with the provided [`ArrayMatching`].
+pub fn json_diff(
+    expected: &Value,
+    actual: &Value,
+    partial: Option<ArrayMatching>,
+) -> Vec<JsonDiff> {
+    let mut diffs = Vec::new();
+    diff_into(&mut diffs, String::new(), expected, actual, partial);
+    diffs
+}
+
+fn diff_into(
+    diffs: &mut Vec<JsonDiff>,
+    pointer: String,
+    expected: &Value,
+    actual: &Value,
+    partial: Option<ArrayMatching>,
+) {
+    let mut push = |pointer, kind| diffs.push(JsonDiff { pointer, kind });
+
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let pointer = format!("{pointer}/{}", escape(key));
+                match actual.get(key) {
+                    Some(other) => diff_into(diffs, pointer, value, other, partial),
+                    None => diffs.push(JsonDiff {
+                        pointer,
+                        kind: JsonDiffKind::Missing {
+                            expected: value.to_string(),
+                        },
+                    }),
+                }
+            }
+            if partial.is_none() {
+                for (key, value) in actual {
+                    if !expected.contains_key(key) {
+                        diffs.push(JsonDiff {
+                            pointer: format!("{pointer}/{}", escape(key)),
+                            kind: JsonDiffKind::Unexpected {
+                                actual: value.to_string(),
+                            },
+                        });
+                    }
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual))
+            if matches!(
+                partial,
+                Some(ArrayMatching::AnyOrder | ArrayMatching::Subset)
+            ) =>
+        {
+            if partial == Some(ArrayMatching::AnyOrder) && expected.len() != actual.len() {
+                push(
+                    pointer.clone(),
+                    JsonDiffKind::Length {
+                        expected: expected.len(),
+                        actual: actual.len(),
+                    },
+                );
+            }
+            for index in unmatched_items(expected, actual, partial.unwrap_or_default()) {
+                push(
+                    format!("{pointer}/{index}"),
+                    JsonDiffKind::Missing {
+                        expected: expected[index].to_string(),
+                    },
+                );
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            let length_matches = match partial {
+                Some(ArrayMatching::Prefix) => expected.len() <= actual.len(),
+                _ => expected.len() == actual.len(),
+            };
+            if !length_matches {
+                push(
+                    pointer.clone(),
+                    JsonDiffKind::Length {
+                        expected: expected.len(),
+                        actual: actual.len(),
+                    },
+                );
+            }
+            for (index, (value, other)) in expected.iter().zip(actual).enumerate() {
+                diff_into(diffs, format!("{pointer}/{index}"), value, other, partial);
+            }
+        }
+        (expected, actual) if expected != actual => push(
+            pointer,
+            JsonDiffKind::Value {
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            },
+        ),
+        _ => (),
+    }
+}
+
+/// Escape a key for use in a JSON pointer (RFC 6901)
+pub(super) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Return the indices of the `expected` items that can't be matched with a distinct `actual` item
+///
+/// This finds a maximum matching between both arrays, so an item that could match multiple actual
+/// items doesn't prevent others from matching.
+fn unmatched_items(expected: &[Value], actual: &[Value], arrays: ArrayMatching) -> Vec<usize> {
+    fn assign(
+        index: usize,
+        expected: &[Value],
+        actual: &[Value],
+        arrays: ArrayMatching,
+        owners: &mut [Option<usize>],
+        seen: &mut [bool],
+    ) -> bool {
+        for (other, value) in actual.iter().enumerate() {
+            if seen[other] || !expected[index].json_eq_with(value, arrays) {
+                continue;
+            }
+            seen[other] = true;
+
+            let assigned = match owners[other] {
+                Some(owner) => assign(owner, expected, actual, arrays, owners, seen),
+                None => true,
+            };
+            if assigned {
+                owners[other] = Some(index);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    let mut owners = vec![None; actual.len()];
+    (0..expected.len())
+        .filter(|&index| {
+            let mut seen = vec![false; actual.len()];
+            !assign(index, expected, actual, arrays, &mut owners, &mut seen)
+        })
+        .collect()
+}
+
 /// Uni-directional matches for [`Value`]s
 ///
-/// This will return true if all of the properties or items of `self` are in `other`, but does not
-/// check if the inverse is true.
+/// This will return true if all of the properties of `self` are in `other`, and if arrays match
+/// according to the [`ArrayMatching`] mode, but does not check if the inverse is true.
 pub trait JsonEq<Rhs = Self> {
-    fn json_eq(&self, other: &Rhs) -> bool;
+    fn json_eq_with(&self, other: &Rhs, arrays: ArrayMatching) -> bool;
+
+    fn json_eq(&self, other: &Rhs) -> bool {
+        self.json_eq_with(other, ArrayMatching::default())
+    }
 }
 
 impl JsonEq for Value {
-    fn json_eq(&self, other: &Self) -> bool {
+    fn json_eq_with(&self, other: &Self, arrays: ArrayMatching) -> bool {
         match self {
             Value::Array(values) => match other {
-                Value::Array(other_values) => values.json_eq(other_values),
+                Value::Array(other_values) => values.json_eq_with(other_values, arrays),
                 _ => false,
             },
             Value::Object(values) => match other {
-                Value::Object(other_values) => values.json_eq(other_values),
+                Value::Object(other_values) => values.json_eq_with(other_values, arrays),
                 _ => false,
             },
             value => value == other,
@@ -25,42 +213,37 @@ impl JsonEq for Value {
 }
 
 impl JsonEq for Vec<Value> {
-    fn json_eq(&self, other: &Self) -> bool {
-        'outer: for value in self.iter() {
-            for other_value in other.iter() {
-                if value.json_eq(other_value) {
-                    println!("found {value:?} in {other:?}");
-                    continue 'outer;
-                }
-            }
+    fn json_eq_with(&self, other: &Self, arrays: ArrayMatching) -> bool {
+        let in_order = || {
+            self.iter()
+                .zip(other)
+                .all(|(value, other_value)| value.json_eq_with(other_value, arrays))
+        };
 
-            println!("didnt find {value:?} in {other:?}");
-            return false;
+        match arrays {
+            ArrayMatching::Exact => self.len() == other.len() && in_order(),
+            ArrayMatching::Prefix => self.len() <= other.len() && in_order(),
+            ArrayMatching::AnyOrder => {
+                self.len() == other.len() && unmatched_items(self, other, arrays).is_empty()
+            }
+            ArrayMatching::Subset => unmatched_items(self, other, arrays).is_empty(),
         }
-
-        true
     }
 }
 
 impl JsonEq for Map<String, Value> {
-    fn json_eq(&self, other: &Self) -> bool {
-        for (key, value) in self.iter() {
-            if !other
+    fn json_eq_with(&self, other: &Self, arrays: ArrayMatching) -> bool {
+        self.iter().all(|(key, value)| {
+            other
                 .get(key)
-                .map(|other_value| value.json_eq(other_value))
+                .map(|other_value| value.json_eq_with(other_value, arrays))
                 .unwrap_or(false)
-            {
-                println!("didn't find {key} in {other:?}");
-                return false;
-            }
-        }
-
-        true
+        })
     }
 }
 
 impl JsonEq for String {
-    fn json_eq(&self, other: &Self) -> bool {
+    fn json_eq_with(&self, other: &Self, _arrays: ArrayMatching) -> bool {
         self == other
     }
 }
@@ -136,7 +319,96 @@ mod tests {
     #[case(json!({"a": { "b": [1, 2]}, "d": [3, 4, 5]}), json!({"a": { "b": [1, 2], "c": "text"}, "d": [3, 4, 5]}), true)]
     #[case(json!({"a": { "b": [1], "c": "text"}, "d": [3, 4, 5]}), json!({"a": { "b": [1, 2], "c": "text"}, "d": [3, 4, 5]}), true)]
     #[case(json!({"a": { "b": [1, 2], "c": "text"}}), json!({"a": { "b": [1, 2], "c": "text"}, "d": [3, 4, 5]}), true)]
+    // Duplicates
+    #[case(json!([1, 1]), json!([1, 2]), false)]
+    #[case(json!([1, 1]), json!([2, 1, 1]), true)]
+    #[case(json!([{}, {"a": 1}]), json!([{"a": 1}, {"b": 2}]), true)]
     fn json_eq(#[case] a: Value, #[case] b: Value, #[case] expected: bool) {
         assert_that!(a.json_eq(&b)).is_equal_to(expected);
     }
+
+    #[rstest]
+    #[case(ArrayMatching::Exact, json!([1, 2]), json!([1, 2]), true)]
+    #[case(ArrayMatching::Exact, json!([1, 2]), json!([2, 1]), false)]
+    #[case(ArrayMatching::Exact, json!([1, 2]), json!([1, 2, 3]), false)]
+    #[case(ArrayMatching::Exact, json!([{"a": 1}]), json!([{"a": 1, "b": 2}]), true)]
+    #[case(ArrayMatching::AnyOrder, json!([1, 2]), json!([2, 1]), true)]
+    #[case(ArrayMatching::AnyOrder, json!([1, 2]), json!([2, 1, 3]), false)]
+    #[case(ArrayMatching::AnyOrder, json!([1, 1]), json!([1, 2]), false)]
+    #[case(ArrayMatching::Subset, json!([3, 1]), json!([1, 2, 3]), true)]
+    #[case(ArrayMatching::Subset, json!([1, 4]), json!([1, 2, 3]), false)]
+    #[case(ArrayMatching::Prefix, json!([1, 2]), json!([1, 2, 3]), true)]
+    #[case(ArrayMatching::Prefix, json!([2, 3]), json!([1, 2, 3]), false)]
+    #[case(ArrayMatching::Prefix, json!([1, 2, 3]), json!([1, 2]), false)]
+    #[case(ArrayMatching::Exact, json!({"a": [[1, 2]]}), json!({"a": [[2, 1]]}), false)]
+    #[case(ArrayMatching::AnyOrder, json!({"a": [[1, 2]]}), json!({"a": [[2, 1]]}), true)]
+    fn json_eq_with(
+        #[case] arrays: ArrayMatching,
+        #[case] a: Value,
+        #[case] b: Value,
+        #[case] expected: bool,
+    ) {
+        assert_that!(a.json_eq_with(&b, arrays)).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case(json!({"a": 1}), json!({"a": 1}), None, vec![])]
+    #[case(json!({"a": 1}), json!({"a": 2}), None, vec![
+        ("/a", JsonDiffKind::Value { expected: "1".into(), actual: "2".into() }),
+    ])]
+    #[case(json!({"a": 1}), json!({"b": 1}), None, vec![
+        ("/a", JsonDiffKind::Missing { expected: "1".into() }),
+        ("/b", JsonDiffKind::Unexpected { actual: "1".into() }),
+    ])]
+    #[case(json!({"a": 1}), json!({"a": 1, "b": 1}), Some(ArrayMatching::Subset), vec![])]
+    #[case(json!({"Item": {"Host": {"S": "a"}}}), json!({"Item": {"Host": {"S": "b"}}}), Some(ArrayMatching::Subset), vec![
+        ("/Item/Host/S", JsonDiffKind::Value { expected: r#""a""#.into(), actual: r#""b""#.into() }),
+    ])]
+    #[case(json!([1, 2]), json!([1, 3, 4]), None, vec![
+        ("", JsonDiffKind::Length { expected: 2, actual: 3 }),
+        ("/1", JsonDiffKind::Value { expected: "2".into(), actual: "3".into() }),
+    ])]
+    #[case(json!([3, 1]), json!([1, 2, 3]), Some(ArrayMatching::Subset), vec![])]
+    #[case(json!({"a/b": [4]}), json!({"a/b": [1, 2, 3]}), Some(ArrayMatching::Subset), vec![
+        ("/a~1b/0", JsonDiffKind::Missing { expected: "4".into() }),
+    ])]
+    #[case(json!([1, 1]), json!([1, 2]), Some(ArrayMatching::Subset), vec![
+        ("/1", JsonDiffKind::Missing { expected: "1".into() }),
+    ])]
+    #[case(json!([1, 2]), json!([2, 1, 3]), Some(ArrayMatching::AnyOrder), vec![
+        ("", JsonDiffKind::Length { expected: 2, actual: 3 }),
+    ])]
+    #[case(json!([1, 3]), json!([1, 2, 3]), Some(ArrayMatching::Prefix), vec![
+        ("/1", JsonDiffKind::Value { expected: "3".into(), actual: "2".into() }),
+    ])]
+    #[case(json!([{"a": 1}]), json!([{"a": 1, "b": 2}]), Some(ArrayMatching::Exact), vec![])]
+    fn json_diff(
+        #[case] expected: Value,
+        #[case] actual: Value,
+        #[case] partial: Option<ArrayMatching>,
+        #[case] diffs: Vec<(&str, JsonDiffKind)>,
+    ) {
+        let diffs = diffs
+            .into_iter()
+            .map(|(pointer, kind)| JsonDiff {
+                pointer: pointer.to_string(),
+                kind,
+            })
+            .collect::<Vec<_>>();
+
+        assert_that!(super::json_diff(&expected, &actual, partial)).is_equal_to(diffs);
+    }
+
+    #[rstest]
+    #[case(b"{\"a\": 1}", Ok(json!({"a": 1})))]
+    #[case(b"\xff\xfe", Err("body is not valid UTF-8"))]
+    #[case(b"not json", Err("invalid JSON payload"))]
+    fn parse_payload(#[case] body: &[u8], #[case] expected: Result<Value, &str>) {
+        let payload = super::parse_payload(body);
+
+        match expected {
+            Ok(value) => assert_that!(payload).is_ok().is_equal_to(value),
+            Err(message) => assert_that!(payload.unwrap_err().to_string()).contains(message),
+        }
+    }
 }
@@ -0,0 +1,260 @@
+use serde_json::Value;
+use std::fmt;
+
+/// Parsed JSONPath expression
+///
+/// This supports a subset of JSONPath: the root `$`, child members with `.name` or `['name']`,
+/// array indices with `[0]`, and wildcards with `.*` or `[*]`. It can also be created from a JSON
+/// Pointer, such as `/Item/Host/S`, with [`JsonPath::parse_pointer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Member(String),
+    Index(usize),
+    Wildcard,
+    /// JSON Pointer reference token, matching either an object member or an array index
+    Token(String),
+}
+
+impl JsonPath {
+    /// Parse a JSONPath expression
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut chars = source.chars().peekable();
+        let mut segments = Vec::new();
+
+        if chars.next() != Some('$') {
+            return Err(format!("`{source}` must start with `$`"));
+        }
+
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => {
+                    let mut name = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c == '.' || c == '[' {
+                            break;
+                        }
+                        name.push(c);
+                        chars.next();
+                    }
+                    match name.as_str() {
+                        "" => return Err(format!("`{source}` contains an empty member name")),
+                        "*" => segments.push(Segment::Wildcard),
+                        _ => segments.push(Segment::Member(name)),
+                    }
+                }
+                '[' => {
+                    let mut inner = String::new();
+                    for c in chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                        inner.push(c);
+                    }
+                    let inner = inner.trim();
+
+                    let segment = if inner == "*" {
+                        Segment::Wildcard
+                    } else if let Some(name) = inner
+                        .strip_prefix('\'')
+                        .and_then(|i| i.strip_suffix('\''))
+                        .or_else(|| inner.strip_prefix('"').and_then(|i| i.strip_suffix('"')))
+                    {
+                        Segment::Member(name.to_string())
+                    } else {
+                        Segment::Index(
+                            inner
+                                .parse()
+                                .map_err(|_| format!("`{source}` contains an invalid index"))?,
+                        )
+                    };
+                    segments.push(segment);
+                }
+                c => return Err(format!("`{source}` contains an unexpected character `{c}`")),
+            }
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// Parse a JSON Pointer, as defined in RFC 6901
+    pub fn parse_pointer(source: &str) -> Result<Self, String> {
+        let segments = match source {
+            "" => Vec::new(),
+            _ => source
+                .strip_prefix('/')
+                .ok_or_else(|| format!("`{source}` must start with `/`"))?
+                .split('/')
+                .map(|token| Segment::Token(token.replace("~1", "/").replace("~0", "~")))
+                .collect(),
+        };
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// Return all the values selected by this expression
+    pub fn select<'v>(&self, value: &'v Value) -> Vec<&'v Value> {
+        let mut current = vec![value];
+
+        for segment in &self.segments {
+            current = current
+                .into_iter()
+                .flat_map(|value| -> Vec<&Value> {
+                    match (segment, value) {
+                        (Segment::Member(name) | Segment::Token(name), Value::Object(map)) => {
+                            map.get(name).into_iter().collect()
+                        }
+                        (Segment::Index(index), Value::Array(values)) => {
+                            values.get(*index).into_iter().collect()
+                        }
+                        (Segment::Token(token), Value::Array(values)) => token
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|index| values.get(index))
+                            .into_iter()
+                            .collect(),
+                        (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+                        (Segment::Wildcard, Value::Array(values)) => values.iter().collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+
+        current
+    }
+
+    /// Remove all the values selected by this expression
+    ///
+    /// Removing the root `$` replaces the whole document with `null`.
+    pub fn remove(&self, value: &mut Value) {
+        let Some((last, parents)) = self.segments.split_last() else {
+            *value = Value::Null;
+            return;
+        };
+
+        let mut current = vec![value];
+        for segment in parents {
+            current = current
+                .into_iter()
+                .flat_map(|value| -> Vec<&mut Value> {
+                    match (segment, value) {
+                        (Segment::Member(name) | Segment::Token(name), Value::Object(map)) => {
+                            map.get_mut(name).into_iter().collect()
+                        }
+                        (Segment::Index(index), Value::Array(values)) => {
+                            values.get_mut(*index).into_iter().collect()
+                        }
+                        (Segment::Token(token), Value::Array(values)) => {
+                            match token.parse::<usize>() {
+                                Ok(index) => values.get_mut(index).into_iter().collect(),
+                                Err(_) => Vec::new(),
+                            }
+                        }
+                        (Segment::Wildcard, Value::Object(map)) => map.values_mut().collect(),
+                        (Segment::Wildcard, Value::Array(values)) => values.iter_mut().collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+
+        for value in current {
+            match (last, value) {
+                (Segment::Member(name) | Segment::Token(name), Value::Object(map)) => {
+                    map.remove(name);
+                }
+                (Segment::Index(index), Value::Array(values)) if *index < values.len() => {
+                    values.remove(*index);
+                }
+                (Segment::Token(token), Value::Array(values)) => {
+                    if let Some(index) = token.parse().ok().filter(|i| *i < values.len()) {
+                        values.remove(index);
+                    }
+                }
+                (Segment::Wildcard, Value::Object(map)) => map.clear(),
+                (Segment::Wildcard, Value::Array(values)) => values.clear(),
+                _ => (),
+            }
+        }
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    use serde_json::json;
+
+    #[rstest]
+    #[case("$", vec![json!({"Item": {"Host": {"S": "some_host"}}, "list": [1, 2]})])]
+    #[case("$.Item.Host.S", vec![json!("some_host")])]
+    #[case("$['Item'][\"Host\"].S", vec![json!("some_host")])]
+    #[case("$.list[1]", vec![json!(2)])]
+    #[case("$.list[*]", vec![json!(1), json!(2)])]
+    #[case("$.list.*", vec![json!(1), json!(2)])]
+    #[case("$.missing", vec![])]
+    #[case("$.list[5]", vec![])]
+    fn json_path_select(#[case] path: &str, #[case] expected: Vec<Value>) {
+        let value = json!({"Item": {"Host": {"S": "some_host"}}, "list": [1, 2]});
+        let path = JsonPath::parse(path).unwrap();
+
+        let selected = path.select(&value).into_iter().cloned().collect::<Vec<_>>();
+        assert_that!(selected).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case("", vec![json!({"Item": {"Host": {"S": "some_host"}}, "list": [1, 2]})])]
+    #[case("/Item/Host/S", vec![json!("some_host")])]
+    #[case("/list/1", vec![json!(2)])]
+    #[case("/list/a", vec![])]
+    fn json_path_select_pointer(#[case] pointer: &str, #[case] expected: Vec<Value>) {
+        let value = json!({"Item": {"Host": {"S": "some_host"}}, "list": [1, 2]});
+        let path = JsonPath::parse_pointer(pointer).unwrap();
+
+        let selected = path.select(&value).into_iter().cloned().collect::<Vec<_>>();
+        assert_that!(selected).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case("$", json!(null))]
+    #[case("$.Item.Host", json!({"Item": {}, "list": [{"id": 1}, {"id": 2}]}))]
+    #[case("$.list[*].id", json!({"Item": {"Host": {"S": "some_host"}}, "list": [{}, {}]}))]
+    #[case("$.list[0]", json!({"Item": {"Host": {"S": "some_host"}}, "list": [{"id": 2}]}))]
+    #[case("$.missing.id", json!({"Item": {"Host": {"S": "some_host"}}, "list": [{"id": 1}, {"id": 2}]}))]
+    fn json_path_remove(#[case] path: &str, #[case] expected: Value) {
+        let mut value =
+            json!({"Item": {"Host": {"S": "some_host"}}, "list": [{"id": 1}, {"id": 2}]});
+        JsonPath::parse(path).unwrap().remove(&mut value);
+
+        assert_that!(value).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case("Item")]
+    #[case("$..Item")]
+    #[case("$[abc]")]
+    #[case("$Item")]
+    fn json_path_invalid(#[case] path: &str) {
+        assert_that!(JsonPath::parse(path)).is_err();
+    }
+}
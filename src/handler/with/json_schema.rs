@@ -0,0 +1,386 @@
+use serde_json::Value;
+
+use super::{json::escape, JsonDiff, JsonDiffKind};
+
+/// Keywords that are validated by [`JsonSchema`]
+const KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "required",
+    "properties",
+    "additionalProperties",
+    "minProperties",
+    "maxProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+];
+
+/// Keywords that don't affect validation
+const ANNOTATIONS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "format",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+/// JSON Schema document used to validate request payloads
+///
+/// This supports a subset of JSON Schema: the keywords in [`KEYWORDS`] are validated, and the
+/// ones in [`ANNOTATIONS`] are ignored. Other keywords, such as `$ref` or `pattern`, are rejected
+/// when parsing the schema, so a schema never matches more payloads than expected.
+#[derive(Debug, Clone)]
+pub struct JsonSchema(Value);
+
+impl JsonSchema {
+    /// Parse a JSON Schema document
+    pub fn parse(schema: Value) -> Result<Self, String> {
+        check(&schema, "")?;
+        Ok(Self(schema))
+    }
+
+    /// Return the schema document
+    pub fn schema(&self) -> &Value {
+        &self.0
+    }
+
+    /// Validate `value` against the schema, and return all the failed keywords
+    pub fn validate(&self, value: &Value) -> Vec<JsonDiff> {
+        let mut errors = Vec::new();
+        validate(&mut errors, String::new(), &self.0, value);
+        errors
+    }
+}
+
+/// Check that the schema only uses supported keywords
+fn check(schema: &Value, path: &str) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(schema) => schema,
+        _ => return Err(format!("schema at `{path}` must be an object or a boolean")),
+    };
+
+    for (keyword, value) in schema {
+        let path = format!("{path}/{}", escape(keyword));
+        match keyword.as_str() {
+            "properties" => {
+                for (name, value) in value
+                    .as_object()
+                    .ok_or_else(|| format!("`{path}` must be an object"))?
+                {
+                    check(value, &format!("{path}/{}", escape(name)))?;
+                }
+            }
+            "additionalProperties" | "items" | "not" => check(value, &path)?,
+            "allOf" | "anyOf" | "oneOf" => {
+                for (index, value) in value
+                    .as_array()
+                    .ok_or_else(|| format!("`{path}` must be an array"))?
+                    .iter()
+                    .enumerate()
+                {
+                    check(value, &format!("{path}/{index}"))?;
+                }
+            }
+            keyword if KEYWORDS.contains(&keyword) || ANNOTATIONS.contains(&keyword) => (),
+            _ => return Err(format!("unsupported keyword `{path}`")),
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(errors: &mut Vec<JsonDiff>, pointer: String, schema: &Value, value: &Value) {
+    let schema = match schema {
+        Value::Bool(false) => {
+            errors.push(error(&pointer, "false", "no value is allowed".to_string()));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+    let number = |keyword| schema.get(keyword).and_then(Value::as_f64);
+    let count = |keyword| schema.get(keyword).and_then(Value::as_u64);
+
+    if let Some(types) = schema.get("type") {
+        let types = match types {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            types => types.as_str().into_iter().collect::<Vec<_>>(),
+        };
+        if !types.iter().any(|name| is_type(value, name)) {
+            let message = format!("expected {}, got {}", types.join(" or "), type_name(value));
+            errors.push(error(&pointer, "type", message));
+        }
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        if !values.contains(value) {
+            let message = format!(
+                "expected one of {}, got {value}",
+                Value::from(values.clone())
+            );
+            errors.push(error(&pointer, "enum", message));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            let message = format!("expected {expected}, got {value}");
+            errors.push(error(&pointer, "const", message));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !map.contains_key(name) {
+                    let pointer = format!("{pointer}/{}", escape(name));
+                    errors.push(error(&pointer, "required", "missing property".to_string()));
+                }
+            }
+            for (name, value) in map {
+                let child = format!("{pointer}/{}", escape(name));
+                match (
+                    properties.and_then(|p| p.get(name)),
+                    schema.get("additionalProperties"),
+                ) {
+                    (Some(schema), _) => validate(errors, child, schema, value),
+                    (None, Some(Value::Bool(false))) => {
+                        let message = format!("unexpected property {value}");
+                        errors.push(error(&child, "additionalProperties", message));
+                    }
+                    (None, Some(schema)) => validate(errors, child, schema, value),
+                    (None, None) => (),
+                }
+            }
+            let length = map.len() as u64;
+            if let Some(min) = count("minProperties").filter(|min| length < *min) {
+                let message = format!("expected at least {min} properties, got {length}");
+                errors.push(error(&pointer, "minProperties", message));
+            }
+            if let Some(max) = count("maxProperties").filter(|max| length > *max) {
+                let message = format!("expected at most {max} properties, got {length}");
+                errors.push(error(&pointer, "maxProperties", message));
+            }
+        }
+        Value::Array(items) => {
+            if let Some(schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate(errors, format!("{pointer}/{index}"), schema, item);
+                }
+            }
+            let length = items.len() as u64;
+            if let Some(min) = count("minItems").filter(|min| length < *min) {
+                let message = format!("expected at least {min} items, got {length}");
+                errors.push(error(&pointer, "minItems", message));
+            }
+            if let Some(max) = count("maxItems").filter(|max| length > *max) {
+                let message = format!("expected at most {max} items, got {length}");
+                errors.push(error(&pointer, "maxItems", message));
+            }
+            if schema.get("uniqueItems") == Some(&Value::Bool(true))
+                && items
+                    .iter()
+                    .enumerate()
+                    .any(|(index, item)| items[..index].contains(item))
+            {
+                let message = "expected unique items".to_string();
+                errors.push(error(&pointer, "uniqueItems", message));
+            }
+        }
+        Value::String(value) => {
+            let length = value.chars().count() as u64;
+            if let Some(min) = count("minLength").filter(|min| length < *min) {
+                let message = format!("expected at least {min} characters, got {length}");
+                errors.push(error(&pointer, "minLength", message));
+            }
+            if let Some(max) = count("maxLength").filter(|max| length > *max) {
+                let message = format!("expected at most {max} characters, got {length}");
+                errors.push(error(&pointer, "maxLength", message));
+            }
+        }
+        Value::Number(value) => {
+            let value = value.as_f64().unwrap_or_default();
+            if let Some(min) = number("minimum").filter(|min| value < *min) {
+                let message = format!("expected at least {min}, got {value}");
+                errors.push(error(&pointer, "minimum", message));
+            }
+            if let Some(max) = number("maximum").filter(|max| value > *max) {
+                let message = format!("expected at most {max}, got {value}");
+                errors.push(error(&pointer, "maximum", message));
+            }
+            if let Some(min) = number("exclusiveMinimum").filter(|min| value <= *min) {
+                let message = format!("expected more than {min}, got {value}");
+                errors.push(error(&pointer, "exclusiveMinimum", message));
+            }
+            if let Some(max) = number("exclusiveMaximum").filter(|max| value >= *max) {
+                let message = format!("expected less than {max}, got {value}");
+                errors.push(error(&pointer, "exclusiveMaximum", message));
+            }
+            if let Some(factor) = number("multipleOf")
+                .filter(|factor| *factor > 0.0 && (value / factor).fract() != 0.0)
+            {
+                let message = format!("expected a multiple of {factor}, got {value}");
+                errors.push(error(&pointer, "multipleOf", message));
+            }
+        }
+        _ => (),
+    }
+
+    // Sub-schemas that apply to the same value
+    for schema in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        validate(errors, pointer.clone(), schema, value);
+    }
+    if let Some(schemas) = schema.get("anyOf").and_then(Value::as_array) {
+        if !schemas.iter().any(|schema| is_valid(schema, value)) {
+            let message = "expected a value matching at least one schema".to_string();
+            errors.push(error(&pointer, "anyOf", message));
+        }
+    }
+    if let Some(schemas) = schema.get("oneOf").and_then(Value::as_array) {
+        let matches = schemas
+            .iter()
+            .filter(|schema| is_valid(schema, value))
+            .count();
+        if matches != 1 {
+            let message = format!("expected a value matching exactly one schema, got {matches}");
+            errors.push(error(&pointer, "oneOf", message));
+        }
+    }
+    if let Some(schema) = schema.get("not") {
+        if is_valid(schema, value) {
+            let message = "expected a value not matching the schema".to_string();
+            errors.push(error(&pointer, "not", message));
+        }
+    }
+}
+
+fn is_valid(schema: &Value, value: &Value) -> bool {
+    let mut errors = Vec::new();
+    validate(&mut errors, String::new(), schema, value);
+    errors.is_empty()
+}
+
+fn error(pointer: &str, keyword: &str, message: String) -> JsonDiff {
+    JsonDiff {
+        pointer: pointer.to_string(),
+        kind: JsonDiffKind::Schema {
+            keyword: keyword.to_string(),
+            message,
+        },
+    }
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => match value {
+            Value::Number(value) => {
+                value.is_i64()
+                    || value.is_u64()
+                    || value.as_f64().is_some_and(|value| value.fract() == 0.0)
+            }
+            _ => false,
+        },
+        name => type_name(value) == name,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    fn schema() -> JsonSchema {
+        JsonSchema::parse(json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "name": {"type": "string", "minLength": 1},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "uniqueItems": true},
+                "kind": {"oneOf": [{"const": "x"}, {"const": "y"}]}
+            },
+            "additionalProperties": false
+        }))
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case(json!({"id": 1, "name": "first"}), vec![])]
+    #[case(json!({"id": 1, "name": "first", "tags": ["a", "b"], "kind": "x"}), vec![])]
+    #[case(json!("not an object"), vec![("", "type")])]
+    #[case(json!({"id": 1}), vec![("/name", "required")])]
+    #[case(json!({"id": 0, "name": ""}), vec![("/id", "minimum"), ("/name", "minLength")])]
+    #[case(json!({"id": 1.5, "name": "first"}), vec![("/id", "type")])]
+    #[case(json!({"id": 1, "name": "first", "extra": true}), vec![("/extra", "additionalProperties")])]
+    #[case(json!({"id": 1, "name": "first", "tags": ["a", "c", "a"]}), vec![("/tags/1", "enum"), ("/tags", "uniqueItems")])]
+    #[case(json!({"id": 1, "name": "first", "kind": "z"}), vec![("/kind", "oneOf")])]
+    fn test_validate(#[case] value: Value, #[case] expected: Vec<(&str, &str)>) {
+        let errors = schema()
+            .validate(&value)
+            .into_iter()
+            .map(|diff| match diff.kind {
+                JsonDiffKind::Schema { keyword, .. } => (diff.pointer, keyword),
+                kind => panic!("unexpected kind {kind:?}"),
+            })
+            .collect::<Vec<_>>();
+        let expected = expected
+            .into_iter()
+            .map(|(pointer, keyword)| (pointer.to_string(), keyword.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_that!(errors).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case(json!({"$ref": "#/definitions/item"}))]
+    #[case(json!({"properties": {"name": {"pattern": "^a"}}}))]
+    #[case(json!({"allOf": [{"type": "string"}, 1]}))]
+    #[case(json!("string"))]
+    fn test_parse_unsupported(#[case] schema: Value) {
+        assert_that!(JsonSchema::parse(schema)).is_err();
+    }
+}
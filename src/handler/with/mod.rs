@@ -1,7 +1,13 @@
-use crate::{error::BoxError, Error};
+use crate::{
+    codec::{encode_base64, parse_digest, RawBody, Trailers},
+    error::BoxError,
+    Error,
+};
 use colored::Colorize;
 use hyper::{
-    http::{HeaderName, HeaderValue},
+    body::Bytes,
+    header::AUTHORIZATION,
+    http::{Extensions, HeaderName, HeaderValue},
     HeaderMap, Method, Request, Uri,
 };
 use itertools::Itertools;
@@ -11,27 +17,265 @@ use std::{
     cmp::{max, min},
     collections::HashSet,
     error::Error as StdError,
+    future::{ready, Future},
+    pin::Pin,
+    sync::Arc,
 };
 
+mod asynchronous;
+pub use asynchronous::WithAsync;
+mod body;
+pub(crate) use body::StreamedBody;
+use body::{BodyLen, BodySha256, BodyStreamFactory};
+pub use body::{BodyStream, BodyStreams};
+mod combinators;
+pub use combinators::{all_of, any_of, not, AllOf, AnyOf, Not};
 #[cfg(feature = "json")]
 mod json;
 #[cfg(feature = "json")]
-use json::JsonEq;
+pub use json::ArrayMatching;
+#[cfg(feature = "json")]
+pub(crate) use json::JsonEq;
+#[cfg(feature = "json")]
+use json::{json_diff, parse_payload};
+#[cfg(feature = "json")]
+mod json_path;
+#[cfg(feature = "json")]
+mod json_schema;
+#[cfg(feature = "json")]
+use json_path::JsonPath;
+#[cfg(feature = "json")]
+use json_schema::JsonSchema;
+mod multipart;
+pub(crate) use multipart::form_parts;
+pub use multipart::{Multipart, MultipartPart};
+mod path;
+pub use path::PathParams;
+use path::PathTemplate;
+mod pattern;
+use pattern::Pattern;
 mod report;
-pub use report::{Reason, Report};
+pub use report::{JsonDiff, JsonDiffKind, Reason, Report};
+#[cfg(feature = "sigv4")]
+mod sigv4;
+#[cfg(feature = "sigv4")]
+use sigv4::SigV4;
+#[cfg(feature = "sigv4")]
+pub use sigv4::SigV4Credentials;
+mod soap;
+pub(crate) use soap::soap_action;
+mod uri;
+pub(crate) use uri::uri_component;
+use uri::uri_mismatches;
+pub use uri::{UriComponent, UriNormalization, UriParts};
 
+/// Future returned by [`With::with_async`]
+pub type WithFuture<'w> =
+    Pin<Box<dyn Future<Output = Result<Report, BoxError>> + Send + Sync + 'w>>;
+
+/// Trait for matching incoming requests against a mock case
+///
+/// This is implemented for closures that take a `&Request<Bytes>` and return a
+/// `Result<impl Into<Report>, impl Error>`, for [`WithHandler`], and for the combinators
+/// [`any_of`], [`all_of`], and [`not`].
+///
+/// ## Custom matchers
+///
+/// Other crates can implement this trait to provide reusable matchers, and register them with
+/// [`crate::CaseBuilder::with_matcher`]. Only [`With::with`] and [`With::print_pretty`] are
+/// required. A matcher that rejects a request should return a [`Report::Mismatch`] with a
+/// [`Reason::Custom`], and can implement [`With::expected`] and [`With::actual`] so the
+/// [`crate::MismatchReport`] shows the values it compared.
+///
+/// ```rust
+/// # use hyper::{body::Bytes, Request};
+/// # use mock_http_connector::{all_of, BoxError, Connector, Error, Reason, Report, With, WithHandler, WithPrint};
+/// # use std::collections::HashSet;
+/// /// Matcher for requests with an allowed `x-api-key` header
+/// struct ApiKey(Vec<String>);
+///
+/// fn api_key(req: &Request<Bytes>) -> Option<String> {
+///     req.headers().get("x-api-key")?.to_str().ok().map(ToString::to_string)
+/// }
+///
+/// impl With for ApiKey {
+///     fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
+///         let matched = api_key(req).is_some_and(|key| self.0.contains(&key));
+///         Ok(match matched {
+///             true => Report::Match,
+///             false => Some(Reason::Custom("api key".into())).into(),
+///         })
+///     }
+///
+///     fn print_pretty(&self, _report: &HashSet<Reason>) -> WithPrint<'_> {
+///         WithPrint { name: "api key".into(), body: Some(self.0.join(", ").into()) }
+///     }
+///
+///     fn expected(&self, reason: &Reason) -> Option<String> {
+///         matches!(reason, Reason::Custom(name) if name == "api key").then(|| self.0.join(" | "))
+///     }
+///
+///     fn actual(&self, _reason: &Reason, req: &Request<Bytes>) -> Option<String> {
+///         api_key(req)
+///     }
+/// }
+///
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_matcher(all_of([
+///         WithHandler::default().with_uri("https://test.example/users")?.boxed(),
+///         ApiKey(vec!["some-key".to_string()]).boxed(),
+///     ]))
+///     .returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
 pub trait With: Send + Sync {
-    fn with(&self, req: &Request<String>) -> Result<Report, BoxError>;
+    /// Check if the incoming [`Request`] matches
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError>;
+
+    /// Check if the incoming [`Request`] matches, for matchers that need to await something
+    ///
+    /// By default, it returns the result of [`With::with`].
+    fn with_async<'w>(&'w self, req: &'w Request<Bytes>) -> WithFuture<'w> {
+        Box::pin(ready(self.with(req)))
+    }
+
+    /// Check if the incoming [`Request`] matches, and attach typed extensions for the response
+    ///
+    /// If the case matches, the values inserted in `extensions` are added to the request passed
+    /// to [`crate::Returning`], so work done while matching, such as deserializing the body,
+    /// doesn't need to be repeated when building the response. They are discarded otherwise.
+    ///
+    /// The connector always uses this method. By default, it returns the result of
+    /// [`With::with_async`] without adding any extensions.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use hyper::{body::Bytes, http::Extensions, Request};
+    /// # use mock_http_connector::{With, WithFuture, WithPrint, Reason, Report};
+    /// # use std::collections::HashSet;
+    /// /// Matcher for requests with a numeric `x-user-id` header
+    /// struct UserId;
+    ///
+    /// impl UserId {
+    ///     fn parse(req: &Request<Bytes>) -> Option<u64> {
+    ///         req.headers().get("x-user-id")?.to_str().ok()?.parse().ok()
+    ///     }
+    /// }
+    ///
+    /// impl With for UserId {
+    ///     fn with(&self, req: &Request<Bytes>) -> Result<Report, Box<dyn std::error::Error + Send + Sync>> {
+    ///         Ok(Self::parse(req).is_some().into())
+    ///     }
+    ///
+    ///     fn with_extensions<'w>(
+    ///         &'w self,
+    ///         req: &'w Request<Bytes>,
+    ///         extensions: &'w mut Extensions,
+    ///     ) -> WithFuture<'w> {
+    ///         // The `returning` closure reads the id with `req.extensions().get::<u64>()`
+    ///         let id = Self::parse(req);
+    ///         let matched = id.is_some();
+    ///         if let Some(id) = id {
+    ///             extensions.insert(id);
+    ///         }
+    ///         Box::pin(async move { Ok(matched.into()) })
+    ///     }
+    ///
+    ///     fn print_pretty(&self, _report: &HashSet<Reason>) -> WithPrint<'_> {
+    ///         WithPrint { name: "user id".into(), body: None }
+    ///     }
+    /// }
+    /// ```
+    fn with_extensions<'w>(
+        &'w self,
+        req: &'w Request<Bytes>,
+        extensions: &'w mut Extensions,
+    ) -> WithFuture<'w> {
+        let _ = extensions;
+        self.with_async(req)
+    }
 
+    /// Return a printable representation of this matcher for diagnostics
+    ///
+    /// `report` contains the reasons why the last request didn't match.
     #[allow(clippy::mutable_key_type)]
     fn print_pretty(&self, report: &HashSet<Reason>) -> WithPrint<'_>;
+
+    /// Return the value this matcher expects for a given [`Reason`], if any
+    ///
+    /// This is used to build the [`crate::MismatchReport`] when no cases match a request.
+    fn expected(&self, _reason: &Reason) -> Option<String> {
+        None
+    }
+
+    /// Return the value found in the request for a given [`Reason`], if any
+    ///
+    /// This is used to build the [`crate::MismatchReport`] for [`Reason::Custom`], as the
+    /// connector only knows how to extract the actual values of the built-in reasons.
+    fn actual(&self, _reason: &Reason, _req: &Request<Bytes>) -> Option<String> {
+        None
+    }
+
+    /// Add the [`BodyStream`]s of this matcher to `streams`
+    ///
+    /// This is used when the connector streams request bodies, to consume them incrementally
+    /// before the request is matched. See [`crate::Builder::stream_request_bodies`].
+    #[doc(hidden)]
+    fn body_streams(&self, _streams: &mut BodyStreams) {}
+
+    /// Box this matcher, to combine matchers of different types
+    fn boxed(self) -> Box<dyn With>
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl With for Box<dyn With> {
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
+        self.as_ref().with(req)
+    }
+
+    fn with_async<'w>(&'w self, req: &'w Request<Bytes>) -> WithFuture<'w> {
+        self.as_ref().with_async(req)
+    }
+
+    fn print_pretty(&self, report: &HashSet<Reason>) -> WithPrint<'_> {
+        self.as_ref().print_pretty(report)
+    }
+
+    fn expected(&self, reason: &Reason) -> Option<String> {
+        self.as_ref().expected(reason)
+    }
+
+    fn actual(&self, reason: &Reason, req: &Request<Bytes>) -> Option<String> {
+        self.as_ref().actual(reason, req)
+    }
+
+    fn body_streams(&self, streams: &mut BodyStreams) {
+        self.as_ref().body_streams(streams)
+    }
+
+    fn with_extensions<'w>(
+        &'w self,
+        req: &'w Request<Bytes>,
+        extensions: &'w mut Extensions,
+    ) -> WithFuture<'w> {
+        self.as_ref().with_extensions(req, extensions)
+    }
 }
 
 #[derive(Debug)]
 pub struct DefaultWith;
 
 impl With for DefaultWith {
-    fn with(&self, _req: &Request<String>) -> Result<Report, BoxError> {
+    fn with(&self, _req: &Request<Bytes>) -> Result<Report, BoxError> {
         Ok(Report::Match)
     }
 
@@ -45,11 +289,11 @@ impl With for DefaultWith {
 
 impl<F, E, R> With for F
 where
-    F: Fn(&Request<String>) -> Result<R, E> + Any + Send + Sync,
+    F: Fn(&Request<Bytes>) -> Result<R, E> + Any + Send + Sync,
     R: Into<Report> + Send + Sync + 'static,
     E: StdError + Send + Sync + 'static,
 {
-    fn with(&self, req: &Request<String>) -> Result<Report, BoxError> {
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
         (self)(req).map(Into::into).map_err(Into::into)
     }
 
@@ -64,20 +308,55 @@ where
     }
 }
 
+/// Printable representation of a [`With`] matcher
 pub struct WithPrint<'w> {
+    /// Name of the matcher
     pub name: Cow<'w, str>,
+    /// Optional multi-line description of what the matcher expects
     pub body: Option<Cow<'w, str>>,
 }
 
+/// Matcher for the common attributes of a request
+///
+/// This is the matcher used by the `with_*` methods of [`crate::CaseBuilder`]. It can also be
+/// created directly to be used with combinators such as [`any_of`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{any_of, With, WithHandler};
+/// # fn main() -> Result<(), mock_http_connector::Error> {
+/// let matcher = any_of([
+///     WithHandler::default().with_uri("https://test.example/a")?,
+///     WithHandler::default().with_uri("https://test.example/b")?,
+/// ]);
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Default, Debug)]
 pub struct WithHandler {
     uri: Option<Uri>,
-    method: Option<Method>,
+    uri_normalization: UriNormalization,
+    uri_parts: Option<UriParts>,
+    path_template: Option<PathTemplate>,
+    host: Option<String>,
+    scheme: Option<String>,
+    methods: Vec<Method>,
     headers: Vec<(HeaderName, HeaderCheck)>,
+    trailers: Vec<(HeaderName, HeaderValue)>,
     body: Option<Body>,
+    body_streams: Vec<BodyStreamFactory>,
+    multipart: Option<Multipart>,
+    soap_action: Option<String>,
+    #[cfg(feature = "json")]
+    json_paths: Vec<(JsonPath, serde_json::Value)>,
+    #[cfg(feature = "sigv4")]
+    sigv4: Option<SigV4>,
+    closures: Vec<Closure>,
 }
 
 impl WithHandler {
+    /// Match requests with the specified [`Uri`]
     pub fn with_uri<U>(mut self, uri: U) -> Result<Self, Error>
     where
         U: TryInto<Uri>,
@@ -87,15 +366,87 @@ impl WithHandler {
         Ok(self)
     }
 
+    /// Match requests with the specified [`Uri`], ignoring the differences set in `normalization`
+    pub fn with_uri_normalized<U>(
+        mut self,
+        uri: U,
+        normalization: UriNormalization,
+    ) -> Result<Self, Error>
+    where
+        U: TryInto<Uri>,
+        U::Error: Into<hyper::http::Error>,
+    {
+        self.uri_normalization = normalization;
+        self.with_uri(uri)
+    }
+
+    /// Match requests where the URI contains the expected components
+    pub fn with_uri_parts(mut self, parts: UriParts) -> Self {
+        self.uri_parts = Some(parts);
+        self
+    }
+
+    /// Match requests with a path matching the template, such as `/users/{id}`
+    ///
+    /// The captured parameters are added to the request extensions as [`PathParams`].
+    pub fn with_path_template(mut self, template: &str) -> Result<Self, Error> {
+        self.path_template = Some(PathTemplate::parse(template).map_err(Error::PathTemplate)?);
+        Ok(self)
+    }
+
+    /// Match requests sent to the provided host, regardless of the rest of the URI
+    ///
+    /// Hosts are compared case-insensitively.
+    pub fn with_host<H>(mut self, host: H) -> Self
+    where
+        H: Into<String>,
+    {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Match requests using the provided scheme, such as `http` or `https`
+    ///
+    /// Schemes are compared case-insensitively.
+    pub fn with_scheme<S>(mut self, scheme: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.scheme = Some(scheme.into());
+        self
+    }
+
+    /// Match requests with the specified [`Method`]
     pub fn with_method<M>(mut self, method: M) -> Result<Self, Error>
     where
         M: TryInto<Method>,
         M::Error: Into<hyper::http::Error>,
     {
-        self.method = Some(method.try_into().map_err(Into::into)?);
+        self.methods = vec![method.try_into().map_err(Into::into)?];
+        Ok(self)
+    }
+
+    /// Match requests using any of the provided [`Method`]s
+    ///
+    /// This returns an error if `methods` is empty, as no requests could match.
+    pub fn with_method_in<IM, M>(mut self, methods: IM) -> Result<Self, Error>
+    where
+        IM: IntoIterator<Item = M>,
+        M: TryInto<Method>,
+        M::Error: Into<hyper::http::Error>,
+    {
+        self.methods = methods
+            .into_iter()
+            .map(|method| method.try_into())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)?;
+        if self.methods.is_empty() {
+            return Err(Error::Method("no methods to match".to_string()));
+        }
         Ok(self)
     }
 
+    /// Match requests that contain at least one entry with this header value
     pub fn with_header<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
     where
         K: TryInto<HeaderName>,
@@ -111,6 +462,7 @@ impl WithHandler {
         Ok(self)
     }
 
+    /// Match requests that contain exactly one entry for this header, with this value
     pub fn with_header_once<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
     where
         K: TryInto<HeaderName>,
@@ -126,6 +478,7 @@ impl WithHandler {
         Ok(self)
     }
 
+    /// Match requests where all entries for this header correspond to the given values
     pub fn with_header_all<K, IV, V>(mut self, key: K, values: IV) -> Result<Self, Error>
     where
         K: TryInto<HeaderName>,
@@ -148,6 +501,135 @@ impl WithHandler {
         Ok(self)
     }
 
+    /// Match requests that contain at least one entry with an equivalent header value
+    ///
+    /// Values are compared case-insensitively, and ignoring whitespace around `;`, `,` and `=`.
+    pub fn with_header_loose<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            HeaderCheck::Loose(value.try_into().map_err(Into::into)?),
+        ));
+
+        Ok(self)
+    }
+
+    /// Match requests that contain at least one entry for this header for which `f` returns `true`
+    pub fn with_header_fn<K, F>(mut self, key: K, f: F) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            HeaderCheck::Predicate(HeaderPredicate(Arc::new(f))),
+        ));
+
+        Ok(self)
+    }
+
+    /// Match requests that contain at least one entry for this header that matches the regular
+    /// expression `pattern`
+    pub fn with_header_matches<K>(mut self, key: K, pattern: &str) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            HeaderCheck::Matches(Pattern::parse(pattern).map_err(Error::Pattern)?),
+        ));
+
+        Ok(self)
+    }
+
+    /// Match requests that contain exactly `count` entries for this header, regardless of their
+    /// values
+    pub fn with_header_count<K>(mut self, key: K, count: usize) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            HeaderCheck::Count(count),
+        ));
+
+        Ok(self)
+    }
+
+    /// Match requests that don't contain any entry for this header
+    pub fn with_header_missing<K>(mut self, key: K) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+    {
+        self.headers
+            .push((key.try_into().map_err(Into::into)?, HeaderCheck::Absent));
+
+        Ok(self)
+    }
+
+    /// Match requests with HTTP Basic credentials for `user` and `password`
+    pub fn with_basic_auth(self, user: &str, password: &str) -> Result<Self, Error> {
+        let credentials = encode_base64(format!("{user}:{password}").as_bytes());
+        self.with_header(AUTHORIZATION, format!("Basic {credentials}"))
+    }
+
+    /// Match requests with this bearer token in the `authorization` header
+    pub fn with_bearer_token(self, token: &str) -> Result<Self, Error> {
+        self.with_header(AUTHORIZATION, format!("Bearer {token}"))
+    }
+
+    /// Match requests with a bearer token for which `f` returns `true`
+    pub fn with_bearer_token_matching<F>(self, f: F) -> Result<Self, Error>
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.with_header_fn(AUTHORIZATION, move |value| {
+            value
+                .split_once(' ')
+                .is_some_and(|(scheme, token)| scheme.eq_ignore_ascii_case("bearer") && f(token))
+        })
+    }
+
+    /// Match SOAP requests for this action
+    ///
+    /// The action is read from the `SOAPAction` header, or from the `action` parameter of the
+    /// `content-type` header for SOAP 1.2.
+    pub fn with_soap_action<A>(mut self, action: A) -> Self
+    where
+        A: Into<String>,
+    {
+        self.soap_action = Some(action.into());
+        self
+    }
+
+    /// Match requests that contain at least one trailer entry with this value
+    ///
+    /// Trailers are only sent with chunked transfer-encoding, after the last chunk.
+    pub fn with_trailer<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.trailers.push((
+            key.try_into().map_err(Into::into)?,
+            value.try_into().map_err(Into::into)?,
+        ));
+
+        Ok(self)
+    }
+
+    /// Match requests with the provided payload
     pub fn with_body<B>(mut self, body: B) -> Self
     where
         B: ToString,
@@ -156,82 +638,405 @@ impl WithHandler {
         self
     }
 
+    /// Match requests with the provided payload, as sent on the wire before decoding
+    pub fn with_raw_body<B>(mut self, body: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.body = Some(Body::Raw(Bytes::copy_from_slice(body.as_ref())));
+        self
+    }
+
+    /// Match requests with the provided binary payload
+    pub fn with_body_bytes<B>(mut self, body: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.body = Some(Body::Bytes(Bytes::copy_from_slice(body.as_ref())));
+        self
+    }
+
+    /// Match requests for which the [`BodyStream`] returned by `f` matches the payload
+    ///
+    /// `f` is called once per request, and the payload is passed to the stream as the client sends
+    /// it when the connector streams request bodies.
+    pub fn with_body_stream<F, S>(mut self, f: F) -> Self
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+        S: BodyStream + 'static,
+    {
+        self.body_streams.push(BodyStreamFactory::new(f));
+        self
+    }
+
+    /// Match requests with a payload of exactly `len` bytes
+    pub fn with_body_len(mut self, len: usize) -> Self {
+        self.body_streams.push(BodyStreamFactory::with_label(
+            format!("body length: {len}"),
+            move || BodyLen::new(len),
+        ));
+        self
+    }
+
+    /// Match requests with a payload whose SHA-256 digest is the provided hex-encoded value
+    pub fn with_body_sha256(mut self, hex: &str) -> Result<Self, Error> {
+        let digest = parse_digest(hex).ok_or_else(|| Error::Digest(hex.to_string()))?;
+        self.body_streams.push(BodyStreamFactory::with_label(
+            format!("body sha256: {}", hex.to_ascii_lowercase()),
+            move || BodySha256::new(digest),
+        ));
+        Ok(self)
+    }
+
+    /// Match requests with a valid AWS SigV4 signature for these credentials, region, and service
+    #[cfg(feature = "sigv4")]
+    pub fn with_sigv4(
+        mut self,
+        credentials: SigV4Credentials,
+        region: &str,
+        service: &str,
+    ) -> Self {
+        self.sigv4 = Some(SigV4::new(credentials, region, service));
+        self
+    }
+
+    /// Match requests with a `multipart/*` body that contains the expected parts
+    pub fn with_multipart(mut self, multipart: Multipart) -> Self {
+        self.multipart = Some(multipart);
+        self
+    }
+
+    /// Match requests with a body that exactly matches the provided JSON payload
     #[cfg(feature = "json")]
     pub fn with_json<V>(mut self, value: V) -> Result<Self, Error>
     where
         V: serde::Serialize,
     {
-        self.body = Some(Body::Json(serde_json::to_value(value)?));
+        self.body = Some(Body::Json(serde_json::to_value(value)?, Vec::new()));
+        Ok(self)
+    }
+
+    /// Match requests with a body that exactly matches the provided JSON payload, except for the
+    /// values selected by `paths`
+    ///
+    /// Each path is either a JSONPath expression starting with `$`, or a JSON Pointer.
+    #[cfg(feature = "json")]
+    pub fn with_json_ignoring<V, I, P>(mut self, value: V, paths: I) -> Result<Self, Error>
+    where
+        V: serde::Serialize,
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        let ignored = paths
+            .into_iter()
+            .map(|path| match path.as_ref() {
+                path if path.starts_with('$') => JsonPath::parse(path),
+                path => JsonPath::parse_pointer(path),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::JsonPath)?;
+
+        let mut value = serde_json::to_value(value)?;
+        for path in &ignored {
+            path.remove(&mut value);
+        }
+        self.body = Some(Body::Json(value, ignored));
+        Ok(self)
+    }
+
+    /// Match requests that contain the provided JSON payload, but may contain other properties
+    #[cfg(feature = "json")]
+    pub fn with_json_partial<V>(self, value: V) -> Result<Self, Error>
+    where
+        V: serde::Serialize,
+    {
+        self.with_json_partial_arrays(value, ArrayMatching::default())
+    }
+
+    /// Match requests that contain the provided JSON payload, but may contain other properties,
+    /// comparing arrays with the provided [`ArrayMatching`] mode
+    #[cfg(feature = "json")]
+    pub fn with_json_partial_arrays<V>(
+        mut self,
+        value: V,
+        arrays: ArrayMatching,
+    ) -> Result<Self, Error>
+    where
+        V: serde::Serialize,
+    {
+        self.body = Some(Body::JsonPartial(serde_json::to_value(value)?, arrays));
+        Ok(self)
+    }
+
+    /// Match requests with a JSON body that is valid against the provided JSON Schema
+    #[cfg(feature = "json")]
+    pub fn with_json_schema<V>(mut self, schema: V) -> Result<Self, Error>
+    where
+        V: serde::Serialize,
+    {
+        let schema = JsonSchema::parse(serde_json::to_value(schema)?).map_err(Error::JsonSchema)?;
+        self.body = Some(Body::JsonSchema(schema));
         Ok(self)
     }
 
+    /// Match requests with a JSON body where the JSONPath expression selects the provided value
     #[cfg(feature = "json")]
-    pub fn with_json_partial<V>(mut self, value: V) -> Result<Self, Error>
+    pub fn with_json_path<V>(mut self, path: &str, value: V) -> Result<Self, Error>
     where
         V: serde::Serialize,
     {
-        self.body = Some(Body::JsonPartial(serde_json::to_value(value)?));
+        let path = JsonPath::parse(path).map_err(Error::JsonPath)?;
+        self.json_paths.push((path, serde_json::to_value(value)?));
         Ok(self)
     }
+
+    /// Match requests with a JSON body that deserializes into `T` and for which the closure returns
+    /// `true`
+    #[cfg(feature = "json")]
+    pub fn with_json_de<T, F>(self, with: F) -> Self
+    where
+        T: serde::de::DeserializeOwned,
+        F: Fn(T) -> bool + Send + Sync + 'static,
+    {
+        self.and_with(move |req: &Request<Bytes>| {
+            let matched = serde_json::from_slice::<T>(req.body())
+                .map(&with)
+                .unwrap_or(false);
+            Ok::<_, std::convert::Infallible>((!matched).then_some(Reason::Body(Vec::new())))
+        })
+    }
+
+    /// Match requests for which the closure returns a match, in addition to the other checks
+    pub fn and_with<W, E, R>(mut self, with: W) -> Self
+    where
+        for<'r> W: Fn(&'r Request<Bytes>) -> Result<R, E> + Send + Sync + 'static,
+        R: Into<Report> + Send + Sync + 'static,
+        E: StdError + Send + Sync + 'static,
+    {
+        self.closures.push(Closure(Box::new(with)));
+        self
+    }
 }
 
-impl With for WithHandler {
-    fn with(&self, req: &Request<String>) -> Result<Report, BoxError> {
+/// Closure passed to [`WithHandler::and_with`]
+struct Closure(Box<dyn With>);
+
+impl std::fmt::Debug for Closure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Closure").finish()
+    }
+}
+
+impl WithHandler {
+    /// Check the request, and add the values captured while matching to `extensions`
+    fn check(&self, req: &Request<Bytes>, extensions: &mut Extensions) -> Result<Report, BoxError> {
         let mut reasons = Vec::new();
 
-        if let Some(method) = &self.method {
-            if method != req.method() {
-                reasons.push(Reason::Method);
-            }
+        if !self.methods.is_empty() && !self.methods.contains(req.method()) {
+            reasons.push(Reason::Method);
         }
 
         if let Some(uri) = &self.uri {
-            if uri != req.uri() {
-                reasons.push(Reason::Uri);
+            reasons.extend(
+                uri_mismatches(uri, req.uri(), &self.uri_normalization)
+                    .into_iter()
+                    .map(Reason::Uri),
+            );
+        }
+
+        if let Some(parts) = &self.uri_parts {
+            reasons.extend(parts.mismatches(req.uri()).into_iter().map(Reason::Uri));
+        }
+
+        if let Some(template) = &self.path_template {
+            match template.captures(req.uri().path()) {
+                Some(params) => {
+                    extensions.insert(params);
+                }
+                None => reasons.push(Reason::Uri(UriComponent::Path)),
+            }
+        }
+
+        if let Some(host) = &self.host {
+            if !req
+                .uri()
+                .host()
+                .is_some_and(|req_host| req_host.eq_ignore_ascii_case(host))
+            {
+                reasons.push(Reason::Host);
+            }
+        }
+
+        if let Some(scheme) = &self.scheme {
+            if !req
+                .uri()
+                .scheme_str()
+                .is_some_and(|req_scheme| req_scheme.eq_ignore_ascii_case(scheme))
+            {
+                reasons.push(Reason::Scheme);
             }
         }
 
         for (key, value) in &self.headers {
             if !check_headers(req.headers(), key, value) {
-                reasons.push(Reason::Header(key.clone()));
+                reasons.push(Reason::Header {
+                    name: key.clone(),
+                    check: value.kind(),
+                    actual: req.headers().get_all(key).iter().cloned().collect(),
+                });
+            }
+        }
+
+        if let Some(action) = &self.soap_action {
+            if soap_action(req).as_ref() != Some(action) {
+                reasons.push(Reason::SoapAction);
+            }
+        }
+
+        if !self.trailers.is_empty() {
+            let trailers = req.extensions().get::<Trailers>();
+            for (key, value) in &self.trailers {
+                if !trailers
+                    .is_some_and(|trailers| trailers.0.get_all(key).iter().any(|v| v == value))
+                {
+                    reasons.push(Reason::Trailer(key.clone()));
+                }
             }
         }
 
         match &self.body {
-            Some(Body::String(body)) => {
-                if body != req.body() {
-                    reasons.push(Reason::Body);
+            Some(Body::String(body)) if body.as_bytes() != req.body() => {
+                reasons.push(Reason::Body(Vec::new()));
+            }
+            Some(Body::Bytes(body)) if body != req.body() => {
+                reasons.push(Reason::Body(Vec::new()));
+            }
+            Some(Body::Raw(body)) => {
+                let raw = req
+                    .extensions()
+                    .get::<RawBody>()
+                    .map(|raw| &raw.0)
+                    .unwrap_or(req.body());
+                if body != raw {
+                    reasons.push(Reason::Body(Vec::new()));
+                }
+            }
+            #[cfg(feature = "json")]
+            Some(Body::Json(body, ignored)) => match parse_payload(req.body()) {
+                Ok(mut payload) if !ignored.is_empty() => {
+                    for path in ignored {
+                        path.remove(&mut payload);
+                    }
+                    if body != &payload {
+                        reasons.push(Reason::Body(json_diff(body, &payload, None)));
+                    }
+                }
+                Ok(payload) if body != &payload => {
+                    reasons.push(Reason::Body(json_diff(body, &payload, None)));
+                }
+                Ok(_) => (),
+                Err(diff) => reasons.push(Reason::Body(vec![diff])),
+            },
+            #[cfg(feature = "json")]
+            Some(Body::JsonSchema(schema)) => {
+                // Payloads that are not JSON are reported as a type mismatch on the whole body
+                let errors = match serde_json::from_slice::<serde_json::Value>(req.body()) {
+                    Ok(payload) => schema.validate(&payload),
+                    Err(err) => vec![JsonDiff {
+                        pointer: String::new(),
+                        kind: JsonDiffKind::Schema {
+                            keyword: "type".to_string(),
+                            message: format!("invalid JSON payload: {err}"),
+                        },
+                    }],
+                };
+                if !errors.is_empty() {
+                    reasons.push(Reason::Body(errors));
+                }
+            }
+            #[cfg(feature = "json")]
+            Some(Body::JsonPartial(body, arrays)) => match parse_payload(req.body()) {
+                Ok(payload) if !body.json_eq_with(&payload, *arrays) => {
+                    reasons.push(Reason::Body(json_diff(body, &payload, Some(*arrays))));
                 }
+                Ok(_) => (),
+                Err(diff) => reasons.push(Reason::Body(vec![diff])),
+            },
+            _ => (),
+        }
+
+        if !self.body_streams.iter().all(|stream| stream.matches(req)) {
+            reasons.push(Reason::Body(Vec::new()));
+        }
+
+        if let Some(multipart) = &self.multipart {
+            match multipart.missing(req) {
+                Some(missing) => reasons.extend(
+                    missing
+                        .into_iter()
+                        .map(|name| Reason::MultipartPart(name.to_string())),
+                ),
+                None => reasons.push(Reason::Body(Vec::new())),
             }
-            Some(Body::Json(body)) => {
-                let payload: serde_json::Value = serde_json::from_str(req.body())?;
+        }
 
-                if body != &payload {
-                    reasons.push(Reason::Body);
+        #[cfg(feature = "json")]
+        if !self.json_paths.is_empty() {
+            let payload = serde_json::from_slice::<serde_json::Value>(req.body()).ok();
+            for (path, value) in &self.json_paths {
+                let found = payload
+                    .as_ref()
+                    .map(|payload| path.select(payload).contains(&value))
+                    .unwrap_or(false);
+                if !found {
+                    reasons.push(Reason::JsonPath(path.to_string()));
                 }
             }
-            Some(Body::JsonPartial(body)) => {
-                let payload: serde_json::Value = serde_json::from_str(req.body())?;
+        }
+
+        #[cfg(feature = "sigv4")]
+        if let Some(sigv4) = &self.sigv4 {
+            if let Err(message) = sigv4.verify(req) {
+                reasons.push(Reason::Signature(message));
+            }
+        }
 
-                if !body.json_eq(&payload) {
-                    reasons.push(Reason::Body);
+        for closure in &self.closures {
+            if let Report::Mismatch(inner) = closure.0.with(req)? {
+                if inner.is_empty() {
+                    reasons.push(Reason::Closure);
                 }
+                reasons.extend(inner);
             }
-            None => (),
         }
 
         Ok(reasons.into())
     }
+}
+
+impl With for WithHandler {
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
+        self.check(req, &mut Extensions::new())
+    }
+
+    fn with_extensions<'w>(
+        &'w self,
+        req: &'w Request<Bytes>,
+        extensions: &'w mut Extensions,
+    ) -> WithFuture<'w> {
+        Box::pin(ready(self.check(req, extensions)))
+    }
 
     fn print_pretty(&self, report: &HashSet<Reason>) -> WithPrint<'_> {
         let name = "WithHandler".into();
         let mut print_body = Vec::new();
 
-        if let Some(method) = &self.method {
+        if !self.methods.is_empty() {
+            let method = self.methods.iter().join(" | ");
             print_body.push(format!("method:   {method}"));
             if report.contains(&Reason::Method) {
                 print_body.push(
-                    format!("          {:^<1$}", "", method.to_string().len())
+                    format!("          {:^<1$}", "", method.len())
                         .yellow()
                         .to_string(),
                 );
@@ -240,7 +1045,7 @@ impl With for WithHandler {
 
         if let Some(uri) = &self.uri {
             print_body.push(format!("uri:      {uri}"));
-            if report.contains(&Reason::Uri) {
+            if report.iter().any(|reason| matches!(reason, Reason::Uri(_))) {
                 print_body.push(
                     format!("          {:^<1$}", "", uri.to_string().len())
                         .yellow()
@@ -249,6 +1054,50 @@ impl With for WithHandler {
             }
         }
 
+        if let Some(template) = &self.path_template {
+            let template = template.to_string();
+            print_body.push(format!("path:     {template}"));
+            if report.contains(&Reason::Uri(UriComponent::Path)) {
+                print_body.push(
+                    format!("          {:^<1$}", "", template.len())
+                        .yellow()
+                        .to_string(),
+                );
+            }
+        }
+
+        if let Some(parts) = &self.uri_parts {
+            print_body.push("uri parts:".to_string());
+            for (line, component) in parts.lines() {
+                print_body.push(format!("  {line}"));
+                if report.contains(&Reason::Uri(component)) {
+                    print_body.push(format!("  {}", format!("{:^<1$}", "", line.len()).yellow()));
+                }
+            }
+        }
+
+        if let Some(host) = &self.host {
+            print_body.push(format!("host:     {host}"));
+            if report.contains(&Reason::Host) {
+                print_body.push(
+                    format!("          {:^<1$}", "", host.len())
+                        .yellow()
+                        .to_string(),
+                );
+            }
+        }
+
+        if let Some(scheme) = &self.scheme {
+            print_body.push(format!("scheme:   {scheme}"));
+            if report.contains(&Reason::Scheme) {
+                print_body.push(
+                    format!("          {:^<1$}", "", scheme.len())
+                        .yellow()
+                        .to_string(),
+                );
+            }
+        }
+
         if !self.headers.is_empty() {
             let key_length = self
                 .headers
@@ -256,22 +1105,36 @@ impl With for WithHandler {
                 .fold(0, |acc, (key, _)| max(acc, key.to_string().len()));
 
             print_body.push("headers:".to_string());
-            for (key, value) in &self.headers {
-                let values = match value {
-                    HeaderCheck::AtLeastOnce(value) => vec![value],
-                    HeaderCheck::ExactlyOnce(value) => vec![value],
-                    HeaderCheck::All(values) => values.iter().collect(),
+            let mut headers = self.headers.iter().collect::<Vec<_>>();
+            headers.sort_by_key(|(key, _)| key.as_str());
+            for (key, check) in headers {
+                let values = match check {
+                    HeaderCheck::AtLeastOnce(value) => vec![Some(value)],
+                    HeaderCheck::ExactlyOnce(value) => vec![Some(value)],
+                    HeaderCheck::Loose(value) => vec![Some(value)],
+                    HeaderCheck::All(values) => values.iter().map(Some).collect(),
+                    HeaderCheck::Absent => vec![None],
+                    HeaderCheck::Predicate(_) | HeaderCheck::Matches(_) | HeaderCheck::Count(_) => {
+                        vec![None]
+                    }
                 };
 
                 for value in values {
-                    let value = if let Ok(value) = value.to_str() {
-                        value.into()
-                    } else {
-                        format!("{value:?}")
+                    let value = match (value, check) {
+                        (Some(value), _) => match value.to_str() {
+                            Ok(value) => value.into(),
+                            Err(_) => format!("{value:?}"),
+                        },
+                        (None, HeaderCheck::Predicate(_)) => "<predicate>".to_string(),
+                        (None, HeaderCheck::Matches(pattern)) => format!("/{pattern}/"),
+                        (None, HeaderCheck::Count(count)) => format!("<{}>", entries(*count)),
+                        (None, _) => "<missing>".to_string(),
                     };
 
                     print_body.push(format!("  {key: <key_length$}: {value}"));
-                    if report.contains(&Reason::Header(key.clone())) {
+                    if report.iter().any(|reason| {
+                        matches!(reason, Reason::Header { name, check: kind, .. } if name == key && *kind == check.kind())
+                    }) {
                         print_body.push(format!(
                             "  {: <1$}{2}",
                             "",
@@ -283,9 +1146,72 @@ impl With for WithHandler {
             }
         }
 
+        if let Some(action) = &self.soap_action {
+            print_body.push(format!("soap action: {action}"));
+            if report.contains(&Reason::SoapAction) {
+                print_body.push(
+                    format!("             {:^<1$}", "", action.len())
+                        .yellow()
+                        .to_string(),
+                );
+            }
+        }
+
+        if !self.trailers.is_empty() {
+            let key_length = self
+                .trailers
+                .iter()
+                .fold(0, |acc, (key, _)| max(acc, key.to_string().len()));
+
+            print_body.push("trailers:".to_string());
+            let mut trailers = self.trailers.iter().collect::<Vec<_>>();
+            trailers.sort_by_key(|(key, _)| key.as_str());
+            for (key, value) in trailers {
+                let value = match value.to_str() {
+                    Ok(value) => value.into(),
+                    Err(_) => format!("{value:?}"),
+                };
+
+                print_body.push(format!("  {key: <key_length$}: {value}"));
+                if report.contains(&Reason::Trailer(key.clone())) {
+                    print_body.push(format!(
+                        "  {: <1$}{2}",
+                        "",
+                        key_length + 2,
+                        format!("{:^<1$}", "", value.len()).yellow()
+                    ))
+                }
+            }
+        }
+
         match &self.body {
-            Some(Body::Json(body)) => {
+            #[cfg(feature = "json")]
+            Some(Body::Json(body, ignored)) => {
                 print_body.push("full json match:".to_string());
+                print_json(&mut print_body, body, report);
+                if !ignored.is_empty() {
+                    let ignored = ignored.iter().map(ToString::to_string);
+                    print_body.push(format!(
+                        "ignoring: {}",
+                        ignored.collect::<Vec<_>>().join(", ")
+                    ));
+                }
+            }
+            #[cfg(feature = "json")]
+            Some(Body::JsonPartial(body, arrays)) => {
+                match arrays {
+                    ArrayMatching::Subset => print_body.push("partial json match:".to_string()),
+                    arrays => print_body.push(format!("partial json match (arrays: {arrays:?}):")),
+                }
+                print_json(&mut print_body, body, report);
+            }
+            #[cfg(feature = "json")]
+            Some(Body::JsonSchema(schema)) => {
+                print_body.push("json schema:".to_string());
+                print_json(&mut print_body, schema.schema(), report);
+            }
+            Some(Body::String(body)) => {
+                print_body.push("body:".to_string());
                 let body = format!("{body:#}");
                 let mut body_length = 0;
                 for line in body.trim().split('\n') {
@@ -298,30 +1224,22 @@ impl With for WithHandler {
                         .to_string(),
                 );
             }
-            Some(Body::JsonPartial(body)) => {
-                print_body.push("partial json match:".to_string());
-                let body = format!("{body:#}");
-                let mut body_length = 0;
-                for line in body.trim().split('\n') {
-                    body_length = max(body_length, line.len());
-                    print_body.push(format!("{} {line}", ">".yellow()));
-                }
+            Some(Body::Raw(body)) => {
+                print_body.push("raw body:".to_string());
+                let body = format!("{body:?}");
+                print_body.push(format!("{} {body}", ">".yellow()));
                 print_body.push(
-                    format!("  {:^<1$}", "", min(74, body_length))
+                    format!("  {:^<1$}", "", min(74, body.len()))
                         .yellow()
                         .to_string(),
                 );
             }
-            Some(Body::String(body)) => {
-                print_body.push("body:".to_string());
-                let body = format!("{body:#}");
-                let mut body_length = 0;
-                for line in body.trim().split('\n') {
-                    body_length = max(body_length, line.len());
-                    print_body.push(format!("{} {line}", ">".yellow()));
-                }
+            Some(Body::Bytes(body)) => {
+                print_body.push("binary body:".to_string());
+                let body = format!("{body:?}");
+                print_body.push(format!("{} {body}", ">".yellow()));
                 print_body.push(
-                    format!("  {:^<1$}", "", min(74, body_length))
+                    format!("  {:^<1$}", "", min(74, body.len()))
                         .yellow()
                         .to_string(),
                 );
@@ -329,20 +1247,188 @@ impl With for WithHandler {
             None => (),
         }
 
+        for stream in &self.body_streams {
+            let label = stream.label();
+            print_body.push(label.to_string());
+            if report
+                .iter()
+                .any(|reason| matches!(reason, Reason::Body(_)))
+            {
+                print_body.push(format!("{:^<1$}", "", label.len()).yellow().to_string());
+            }
+        }
+
+        if let Some(multipart) = &self.multipart {
+            print_body.push("multipart:".to_string());
+            for part in multipart.parts() {
+                let line = part.to_string();
+                print_body.push(format!("  {line}"));
+                if report.contains(&Reason::MultipartPart(part.name().to_string())) {
+                    print_body.push(format!("  {}", format!("{:^<1$}", "", line.len()).yellow()));
+                }
+            }
+        }
+
+        #[cfg(feature = "json")]
+        if !self.json_paths.is_empty() {
+            print_body.push("json paths:".to_string());
+            for (path, value) in &self.json_paths {
+                let line = format!("{path} = {value}");
+                print_body.push(format!("  {line}"));
+                if report.contains(&Reason::JsonPath(path.to_string())) {
+                    print_body.push(format!("  {}", format!("{:^<1$}", "", line.len()).yellow()));
+                }
+            }
+        }
+
+        #[cfg(feature = "sigv4")]
+        if let Some(sigv4) = &self.sigv4 {
+            print_body.push("sigv4 signature:".to_string());
+            let line = sigv4.to_string();
+            print_body.push(format!("  {line}"));
+            let message = report.iter().find_map(|reason| match reason {
+                Reason::Signature(message) => Some(message),
+                _ => None,
+            });
+            if let Some(message) = message {
+                print_body.push(format!("  {}", message.yellow()));
+            }
+        }
+
+        for closure in &self.closures {
+            let name = closure.0.print_pretty(report).name;
+            print_body.push(format!("closure:  {name}"));
+            if report.contains(&Reason::Closure) {
+                print_body.push(
+                    format!("          {:^<1$}", "", name.len())
+                        .yellow()
+                        .to_string(),
+                );
+            }
+        }
+
         WithPrint {
             name,
             body: Some(print_body.join("\n").into()),
         }
     }
+
+    fn expected(&self, reason: &Reason) -> Option<String> {
+        match reason {
+            Reason::Method => (!self.methods.is_empty()).then(|| self.methods.iter().join(" | ")),
+            Reason::Uri(UriComponent::Path) if self.path_template.is_some() => {
+                self.path_template.as_ref().map(ToString::to_string)
+            }
+            Reason::Uri(component) => self
+                .uri_parts
+                .as_ref()
+                .and_then(|parts| parts.expected(component))
+                .or_else(|| {
+                    self.uri
+                        .as_ref()
+                        .and_then(|uri| uri_component(uri, component))
+                }),
+            Reason::Host => self.host.clone(),
+            Reason::Scheme => self.scheme.clone(),
+            Reason::Header { name, check, .. } => self
+                .headers
+                .iter()
+                .find(|(key, value)| key == name && value.kind() == *check)
+                .and_then(|(_, value)| match value {
+                    HeaderCheck::AtLeastOnce(value)
+                    | HeaderCheck::ExactlyOnce(value)
+                    | HeaderCheck::Loose(value) => {
+                        Some(String::from_utf8_lossy(value.as_bytes()).into_owned())
+                    }
+                    HeaderCheck::All(values) => Some(
+                        values
+                            .iter()
+                            .map(|value| String::from_utf8_lossy(value.as_bytes()))
+                            .join(", "),
+                    ),
+                    HeaderCheck::Matches(pattern) => Some(format!("/{pattern}/")),
+                    HeaderCheck::Count(count) => Some(entries(*count)),
+                    HeaderCheck::Absent | HeaderCheck::Predicate(_) => None,
+                }),
+            Reason::SoapAction => self.soap_action.clone(),
+            Reason::Trailer(name) => self
+                .trailers
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| String::from_utf8_lossy(value.as_bytes()).into_owned()),
+            Reason::Body(_) => self.body.as_ref().map(|body| match body {
+                Body::String(body) => body.clone(),
+                Body::Bytes(body) | Body::Raw(body) => String::from_utf8_lossy(body).into_owned(),
+                #[cfg(feature = "json")]
+                Body::Json(body, _) | Body::JsonPartial(body, _) => body.to_string(),
+                #[cfg(feature = "json")]
+                Body::JsonSchema(schema) => schema.schema().to_string(),
+            }),
+            Reason::MultipartPart(name) => self.multipart.as_ref().and_then(|multipart| {
+                multipart
+                    .parts()
+                    .iter()
+                    .find(|part| part.name() == name)
+                    .and_then(MultipartPart::expected_content)
+            }),
+            #[cfg(feature = "json")]
+            Reason::JsonPath(name) => self
+                .json_paths
+                .iter()
+                .find(|(path, _)| &path.to_string() == name)
+                .map(|(_, value)| value.to_string()),
+            _ => None,
+        }
+    }
+
+    fn body_streams(&self, streams: &mut BodyStreams) {
+        for stream in &self.body_streams {
+            streams.push(stream);
+        }
+    }
+}
+
+/// Print a JSON document, followed by the differences with the request payload if any
+#[cfg(feature = "json")]
+#[allow(clippy::mutable_key_type)]
+fn print_json(print_body: &mut Vec<String>, body: &serde_json::Value, report: &HashSet<Reason>) {
+    let body = format!("{body:#}");
+    let mut body_length = 0;
+    for line in body.trim().split('\n') {
+        body_length = max(body_length, line.len());
+        print_body.push(format!("{} {line}", ">".yellow()));
+    }
+
+    let diffs = report.iter().find_map(|reason| match reason {
+        Reason::Body(diffs) if !diffs.is_empty() => Some(diffs),
+        _ => None,
+    });
+    match diffs {
+        Some(diffs) => {
+            print_body.push("differences:".to_string());
+            for diff in diffs {
+                print_body.push(format!("  {}", diff.to_string().yellow()));
+            }
+        }
+        None => print_body.push(
+            format!("  {:^<1$}", "", min(74, body_length))
+                .yellow()
+                .to_string(),
+        ),
+    }
 }
 
 #[derive(Debug)]
 pub enum Body {
     String(String),
+    Bytes(Bytes),
+    Raw(Bytes),
+    #[cfg(feature = "json")]
+    Json(serde_json::Value, Vec<JsonPath>),
     #[cfg(feature = "json")]
-    Json(serde_json::Value),
+    JsonPartial(serde_json::Value, ArrayMatching),
     #[cfg(feature = "json")]
-    JsonPartial(serde_json::Value),
+    JsonSchema(JsonSchema),
 }
 
 /// Type of check to perform on headers
@@ -356,6 +1442,117 @@ pub enum HeaderCheck {
     ExactlyOnce(HeaderValue),
     /// All entries correspond to the pattern
     All(Vec<HeaderValue>),
+    /// At least one entry is equivalent to the pattern, ignoring case and optional whitespace
+    Loose(HeaderValue),
+    /// There are no entries for this key
+    Absent,
+    /// At least one entry is valid UTF-8 and satisfies the predicate
+    Predicate(HeaderPredicate),
+    /// At least one entry is valid UTF-8 and matches the regular expression
+    Matches(Pattern),
+    /// There are exactly this many entries for this key, with any value
+    Count(usize),
+}
+
+impl HeaderCheck {
+    /// Kind of check, without the expected values
+    fn kind(&self) -> HeaderCheckKind {
+        match self {
+            Self::AtLeastOnce(_) => HeaderCheckKind::AtLeastOnce,
+            Self::ExactlyOnce(_) => HeaderCheckKind::ExactlyOnce,
+            Self::All(_) => HeaderCheckKind::All,
+            Self::Loose(_) => HeaderCheckKind::Loose,
+            Self::Absent => HeaderCheckKind::Absent,
+            Self::Predicate(_) => HeaderCheckKind::Predicate,
+            Self::Matches(_) => HeaderCheckKind::Matches,
+            Self::Count(_) => HeaderCheckKind::Count,
+        }
+    }
+}
+
+/// Kind of check that failed for a header, see [`Reason::Header`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HeaderCheckKind {
+    /// At least one entry must be equal to the expected value, see [`crate::CaseBuilder::with_header`]
+    AtLeastOnce,
+    /// There must be a single entry, equal to the expected value, see
+    /// [`crate::CaseBuilder::with_header_once`]
+    ExactlyOnce,
+    /// All entries must be equal to the expected values, see [`crate::CaseBuilder::with_header_all`]
+    All,
+    /// At least one entry must be loosely equal to the expected value, see
+    /// [`crate::CaseBuilder::with_header_loose`]
+    Loose,
+    /// There must be no entries, see [`crate::CaseBuilder::with_header_missing`]
+    Absent,
+    /// At least one entry must satisfy a predicate, see [`crate::CaseBuilder::with_header_fn`]
+    Predicate,
+    /// At least one entry must match a regular expression, see
+    /// [`crate::CaseBuilder::with_header_matches`]
+    Matches,
+    /// There must be an exact number of entries, see [`crate::CaseBuilder::with_header_count`]
+    Count,
+}
+
+impl HeaderCheckKind {
+    /// Returns a string representation for the [`HeaderCheckKind`]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AtLeastOnce => "at least once",
+            Self::ExactlyOnce => "exactly once",
+            Self::All => "all entries",
+            Self::Loose => "loosely",
+            Self::Absent => "absent",
+            Self::Predicate => "predicate",
+            Self::Matches => "matches",
+            Self::Count => "count",
+        }
+    }
+}
+
+/// Describe a number of header entries, such as `2 entries`
+fn entries(count: usize) -> String {
+    match count {
+        1 => "1 entry".to_string(),
+        count => format!("{count} entries"),
+    }
+}
+
+/// Predicate for [`HeaderCheck::Predicate`]
+#[derive(Clone)]
+pub struct HeaderPredicate(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for HeaderPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("HeaderPredicate").finish()
+    }
+}
+
+/// Normalize a header value for [`HeaderCheck::Loose`]
+///
+/// This lowercases the value, trims it, collapses runs of whitespace and removes whitespace
+/// around parameter delimiters.
+fn normalize_header_value(value: &HeaderValue) -> String {
+    let value = String::from_utf8_lossy(value.as_bytes()).to_ascii_lowercase();
+
+    let mut normalized = String::with_capacity(value.len());
+    let mut pending_space = false;
+    for c in value.trim().chars() {
+        if c.is_ascii_whitespace() {
+            pending_space = true;
+        } else if matches!(c, ';' | ',' | '=') {
+            normalized.push(c);
+            pending_space = false;
+        } else {
+            if pending_space && !normalized.ends_with([';', ',', '=']) {
+                normalized.push(' ');
+            }
+            normalized.push(c);
+            pending_space = false;
+        }
+    }
+
+    normalized
 }
 
 /// Check headers against key-value pair
@@ -374,6 +1571,18 @@ fn check_headers(
             }) == (1, true)
         }
         HeaderCheck::All(values) => req_values.sorted().eq(values.iter().sorted()),
+        HeaderCheck::Loose(value) => {
+            let value = normalize_header_value(value);
+            req_values.any(|rv| normalize_header_value(rv) == value)
+        }
+        HeaderCheck::Absent => req_values.next().is_none(),
+        HeaderCheck::Predicate(predicate) => {
+            req_values.any(|rv| rv.to_str().map(|rv| (predicate.0)(rv)).unwrap_or(false))
+        }
+        HeaderCheck::Matches(pattern) => {
+            req_values.any(|rv| rv.to_str().map(|rv| pattern.is_match(rv)).unwrap_or(false))
+        }
+        HeaderCheck::Count(count) => req_values.count() == *count,
     };
     found
 }
@@ -384,11 +1593,13 @@ mod tests {
     use rstest::*;
     use speculoos::prelude::*;
 
+    #[cfg(feature = "json")]
     #[derive(serde::Serialize)]
     struct SerializeNamed {
         val: usize,
     }
 
+    #[cfg(feature = "json")]
     #[derive(serde::Serialize)]
     struct SerializeTuple(usize);
 
@@ -418,8 +1629,34 @@ mod tests {
         let with = WithHandler::default();
         assert_that!(with.with_method(method))
             .is_ok()
-            .map(|w| &w.method)
-            .is_some();
+            .map(|w| &w.methods)
+            .has_length(1);
+    }
+
+    #[rstest]
+    #[case(Method::PUT, true)]
+    #[case(Method::PATCH, true)]
+    #[case(Method::POST, false)]
+    fn with_handler_method_in(#[case] method: Method, #[case] expected: bool) {
+        let with = WithHandler::default()
+            .with_method_in([Method::PUT, Method::PATCH])
+            .unwrap();
+        let req = Request::builder()
+            .method(method)
+            .body(Bytes::new())
+            .unwrap();
+
+        assert_that!(with.with(&req).map(|r| matches!(r, Report::Match)))
+            .is_ok()
+            .is_equal_to(expected);
+    }
+
+    #[rstest]
+    fn with_handler_method_in_empty() {
+        let with = WithHandler::default().with_method_in(Vec::<Method>::new());
+
+        assert_that!(with.map_err(|err| err.to_string()))
+            .is_err_containing("invalid method matcher: no methods to match".to_string());
     }
 
     #[rstest]
@@ -438,6 +1675,30 @@ mod tests {
             .has_length(1);
     }
 
+    #[rstest]
+    #[case(Some("0"), true)]
+    #[case(Some("2"), false)]
+    #[case(None, false)]
+    fn with_handler_trailer(#[case] trailer: Option<&'static str>, #[case] expected: bool) {
+        let with = WithHandler::default()
+            .with_trailer("grpc-status", "0")
+            .unwrap();
+        let mut trailers = Trailers::default();
+        if let Some(trailer) = trailer {
+            trailers
+                .0
+                .insert("grpc-status", HeaderValue::from_static(trailer));
+        }
+        let req = Request::builder()
+            .extension(trailers)
+            .body(Bytes::new())
+            .unwrap();
+
+        assert_that!(with.with(&req).map(|r| matches!(r, Report::Match)))
+            .is_ok()
+            .is_equal_to(expected);
+    }
+
     #[rstest]
     #[case("TEST")]
     #[case("TEST".to_string())]
@@ -470,6 +1731,24 @@ mod tests {
             .matches(|b| matches!(b, Body::Json(..)));
     }
 
+    #[cfg(feature = "json")]
+    #[rstest]
+    fn with_handler_json_ignoring_reset() {
+        let with = WithHandler::default()
+            .with_json_ignoring(serde_json::json!({"a": 1, "t": 0}), ["$.t"])
+            .unwrap()
+            .with_json(serde_json::json!({"a": 1}))
+            .unwrap();
+        let req = Request::builder()
+            .body(Bytes::from(r#"{"a": 1, "t": 5}"#))
+            .unwrap();
+
+        // The ignored paths only apply to the body they were set with
+        assert_that!(with.with(&req).map(|r| matches!(r, Report::Match)))
+            .is_ok()
+            .is_false();
+    }
+
     #[rstest]
     #[case(hyper::header::AUTHORIZATION, HeaderCheck::AtLeastOnce("bearer 123".try_into().unwrap()), true)]
     #[case(hyper::header::AUTHORIZATION, HeaderCheck::AtLeastOnce("bearer 1234".try_into().unwrap()), true)]
@@ -479,6 +1758,17 @@ mod tests {
     #[case(hyper::header::AUTHORIZATION, HeaderCheck::All(vec!["bearer 1234".try_into().unwrap()]), false)]
     #[case(hyper::header::AUTHORIZATION, HeaderCheck::All(vec!["bearer 123".try_into().unwrap(), "bearer 1234".try_into().unwrap()]), true)]
     #[case(hyper::header::AUTHORIZATION, HeaderCheck::All(vec!["bearer 1234".try_into().unwrap(), "bearer 123".try_into().unwrap()]), true)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Absent, false)]
+    #[case(hyper::header::ACCEPT, HeaderCheck::Absent, true)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Loose("BEARER  1234".try_into().unwrap()), true)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Loose("bearer1234".try_into().unwrap()), false)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Predicate(HeaderPredicate(Arc::new(|v| v.ends_with("1234")))), true)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Predicate(HeaderPredicate(Arc::new(|v| v.starts_with("Bearer")))), false)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Matches(Pattern::parse("^bearer \\d{4}$").unwrap()), true)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Matches(Pattern::parse("^Bearer").unwrap()), false)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Count(2), true)]
+    #[case(hyper::header::AUTHORIZATION, HeaderCheck::Count(1), false)]
+    #[case(hyper::header::ACCEPT, HeaderCheck::Count(0), true)]
     fn test_check_headers(
         #[case] key: HeaderName,
         #[case] value: HeaderCheck,
@@ -500,4 +1790,16 @@ mod tests {
 
         assert_that!(check_headers(&headers, &key, &value)).is_equal_to(expected);
     }
+
+    #[rstest]
+    #[case("application/json; charset=UTF-8", "application/json;charset=utf-8")]
+    #[case("  text/plain ", "TEXT/PLAIN")]
+    #[case("a , b", "a,b")]
+    #[case("Bearer   abc", "bearer abc")]
+    fn test_normalize_header_value(#[case] left: &str, #[case] right: &str) {
+        let left = normalize_header_value(&HeaderValue::from_str(left).unwrap());
+        let right = normalize_header_value(&HeaderValue::from_str(right).unwrap());
+
+        assert_that!(left).is_equal_to(right);
+    }
 }
@@ -0,0 +1,216 @@
+use hyper::{body::Bytes, header::CONTENT_TYPE, Request};
+use std::fmt;
+
+use crate::{
+    codec::{parse_boundary, parse_digest, parse_multipart, FormPart, Sha256},
+    Error,
+};
+
+/// Expected parts of a `multipart/form-data` request body
+///
+/// The boundary is read from the `content-type` header of the request. Each expected part must
+/// match at least one part of the body with the same field name, and the body may contain other
+/// parts.
+///
+/// This is passed to [`crate::CaseBuilder::with_multipart`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Multipart, MultipartPart};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_multipart(
+///         Multipart::new()
+///             .part(MultipartPart::new("title").content("Quarterly report"))
+///             .part(
+///                 MultipartPart::new("file")
+///                     .filename("report.csv")
+///                     .content_type("text/csv"),
+///             ),
+///     )
+///     .returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Multipart {
+    parts: Vec<MultipartPart>,
+}
+
+impl Multipart {
+    /// Create a new [`Multipart`] matcher without any expected parts
+    ///
+    /// On its own, this matches any valid `multipart/*` body.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect a part in the body
+    pub fn part(mut self, part: MultipartPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    pub(crate) fn parts(&self) -> &[MultipartPart] {
+        &self.parts
+    }
+
+    /// Returns the names of the expected parts that are missing from the request
+    ///
+    /// This returns `None` if the request doesn't have a valid multipart body.
+    pub(crate) fn missing(&self, req: &Request<Bytes>) -> Option<Vec<&str>> {
+        let form_parts = form_parts(req)?;
+        Some(
+            self.parts
+                .iter()
+                .filter(|part| !form_parts.iter().any(|form_part| part.matches(form_part)))
+                .map(|part| part.name.as_str())
+                .collect(),
+        )
+    }
+}
+
+/// Expected part of a `multipart/form-data` request body, see [`Multipart`]
+///
+/// Only the properties set on the part are checked.
+#[derive(Debug, Clone)]
+pub struct MultipartPart {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<String>,
+    content: Option<Bytes>,
+    sha256: Option<(String, [u8; 32])>,
+}
+
+impl MultipartPart {
+    /// Expect a part with this field name
+    pub fn new<N>(name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            content: None,
+            sha256: None,
+        }
+    }
+
+    /// Expect the part to be a file with this name
+    pub fn filename<F>(mut self, filename: F) -> Self
+    where
+        F: Into<String>,
+    {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Expect the part to have this content type
+    ///
+    /// Content types are compared case-insensitively.
+    pub fn content_type<C>(mut self, content_type: C) -> Self
+    where
+        C: Into<String>,
+    {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Expect the part to have exactly this content
+    pub fn content<C>(mut self, content: C) -> Self
+    where
+        C: AsRef<[u8]>,
+    {
+        self.content = Some(Bytes::copy_from_slice(content.as_ref()));
+        self
+    }
+
+    /// Expect the content of the part to have this hex-encoded SHA-256 digest
+    ///
+    /// This is useful for large file uploads, to avoid keeping the expected content around.
+    ///
+    /// ## Errors
+    ///
+    /// This will fail if `hex` is not a valid SHA-256 digest.
+    pub fn content_sha256(mut self, hex: &str) -> Result<Self, Error> {
+        let digest = parse_digest(hex).ok_or_else(|| Error::Digest(hex.to_string()))?;
+        self.sha256 = Some((hex.to_ascii_lowercase(), digest));
+        Ok(self)
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn expected_content(&self) -> Option<String> {
+        self.content
+            .as_ref()
+            .map(|content| String::from_utf8_lossy(content).into_owned())
+    }
+
+    fn matches(&self, form_part: &FormPart<'_>) -> bool {
+        if form_part.name.as_deref() != Some(self.name.as_str()) {
+            return false;
+        }
+        if self.filename.is_some() && form_part.filename != self.filename {
+            return false;
+        }
+        if let Some(content_type) = &self.content_type {
+            if !form_part
+                .content_type
+                .as_ref()
+                .is_some_and(|value| value.eq_ignore_ascii_case(content_type))
+            {
+                return false;
+            }
+        }
+        if let Some(content) = &self.content {
+            if content != form_part.content {
+                return false;
+            }
+        }
+        if let Some((_, digest)) = &self.sha256 {
+            let mut hasher = Sha256::default();
+            hasher.update(form_part.content);
+            if &hasher.finish() != digest {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl fmt::Display for MultipartPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+
+        let mut properties = Vec::new();
+        if let Some(filename) = &self.filename {
+            properties.push(format!("filename={filename:?}"));
+        }
+        if let Some(content_type) = &self.content_type {
+            properties.push(format!("content-type={content_type}"));
+        }
+        if let Some(content) = &self.content {
+            properties.push(format!("content={content:?}"));
+        }
+        if let Some((hex, _)) = &self.sha256 {
+            properties.push(format!("sha256={hex}"));
+        }
+        if !properties.is_empty() {
+            write!(f, " ({})", properties.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the parts of the request body, using the boundary from its `content-type` header
+pub(crate) fn form_parts(req: &Request<Bytes>) -> Option<Vec<FormPart<'_>>> {
+    let boundary = parse_boundary(req.headers().get(CONTENT_TYPE)?.to_str().ok()?)?;
+    parse_multipart(&boundary, req.body())
+}
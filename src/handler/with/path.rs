@@ -0,0 +1,154 @@
+use std::fmt;
+
+/// Parsed path template, such as `/users/{id}/orders/{order_id}`
+///
+/// Each `{name}` segment matches exactly one non-empty path segment, and other segments must be
+/// equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PathTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+}
+
+impl PathTemplate {
+    /// Parse a path template
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if !source.starts_with('/') {
+            return Err(format!("`{source}` must start with `/`"));
+        }
+
+        let segments = source[1..]
+            .split('/')
+            .map(|segment| match segment.strip_prefix('{') {
+                Some(name) => match name.strip_suffix('}') {
+                    Some(name) if !name.is_empty() && !name.contains(['{', '}']) => {
+                        Ok(Segment::Param(name.to_string()))
+                    }
+                    _ => Err(format!(
+                        "`{source}` contains an invalid parameter `{segment}`"
+                    )),
+                },
+                None if segment.contains(['{', '}']) => Err(format!(
+                    "`{source}` contains a parameter that is not a whole segment"
+                )),
+                None => Ok(Segment::Literal(segment.to_string())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// Match `path` against the template, and return the captured parameters
+    pub fn captures(&self, path: &str) -> Option<PathParams> {
+        let parts = path.strip_prefix('/')?.split('/').collect::<Vec<_>>();
+        if parts.len() != self.segments.len() {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        for (segment, part) in self.segments.iter().zip(parts) {
+            match segment {
+                Segment::Literal(literal) if literal != part => return None,
+                Segment::Literal(_) => (),
+                Segment::Param(_) if part.is_empty() => return None,
+                Segment::Param(name) => params.push((name.clone(), part.to_string())),
+            }
+        }
+
+        Some(PathParams(params))
+    }
+}
+
+impl fmt::Display for PathTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Parameters captured by [`crate::CaseBuilder::with_path_template`]
+///
+/// This is stored in the request extensions of matching requests, so the `returning` closure can
+/// read it.
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::{body::Bytes, Request};
+/// # use mock_http_connector::{Connector, Error, PathParams};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_path_template("/users/{id}")
+///     .returning(|req: Request<Bytes>| async move {
+///         let id = req.extensions().get::<PathParams>().and_then(|p| p.get("id"));
+///         format!("user {}", id.unwrap_or_default())
+///     })?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathParams(Vec<(String, String)>);
+
+impl PathParams {
+    /// Returns the value captured for the parameter `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns an iterator over the parameter names and values, in the order of the template
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case("/users/{id}", "/users/42", Some(vec![("id", "42")]))]
+    #[case("/users/{id}/orders/{order_id}", "/users/42/orders/7", Some(vec![("id", "42"), ("order_id", "7")]))]
+    #[case("/users/{id}", "/users/", None)]
+    #[case("/users/{id}", "/users/42/orders", None)]
+    #[case("/users/{id}", "/accounts/42", None)]
+    #[case("/", "/", Some(vec![]))]
+    fn path_template_captures(
+        #[case] template: &str,
+        #[case] path: &str,
+        #[case] expected: Option<Vec<(&str, &str)>>,
+    ) {
+        let template = PathTemplate::parse(template).unwrap();
+
+        let captures = template.captures(path);
+        let captures = captures
+            .as_ref()
+            .map(|params| params.iter().collect::<Vec<_>>());
+        assert_that!(captures).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case("users/{id}")]
+    #[case("/users/{}")]
+    #[case("/users/{id")]
+    #[case("/users/id-{id}")]
+    fn path_template_invalid(#[case] template: &str) {
+        assert_that!(PathTemplate::parse(template)).is_err();
+    }
+}
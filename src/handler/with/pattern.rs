@@ -0,0 +1,341 @@
+use std::{fmt, iter::Peekable, str::Chars};
+
+/// Parsed regular expression, such as `^Bearer [A-Za-z0-9._-]+$`
+///
+/// This supports a subset of the usual syntax: literals, `.`, character classes such as `[a-z]`
+/// or `[^,]`, the `\d`, `\w` and `\s` classes and their negations, groups with `(...)` or
+/// `(?:...)`, alternations with `|`, the `*`, `+`, `?`, `{n}`, `{n,}` and `{n,m}` quantifiers, and
+/// the `^` and `$` anchors. The pattern matches if it matches any part of the value, unless it is
+/// anchored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Pattern {
+    source: String,
+    alternatives: Vec<Vec<Node>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Group(Vec<Vec<Node>>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+
+impl Pattern {
+    /// Parse a regular expression
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut chars = source.chars().peekable();
+        let alternatives = parse_alternatives(&mut chars)?;
+        match chars.next() {
+            Some(c) => Err(format!("`{source}` contains an unmatched `{c}`")),
+            None => Ok(Self {
+                source: source.to_string(),
+                alternatives,
+            }),
+        }
+    }
+
+    /// Returns `true` if the pattern matches `value`
+    pub fn is_match(&self, value: &str) -> bool {
+        let input = value.chars().collect::<Vec<_>>();
+        (0..=input.len())
+            .any(|start| match_alternatives(&self.alternatives, &input, start, &mut |_| true))
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn parse_alternatives(chars: &mut Peekable<Chars>) -> Result<Vec<Vec<Node>>, String> {
+    let mut alternatives = vec![parse_sequence(chars)?];
+    while chars.next_if_eq(&'|').is_some() {
+        alternatives.push(parse_sequence(chars)?);
+    }
+    Ok(alternatives)
+}
+
+fn parse_sequence(chars: &mut Peekable<Chars>) -> Result<Vec<Node>, String> {
+    let mut nodes = Vec::new();
+    while let Some(c) = chars.next_if(|c| !matches!(c, '|' | ')')) {
+        let node = match c {
+            '(' => {
+                if chars.next_if_eq(&'?').is_some() && chars.next_if_eq(&':').is_none() {
+                    return Err("only non-capturing groups with `(?:` are supported".to_string());
+                }
+                let alternatives = parse_alternatives(chars)?;
+                if chars.next_if_eq(&')').is_none() {
+                    return Err("unclosed group".to_string());
+                }
+                Node::Group(alternatives)
+            }
+            '[' => parse_class(chars)?,
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '\\' => match parse_escape(chars)? {
+                Escape::Char(c) => Node::Char(c),
+                Escape::Class(ranges, negated) => Node::Class {
+                    ranges: ranges.to_vec(),
+                    negated,
+                },
+            },
+            '*' | '+' | '?' | '{' => return Err(format!("nothing to repeat before `{c}`")),
+            c => Node::Char(c),
+        };
+
+        nodes.push(match parse_quantifier(chars)? {
+            Some((min, max)) => Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+            },
+            None => node,
+        });
+    }
+    Ok(nodes)
+}
+
+enum Escape {
+    Char(char),
+    Class(&'static [(char, char)], bool),
+}
+
+fn parse_escape(chars: &mut Peekable<Chars>) -> Result<Escape, String> {
+    match chars.next() {
+        Some('d') => Ok(Escape::Class(DIGIT, false)),
+        Some('D') => Ok(Escape::Class(DIGIT, true)),
+        Some('w') => Ok(Escape::Class(WORD, false)),
+        Some('W') => Ok(Escape::Class(WORD, true)),
+        Some('s') => Ok(Escape::Class(SPACE, false)),
+        Some('S') => Ok(Escape::Class(SPACE, true)),
+        Some(c) if c.is_ascii_alphanumeric() => Err(format!("unsupported escape `\\{c}`")),
+        Some(c) => Ok(Escape::Char(c)),
+        None => Err("trailing `\\`".to_string()),
+    }
+}
+
+fn parse_class(chars: &mut Peekable<Chars>) -> Result<Node, String> {
+    let negated = chars.next_if_eq(&'^').is_some();
+    let mut ranges = Vec::new();
+    loop {
+        let start = match chars.next() {
+            Some(']') => break,
+            Some('\\') => match parse_escape(chars)? {
+                Escape::Char(c) => c,
+                Escape::Class(class, false) => {
+                    ranges.extend_from_slice(class);
+                    continue;
+                }
+                Escape::Class(_, true) => {
+                    return Err("negated classes are not supported within `[...]`".to_string())
+                }
+            },
+            Some(c) => c,
+            None => return Err("unclosed character class".to_string()),
+        };
+
+        let end = match chars.peek() {
+            Some('-') => {
+                chars.next();
+                match chars.next() {
+                    Some(']') => {
+                        ranges.extend([(start, start), ('-', '-')]);
+                        break;
+                    }
+                    Some('\\') => match parse_escape(chars)? {
+                        Escape::Char(c) => c,
+                        Escape::Class(..) => return Err("invalid range in class".to_string()),
+                    },
+                    Some(c) => c,
+                    None => return Err("unclosed character class".to_string()),
+                }
+            }
+            _ => start,
+        };
+        if start > end {
+            return Err(format!("invalid range `{start}-{end}`"));
+        }
+        ranges.push((start, end));
+    }
+    Ok(Node::Class { ranges, negated })
+}
+
+fn parse_quantifier(chars: &mut Peekable<Chars>) -> Result<Option<(usize, Option<usize>)>, String> {
+    let quantifier = match chars.peek() {
+        Some('*') => (0, None),
+        Some('+') => (1, None),
+        Some('?') => (0, Some(1)),
+        Some('{') => {
+            chars.next();
+            let mut bounds = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => bounds.push(c),
+                    None => return Err("unclosed repetition".to_string()),
+                }
+            }
+            let parse = |value: &str| {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid repetition `{{{bounds}}}`"))
+            };
+            let (min, max) = match bounds.split_once(',') {
+                Some((min, "")) => (parse(min)?, None),
+                Some((min, max)) => (parse(min)?, Some(parse(max)?)),
+                None => (parse(&bounds)?, Some(parse(&bounds)?)),
+            };
+            if max.is_some_and(|max| max < min) {
+                return Err(format!("invalid repetition `{{{bounds}}}`"));
+            }
+            return Ok(Some((min, max)));
+        }
+        _ => return Ok(None),
+    };
+    chars.next();
+    Ok(Some(quantifier))
+}
+
+/// Match any of the alternatives at `pos`, then call `next` with the end position
+///
+/// This backtracks until `next` returns `true`, or there are no other ways to match.
+fn match_alternatives(
+    alternatives: &[Vec<Node>],
+    input: &[char],
+    pos: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    alternatives
+        .iter()
+        .any(|nodes| match_sequence(nodes, input, pos, next))
+}
+
+fn match_sequence(
+    nodes: &[Node],
+    input: &[char],
+    pos: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    match nodes.split_first() {
+        Some((node, rest)) => match_node(node, input, pos, &mut |end| {
+            match_sequence(rest, input, end, next)
+        }),
+        None => next(pos),
+    }
+}
+
+fn match_node(
+    node: &Node,
+    input: &[char],
+    pos: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    match node {
+        Node::Start => pos == 0 && next(pos),
+        Node::End => pos == input.len() && next(pos),
+        Node::Group(alternatives) => match_alternatives(alternatives, input, pos, next),
+        Node::Repeat { node, min, max } => match_repeat(node, *min, *max, input, pos, next),
+        Node::Char(expected) => input.get(pos) == Some(expected) && next(pos + 1),
+        Node::Any => pos < input.len() && next(pos + 1),
+        Node::Class { ranges, negated } => {
+            input.get(pos).is_some_and(|c| {
+                ranges.iter().any(|(start, end)| (start..=end).contains(&c)) != *negated
+            }) && next(pos + 1)
+        }
+    }
+}
+
+/// Match `node` greedily between `min` and `max` times
+fn match_repeat(
+    node: &Node,
+    min: usize,
+    max: Option<usize>,
+    input: &[char],
+    pos: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    let more = max != Some(0)
+        && match_node(node, input, pos, &mut |end| {
+            // Stop repeating empty matches once the minimum is reached, to avoid looping forever
+            (end != pos || min > 0)
+                && match_repeat(
+                    node,
+                    min.saturating_sub(1),
+                    max.map(|max| max - 1),
+                    input,
+                    end,
+                    next,
+                )
+        });
+    more || (min == 0 && next(pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case("Bearer", "Bearer 1234", true)]
+    #[case("^Bearer [0-9]+$", "Bearer 1234", true)]
+    #[case("^Bearer [0-9]+$", "Bearer 12a4", false)]
+    #[case("^Bearer \\d{4}$", "Bearer 1234", true)]
+    #[case("^Bearer \\d{4}$", "Bearer 12345", false)]
+    #[case(
+        "^[0-9a-f]{8}(-[0-9a-f]{4}){3}-[0-9a-f]{12}$",
+        "0b9f1c2e-5d3a-4f6b-8c7d-9e0f1a2b3c4d",
+        true
+    )]
+    #[case(
+        "^[0-9a-f]{8}(-[0-9a-f]{4}){3}-[0-9a-f]{12}$",
+        "0b9f1c2e-5d3a-4f6b-8c7d",
+        false
+    )]
+    #[case("^(?:Basic|Bearer) \\S+$", "Basic dXNlcjpwYXNz", true)]
+    #[case("^(?:Basic|Bearer) \\S+$", "Digest dXNlcjpwYXNz", false)]
+    #[case("^a.*b$", "axxb", true)]
+    #[case("^a.*b$", "axxc", false)]
+    #[case("^[^,]+$", "gzip", true)]
+    #[case("^[^,]+$", "gzip, br", false)]
+    #[case("^(a*)*$", "aaa", true)]
+    #[case("^colou?r$", "color", true)]
+    #[case("^[\\w.-]+$", "api.v2-beta", true)]
+    #[case("^$", "", true)]
+    fn pattern_is_match(#[case] pattern: &str, #[case] value: &str, #[case] expected: bool) {
+        let pattern = Pattern::parse(pattern).unwrap();
+
+        assert_that!(pattern.is_match(value)).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case("(abc")]
+    #[case("abc)")]
+    #[case("[abc")]
+    #[case("[z-a]")]
+    #[case("*abc")]
+    #[case("a{2,1}")]
+    #[case("a{x}")]
+    #[case("\\q")]
+    #[case("(?<name>a)")]
+    fn pattern_parse_invalid(#[case] pattern: &str) {
+        assert_that!(Pattern::parse(pattern)).is_err();
+    }
+}
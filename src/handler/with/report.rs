@@ -1,6 +1,8 @@
-use std::{borrow::Cow, collections::HashSet};
+use std::{borrow::Cow, cmp::Ordering, collections::HashSet, fmt};
 
-use hyper::http::HeaderName;
+use hyper::http::{HeaderName, HeaderValue};
+
+use super::{HeaderCheckKind, UriComponent};
 
 /// Report if a `with` clause for a case matched with an incoming request
 ///
@@ -50,16 +52,59 @@ impl From<Option<Reason>> for Report {
 }
 
 /// Reason for mismatch on a case
+///
+/// Reasons are ordered by their string representation, then by the differences they contain, so
+/// reports are the same between runs.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Reason {
     /// Mismatch on the request method
     Method,
-    /// Mismatch on the request URI
-    Uri,
+    /// Mismatch on a component of the request URI
+    Uri(UriComponent),
+    /// Mismatch on the request host
+    Host,
+    /// Mismatch on the request scheme
+    Scheme,
     /// Mismatch on one header
-    Header(HeaderName),
+    Header {
+        /// Name of the header
+        name: HeaderName,
+        /// Kind of check that failed
+        check: HeaderCheckKind,
+        /// All the values the request contains for this header, in order
+        actual: Vec<HeaderValue>,
+    },
+    /// Mismatch on one trailer
+    Trailer(HeaderName),
+    /// Mismatch on the SOAP action
+    SoapAction,
     /// Mismatch on the payload body
-    Body,
+    ///
+    /// For JSON matchers, this contains the differences between the expected and the actual
+    /// payloads.
+    Body(Vec<JsonDiff>),
+    /// Mismatch on a JSONPath expression
+    JsonPath(String),
+    /// Missing part in a multipart body, identified by its field name
+    MultipartPart(String),
+    /// Invalid AWS SigV4 signature, with the reason why it isn't valid
+    ///
+    /// See [`crate::CaseBuilder::with_sigv4`].
+    Signature(String),
+    /// Mismatch on a closure passed to `and_with`
+    Closure,
+    /// Mismatch reported by a custom [`crate::With`] matcher, identified by its name
+    ///
+    /// Custom matchers can also report the expected and actual values for this reason with
+    /// [`crate::With::expected`] and [`crate::With::actual`].
+    Custom(String),
+    /// The case already received all the calls expected with [`crate::CaseBuilder::times`]
+    ///
+    /// This is only reported for strict and consumed cases, see [`crate::CaseBuilder::strict`]
+    /// and [`crate::CaseBuilder::consumed`].
+    Times,
+    /// The scenario of the case isn't in the state required with [`crate::CaseBuilder::in_state`]
+    Scenario,
 }
 
 impl Reason {
@@ -67,9 +112,128 @@ impl Reason {
     pub fn as_str(&self) -> Cow<'static, str> {
         match self {
             Self::Method => "method".into(),
-            Self::Uri => "uri".into(),
-            Self::Header(name) => format!("header `{name}`").into(),
-            Self::Body => "body".into(),
+            Self::Uri(component) => format!("uri {component}").into(),
+            Self::Host => "host".into(),
+            Self::Scheme => "scheme".into(),
+            Self::Header { name, .. } => format!("header `{name}`").into(),
+            Self::Trailer(name) => format!("trailer `{name}`").into(),
+            Self::SoapAction => "soap action".into(),
+            Self::Body(_) => "body".into(),
+            Self::JsonPath(path) => format!("json path `{path}`").into(),
+            Self::MultipartPart(name) => format!("multipart part `{name}`").into(),
+            Self::Signature(_) => "sigv4 signature".into(),
+            Self::Closure => "closure".into(),
+            Self::Custom(name) => name.clone().into(),
+            Self::Times => "times".into(),
+            Self::Scenario => "scenario state".into(),
+        }
+    }
+}
+
+impl PartialOrd for Reason {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Reason {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str()
+            .cmp(&other.as_str())
+            .then_with(|| match (self, other) {
+                (Self::Body(left), Self::Body(right)) => left.cmp(right),
+                (
+                    Self::Header {
+                        check: left_check,
+                        actual: left_actual,
+                        ..
+                    },
+                    Self::Header {
+                        check: right_check,
+                        actual: right_actual,
+                        ..
+                    },
+                ) => left_check
+                    .cmp(right_check)
+                    .then_with(|| left_actual.cmp(right_actual)),
+                // Other reasons with the same representation are equal
+                _ => Ordering::Equal,
+            })
+    }
+}
+
+/// Difference between an expected and an actual JSON payload
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JsonDiff {
+    /// JSON pointer to the value that differs, such as `/Item/Host/S`
+    pub pointer: String,
+    /// Kind of difference
+    pub kind: JsonDiffKind,
+}
+
+/// Kind of difference between an expected and an actual JSON value
+///
+/// Values are stored as serialized JSON.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum JsonDiffKind {
+    /// The value is missing from the actual payload
+    Missing {
+        /// Expected value
+        expected: String,
+    },
+    /// The value is present in the actual payload, but not expected
+    Unexpected {
+        /// Actual value
+        actual: String,
+    },
+    /// The values are different
+    Value {
+        /// Expected value
+        expected: String,
+        /// Actual value
+        actual: String,
+    },
+    /// The arrays have different lengths
+    Length {
+        /// Expected length
+        expected: usize,
+        /// Actual length
+        actual: usize,
+    },
+    /// The value doesn't satisfy a JSON Schema keyword
+    Schema {
+        /// Keyword that failed, such as `type` or `required`
+        keyword: String,
+        /// Description of the failure
+        message: String,
+    },
+    /// The actual payload isn't valid JSON, such as a body that isn't valid UTF-8
+    Invalid {
+        /// Description of the failure
+        message: String,
+    },
+}
+
+impl fmt::Display for JsonDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        match &self.kind {
+            JsonDiffKind::Missing { expected } => write!(f, "{pointer}: missing {expected}"),
+            JsonDiffKind::Unexpected { actual } => write!(f, "{pointer}: unexpected {actual}"),
+            JsonDiffKind::Value { expected, actual } => {
+                write!(f, "{pointer}: expected {expected}, got {actual}")
+            }
+            JsonDiffKind::Length { expected, actual } => {
+                write!(f, "{pointer}: expected {expected} items, got {actual}")
+            }
+            JsonDiffKind::Schema { keyword, message } => {
+                write!(f, "{pointer}: {message} (`{keyword}`)")
+            }
+            JsonDiffKind::Invalid { message } => write!(f, "{pointer}: {message}"),
         }
     }
 }
@@ -0,0 +1,25 @@
+use hyper::{body::Bytes, header::CONTENT_TYPE, Request};
+
+use crate::codec::parse_params;
+
+/// Returns the SOAP action of the request
+///
+/// SOAP 1.1 sends the action in the `SOAPAction` header, usually quoted, while SOAP 1.2 sends it
+/// as the `action` parameter of the `content-type` header.
+pub(crate) fn soap_action(req: &Request<Bytes>) -> Option<String> {
+    if let Some(action) = req.headers().get("soapaction") {
+        let action = action.to_str().ok()?.trim();
+        let action = action
+            .strip_prefix('"')
+            .and_then(|action| action.strip_suffix('"'))
+            .unwrap_or(action);
+        return Some(action.to_string());
+    }
+
+    let content_type = req.headers().get(CONTENT_TYPE)?.to_str().ok()?;
+    let (_, params) = content_type.split_once(';')?;
+    parse_params(params)
+        .into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("action"))
+        .map(|(_, action)| action)
+}
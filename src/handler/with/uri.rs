@@ -0,0 +1,505 @@
+use hyper::Uri;
+use std::{borrow::Cow, fmt};
+
+/// Component of a URI, used to report which part of the URI didn't match
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UriComponent {
+    /// Scheme, such as `https`
+    Scheme,
+    /// Authority, such as `test.example:8080`
+    Authority,
+    /// Path, such as `/api/items`
+    Path,
+    /// Whole query string
+    Query,
+    /// Query parameter with this name
+    QueryParam(String),
+}
+
+impl UriComponent {
+    /// Returns a string representation for the [`UriComponent`]
+    pub fn as_str(&self) -> Cow<'static, str> {
+        match self {
+            Self::Scheme => "scheme".into(),
+            Self::Authority => "authority".into(),
+            Self::Path => "path".into(),
+            Self::Query => "query".into(),
+            Self::QueryParam(name) => format!("query parameter `{name}`").into(),
+        }
+    }
+}
+
+impl fmt::Display for UriComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.as_str())
+    }
+}
+
+/// Expected components of a request URI
+///
+/// Unlike [`crate::CaseBuilder::with_uri`], only the components set here are checked, so a case
+/// can match a path regardless of the host, or a single query parameter regardless of the others.
+///
+/// This is passed to [`crate::CaseBuilder::with_uri_parts`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, UriParts};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_uri_parts(
+///         UriParts::new()
+///             .scheme("https")
+///             .path_prefix("/api/")
+///             .query_param("page", "2"),
+///     )
+///     .returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UriParts {
+    scheme: Option<String>,
+    authority: Option<String>,
+    path: Option<PathCheck>,
+    query_params: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug, Clone)]
+enum PathCheck {
+    Exact(String),
+    Prefix(String),
+}
+
+impl UriParts {
+    /// Create a new [`UriParts`] matcher, which matches any URI until components are added
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect this scheme, compared case-insensitively
+    pub fn scheme<S>(mut self, scheme: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.scheme = Some(scheme.into());
+        self
+    }
+
+    /// Expect this authority, such as `test.example` or `test.example:8080`
+    ///
+    /// Authorities are compared case-insensitively.
+    pub fn authority<A>(mut self, authority: A) -> Self
+    where
+        A: Into<String>,
+    {
+        self.authority = Some(authority.into());
+        self
+    }
+
+    /// Expect exactly this path
+    pub fn path<P>(mut self, path: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.path = Some(PathCheck::Exact(path.into()));
+        self
+    }
+
+    /// Expect a path that starts with `prefix`
+    pub fn path_prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.path = Some(PathCheck::Prefix(prefix.into()));
+        self
+    }
+
+    /// Expect a query parameter with this value
+    ///
+    /// Names and values are percent-decoded before comparison, and other query parameters are
+    /// ignored.
+    pub fn query_param<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.query_params.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Expect the query not to contain this parameter
+    pub fn query_param_missing<K>(mut self, key: K) -> Self
+    where
+        K: Into<String>,
+    {
+        self.query_params.push((key.into(), None));
+        self
+    }
+
+    /// Returns the components of `uri` that don't match
+    pub(crate) fn mismatches(&self, uri: &Uri) -> Vec<UriComponent> {
+        let mut mismatches = Vec::new();
+
+        if let Some(scheme) = &self.scheme {
+            if !uri
+                .scheme_str()
+                .is_some_and(|value| value.eq_ignore_ascii_case(scheme))
+            {
+                mismatches.push(UriComponent::Scheme);
+            }
+        }
+
+        if let Some(authority) = &self.authority {
+            if !uri
+                .authority()
+                .is_some_and(|value| value.as_str().eq_ignore_ascii_case(authority))
+            {
+                mismatches.push(UriComponent::Authority);
+            }
+        }
+
+        match &self.path {
+            Some(PathCheck::Exact(path)) if uri.path() != path => {
+                mismatches.push(UriComponent::Path);
+            }
+            Some(PathCheck::Prefix(prefix)) if !uri.path().starts_with(prefix.as_str()) => {
+                mismatches.push(UriComponent::Path);
+            }
+            _ => (),
+        }
+
+        let params = query_params(uri);
+        for (key, value) in &self.query_params {
+            let mut values = params
+                .iter()
+                .filter(|(name, _)| name == key)
+                .map(|(_, value)| value);
+            let matched = match value {
+                Some(value) => values.any(|v| v == value),
+                None => values.next().is_none(),
+            };
+            if !matched {
+                mismatches.push(UriComponent::QueryParam(key.clone()));
+            }
+        }
+
+        mismatches
+    }
+
+    /// Returns the expected value for a component, if any
+    pub(crate) fn expected(&self, component: &UriComponent) -> Option<String> {
+        match component {
+            UriComponent::Scheme => self.scheme.clone(),
+            UriComponent::Authority => self.authority.clone(),
+            UriComponent::Path => self.path.as_ref().map(|path| match path {
+                PathCheck::Exact(path) | PathCheck::Prefix(path) => path.clone(),
+            }),
+            UriComponent::Query => None,
+            UriComponent::QueryParam(name) => self
+                .query_params
+                .iter()
+                .find(|(key, _)| key == name)
+                .and_then(|(_, value)| value.clone()),
+        }
+    }
+
+    /// Lines describing the expected components, with the component they check
+    pub(crate) fn lines(&self) -> Vec<(String, UriComponent)> {
+        let mut lines = Vec::new();
+        if let Some(scheme) = &self.scheme {
+            lines.push((format!("scheme:      {scheme}"), UriComponent::Scheme));
+        }
+        if let Some(authority) = &self.authority {
+            lines.push((format!("authority:   {authority}"), UriComponent::Authority));
+        }
+        match &self.path {
+            Some(PathCheck::Exact(path)) => {
+                lines.push((format!("path:        {path}"), UriComponent::Path));
+            }
+            Some(PathCheck::Prefix(prefix)) => {
+                lines.push((format!("path prefix: {prefix}"), UriComponent::Path));
+            }
+            None => (),
+        }
+        for (key, value) in &self.query_params {
+            let value = value.as_deref().unwrap_or("<missing>");
+            lines.push((
+                format!("query:       {key}={value}"),
+                UriComponent::QueryParam(key.clone()),
+            ));
+        }
+        lines
+    }
+}
+
+/// Cosmetic differences to ignore when comparing URIs
+///
+/// This is passed to [`crate::CaseBuilder::with_uri_normalized`]. By default, nothing is
+/// normalized, and URIs must be equal.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, UriNormalization};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     // Also matches `https://test.example:443/items/?b=2&a=1`
+///     .with_uri_normalized(
+///         "https://test.example/items?a=1&b=2",
+///         UriNormalization::new()
+///             .ignore_trailing_slash()
+///             .ignore_default_port()
+///             .ignore_query_order(),
+///     )
+///     .returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UriNormalization {
+    trailing_slash: bool,
+    default_port: bool,
+    percent_decoding: bool,
+    query_order: bool,
+}
+
+impl UriNormalization {
+    /// Create a new [`UriNormalization`] that doesn't normalize anything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Normalize all the supported differences
+    pub fn all() -> Self {
+        Self {
+            trailing_slash: true,
+            default_port: true,
+            percent_decoding: true,
+            query_order: true,
+        }
+    }
+
+    /// Ignore trailing slashes at the end of the path, so `/items/` and `/items` are equal
+    pub fn ignore_trailing_slash(mut self) -> Self {
+        self.trailing_slash = true;
+        self
+    }
+
+    /// Ignore the port when it is the default one for the scheme, `:80` for `http` and `:443`
+    /// for `https`
+    pub fn ignore_default_port(mut self) -> Self {
+        self.default_port = true;
+        self
+    }
+
+    /// Percent-decode the path and query parameters before comparing them, so `/a%20b` and
+    /// `/a b` are equal
+    pub fn decode_percent(mut self) -> Self {
+        self.percent_decoding = true;
+        self
+    }
+
+    /// Ignore the order of the query parameters
+    pub fn ignore_query_order(mut self) -> Self {
+        self.query_order = true;
+        self
+    }
+
+    fn authority(&self, uri: &Uri) -> Option<String> {
+        let authority = uri.authority()?;
+        let default_port = match uri.scheme_str() {
+            Some("http") => Some(80),
+            Some("https") => Some(443),
+            _ => None,
+        };
+        if self.default_port
+            && authority.port_u16().is_some()
+            && authority.port_u16() == default_port
+        {
+            return Some(authority.host().to_ascii_lowercase());
+        }
+        Some(authority.as_str().to_ascii_lowercase())
+    }
+
+    fn path(&self, uri: &Uri) -> String {
+        let mut path = if self.percent_decoding {
+            decode(uri.path(), false)
+        } else {
+            uri.path().to_string()
+        };
+        if self.trailing_slash {
+            let trimmed = path.trim_end_matches('/').len();
+            path.truncate(trimmed.max(1));
+        }
+        path
+    }
+
+    fn query(&self, uri: &Uri) -> Option<Vec<(String, String)>> {
+        if !self.percent_decoding && !self.query_order {
+            return uri
+                .query()
+                .map(|query| vec![(query.to_string(), String::new())]);
+        }
+
+        let mut params = if self.percent_decoding {
+            query_params(uri)
+        } else {
+            raw_query_params(uri)
+        };
+        if self.query_order {
+            params.sort();
+        }
+        (!params.is_empty()).then_some(params)
+    }
+}
+
+/// Returns the components that differ between two URIs, ignoring the normalized differences
+pub(crate) fn uri_mismatches(
+    expected: &Uri,
+    actual: &Uri,
+    normalization: &UriNormalization,
+) -> Vec<UriComponent> {
+    let mut mismatches = Vec::new();
+    if expected.scheme() != actual.scheme() {
+        mismatches.push(UriComponent::Scheme);
+    }
+    if normalization.authority(expected) != normalization.authority(actual) {
+        mismatches.push(UriComponent::Authority);
+    }
+    if normalization.path(expected) != normalization.path(actual) {
+        mismatches.push(UriComponent::Path);
+    }
+    if normalization.query(expected) != normalization.query(actual) {
+        mismatches.push(UriComponent::Query);
+    }
+    mismatches
+}
+
+/// Returns the value of a component of `uri`
+pub(crate) fn uri_component(uri: &Uri, component: &UriComponent) -> Option<String> {
+    match component {
+        UriComponent::Scheme => uri.scheme_str().map(ToString::to_string),
+        UriComponent::Authority => uri.authority().map(ToString::to_string),
+        UriComponent::Path => Some(uri.path().to_string()),
+        UriComponent::Query => uri.query().map(ToString::to_string),
+        UriComponent::QueryParam(name) => {
+            let values = query_params(uri)
+                .into_iter()
+                .filter(|(key, _)| key == name)
+                .map(|(_, value)| value)
+                .collect::<Vec<_>>();
+            (!values.is_empty()).then(|| values.join(", "))
+        }
+    }
+}
+
+/// Parse and percent-decode the query parameters of `uri`
+fn query_params(uri: &Uri) -> Vec<(String, String)> {
+    raw_query_params(uri)
+        .into_iter()
+        .map(|(key, value)| (percent_decode(&key), percent_decode(&value)))
+        .collect()
+}
+
+/// Parse the query parameters of `uri`, without decoding them
+fn raw_query_params(uri: &Uri) -> Vec<(String, String)> {
+    uri.query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.to_string(), value.to_string())
+        })
+        .collect()
+}
+
+/// Decode a `application/x-www-form-urlencoded` value
+fn percent_decode(value: &str) -> String {
+    decode(value, true)
+}
+
+/// Decode percent-encoded bytes in `value`, and `+` as spaces if `plus` is `true`
+pub(super) fn decode(value: &str, plus: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' if plus => decoded.push(b' '),
+            b'%' => match value
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case("a+b", "a b")]
+    #[case("a%20b%2Fc", "a b/c")]
+    #[case("caf%C3%A9", "café")]
+    #[case("100%", "100%")]
+    #[case("%zz", "%zz")]
+    fn uri_percent_decode(#[case] value: &str, #[case] expected: &str) {
+        assert_that!(percent_decode(value)).is_equal_to(expected.to_string());
+    }
+
+    #[rstest]
+    #[case("http://test.example/a?b=c", "http://test.example/a?b=c", vec![])]
+    #[case("http://test.example/a", "https://test.example/a", vec![UriComponent::Scheme])]
+    #[case("http://test.example/a", "http://other.example/b", vec![UriComponent::Authority, UriComponent::Path])]
+    #[case("http://test.example/a?b=c", "http://test.example/a", vec![UriComponent::Query])]
+    fn uri_components(
+        #[case] expected: Uri,
+        #[case] actual: Uri,
+        #[case] mismatches: Vec<UriComponent>,
+    ) {
+        assert_that!(uri_mismatches(&expected, &actual, &UriNormalization::new()))
+            .is_equal_to(mismatches);
+    }
+
+    #[rstest]
+    #[case("http://test.example/a/", "http://test.example/a", UriNormalization::new().ignore_trailing_slash(), vec![])]
+    #[case("http://test.example/", "http://test.example", UriNormalization::new().ignore_trailing_slash(), vec![])]
+    #[case("http://test.example/a/", "http://test.example/a", UriNormalization::new(), vec![UriComponent::Path])]
+    #[case("https://test.example:443/a", "https://test.example/a", UriNormalization::new().ignore_default_port(), vec![])]
+    #[case("http://test.example:80/a", "http://Test.Example/a", UriNormalization::new().ignore_default_port(), vec![])]
+    #[case("http://test.example:443/a", "http://test.example/a", UriNormalization::new().ignore_default_port(), vec![UriComponent::Authority])]
+    #[case("http://test.example/%41b?c=d%2Fe", "http://test.example/Ab?c=d/e", UriNormalization::new().decode_percent(), vec![])]
+    #[case("http://test.example/a+b", "http://test.example/a%20b", UriNormalization::new().decode_percent(), vec![UriComponent::Path])]
+    #[case("http://test.example/?a=1&b=2", "http://test.example/?b=2&a=1", UriNormalization::new().ignore_query_order(), vec![])]
+    #[case("http://test.example/?a=1&b=2", "http://test.example/?b=2&a=1", UriNormalization::new(), vec![UriComponent::Query])]
+    #[case("http://test.example/?a=1", "http://test.example/?a=2", UriNormalization::all(), vec![UriComponent::Query])]
+    fn uri_normalization(
+        #[case] expected: Uri,
+        #[case] actual: Uri,
+        #[case] normalization: UriNormalization,
+        #[case] mismatches: Vec<UriComponent>,
+    ) {
+        assert_that!(uri_mismatches(&expected, &actual, &normalization)).is_equal_to(mismatches);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    case::{AddCase, Case},
+    connector::InnerConnector,
+    CaseBuilder,
+};
+
+/// Group of mock cases restricted to a single host
+///
+/// Requests are first routed by host: cases created through [`HostScope::expect`] are only
+/// evaluated for requests sent to that host, and they don't show up in the mismatch reports for
+/// requests sent to other hosts. Hosts are compared case-insensitively.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error};
+/// # || {
+/// let mut builder = Connector::builder();
+///
+/// let mut dynamodb = builder.for_host("dynamodb.eu-north-1.amazonaws.com");
+/// dynamodb
+///     .expect()
+///     .with_header("x-amz-target", "DynamoDB_20120810.GetItem")
+///     .returning("{}")?;
+///
+/// let mut sqs = builder.for_host("sqs.eu-north-1.amazonaws.com");
+/// sqs.expect().with_method("POST").returning("OK")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+pub struct HostScope<'c> {
+    connector: &'c InnerConnector,
+    host: String,
+}
+
+impl<'c> HostScope<'c> {
+    pub(crate) fn new(connector: &'c InnerConnector, host: String) -> Self {
+        Self { connector, host }
+    }
+
+    /// Create a new expected case for this host
+    pub fn expect(&mut self) -> CaseBuilder<'_> {
+        CaseBuilder::new(self)
+    }
+}
+
+impl AddCase for HostScope<'_> {
+    fn add_case(&self, case: Case) {
+        self.connector.add_case(case.with_host(self.host.clone()));
+    }
+}
@@ -0,0 +1,106 @@
+use hyper::{header::CONTENT_TYPE, Method, Uri};
+
+use crate::{
+    case::{AddCase, Case},
+    connector::InnerConnector,
+    CaseBuilder, Error, MockResponse, UriParts, WithHandler,
+};
+
+/// Group of mock cases for a single JSON API, see [`crate::Builder::expect_json_api`]
+///
+/// Cases created through [`JsonApi::expect`] have sensible defaults for a JSON API:
+///
+/// * They match a path relative to the base URL of the API, with any query string.
+/// * Requests with a payload, sent with `POST`, `PUT`, or `PATCH`, must have a
+///   `content-type: application/json` header.
+/// * Like with [`crate::HostScope`], they are only evaluated for requests sent to the host of the
+///   API.
+///
+/// Requests sent to the host of the API that don't match any of its cases receive a `404 Not
+/// Found` response with a JSON body.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error};
+/// # || {
+/// let mut builder = Connector::builder();
+/// let mut api = builder.expect_json_api("https://api.example/v1")?;
+/// api.expect("GET", "/users/42")
+///     .returning_json(serde_json::json!({"id": 42, "name": "some user"}))?;
+/// api.expect("POST", "/users")
+///     .with_json(serde_json::json!({"name": "new user"}))
+///     .returning_json(serde_json::json!({"id": 43, "name": "new user"}))?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+pub struct JsonApi<'c> {
+    connector: &'c InnerConnector,
+    scheme: Option<String>,
+    host: String,
+    authority: String,
+    base_path: String,
+}
+
+impl<'c> JsonApi<'c> {
+    pub(crate) fn new(connector: &'c InnerConnector, base_url: Uri) -> Result<Self, Error> {
+        let (Some(host), Some(authority)) = (base_url.host(), base_url.authority()) else {
+            return Err(Error::JsonApi(format!("`{base_url}` must have a host")));
+        };
+
+        let api = Self {
+            connector,
+            scheme: base_url.scheme_str().map(ToString::to_string),
+            host: host.to_string(),
+            authority: authority.to_string(),
+            base_path: base_url.path().trim_end_matches('/').to_string(),
+        };
+
+        // Answer other requests to the API, without showing up in reports for other hosts
+        CaseBuilder::new(&api)
+            .name(format!("json api fallback for `{}`", api.authority))
+            .priority(i32::MIN)
+            .returning(
+                MockResponse::not_found().json(serde_json::json!({"error": "not found"}))?,
+            )?;
+
+        Ok(api)
+    }
+
+    /// Create a new expected case for `method` and `path`, relative to the base URL of the API
+    pub fn expect<M>(&mut self, method: M, path: &str) -> CaseBuilder<'_, WithHandler>
+    where
+        M: TryInto<Method>,
+        M::Error: Into<hyper::http::Error>,
+    {
+        let mut parts = UriParts::new()
+            .authority(self.authority.clone())
+            .path(format!(
+                "{}/{}",
+                self.base_path,
+                path.trim_start_matches('/')
+            ));
+        if let Some(scheme) = &self.scheme {
+            parts = parts.scheme(scheme.clone());
+        }
+
+        CaseBuilder::new(self)
+            .with_uri_parts(parts)
+            .and_then_with(|with| {
+                let method = method.try_into().map_err(Into::into)?;
+                let has_payload = [Method::POST, Method::PUT, Method::PATCH].contains(&method);
+                let with = with.with_method(method)?;
+                if has_payload {
+                    with.with_header(CONTENT_TYPE, "application/json")
+                } else {
+                    Ok(with)
+                }
+            })
+    }
+}
+
+impl AddCase for JsonApi<'_> {
+    fn add_case(&self, case: Case) {
+        self.connector.add_case(case.with_host(self.host.clone()));
+    }
+}
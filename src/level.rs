@@ -19,6 +19,7 @@
 /// ```rust
 /// use mock_http_connector::Level;
 ///
+/// assert!(Level::Missing < Level::Strict);
 /// assert!(Level::Error < Level::Missing);
 /// assert!(Level::None < Level::Error);
 /// assert_eq!(Level::Missing, Level::Missing);
@@ -35,6 +36,16 @@
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Level {
+    /// Panic with the diagnostic information when no cases match the incoming request
+    ///
+    /// The request fails with [`crate::Error::Unmatched`], which contains the diagnostic report,
+    /// as the source of the client error. The test then panics with the same report on the next
+    /// call to [`crate::Connector::checkpoint`], or when the last clone of the connector is
+    /// dropped.
+    ///
+    /// This behaves like [`crate::OnUnmatched::Panic`], and takes precedence over the behavior
+    /// set with [`crate::Builder::on_unmatched`].
+    Strict = 3,
     /// Display information when no cases match the incoming request
     #[default]
     Missing = 2,
@@ -46,7 +57,7 @@ pub enum Level {
 
 impl PartialOrd for Level {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        (*self as u8).partial_cmp(&(*other as u8))
+        Some(self.cmp(other))
     }
 }
 
@@ -3,17 +3,76 @@
 
 mod builder;
 mod case;
+mod clock;
+mod codec;
 mod connector;
+mod diagnostics;
 mod error;
+mod expect;
+mod fault;
+mod guard;
 mod handler;
+mod host;
+#[cfg(feature = "json")]
+mod json_api;
 mod level;
+mod macros;
+mod metrics;
+mod mismatch;
+#[cfg(feature = "recording")]
+mod recording;
+mod report;
 mod response;
+mod scenario;
+mod sequence;
+mod service;
+mod state;
 mod stream;
+mod tls;
+mod unmatched;
+#[cfg(feature = "json")]
+mod wiremock;
 
 pub use builder::{Builder, CaseBuilder};
 use case::Case;
-pub use connector::Connector;
-pub use error::Error;
-pub use handler::{Reason, Report, Returning};
+pub use case::{CaseContext, Checkpoint, CheckpointKind};
+pub use clock::{Clock, SleepFuture, TokioClock};
+pub use connector::{ConnectFuture, Connector};
+pub use diagnostics::ColorChoice;
+pub use error::{BoxError, Error};
+pub use expect::ExpectContinue;
+pub use fault::FaultProfile;
+pub use guard::MockGuard;
+#[cfg(feature = "json")]
+pub use handler::ArrayMatching;
+#[cfg(feature = "sigv4")]
+pub use handler::SigV4Credentials;
+pub use handler::{
+    all_of, any_of, not, AllOf, AnyOf, BodyStream, HeaderCheckKind, JsonDiff, JsonDiffKind,
+    Multipart, MultipartPart, Not, PathParams, Reason, Report, Returning, Template, UriComponent,
+    UriNormalization, UriParts, With, WithAsync, WithFuture, WithHandler, WithPrint,
+};
+pub use host::HostScope;
+#[cfg(feature = "json")]
+pub use json_api::JsonApi;
 pub use level::Level;
-pub use response::{IntoResponse, IntoResponseFuture};
+pub use metrics::{CaseMetrics, Histogram, Metrics};
+pub use mismatch::{CaseMismatch, Mismatch, MismatchReport};
+#[cfg(feature = "recording")]
+pub use recording::{Fixture, FixtureEntry, RecordedRequest, RecordedResponse};
+pub use report::CaseReport;
+#[cfg(feature = "gzip")]
+pub use response::Gzip;
+#[cfg(feature = "json")]
+pub use response::Json;
+pub use response::{
+    Backoff, Chunked, Forward, IntoResponse, IntoResponseFuture, MockResponse, RateLimited,
+    SoapFault, Sse, SseEvent, Upgrade,
+};
+pub use scenario::Scenario;
+pub use sequence::Sequence;
+pub use service::MockService;
+pub use state::State;
+pub use stream::MockStream;
+pub use tls::{Tls, TlsInfo};
+pub use unmatched::OnUnmatched;
@@ -0,0 +1,73 @@
+/// Declare a [`crate::Connector`] and its mock cases in a concise form
+///
+/// Each case starts with a method and a URI, followed by an optional list of options between
+/// braces, and the response after `=>`. Cases are separated by semicolons. The macro evaluates to
+/// a `Result<Connector, Error>`.
+///
+/// * The method is the name of an HTTP method, like `GET` or `POST`, or `ANY` to match all
+///   methods.
+/// * `header(key, value)`, `body(body)`, and `json(value)` options are shorthands for
+///   [`crate::CaseBuilder::with_header`], [`crate::CaseBuilder::with_body`], and
+///   [`crate::CaseBuilder::with_json`].
+/// * Any other option is called as a method of [`crate::CaseBuilder`], such as `times(2)` or
+///   `with_header_missing("authorization")`.
+/// * The response is anything accepted by [`crate::CaseBuilder::returning`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{mock_connector, Error};
+/// # || {
+/// let connector = mock_connector! {
+///     GET "https://test.example/users" => (200, "[]");
+///     POST "https://test.example/users" {
+///         header("content-type", "application/json"),
+///         body(r#"{"name": "some user"}"#),
+///         times(1),
+///     } => (201, r#"{"id": 42}"#);
+///     ANY "https://test.example/health" => 204;
+/// }?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[macro_export]
+macro_rules! mock_connector {
+    // Start of a case
+    (@case $builder:ident ANY $uri:literal) => {
+        $builder.expect().with_uri($uri)
+    };
+    (@case $builder:ident $method:ident $uri:literal) => {
+        $builder.expect().with_method(stringify!($method)).with_uri($uri)
+    };
+
+    // Options of a case
+    (@opts ($case:expr) $(,)?) => {
+        $case
+    };
+    (@opts ($case:expr) header($($args:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::mock_connector!(@opts ($case.with_header($($args)*)) $($($rest)*)?)
+    };
+    (@opts ($case:expr) body($($args:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::mock_connector!(@opts ($case.with_body($($args)*)) $($($rest)*)?)
+    };
+    (@opts ($case:expr) json($($args:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::mock_connector!(@opts ($case.with_json($($args)*)) $($($rest)*)?)
+    };
+    (@opts ($case:expr) $method:ident($($args:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::mock_connector!(@opts ($case.$method($($args)*)) $($($rest)*)?)
+    };
+
+    ($($method:ident $uri:literal $({ $($opts:tt)* })? => $returning:expr);* $(;)?) => {
+        (|| -> ::std::result::Result<$crate::Connector, $crate::Error> {
+            let mut builder = $crate::Connector::builder();
+            $(
+                $crate::mock_connector!(
+                    @opts ($crate::mock_connector!(@case builder $method $uri))
+                    $($($opts)*)?
+                )
+                .returning($returning)?;
+            )*
+            Ok(builder.build())
+        })()
+    };
+}
@@ -0,0 +1,179 @@
+use std::{
+    iter::Sum,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Metrics collected by the connector, see [`crate::Builder::metrics`]
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder.metrics(true);
+/// builder.expect().name("batch").returning("OK")?;
+/// let connector = builder.build();
+///
+/// // Send requests through the connector...
+///
+/// let metrics = connector.metrics().unwrap();
+/// assert_eq!(metrics.cases[0].name, "batch");
+/// assert_eq!(metrics.cases[0].requests, 0);
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metrics {
+    /// Metrics for each mock case, in the order they are matched
+    pub cases: Vec<CaseMetrics>,
+    /// Number of requests that didn't match any mock case
+    pub unmatched: usize,
+}
+
+/// Metrics for a single mock case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseMetrics {
+    /// Name of the mock case, or of its matcher if no name was set
+    pub name: String,
+    /// Number of requests that matched the case
+    pub requests: usize,
+    /// Time spent looking for a mock case, for requests that matched this case
+    ///
+    /// This includes the time spent evaluating the cases checked before this one.
+    pub match_latency: Histogram<Duration>,
+    /// Sizes of the request bodies, in bytes
+    pub body_size: Histogram<usize>,
+}
+
+/// Samples recorded for a metric, in the order they were recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram<T> {
+    samples: Vec<T>,
+}
+
+impl<T> Default for Histogram<T> {
+    fn default() -> Self {
+        Self {
+            samples: Vec::new(),
+        }
+    }
+}
+
+impl<T> Histogram<T>
+where
+    T: Copy + Ord,
+{
+    /// All the recorded samples
+    pub fn samples(&self) -> &[T] {
+        &self.samples
+    }
+
+    /// Number of recorded samples
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns `true` if no samples were recorded
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Smallest recorded sample
+    pub fn min(&self) -> Option<T> {
+        self.samples.iter().copied().min()
+    }
+
+    /// Largest recorded sample
+    pub fn max(&self) -> Option<T> {
+        self.samples.iter().copied().max()
+    }
+
+    /// Sum of all the recorded samples
+    pub fn sum(&self) -> T
+    where
+        T: Sum,
+    {
+        self.samples.iter().copied().sum()
+    }
+
+    /// Sample at the given percentile, between `0.0` and `100.0`, using the nearest-rank method
+    ///
+    /// Percentiles outside of that range are clamped.
+    pub fn percentile(&self, percentile: f64) -> Option<T> {
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied()
+    }
+
+    pub(crate) fn record(&mut self, sample: T) {
+        self.samples.push(sample);
+    }
+}
+
+/// Metrics shared between a mock case and its clones
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CaseRecorder {
+    inner: Arc<Mutex<Samples>>,
+}
+
+#[derive(Debug, Default)]
+struct Samples {
+    match_latency: Histogram<Duration>,
+    body_size: Histogram<usize>,
+}
+
+impl CaseRecorder {
+    /// Record a request that matched the case
+    pub fn record(&self, match_latency: Duration, body_size: usize) {
+        let mut samples = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        samples.match_latency.record(match_latency);
+        samples.body_size.record(body_size);
+    }
+
+    /// Metrics for the case with this name
+    pub fn metrics(&self, name: String) -> CaseMetrics {
+        let samples = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        CaseMetrics {
+            name,
+            requests: samples.body_size.len(),
+            match_latency: samples.match_latency.clone(),
+            body_size: samples.body_size.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case(0.0, Some(1))]
+    #[case(25.0, Some(1))]
+    #[case(50.0, Some(2))]
+    #[case(90.0, Some(4))]
+    #[case(100.0, Some(4))]
+    #[case(150.0, Some(4))]
+    fn histogram_percentile(#[case] percentile: f64, #[case] expected: Option<usize>) {
+        let mut histogram = Histogram::default();
+        for sample in [4, 1, 3, 2] {
+            histogram.record(sample);
+        }
+
+        assert_that!(histogram.percentile(percentile)).is_equal_to(expected);
+    }
+
+    #[rstest]
+    fn histogram_empty() {
+        let histogram = Histogram::<usize>::default();
+
+        assert_that!(histogram.percentile(50.0)).is_none();
+        assert_that!(histogram.min()).is_none();
+        assert_that!(histogram.sum()).is_equal_to(0);
+    }
+}
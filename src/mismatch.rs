@@ -0,0 +1,245 @@
+use hyper::{body::Bytes, header::AUTHORIZATION, HeaderMap, Method, Request, Uri};
+use std::collections::HashSet;
+
+use crate::{
+    codec::Trailers,
+    handler::{form_parts, soap_action, uri_component},
+    Case, Reason,
+};
+
+/// Structured report for a request that didn't match any mock case
+///
+/// This contains the same information as the diagnostic report printed by the connector, but
+/// as values that can be inspected in tests or rendered by other tools. See
+/// [`crate::Connector::last_report`].
+#[derive(Debug, Clone)]
+pub struct MismatchReport {
+    /// Method of the incoming request
+    pub method: Method,
+    /// URI of the incoming request
+    pub uri: Uri,
+    /// Headers of the incoming request
+    pub headers: HeaderMap,
+    /// Body of the incoming request
+    pub body: Bytes,
+    /// Mismatches for each mock case, in the order they were evaluated
+    pub cases: Vec<CaseMismatch>,
+}
+
+/// Mismatches between a request and a single mock case
+#[derive(Debug, Clone)]
+pub struct CaseMismatch {
+    /// Name of the matcher for this case
+    pub name: String,
+    /// Attributes of the request that didn't match
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Single attribute of a request that didn't match a mock case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Attribute that didn't match
+    pub reason: Reason,
+    /// Value expected by the mock case, if known
+    pub expected: Option<String>,
+    /// Value found in the request, if known
+    pub actual: Option<String>,
+    /// Position of the first byte that differs between `expected` and `actual`, if both are known
+    pub diff_position: Option<usize>,
+}
+
+impl MismatchReport {
+    #[allow(clippy::mutable_key_type)]
+    pub(crate) fn new(req: &Request<Bytes>, reports: &[(&Case, HashSet<Reason>)]) -> Self {
+        let cases = reports
+            .iter()
+            .map(|(case, reasons)| {
+                let mut reasons = reasons.iter().collect::<Vec<_>>();
+                reasons.sort();
+
+                CaseMismatch {
+                    name: case.name(),
+                    mismatches: reasons
+                        .into_iter()
+                        .map(|reason| Mismatch::new(reason.clone(), case, req))
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Self {
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            headers: req.headers().clone(),
+            body: req.body().clone(),
+            cases,
+        }
+    }
+}
+
+impl Mismatch {
+    pub(crate) fn new(reason: Reason, case: &Case, req: &Request<Bytes>) -> Self {
+        let report = case.report();
+        let expected = match &reason {
+            Reason::Times => report.times.map(|times| times.to_string()),
+            Reason::Scenario => case
+                .scenario_states()
+                .and_then(|(state, _)| state.map(ToString::to_string)),
+            reason => case.with.expected(reason),
+        };
+        let actual = match &reason {
+            Reason::Method => Some(req.method().to_string()),
+            Reason::Uri(component) => uri_component(req.uri(), component),
+            Reason::Host => req.uri().host().map(ToString::to_string),
+            Reason::Scheme => req.uri().scheme_str().map(ToString::to_string),
+            Reason::Header { actual, .. } => (!actual.is_empty()).then(|| {
+                actual
+                    .iter()
+                    .map(|value| String::from_utf8_lossy(value.as_bytes()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+            Reason::Trailer(name) => {
+                let values = req
+                    .extensions()
+                    .get::<Trailers>()
+                    .into_iter()
+                    .flat_map(|trailers| trailers.0.get_all(name))
+                    .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                    .collect::<Vec<_>>();
+                (!values.is_empty()).then(|| values.join(", "))
+            }
+            Reason::SoapAction => soap_action(req),
+            Reason::Body(_) => Some(String::from_utf8_lossy(req.body()).into_owned()),
+            Reason::MultipartPart(name) => form_parts(req).and_then(|parts| {
+                parts
+                    .into_iter()
+                    .find(|part| part.name.as_deref() == Some(name.as_str()))
+                    .map(|part| String::from_utf8_lossy(part.content).into_owned())
+            }),
+            Reason::Signature(_) => req
+                .headers()
+                .get(AUTHORIZATION)
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned()),
+            Reason::JsonPath(_) | Reason::Closure => None,
+            Reason::Custom(_) => case.with.actual(&reason, req),
+            Reason::Times => Some(report.seen.to_string()),
+            Reason::Scenario => case.scenario_states().map(|(_, current)| current),
+        };
+        let diff_position = expected
+            .as_deref()
+            .zip(actual.as_deref())
+            .and_then(|(expected, actual)| diff_position(expected, actual));
+
+        Self {
+            reason,
+            expected,
+            actual,
+            diff_position,
+        }
+    }
+}
+
+/// Position of the first byte that differs between two strings
+pub(crate) fn diff_position(left: &str, right: &str) -> Option<usize> {
+    let (left, right) = (left.as_bytes(), right.as_bytes());
+    left.iter()
+        .zip(right)
+        .position(|(l, r)| l != r)
+        .or_else(|| (left.len() != right.len()).then(|| left.len().min(right.len())))
+}
+
+/// Line of a diff between an expected and an actual string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiffLine<'a> {
+    /// Line present in both strings
+    Same(&'a str),
+    /// Line only present in the expected string
+    Removed(&'a str),
+    /// Line only present in the actual string
+    Added(&'a str),
+}
+
+/// Maximum number of line pairs compared when diffing, to keep large bodies cheap to report
+const MAX_DIFF_CELLS: usize = 250_000;
+
+/// Line-by-line diff between `expected` and `actual`, based on their longest common subsequence
+///
+/// For large inputs, this shows all the lines of `expected` as removed and all the lines of
+/// `actual` as added.
+pub(crate) fn diff_lines<'a>(expected: &'a str, actual: &'a str) -> Vec<DiffLine<'a>> {
+    let expected = expected.split('\n').collect::<Vec<_>>();
+    let actual = actual.split('\n').collect::<Vec<_>>();
+    let (n, m) = (expected.len(), actual.len());
+    if n.saturating_mul(m) > MAX_DIFF_CELLS {
+        return expected
+            .into_iter()
+            .map(DiffLine::Removed)
+            .chain(actual.into_iter().map(DiffLine::Added))
+            .collect();
+    }
+
+    // lengths[i][j] is the length of the common subsequence of expected[i..] and actual[j..]
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if expected[i] == actual[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if expected[i] == actual[j] {
+            lines.push(DiffLine::Same(expected[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(DiffLine::Removed(expected[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(actual[j]));
+            j += 1;
+        }
+    }
+    lines.extend(expected[i..].iter().copied().map(DiffLine::Removed));
+    lines.extend(actual[j..].iter().copied().map(DiffLine::Added));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case("hello world", "hello world", None)]
+    #[case("hello world", "hello there", Some(6))]
+    #[case("hello", "hello world", Some(5))]
+    #[case("", "hello", Some(0))]
+    fn mismatch_diff_position(
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_that!(diff_position(left, right)).is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case("a=1&b=2", "a=1&b=3", vec![DiffLine::Removed("a=1&b=2"), DiffLine::Added("a=1&b=3")])]
+    #[case("a\nb\nc", "a\nc", vec![DiffLine::Same("a"), DiffLine::Removed("b"), DiffLine::Same("c")])]
+    #[case("a\nc", "a\nb\nc", vec![DiffLine::Same("a"), DiffLine::Added("b"), DiffLine::Same("c")])]
+    #[case("a\nb", "a\nb", vec![DiffLine::Same("a"), DiffLine::Same("b")])]
+    fn mismatch_diff_lines(
+        #[case] expected: &str,
+        #[case] actual: &str,
+        #[case] lines: Vec<DiffLine<'static>>,
+    ) {
+        assert_that!(diff_lines(expected, actual)).is_equal_to(lines);
+    }
+}
@@ -0,0 +1,385 @@
+use hyper::{
+    body::Bytes,
+    header::{CONTENT_TYPE, TRANSFER_ENCODING},
+    HeaderMap, Request, Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fs, path::Path};
+
+use crate::{
+    error::BoxError,
+    handler::{Returning, Sealed},
+    response::{ChunkedBody, ResponseFuture},
+    Error,
+};
+
+/// Collection of request/response pairs that can be saved to, or loaded from, a file
+///
+/// Fixtures are created by connectors with recording enabled (see
+/// [`crate::Builder::record`] and [`crate::Connector::recording`]), and can be replayed with
+/// [`crate::Builder::load_fixture`].
+///
+/// Payloads are stored as UTF-8 strings, so fixtures are meant for text-based APIs. Invalid UTF-8
+/// sequences are replaced when recording.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Fixture, FixtureEntry, RecordedRequest, RecordedResponse};
+/// let fixture = Fixture {
+///     entries: vec![FixtureEntry {
+///         request: RecordedRequest {
+///             method: "GET".to_string(),
+///             uri: "http://test.example/".to_string(),
+///             headers: Vec::new(),
+///             body: String::new(),
+///         },
+///         response: RecordedResponse {
+///             status: 200,
+///             headers: Vec::new(),
+///             body: "OK".to_string(),
+///         },
+///     }],
+/// };
+///
+/// let json = fixture.to_json().unwrap();
+/// assert_eq!(Fixture::from_json(&json).unwrap(), fixture);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// Recorded request/response pairs, in the order they happened
+    pub entries: Vec<FixtureEntry>,
+}
+
+/// Single request/response pair in a [`Fixture`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureEntry {
+    /// Incoming request
+    pub request: RecordedRequest,
+    /// Response returned for that request
+    pub response: RecordedResponse,
+}
+
+/// Request stored in a [`Fixture`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// Request method, such as `GET`
+    pub method: String,
+    /// Full request URI
+    pub uri: String,
+    /// Request headers, in order
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Request payload
+    #[serde(default)]
+    pub body: String,
+}
+
+/// Response stored in a [`Fixture`]
+///
+/// This also implements [`Returning`], to send back the recorded response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// Status code
+    pub status: u16,
+    /// Response headers, in order
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Response payload
+    #[serde(default)]
+    pub body: String,
+}
+
+impl Fixture {
+    /// Parse a fixture from JSON
+    ///
+    /// This accepts both the format produced by [`Fixture::to_json`] and HAR files.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        match value.get("log") {
+            Some(log) => from_har(log),
+            None => Ok(serde_json::from_value(value)?),
+        }
+    }
+
+    /// Serialize the fixture into JSON
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Serialize the fixture into a HAR (HTTP Archive) file
+    ///
+    /// HAR files can be opened by most browsers and HTTP debugging tools. Timings are not
+    /// recorded, and are always set to `0`.
+    pub fn to_har(&self) -> Result<String, Error> {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let request = &entry.request;
+                let response = &entry.response;
+                let status_text = StatusCode::from_u16(response.status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason())
+                    .unwrap_or_default();
+
+                json!({
+                    "startedDateTime": "1970-01-01T00:00:00.000Z",
+                    "time": 0,
+                    "request": {
+                        "method": request.method,
+                        "url": request.uri,
+                        "httpVersion": "HTTP/1.1",
+                        "cookies": [],
+                        "headers": har_headers(&request.headers),
+                        "queryString": [],
+                        "postData": {
+                            "mimeType": content_type(&request.headers),
+                            "text": request.body,
+                        },
+                        "headersSize": -1,
+                        "bodySize": request.body.len(),
+                    },
+                    "response": {
+                        "status": response.status,
+                        "statusText": status_text,
+                        "httpVersion": "HTTP/1.1",
+                        "cookies": [],
+                        "headers": har_headers(&response.headers),
+                        "content": {
+                            "size": response.body.len(),
+                            "mimeType": content_type(&response.headers),
+                            "text": response.body,
+                        },
+                        "redirectURL": "",
+                        "headersSize": -1,
+                        "bodySize": response.body.len(),
+                    },
+                    "cache": {},
+                    "timings": { "send": 0, "wait": 0, "receive": 0 },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::to_string_pretty(&json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": entries,
+            }
+        }))?)
+    }
+
+    /// Load a fixture from a file
+    ///
+    /// See [`Fixture::from_json`] for the supported formats.
+    pub fn load<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Save the fixture into a file
+    ///
+    /// Files with a `.har` extension are saved as HAR files, and all other files use the format
+    /// from [`Fixture::to_json`].
+    pub fn save<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let data = match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("har") => self.to_har()?,
+            _ => self.to_json()?,
+        };
+        fs::write(path, data)?;
+
+        Ok(())
+    }
+}
+
+impl RecordedRequest {
+    pub(crate) fn new(req: &Request<Bytes>) -> Self {
+        Self {
+            method: req.method().to_string(),
+            uri: req.uri().to_string(),
+            headers: recorded_headers(req.headers()),
+            body: String::from_utf8_lossy(req.body()).into_owned(),
+        }
+    }
+}
+
+impl RecordedResponse {
+    pub(crate) fn new(res: &Response<Bytes>) -> Self {
+        // Chunked responses are stored as a single payload, without the framing
+        let body = match res.extensions().get::<ChunkedBody>() {
+            Some(chunked) => chunked.chunks.concat(),
+            None => res.body().to_vec(),
+        };
+
+        Self {
+            status: res.status().as_u16(),
+            headers: recorded_headers(res.headers())
+                .into_iter()
+                .filter(|(key, _)| !key.eq_ignore_ascii_case(TRANSFER_ENCODING.as_str()))
+                .collect(),
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }
+    }
+}
+
+impl Returning for RecordedResponse {
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let mut res = Response::builder().status(self.status);
+        for (key, value) in &self.headers {
+            res = res.header(key, value);
+        }
+        let res = res.body(Bytes::from(self.body.clone()));
+
+        Box::pin(async move { Ok(res?) })
+    }
+}
+
+impl Sealed for RecordedResponse {}
+
+fn recorded_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+fn content_type(headers: &[(String, String)]) -> &str {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(CONTENT_TYPE.as_str()))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or_default()
+}
+
+fn har_headers(headers: &[(String, String)]) -> serde_json::Value {
+    headers
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+/// Convert the `log` object of a HAR file into a [`Fixture`]
+fn from_har(log: &serde_json::Value) -> Result<Fixture, Error> {
+    fn str_field<'v>(value: &'v serde_json::Value, pointer: &str) -> Result<&'v str, BoxError> {
+        value
+            .pointer(pointer)
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| format!("missing HAR field `{pointer}`").into())
+    }
+
+    fn headers(value: &serde_json::Value, pointer: &str) -> Vec<(String, String)> {
+        value
+            .pointer(pointer)
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|header| {
+                Some((
+                    header.get("name")?.as_str()?.to_string(),
+                    header.get("value")?.as_str()?.to_string(),
+                ))
+            })
+            .collect()
+    }
+
+    let entries = log
+        .get("entries")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|entry| {
+            let status = entry
+                .pointer("/response/status")
+                .and_then(serde_json::Value::as_u64)
+                .and_then(|status| u16::try_from(status).ok())
+                .ok_or("missing HAR field `/response/status`")?;
+
+            Ok(FixtureEntry {
+                request: RecordedRequest {
+                    method: str_field(entry, "/request/method")?.to_string(),
+                    uri: str_field(entry, "/request/url")?.to_string(),
+                    headers: headers(entry, "/request/headers"),
+                    body: str_field(entry, "/request/postData/text")
+                        .unwrap_or_default()
+                        .to_string(),
+                },
+                response: RecordedResponse {
+                    status,
+                    headers: headers(entry, "/response/headers"),
+                    body: str_field(entry, "/response/content/text")
+                        .unwrap_or_default()
+                        .to_string(),
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, BoxError>>()?;
+
+    Ok(Fixture { entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speculoos::prelude::*;
+
+    fn fixture() -> Fixture {
+        Fixture {
+            entries: vec![FixtureEntry {
+                request: RecordedRequest {
+                    method: "POST".to_string(),
+                    uri: "http://test.example/items".to_string(),
+                    headers: vec![("content-type".to_string(), "application/json".to_string())],
+                    body: r#"{"id":1}"#.to_string(),
+                },
+                response: RecordedResponse {
+                    status: 201,
+                    headers: vec![("x-request-id".to_string(), "1234".to_string())],
+                    body: "created".to_string(),
+                },
+            }],
+        }
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let fixture = fixture();
+        let json = fixture.to_json().unwrap();
+
+        assert_that!(Fixture::from_json(&json))
+            .is_ok()
+            .is_equal_to(fixture);
+    }
+
+    #[test]
+    fn test_har_roundtrip() {
+        let fixture = fixture();
+        let har = fixture.to_har().unwrap();
+
+        assert_that!(Fixture::from_json(&har))
+            .is_ok()
+            .is_equal_to(fixture);
+    }
+
+    #[test]
+    fn test_har_missing_fields() {
+        let har = r#"{"log": {"entries": [{"request": {"method": "GET"}}]}}"#;
+
+        assert_that!(Fixture::from_json(har)).is_err();
+    }
+}
@@ -0,0 +1,51 @@
+/// Statistics for a single mock case, at the time [`crate::Connector::report`] was called
+///
+/// This can be used for intermediate assertions before the final [`crate::Connector::checkpoint`],
+/// for example to check that a token was refreshed exactly once so far.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .times(2)
+///     .name("token refresh")
+///     .returning("OK")?;
+/// let connector = builder.build();
+///
+/// let report = connector.report();
+/// assert_eq!(report[0].name, "token refresh");
+/// assert_eq!(report[0].seen, 0);
+/// assert_eq!(report[0].pending(), Some(2));
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseReport {
+    /// Name of the mock case, or of its matcher if no name was set
+    pub name: String,
+    /// Number of calls expected with [`crate::CaseBuilder::times`], if any
+    pub times: Option<usize>,
+    /// Number of requests that matched the case so far
+    pub seen: usize,
+}
+
+impl CaseReport {
+    /// Number of calls still expected before the case is satisfied
+    ///
+    /// This returns `None` if the case doesn't expect a number of calls, and `Some(0)` once it
+    /// received all the expected calls, or more.
+    pub fn pending(&self) -> Option<usize> {
+        self.times.map(|times| times.saturating_sub(self.seen))
+    }
+
+    /// Returns `true` if the case received exactly the expected number of calls
+    ///
+    /// Cases without an expected number of calls are always satisfied.
+    pub fn is_satisfied(&self) -> bool {
+        self.times.map_or(true, |times| times == self.seen)
+    }
+}
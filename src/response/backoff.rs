@@ -0,0 +1,131 @@
+use hyper::{body::Bytes, header::RETRY_AFTER, Request, Response, StatusCode};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{handler::Sealed, response::ResponseFuture, Returning};
+
+/// [`Returning`] implementation that fails a number of times before succeeding, to test retries
+///
+/// The first `failures` requests receive an empty response with the failure status, which is
+/// `503 Service Unavailable` by default, and an optional `retry-after` header. Further requests
+/// receive the regular response, which is an empty `200 OK` response by default, or the response
+/// set with [`Backoff::success`].
+///
+/// Use [`crate::CaseBuilder::returning_backoff`] to also check that the client made exactly
+/// `failures + 1` attempts when calling [`crate::Connector::checkpoint`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::StatusCode;
+/// # use mock_http_connector::{Backoff, Connector, Error};
+/// # use std::time::Duration;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder.expect().returning_backoff(
+///     Backoff::new(3)
+///         .status(StatusCode::TOO_MANY_REQUESTS)
+///         .retry_after_each([
+///             Duration::from_secs(1),
+///             Duration::from_secs(2),
+///             Duration::from_secs(4),
+///         ])
+///         .success((200, "some payload")),
+/// )?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone)]
+pub struct Backoff<R = u16> {
+    failures: usize,
+    status: StatusCode,
+    retry_after: Vec<Duration>,
+    inner: R,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Backoff {
+    /// Create a new [`Backoff`] response, failing the first `failures` requests
+    pub fn new(failures: usize) -> Self {
+        Self {
+            failures,
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            retry_after: Vec::new(),
+            inner: StatusCode::OK.as_u16(),
+            calls: Arc::default(),
+        }
+    }
+}
+
+impl<R> Backoff<R> {
+    /// Set the status code of the failed responses, such as `429 Too Many Requests`
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Set the same `retry-after` header on all the failed responses
+    pub fn retry_after(self, delay: Duration) -> Self {
+        self.retry_after_each([delay])
+    }
+
+    /// Set the `retry-after` header of each failed response, in order
+    ///
+    /// If there are more failures than delays, the last delay is used for the remaining failures.
+    /// Delays are sent as a number of seconds, rounded up.
+    pub fn retry_after_each<I>(mut self, delays: I) -> Self
+    where
+        I: IntoIterator<Item = Duration>,
+    {
+        self.retry_after = delays.into_iter().collect();
+        self
+    }
+
+    /// Set the response once all the failures were returned
+    pub fn success<I>(self, inner: I) -> Backoff<I>
+    where
+        I: Returning,
+    {
+        Backoff {
+            failures: self.failures,
+            status: self.status,
+            retry_after: self.retry_after,
+            inner,
+            calls: self.calls,
+        }
+    }
+
+    /// Number of requests expected from a client that retries until it succeeds
+    pub(crate) fn attempts(&self) -> usize {
+        self.failures + 1
+    }
+}
+
+impl<R> Returning for Backoff<R>
+where
+    R: Returning,
+{
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let call = self.calls.fetch_add(1, Ordering::AcqRel);
+        if call >= self.failures {
+            return self.inner.returning(req);
+        }
+
+        let mut res = Response::builder().status(self.status);
+        if let Some(delay) = self.retry_after.get(call).or(self.retry_after.last()) {
+            // Round up, so clients don't retry too early
+            let secs = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+            res = res.header(RETRY_AFTER, secs);
+        }
+        let res = res.body(Bytes::new());
+
+        Box::pin(async move { Ok(res?) })
+    }
+}
+
+impl<R> Sealed for Backoff<R> where R: Returning {}
@@ -0,0 +1,224 @@
+use hyper::{
+    body::Bytes,
+    header::{TRAILER, TRANSFER_ENCODING},
+    http::{HeaderName, HeaderValue},
+    Request, Response, StatusCode,
+};
+use itertools::Itertools;
+use std::time::Duration;
+
+use crate::{handler::Sealed, response::ResponseFuture, Error, Returning};
+
+/// [`Returning`] implementation that sends the response body with chunked transfer-encoding
+///
+/// Each chunk is sent separately to the client, optionally with a delay before each chunk, to
+/// test clients that consume response bodies incrementally.
+///
+/// Delays use the [`crate::Clock`] of the connector. By default, this is [`tokio::time::sleep`],
+/// which requires the time driver to be enabled on the tokio runtime.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Chunked, Connector, Error};
+/// # use std::time::Duration;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .returning(Chunked::new(["part1", "part2"]).delay(Duration::from_millis(10)))?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone)]
+pub struct Chunked {
+    status: StatusCode,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: ChunkedBody,
+}
+
+impl Chunked {
+    /// Create a new [`Chunked`] response with the given chunks
+    pub fn new<I, B>(chunks: I) -> Self
+    where
+        I: IntoIterator<Item = B>,
+        B: Into<Bytes>,
+    {
+        Self {
+            status: StatusCode::OK,
+            headers: Vec::new(),
+            body: ChunkedBody {
+                chunks: chunks.into_iter().map(Into::into).collect(),
+                delay: None,
+                keep_open: false,
+                trailers: Vec::new(),
+            },
+        }
+    }
+
+    /// Set the status code of the response
+    pub fn status<S>(mut self, status: S) -> Result<Self, Error>
+    where
+        S: TryInto<StatusCode>,
+        S::Error: Into<hyper::http::Error>,
+    {
+        self.status = status.try_into().map_err(Into::into)?;
+        Ok(self)
+    }
+
+    /// Add a header to the response
+    pub fn header<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            value.try_into().map_err(Into::into)?,
+        ));
+        Ok(self)
+    }
+
+    /// Add a trailer, sent after the last chunk
+    ///
+    /// The `trailer` header is set with the names of all trailers, unless it was added with
+    /// [`Chunked::header`].
+    pub fn trailer<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.body.trailers.push((
+            key.try_into().map_err(Into::into)?,
+            value.try_into().map_err(Into::into)?,
+        ));
+        Ok(self)
+    }
+
+    /// Wait for `delay` before sending each chunk
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.body.delay = Some(delay);
+        self
+    }
+}
+
+impl Returning for Chunked {
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let mut res = Response::builder()
+            .status(self.status)
+            .header(TRANSFER_ENCODING, "chunked");
+        for (key, value) in &self.headers {
+            res = res.header(key, value);
+        }
+        if !self.body.trailers.is_empty() && !self.headers.iter().any(|(key, _)| key == TRAILER) {
+            let names = self.body.trailers.iter().map(|(key, _)| key).join(", ");
+            res = res.header(TRAILER, names);
+        }
+        let res = res.extension(self.body.clone()).body(Bytes::new());
+
+        Box::pin(async move { Ok(res?) })
+    }
+}
+
+impl Sealed for Chunked {}
+
+/// Chunks to send after the response head
+///
+/// This is stored in the [`Response`] extensions, as the response body itself is a single
+/// [`Bytes`] value.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkedBody {
+    pub chunks: Vec<Bytes>,
+    pub delay: Option<Duration>,
+    /// Keep the connection open after the last chunk, without terminating the body
+    pub keep_open: bool,
+    /// Trailers sent with the terminating chunk
+    pub trailers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl ChunkedBody {
+    /// Encode the chunks with chunked transfer-encoding framing
+    ///
+    /// This returns one segment per chunk, followed by the terminating chunk unless the body is
+    /// kept open.
+    pub fn encode(self) -> impl Iterator<Item = (Option<Duration>, Vec<u8>)> {
+        let delay = self.delay;
+        let end = (!self.keep_open).then(|| {
+            let mut data = b"0\r\n".to_vec();
+            for (key, value) in &self.trailers {
+                data.extend(key.as_str().as_bytes());
+                data.extend(b": ");
+                data.extend(value.as_bytes());
+                data.extend(b"\r\n");
+            }
+            data.extend(b"\r\n");
+            (None, data)
+        });
+
+        self.chunks
+            .into_iter()
+            // An empty chunk would terminate the body early
+            .filter(|chunk| !chunk.is_empty())
+            .map(move |chunk| {
+                let mut data = format!("{:x}\r\n", chunk.len()).into_bytes();
+                data.extend(&chunk);
+                data.extend(b"\r\n");
+                (delay, data)
+            })
+            .chain(end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn chunked_encode() {
+        let body = ChunkedBody {
+            chunks: vec!["hello ".into(), "".into(), "world!".into()],
+            delay: None,
+            keep_open: false,
+            trailers: Vec::new(),
+        };
+
+        let data = body.encode().flat_map(|(_, data)| data).collect::<Vec<_>>();
+        assert_that!(String::from_utf8(data).unwrap())
+            .is_equal_to("6\r\nhello \r\n6\r\nworld!\r\n0\r\n\r\n".to_string());
+    }
+
+    #[test]
+    fn chunked_encode_keep_open() {
+        let body = ChunkedBody {
+            chunks: vec!["hello".into()],
+            delay: None,
+            keep_open: true,
+            trailers: Vec::new(),
+        };
+
+        let data = body.encode().flat_map(|(_, data)| data).collect::<Vec<_>>();
+        assert_that!(String::from_utf8(data).unwrap()).is_equal_to("5\r\nhello\r\n".to_string());
+    }
+
+    #[test]
+    fn chunked_encode_trailers() {
+        let body = ChunkedBody {
+            chunks: vec!["hello".into()],
+            delay: None,
+            keep_open: false,
+            trailers: vec![(
+                HeaderName::from_static("x-checksum"),
+                HeaderValue::from_static("abc"),
+            )],
+        };
+
+        let data = body.encode().flat_map(|(_, data)| data).collect::<Vec<_>>();
+        assert_that!(String::from_utf8(data).unwrap())
+            .is_equal_to("5\r\nhello\r\n0\r\nx-checksum: abc\r\n\r\n".to_string());
+    }
+}
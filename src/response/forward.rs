@@ -0,0 +1,119 @@
+use hyper::{
+    body::Bytes,
+    client::connect::Connect,
+    header::{CONTENT_LENGTH, TRANSFER_ENCODING},
+    Body, Client, Request, Response,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "gzip")]
+use hyper::header::CONTENT_ENCODING;
+
+use crate::{error::BoxError, handler::Sealed, response::ResponseFuture, Returning};
+
+/// [`Returning`] implementation that forwards requests to a real [`Client`]
+///
+/// This can be used for hybrid tests, where only some dependencies are mocked and other requests
+/// are sent to real services, either for specific cases or as a fallback with
+/// [`crate::Builder::fallback`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Forward};
+/// # || {
+/// let mut builder = Connector::builder();
+/// // Mock a single dependency, and send everything else to real services
+/// builder
+///     .expect()
+///     .with_host("api.example.com")
+///     .returning("OK")?;
+/// builder.fallback(Forward::new(hyper::Client::new()));
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+///
+/// ## Remark
+///
+/// The request payload is forwarded after decoding, so the request is sent without its original
+/// transfer or content encodings. The response payload is read in full before it is returned to
+/// the client.
+#[derive(Debug, Clone)]
+pub struct Forward<C> {
+    client: Client<C, Body>,
+}
+
+impl<C> Forward<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    /// Create a new [`Forward`] response that sends requests through `client`
+    pub fn new(client: Client<C, Body>) -> Self {
+        Self { client }
+    }
+}
+
+impl<C> Returning for Forward<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let (mut parts, body) = req.into_parts();
+
+        // The payload was already decoded, so let hyper frame it again
+        parts.headers.remove(TRANSFER_ENCODING);
+        parts.headers.remove(CONTENT_LENGTH);
+        #[cfg(feature = "gzip")]
+        if parts
+            .headers
+            .get(CONTENT_ENCODING)
+            .is_some_and(|value| matches!(value.as_bytes(), b"gzip" | b"x-gzip" | b"deflate"))
+        {
+            parts.headers.remove(CONTENT_ENCODING);
+        }
+
+        let client = self.client.clone();
+        let fut = async move {
+            let res = client
+                .request(Request::from_parts(parts, body.into()))
+                .await?;
+            let (mut parts, body) = res.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+
+            // The body is sent back in one piece
+            parts.headers.remove(TRANSFER_ENCODING);
+
+            Ok::<_, BoxError>(Response::from_parts(parts, body))
+        };
+
+        Box::pin(SyncFuture(Mutex::new(Box::pin(fut))))
+    }
+}
+
+impl<C> Sealed for Forward<C> {}
+
+/// Wrapper to make a [`Send`] future [`Sync`]
+///
+/// The future is only ever accessed through `&mut self`, so the lock is never contended.
+struct SyncFuture<F>(Mutex<F>);
+
+impl<F> Future for SyncFuture<F>
+where
+    F: Future + Unpin,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fut = self
+            .get_mut()
+            .0
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner());
+        Pin::new(fut).poll(cx)
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{error::BoxError, IntoResponse};
-use hyper::Response;
+use hyper::{body::Bytes, Response};
 use std::{future::Future, pin::Pin};
 
 pub type ResponseFuture =
-    Pin<Box<dyn Future<Output = Result<Response<String>, BoxError>> + Send + Sync + 'static>>;
+    Pin<Box<dyn Future<Output = Result<Response<Bytes>, BoxError>> + Send + Sync + 'static>>;
 
 /// Trait for [`Future`]s that return a valid response for [`crate::Returning`]
 ///
@@ -17,7 +17,7 @@ pub type ResponseFuture =
 /// let res_fut = fut.into_response_future();
 /// ```
 pub trait IntoResponseFuture {
-    /// Return a [`Future`] that resolves to `Result<Response<String>, BoxError>`
+    /// Return a [`Future`] that resolves to `Result<Response<Bytes>, BoxError>`
     fn into_response_future(self) -> ResponseFuture;
 }
 
@@ -0,0 +1,79 @@
+use hyper::{
+    body::Bytes,
+    header::{CONTENT_ENCODING, CONTENT_LENGTH},
+    http::HeaderValue,
+    Request, Response,
+};
+
+use crate::{
+    codec::encode_gzip,
+    handler::Sealed,
+    response::{ChunkedBody, ResponseFuture},
+    Returning,
+};
+
+/// [`Returning`] implementation that compresses the response body with gzip
+///
+/// This wraps any other [`Returning`] implementation, compresses the body of its response, and
+/// sets the `Content-Encoding: gzip` header.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Gzip};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .returning(Gzip::new((200, "some compressed payload")))?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+///
+/// ## Remark
+///
+/// The body is stored in uncompressed deflate blocks. This produces a valid gzip payload for any
+/// client, but the payload is slightly larger than the original body.
+///
+/// When wrapping a [`crate::Chunked`] response, all chunks are compressed and sent as a single
+/// chunk.
+#[derive(Debug, Clone)]
+pub struct Gzip<R> {
+    inner: R,
+}
+
+impl<R> Gzip<R>
+where
+    R: Returning,
+{
+    /// Create a new [`Gzip`] response from another [`Returning`] implementation
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R> Returning for Gzip<R>
+where
+    R: Returning,
+{
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let fut = self.inner.returning(req);
+
+        Box::pin(async move {
+            let (mut parts, body) = fut.await?.into_parts();
+            parts
+                .headers
+                .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            parts.headers.remove(CONTENT_LENGTH);
+
+            if let Some(chunked) = parts.extensions.get_mut::<ChunkedBody>() {
+                let data = chunked.chunks.concat();
+                chunked.chunks = vec![encode_gzip(&data).into()];
+            }
+
+            Ok(Response::from_parts(parts, encode_gzip(&body).into()))
+        })
+    }
+}
+
+impl<R> Sealed for Gzip<R> where R: Returning {}
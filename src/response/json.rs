@@ -0,0 +1,113 @@
+use hyper::{body::Bytes, header::CONTENT_TYPE, Request, Response, StatusCode};
+use serde::Serialize;
+use std::error::Error as StdError;
+
+use crate::{error::BoxError, handler::Sealed, response::ResponseFuture, IntoResponse, Returning};
+
+/// Response with any [`Serialize`] value as its JSON body
+///
+/// The value is serialized for each request, and the response has a `content-type:
+/// application/json` header. Pair it with a status code to return something other than
+/// `200 OK`.
+///
+/// This can be passed to [`crate::CaseBuilder::returning`], or returned from a closure as an
+/// [`IntoResponse`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::StatusCode;
+/// # use mock_http_connector::{Connector, Error, Json};
+/// #[derive(serde::Serialize)]
+/// struct Item {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder.expect().with_method("GET").returning(Json(Item {
+///     id: 42,
+///     name: "some item".to_string(),
+/// }))?;
+/// builder.expect().with_method("POST").returning((
+///     StatusCode::CREATED,
+///     Json(Item {
+///         id: 43,
+///         name: "new item".to_string(),
+///     }),
+/// ))?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T>
+where
+    T: Serialize,
+{
+    fn response(&self, status: StatusCode) -> Result<Response<Bytes>, BoxError> {
+        Ok(Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&self.0)?.into())?)
+    }
+}
+
+impl<T> Returning for Json<T>
+where
+    T: Serialize + Send + Sync,
+{
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let res = self.response(StatusCode::OK);
+        Box::pin(async { res })
+    }
+}
+
+impl<T> Sealed for Json<T> where T: Serialize + Send + Sync {}
+
+impl<S, T> Returning for (S, Json<T>)
+where
+    S: TryInto<StatusCode> + Clone + Send + Sync,
+    S::Error: StdError + Send + Sync + 'static,
+    T: Serialize + Send + Sync,
+{
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let res = self
+            .0
+            .clone()
+            .try_into()
+            .map_err(Into::into)
+            .and_then(|status| self.1.response(status));
+        Box::pin(async { res })
+    }
+}
+
+impl<S, T> Sealed for (S, Json<T>)
+where
+    S: TryInto<StatusCode> + Clone + Send + Sync,
+    S::Error: StdError + Send + Sync + 'static,
+    T: Serialize + Send + Sync,
+{
+}
+
+impl<T> IntoResponse for Json<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        self.response(StatusCode::OK)
+    }
+}
+
+impl<S, T> IntoResponse for (S, Json<T>)
+where
+    S: TryInto<StatusCode>,
+    S::Error: StdError + Send + Sync + 'static,
+    T: Serialize,
+{
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        self.1.response(self.0.try_into()?)
+    }
+}
@@ -0,0 +1,156 @@
+use hyper::{
+    body::Bytes,
+    http::{HeaderName, HeaderValue},
+    Request, Response, StatusCode,
+};
+use std::time::Duration;
+
+use crate::{clock::SharedClock, handler::Sealed, response::ResponseFuture, Error, Returning};
+
+/// [`Returning`] implementation to build a response step by step
+///
+/// This avoids building [`hyper::Response`] values by hand in tests: start from a status code,
+/// such as [`MockResponse::ok`] or [`MockResponse::not_found`], and add headers, a body, or a
+/// delay.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, MockResponse};
+/// # use std::time::Duration;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder.expect().returning(
+///     MockResponse::ok()
+///         .header("x-request-id", "some-id")?
+///         .body("OK")
+///         .delay(Duration::from_millis(10)),
+/// )?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+///
+/// ## Remark
+///
+/// The delay uses the [`crate::Clock`] of the connector, and applies before the response head is
+/// sent.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: Bytes,
+    delay: Option<Duration>,
+}
+
+impl MockResponse {
+    /// Create a new [`MockResponse`] with the given status code and an empty body
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Bytes::new(),
+            delay: None,
+        }
+    }
+
+    /// Create a new `200 OK` response
+    pub fn ok() -> Self {
+        Self::new(StatusCode::OK)
+    }
+
+    /// Create a new `201 Created` response
+    pub fn created() -> Self {
+        Self::new(StatusCode::CREATED)
+    }
+
+    /// Create a new `204 No Content` response
+    pub fn no_content() -> Self {
+        Self::new(StatusCode::NO_CONTENT)
+    }
+
+    /// Create a new `400 Bad Request` response
+    pub fn bad_request() -> Self {
+        Self::new(StatusCode::BAD_REQUEST)
+    }
+
+    /// Create a new `404 Not Found` response
+    pub fn not_found() -> Self {
+        Self::new(StatusCode::NOT_FOUND)
+    }
+
+    /// Create a new `500 Internal Server Error` response
+    pub fn internal_server_error() -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Add a header to the response
+    ///
+    /// Adding the same header multiple times sends one entry for each value, such as for
+    /// `set-cookie`.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            value.try_into().map_err(Into::into)?,
+        ));
+        Ok(self)
+    }
+
+    /// Set the body of the response
+    pub fn body<B>(mut self, body: B) -> Self
+    where
+        B: Into<Bytes>,
+    {
+        self.body = body.into();
+        self
+    }
+
+    /// Serialize `value` as the JSON body of the response
+    ///
+    /// This also sets the `content-type: application/json` header.
+    ///
+    /// ## Errors
+    ///
+    /// This will fail if `value` cannot be serialized.
+    #[cfg(feature = "json")]
+    pub fn json<V>(self, value: V) -> Result<Self, Error>
+    where
+        V: serde::Serialize,
+    {
+        let body = serde_json::to_vec(&value)?;
+        Ok(self
+            .header(hyper::header::CONTENT_TYPE, "application/json")?
+            .body(body))
+    }
+
+    /// Wait for `delay` before sending the response
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+impl Returning for MockResponse {
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let mut res = Response::builder().status(self.status);
+        for (key, value) in &self.headers {
+            res = res.header(key, value);
+        }
+        let res = res.body(self.body.clone());
+
+        let sleep = self.delay.map(|delay| SharedClock::of(&req).sleep(delay));
+        Box::pin(async move {
+            if let Some(sleep) = sleep {
+                sleep.await;
+            }
+            Ok(res?)
+        })
+    }
+}
+
+impl Sealed for MockResponse {}
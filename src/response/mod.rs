@@ -1,11 +1,41 @@
+mod backoff;
+mod chunked;
+mod forward;
 mod future;
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(feature = "json")]
+mod json;
+mod mock;
+mod rate_limit;
+mod soap;
+mod sse;
+mod upgrade;
+pub use backoff::Backoff;
+pub use chunked::Chunked;
+pub(crate) use chunked::ChunkedBody;
+pub use forward::Forward;
 pub use future::{IntoResponseFuture, ResponseFuture};
+#[cfg(feature = "gzip")]
+pub use gzip::Gzip;
+#[cfg(feature = "json")]
+pub use json::Json;
+pub use mock::MockResponse;
+pub use rate_limit::RateLimited;
+pub use soap::SoapFault;
+pub use sse::{Sse, SseEvent};
+pub use upgrade::Upgrade;
+pub(crate) use upgrade::{OnUpgrade, UpgradedStream};
 
 use crate::error::BoxError;
-use hyper::{Response, StatusCode};
+use hyper::{
+    body::Bytes,
+    http::{HeaderName, HeaderValue},
+    HeaderMap, Response, StatusCode,
+};
 use std::error::Error as StdError;
 
-/// Trait for values that can be transformed into `Result<Response<String>, BoxError>`
+/// Trait for values that can be transformed into `Result<Response<Bytes>, BoxError>`
 ///
 /// All implementations of this trait can be used as the return type for the future passed to
 /// [`crate::CaseBuilder::returning`].
@@ -38,6 +68,34 @@ use std::error::Error as StdError;
 /// let res = (status, body).into_response();
 /// ```
 ///
+/// ### Status, headers, and string-types
+///
+/// Headers can be passed as a [`HeaderMap`], or as a list of header names and values.
+///
+/// ```rust
+/// # use hyper::{header::CONTENT_TYPE, http::HeaderValue, HeaderMap, StatusCode};
+/// # use mock_http_connector::IntoResponse;
+/// let mut headers = HeaderMap::new();
+/// headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+/// let res = (StatusCode::OK, headers, "some body").into_response();
+///
+/// let headers = vec![(CONTENT_TYPE, HeaderValue::from_static("text/plain"))];
+/// let res = (200, headers, "some body").into_response();
+/// ```
+///
+/// ### Binary payloads and empty responses
+///
+/// [`Bytes`] and `Vec<u8>` are used as the response body as-is, with a status code of `200`. The
+/// unit type `()` returns an empty `200` response.
+///
+/// ```rust
+/// # use hyper::body::Bytes;
+/// # use mock_http_connector::IntoResponse;
+/// let res = Bytes::from_static(b"\x00\x01").into_response();
+/// let res = vec![0u8, 1].into_response();
+/// let res = ().into_response();
+/// ```
+///
 #[cfg_attr(
     feature = "json",
     doc = r##"
@@ -54,8 +112,8 @@ let res = payload.into_response();
 "##
 )]
 pub trait IntoResponse {
-    /// Transforms self into a `Result<Response<String>, BoxError>`
-    fn into_response(self) -> Result<Response<String>, BoxError>;
+    /// Transforms self into a `Result<Response<Bytes>, BoxError>`
+    fn into_response(self) -> Result<Response<Bytes>, BoxError>;
 }
 
 impl<R, E> IntoResponse for Result<R, E>
@@ -63,31 +121,33 @@ where
     R: IntoResponse,
     E: StdError + Send + Sync + 'static,
 {
-    fn into_response(self) -> Result<Response<String>, BoxError> {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
         self.map_err(Into::into).and_then(|r| r.into_response())
     }
 }
 
 impl<B> IntoResponse for Response<B>
 where
-    B: ToString,
+    B: Into<Bytes>,
 {
-    fn into_response(self) -> Result<Response<String>, BoxError> {
-        Ok(self.map(|b| b.to_string()))
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        Ok(self.map(Into::into))
     }
 }
 
-impl<'s> IntoResponse for &'s str {
-    fn into_response(self) -> Result<Response<String>, BoxError> {
+impl IntoResponse for &str {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
         Ok(Response::builder()
             .status(StatusCode::OK)
-            .body(self.to_string())?)
+            .body(self.to_string().into())?)
     }
 }
 
 impl IntoResponse for String {
-    fn into_response(self) -> Result<Response<String>, BoxError> {
-        Ok(Response::builder().status(StatusCode::OK).body(self)?)
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(self.into())?)
     }
 }
 
@@ -97,19 +157,67 @@ where
     S::Error: StdError + Send + Sync + 'static,
     B: ToString + 'static,
 {
-    fn into_response(self) -> Result<Response<String>, BoxError> {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
         let status = self.0.try_into();
         let body = self.1.to_string();
-        Ok(Response::builder().status(status?).body(body)?)
+        Ok(Response::builder().status(status?).body(body.into())?)
+    }
+}
+
+impl IntoResponse for Bytes {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        Ok(Response::builder().status(StatusCode::OK).body(self)?)
+    }
+}
+
+impl IntoResponse for Vec<u8> {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(self.into())?)
+    }
+}
+
+impl IntoResponse for () {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(Bytes::new())?)
+    }
+}
+
+impl<S, B> IntoResponse for (S, HeaderMap, B)
+where
+    S: TryInto<StatusCode> + 'static,
+    S::Error: StdError + Send + Sync + 'static,
+    B: ToString + 'static,
+{
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        let mut res = Response::builder().status(self.0.try_into()?);
+        if let Some(headers) = res.headers_mut() {
+            headers.extend(self.1);
+        }
+        Ok(res.body(self.2.to_string().into())?)
+    }
+}
+
+impl<S, B> IntoResponse for (S, Vec<(HeaderName, HeaderValue)>, B)
+where
+    S: TryInto<StatusCode> + 'static,
+    S::Error: StdError + Send + Sync + 'static,
+    B: ToString + 'static,
+{
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        (self.0, self.1.into_iter().collect::<HeaderMap>(), self.2).into_response()
     }
 }
 
 #[cfg(feature = "json")]
 impl IntoResponse for serde_json::Value {
-    fn into_response(self) -> Result<Response<String>, BoxError> {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
         Ok(Response::builder()
             .status(StatusCode::OK)
             .header(hyper::header::CONTENT_TYPE, "application/json")
-            .body(serde_json::to_string(&self)?)?)
+            .body(serde_json::to_string(&self)?.into())?)
     }
 }
@@ -0,0 +1,134 @@
+use hyper::{body::Bytes, header::RETRY_AFTER, Request, StatusCode};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+use crate::{clock::SharedClock, handler::Sealed, response::ResponseFuture, Returning};
+
+/// [`Returning`] implementation that simulates a rate-limited API
+///
+/// Up to `limit` requests per `window` receive the regular response, which is an empty `200 OK`
+/// response by default, or the response set with [`RateLimited::within_limit`]. Further requests
+/// in the same window receive the `over_limit` response, with a `429 Too Many Requests` status
+/// code and a `retry-after` header set to the number of seconds until the next window.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, RateLimited};
+/// # use std::time::Duration;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .returning(
+///         RateLimited::new(10, Duration::from_secs(1), "slow down")
+///             .within_limit((200, "some payload")),
+///     )?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+///
+/// ## Remark
+///
+/// Windows are fixed, and start with the first request after the previous window ended. The
+/// timing uses the [`crate::Clock`] of the connector, which follows `tokio::time::pause` and
+/// `tokio::time::advance` by default.
+///
+/// The `retry-after` header of the `over_limit` response is kept if it sets one.
+#[derive(Debug, Clone)]
+pub struct RateLimited<O, R = u16> {
+    limit: usize,
+    window: Duration,
+    over_limit: O,
+    inner: R,
+    state: Arc<Mutex<Option<Window>>>,
+}
+
+/// Current rate limit window
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    start: Instant,
+    count: usize,
+}
+
+impl<O> RateLimited<O>
+where
+    O: Returning,
+{
+    /// Create a new [`RateLimited`] response, allowing `limit` requests per `window`
+    pub fn new(limit: usize, window: Duration, over_limit: O) -> Self {
+        Self {
+            limit,
+            window,
+            over_limit,
+            inner: StatusCode::OK.as_u16(),
+            state: Arc::default(),
+        }
+    }
+}
+
+impl<O, R> RateLimited<O, R> {
+    /// Set the response for requests within the rate limit
+    pub fn within_limit<I>(self, inner: I) -> RateLimited<O, I>
+    where
+        I: Returning,
+    {
+        RateLimited {
+            limit: self.limit,
+            window: self.window,
+            over_limit: self.over_limit,
+            inner,
+            state: self.state,
+        }
+    }
+
+    /// Count a new request, and return how long until the next window if it is over the limit
+    fn acquire(&self, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+
+        let window = match &mut *state {
+            Some(window) if now < window.start + self.window => window,
+            state => state.insert(Window {
+                start: now,
+                count: 0,
+            }),
+        };
+        window.count += 1;
+
+        (window.count > self.limit).then(|| window.start + self.window - now)
+    }
+}
+
+impl<O, R> Returning for RateLimited<O, R>
+where
+    O: Returning,
+    R: Returning,
+{
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let Some(retry_after) = self.acquire(SharedClock::of(&req).now()) else {
+            return self.inner.returning(req);
+        };
+
+        let fut = self.over_limit.returning(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            *res.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+            if !res.headers().contains_key(RETRY_AFTER) {
+                // Round up, so clients don't retry before the window ends
+                let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                res.headers_mut().insert(RETRY_AFTER, secs.max(1).into());
+            }
+            Ok(res)
+        })
+    }
+}
+
+impl<O, R> Sealed for RateLimited<O, R>
+where
+    O: Returning,
+    R: Returning,
+{
+}
@@ -0,0 +1,97 @@
+use hyper::{body::Bytes, header::CONTENT_TYPE, Request, Response, StatusCode};
+
+use crate::{error::BoxError, handler::Sealed, response::ResponseFuture, IntoResponse, Returning};
+
+/// SOAP 1.1 fault response
+///
+/// This returns a `500 Internal Server Error` response with a SOAP envelope containing a
+/// `soap:Fault` element, as SOAP clients expect for errors.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, SoapFault};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_soap_action("http://example.com/GetStockPrice")
+///     .returning(SoapFault::new("Client", "Unknown stock symbol"))?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoapFault {
+    code: String,
+    string: String,
+}
+
+impl SoapFault {
+    /// Create a new [`SoapFault`] with a fault code and a human-readable description
+    ///
+    /// Codes without a namespace prefix, such as `Client` or `Server`, are qualified with the
+    /// `soap` prefix of the envelope.
+    pub fn new<C, S>(code: C, string: S) -> Self
+    where
+        C: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            code: code.into(),
+            string: string.into(),
+        }
+    }
+
+    fn response(&self) -> Result<Response<Bytes>, BoxError> {
+        let code = if self.code.contains(':') {
+            escape(&self.code)
+        } else {
+            format!("soap:{}", escape(&self.code))
+        };
+        let body = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="utf-8"?>"#,
+                r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">"#,
+                "<soap:Body><soap:Fault>",
+                "<faultcode>{}</faultcode><faultstring>{}</faultstring>",
+                "</soap:Fault></soap:Body></soap:Envelope>",
+            ),
+            code,
+            escape(&self.string),
+        );
+
+        Ok(Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .header(CONTENT_TYPE, "text/xml; charset=utf-8")
+            .body(body.into())?)
+    }
+}
+
+impl Returning for SoapFault {
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let res = self.response();
+        Box::pin(async { res })
+    }
+}
+
+impl Sealed for SoapFault {}
+
+impl IntoResponse for SoapFault {
+    fn into_response(self) -> Result<Response<Bytes>, BoxError> {
+        self.response()
+    }
+}
+
+/// Escape text for an XML element
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
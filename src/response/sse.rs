@@ -0,0 +1,217 @@
+use hyper::{
+    body::Bytes,
+    header::{CACHE_CONTROL, CONTENT_TYPE, TRANSFER_ENCODING},
+    http::{HeaderName, HeaderValue},
+    Request, Response, StatusCode,
+};
+use std::time::Duration;
+
+use crate::{
+    handler::Sealed,
+    response::{ChunkedBody, ResponseFuture},
+    Error, Returning,
+};
+
+/// [`Returning`] implementation that streams Server-Sent Events
+///
+/// The response uses the `text/event-stream` content type, and each event is sent as a separate
+/// chunk, optionally with a delay before each event. By default, the response ends after the last
+/// event, but it can also be kept open with [`Sse::keep_open`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Sse, SseEvent};
+/// # use std::time::Duration;
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder.expect().returning(
+///     Sse::new([
+///         SseEvent::new("first").event("message"),
+///         SseEvent::new("[DONE]"),
+///     ])
+///     .delay(Duration::from_millis(10)),
+/// )?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sse {
+    headers: Vec<(HeaderName, HeaderValue)>,
+    events: Vec<SseEvent>,
+    delay: Option<Duration>,
+    keep_open: bool,
+}
+
+impl Sse {
+    /// Create a new [`Sse`] response with the given events
+    pub fn new<I, E>(events: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<SseEvent>,
+    {
+        Self {
+            headers: Vec::new(),
+            events: events.into_iter().map(Into::into).collect(),
+            delay: None,
+            keep_open: false,
+        }
+    }
+
+    /// Add a header to the response
+    pub fn header<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            value.try_into().map_err(Into::into)?,
+        ));
+        Ok(self)
+    }
+
+    /// Wait for `delay` before sending each event
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Keep the connection open after the last event, instead of ending the response
+    ///
+    /// The client will keep waiting for more events until it drops the response.
+    pub fn keep_open(mut self) -> Self {
+        self.keep_open = true;
+        self
+    }
+}
+
+impl Returning for Sse {
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let mut res = Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "text/event-stream")
+            .header(CACHE_CONTROL, "no-cache")
+            .header(TRANSFER_ENCODING, "chunked");
+        for (key, value) in &self.headers {
+            res = res.header(key, value);
+        }
+        let body = ChunkedBody {
+            chunks: self
+                .events
+                .iter()
+                .map(|event| event.encode().into())
+                .collect(),
+            delay: self.delay,
+            keep_open: self.keep_open,
+            trailers: Vec::new(),
+        };
+        let res = res.extension(body).body(Bytes::new());
+
+        Box::pin(async move { Ok(res?) })
+    }
+}
+
+impl Sealed for Sse {}
+
+/// Single event for an [`Sse`] response
+///
+/// Strings and, with the `json` feature, JSON values can be converted into events that only
+/// contain data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    data: String,
+    event: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Create a new event with the given data
+    ///
+    /// Data containing newlines is sent on multiple `data:` lines.
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            event: None,
+            id: None,
+            retry: None,
+        }
+    }
+
+    /// Set the event type
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Set the event ID
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set the reconnection time for the client
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Encode the event in the `text/event-stream` format
+    fn encode(&self) -> String {
+        let mut data = String::new();
+        if let Some(event) = &self.event {
+            data.push_str(&format!("event: {event}\n"));
+        }
+        if let Some(id) = &self.id {
+            data.push_str(&format!("id: {id}\n"));
+        }
+        if let Some(retry) = &self.retry {
+            data.push_str(&format!("retry: {}\n", retry.as_millis()));
+        }
+        for line in self.data.split('\n') {
+            data.push_str(&format!("data: {line}\n"));
+        }
+        data.push('\n');
+        data
+    }
+}
+
+impl From<&str> for SseEvent {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for SseEvent {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for SseEvent {
+    fn from(value: serde_json::Value) -> Self {
+        Self::new(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use speculoos::prelude::*;
+
+    #[rstest]
+    #[case(SseEvent::new("hello"), "data: hello\n\n")]
+    #[case(SseEvent::new("a\nb"), "data: a\ndata: b\n\n")]
+    #[case(
+        SseEvent::new("hello").event("greeting").id("1").retry(Duration::from_secs(1)),
+        "event: greeting\nid: 1\nretry: 1000\ndata: hello\n\n"
+    )]
+    fn sse_event_encode(#[case] event: SseEvent, #[case] expected: &str) {
+        assert_that!(event.encode()).is_equal_to(expected.to_string());
+    }
+}
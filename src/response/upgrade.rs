@@ -0,0 +1,202 @@
+use hyper::{
+    body::Bytes,
+    header::{CONNECTION, UPGRADE},
+    http::{HeaderName, HeaderValue},
+    Method, Request, Response, StatusCode,
+};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::io::{duplex, AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+
+use crate::{handler::Sealed, response::ResponseFuture, Error, Returning};
+
+/// Size of the in-memory buffer between the client and the upgrade handler
+const UPGRADE_BUFFER_SIZE: usize = 64 * 1024;
+
+type UpgradeFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+type UpgradeHandler = Arc<dyn Fn(DuplexStream) -> UpgradeFuture + Send + Sync + 'static>;
+
+/// [`Returning`] implementation that upgrades the connection and hands the raw byte stream to a
+/// handler
+///
+/// Requests with the `CONNECT` method receive a `200 OK` response, and all other requests receive
+/// a `101 Switching Protocols` response with the `upgrade` header of the request. Once the client
+/// has read the response head, everything it writes to the upgraded connection can be read from
+/// the [`DuplexStream`] passed to the handler, and everything the handler writes is sent back to
+/// the client. The connection is closed when the handler returns.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Upgrade};
+/// # use tokio::io::{AsyncReadExt, AsyncWriteExt};
+/// # || {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_header("upgrade", "echo")
+///     .returning(Upgrade::new(|mut io| async move {
+///         let mut buf = [0; 128];
+///         while let Ok(size @ 1..) = io.read(&mut buf).await {
+///             if io.write_all(&buf[..size]).await.is_err() {
+///                 break;
+///             }
+///         }
+///     }))?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+///
+/// ## Remark
+///
+/// The handler runs as part of the connection, rather than as a separate task, so it only makes
+/// progress while the client reads from or writes to the upgraded connection.
+#[derive(Clone)]
+pub struct Upgrade {
+    status: Option<StatusCode>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    handler: UpgradeHandler,
+}
+
+impl Upgrade {
+    /// Create a new [`Upgrade`] response that passes the upgraded connection to `handler`
+    pub fn new<F, Fut>(handler: F) -> Self
+    where
+        F: Fn(DuplexStream) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            status: None,
+            headers: Vec::new(),
+            handler: Arc::new(move |io| Box::pin(handler(io))),
+        }
+    }
+
+    /// Set the status code of the response
+    ///
+    /// By default, this is `200 OK` for `CONNECT` requests, and `101 Switching Protocols`
+    /// otherwise. The client only upgrades the connection for those status codes.
+    pub fn status<S>(mut self, status: S) -> Result<Self, Error>
+    where
+        S: TryInto<StatusCode>,
+        S::Error: Into<hyper::http::Error>,
+    {
+        self.status = Some(status.try_into().map_err(Into::into)?);
+        Ok(self)
+    }
+
+    /// Add a header to the response
+    pub fn header<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Into<hyper::http::Error>,
+        V: TryInto<HeaderValue>,
+        V::Error: Into<hyper::http::Error>,
+    {
+        self.headers.push((
+            key.try_into().map_err(Into::into)?,
+            value.try_into().map_err(Into::into)?,
+        ));
+        Ok(self)
+    }
+}
+
+impl fmt::Debug for Upgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgrade")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Returning for Upgrade {
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let mut res = if req.method() == Method::CONNECT {
+            Response::builder().status(self.status.unwrap_or(StatusCode::OK))
+        } else {
+            let mut res = Response::builder()
+                .status(self.status.unwrap_or(StatusCode::SWITCHING_PROTOCOLS))
+                .header(CONNECTION, "upgrade");
+            if let Some(protocol) = req.headers().get(UPGRADE) {
+                res = res.header(UPGRADE, protocol);
+            }
+            res
+        };
+        for (key, value) in &self.headers {
+            res = res.header(key, value);
+        }
+        let res = res
+            .extension(OnUpgrade(self.handler.clone()))
+            .body(Bytes::new());
+
+        Box::pin(async move { Ok(res?) })
+    }
+}
+
+impl Sealed for Upgrade {}
+
+/// Handler for the connection once the response head is sent
+///
+/// This is stored in the [`Response`] extensions, like [`crate::response::ChunkedBody`].
+#[derive(Clone)]
+pub(crate) struct OnUpgrade(UpgradeHandler);
+
+impl OnUpgrade {
+    /// Start the handler, and return the client side of the connection
+    pub fn start(self) -> UpgradedStream {
+        let (client, server) = duplex(UPGRADE_BUFFER_SIZE);
+        UpgradedStream {
+            io: client,
+            handler: Some((self.0)(server)),
+        }
+    }
+}
+
+/// Client side of an upgraded connection
+///
+/// Every operation on the stream also drives the handler, until it returns.
+pub(crate) struct UpgradedStream {
+    io: DuplexStream,
+    handler: Option<UpgradeFuture>,
+}
+
+impl UpgradedStream {
+    fn poll_handler(&mut self, cx: &mut Context<'_>) {
+        if let Some(handler) = &mut self.handler {
+            if handler.as_mut().poll(cx).is_ready() {
+                // This also drops the server side of the connection, which closes it
+                self.handler = None;
+            }
+        }
+    }
+
+    pub fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.poll_handler(cx);
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+
+    pub fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        self.poll_handler(cx);
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    pub fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_handler(cx);
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    pub fn poll_shutdown(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_handler(cx);
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
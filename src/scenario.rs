@@ -0,0 +1,64 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// State machine shared by multiple mock cases
+///
+/// Cases attached to a [`Scenario`] through [`crate::CaseBuilder::in_scenario`] can require the
+/// scenario to be in a given state to match a request, with [`crate::CaseBuilder::in_state`], and
+/// move the scenario to a new state when they match one, with
+/// [`crate::CaseBuilder::will_set_state`]. This can be used to model multi-step workflows, where
+/// the same request receives different responses over time.
+///
+/// Cloning a [`Scenario`] returns a handle to the same state machine.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Scenario};
+/// # || {
+/// let job = Scenario::new("created");
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_uri("https://api.example/jobs/1")
+///     .in_scenario(&job)
+///     .in_state("created")
+///     .will_set_state("completed")
+///     .returning(r#"{"status": "running"}"#)?;
+/// builder
+///     .expect()
+///     .with_uri("https://api.example/jobs/1")
+///     .in_scenario(&job)
+///     .in_state("completed")
+///     .returning(r#"{"status": "completed"}"#)?;
+///
+/// assert_eq!(job.state(), "created");
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    state: Arc<Mutex<String>>,
+}
+
+impl Scenario {
+    /// Create a new [`Scenario`] in the `initial` state
+    pub fn new(initial: impl Into<String>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(initial.into())),
+        }
+    }
+
+    /// Returns the current state of the scenario
+    pub fn state(&self) -> String {
+        self.lock().clone()
+    }
+
+    /// Move the scenario to `state`, for example to reset it between tests
+    pub fn set_state(&self, state: impl Into<String>) {
+        *self.lock() = state.into();
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, String> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+/// Ordered expectations across multiple mock cases
+///
+/// Cases added to a [`Sequence`] through [`crate::CaseBuilder::in_sequence`] must be matched in
+/// the order they were added to the sequence. If a request matches a case after a case that comes
+/// later in the sequence has already been matched, [`crate::Connector::checkpoint`] will return an
+/// error.
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::{Connector, Error, Sequence};
+/// # || {
+/// let mut seq = Sequence::new();
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_uri("https://auth.example/token")
+///     .in_sequence(&mut seq)
+///     .returning("token")?;
+/// builder
+///     .expect()
+///     .with_uri("https://api.example/data")
+///     .in_sequence(&mut seq)
+///     .returning("data")?;
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+#[derive(Debug, Default)]
+pub struct Sequence {
+    inner: Arc<SequenceInner>,
+    len: usize,
+}
+
+impl Sequence {
+    /// Create a new empty [`Sequence`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn next_entry(&mut self) -> SequenceEntry {
+        let position = self.len;
+        self.len += 1;
+
+        SequenceEntry {
+            inner: self.inner.clone(),
+            position,
+            violation: Arc::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SequenceInner {
+    /// Highest position matched so far
+    last: AtomicUsize,
+}
+
+/// Position of a mock case within a [`Sequence`]
+#[derive(Debug, Clone)]
+pub(crate) struct SequenceEntry {
+    inner: Arc<SequenceInner>,
+    position: usize,
+    /// First position that was matched before this entry, if this entry was called out of order
+    violation: Arc<Mutex<Option<usize>>>,
+}
+
+impl SequenceEntry {
+    /// Record that the case at this position was matched
+    pub fn record(&self) {
+        let last = self.inner.last.fetch_max(self.position, Ordering::AcqRel);
+
+        if last > self.position {
+            let mut violation = self.violation.lock().unwrap_or_else(|err| err.into_inner());
+            violation.get_or_insert(last);
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the position that was matched before this entry, if it was called out of order
+    pub fn violation(&self) -> Option<usize> {
+        *self.violation.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn sequence_in_order() {
+        let mut seq = Sequence::new();
+        let first = seq.next_entry();
+        let second = seq.next_entry();
+
+        first.record();
+        first.record();
+        second.record();
+
+        assert_that!(first.violation()).is_none();
+        assert_that!(second.violation()).is_none();
+    }
+
+    #[test]
+    fn sequence_out_of_order() {
+        let mut seq = Sequence::new();
+        let first = seq.next_entry();
+        let second = seq.next_entry();
+
+        second.record();
+        first.record();
+
+        assert_that!(first.violation()).is_some().is_equal_to(1);
+        assert_that!(second.violation()).is_none();
+    }
+}
@@ -0,0 +1,167 @@
+#[cfg(feature = "gzip")]
+use hyper::header::CONTENT_ENCODING;
+use hyper::{
+    body::{Buf, Bytes, HttpBody},
+    header::TRANSFER_ENCODING,
+    service::Service,
+    Body, Request, Response,
+};
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "gzip")]
+use crate::connector::decompress;
+use crate::{
+    codec::{RawBody, Trailers},
+    connector::InnerConnector,
+    error::BoxError,
+    response::ChunkedBody,
+    stream::ConnectionOptions,
+    Error,
+};
+
+type ServiceFuture = Pin<Box<dyn Future<Output = Result<Response<Body>, Error>> + Send>>;
+
+/// Mock [`Service`] that answers requests with the mock cases of a [`crate::Connector`]
+///
+/// Incoming requests are matched against the same mock cases, and receive the same responses, as
+/// requests sent through the connector, but without going through HTTP/1.1. This is useful to
+/// mock at the service layer, for example to test [`tower`] middlewares, or with clients that
+/// don't use a [`hyper::client::connect::Connect`] implementation.
+///
+/// [`Service`] is the trait from the `tower-service` crate, so this works with any tower
+/// middleware.
+///
+/// [`tower`]: https://docs.rs/tower
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::{service::Service, Body, Request};
+/// # use mock_http_connector::{Connector, Error};
+/// # async {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_uri("http://test.example/")
+///     .returning("OK")?;
+/// let connector = builder.build();
+///
+/// let mut service = connector.service();
+/// let res = service
+///     .call(Request::get("http://test.example/").body(Body::empty())?)
+///     .await?;
+/// assert_eq!(res.status(), 200);
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
+///
+/// ## Remark
+///
+/// Options that only make sense for a connection are ignored, such as delays between chunks,
+/// connection upgrades, or closing the connection after a response. Responses that would abort
+/// the connection return an [`io::ErrorKind::ConnectionReset`] error instead.
+#[derive(Clone)]
+pub struct MockService {
+    inner: Arc<InnerConnector>,
+}
+
+impl MockService {
+    pub(crate) fn new(inner: Arc<InnerConnector>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<B> Service<Request<B>> for MockService
+where
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = Error;
+    type Future = ServiceFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let req = into_request(req).await?;
+            let res = inner
+                .respond(req)
+                .await
+                .map_err(|err| match err.downcast() {
+                    Ok(err) => *err,
+                    Err(err) => Error::Runtime(err),
+                })?;
+            into_response(res)
+        })
+    }
+}
+
+/// Collect the body of `req`, so it can be passed to the mock cases
+async fn into_request<B>(req: Request<B>) -> Result<Request<Bytes>, Error>
+where
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    let (parts, body) = req.into_parts();
+    let mut body = Box::pin(body);
+
+    let mut data = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let mut chunk = chunk.map_err(Into::into)?;
+        data.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+    }
+    let trailers = body.trailers().await.map_err(Into::into)?;
+
+    let raw = Bytes::from(data);
+    #[cfg(feature = "gzip")]
+    let body = decompress(
+        parts
+            .headers
+            .get(CONTENT_ENCODING)
+            .map(|value| value.as_bytes()),
+        raw.clone(),
+    );
+    #[cfg(not(feature = "gzip"))]
+    let body = raw.clone();
+
+    let mut req = Request::from_parts(parts, body);
+    req.extensions_mut().insert(RawBody(raw));
+    req.extensions_mut()
+        .insert(Trailers(trailers.unwrap_or_default()));
+    Ok(req)
+}
+
+/// Convert the response of a mock case into a [`Response<Body>`]
+fn into_response(mut res: Response<Bytes>) -> Result<Response<Body>, Error> {
+    let options = res
+        .extensions_mut()
+        .remove::<ConnectionOptions>()
+        .unwrap_or_default();
+    if options.reset || options.truncate || options.abort_after.is_some() {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "connection aborted by the mock case",
+        )));
+    }
+
+    // Chunks are only framed on the wire, so they are sent as a single body
+    if let Some(chunked) = res.extensions_mut().remove::<ChunkedBody>() {
+        res.headers_mut().remove(TRANSFER_ENCODING);
+        let body = chunked.chunks.concat();
+        *res.body_mut() = body.into();
+    }
+
+    Ok(res.map(Body::from))
+}
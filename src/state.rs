@@ -0,0 +1,92 @@
+use hyper::{body::Bytes, Request};
+use std::{
+    future::ready,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    handler::{Returning, Sealed},
+    response::ResponseFuture,
+    IntoResponse,
+};
+
+/// Shared mutable state for mock cases
+///
+/// Cloning a [`State`] returns a handle to the same value, so the same state can be used by
+/// multiple cases and inspected by the test afterwards. See [`crate::CaseBuilder::with_state`]
+/// and [`crate::CaseBuilder::returning_state`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use mock_http_connector::State;
+/// let counter = State::new(0);
+/// *counter.lock() += 1;
+///
+/// assert_eq!(counter.get(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct State<T> {
+    inner: Arc<Mutex<T>>,
+}
+
+impl<T> State<T> {
+    /// Create a new [`State`] with an initial value
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(value)),
+        }
+    }
+
+    /// Lock the state for reading or writing
+    ///
+    /// ## Remark
+    ///
+    /// This will ignore poisoning: if a closure panicked while holding the lock, you will still
+    /// get access to the value.
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Return a copy of the current value
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock().clone()
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// [`Returning`] implementation that passes a [`State`] to a closure
+pub(crate) struct ReturningState<T, F> {
+    state: State<T>,
+    f: F,
+}
+
+impl<T, F> ReturningState<T, F> {
+    pub fn new(state: State<T>, f: F) -> Self {
+        Self { state, f }
+    }
+}
+
+impl<T, F, R> Returning for ReturningState<T, F>
+where
+    T: Send,
+    F: Fn(&mut T, Request<Bytes>) -> R + Send + Sync,
+    R: IntoResponse,
+{
+    fn returning(&self, req: Request<Bytes>) -> ResponseFuture {
+        let res = (self.f)(&mut self.state.lock(), req).into_response();
+        Box::pin(ready(res))
+    }
+}
+
+impl<T, F> Sealed for ReturningState<T, F> {}
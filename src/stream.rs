@@ -1,25 +1,88 @@
 use std::{
     cmp::min,
+    collections::VecDeque,
     future::Future,
     io,
     pin::Pin,
     sync::Arc,
     task::{ready, Context, Poll, Waker},
+    time::Duration,
 };
 
 use httparse::{Request, Status};
 use hyper::{
+    body::Bytes,
     client::connect::{Connected, Connection},
-    Response, Uri,
+    header::{HeaderName, CONNECTION, CONTENT_LENGTH, EXPECT, TRANSFER_ENCODING},
+    Response, StatusCode, Uri,
 };
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
-use crate::{connector::InnerConnector, response::ResponseFuture, Error};
+use crate::{
+    case::InFlight,
+    clock::{SharedClock, SleepFuture},
+    codec::{content_length, is_chunked, request_len, ChunkedDecoder},
+    connector::InnerConnector,
+    handler::BodyStreams,
+    response::{ChunkedBody, OnUpgrade, ResponseFuture, UpgradedStream},
+    Error, ExpectContinue, TlsInfo,
+};
+
+/// Number of headers that can be parsed before growing the header storage
+const DEFAULT_HEADER_CAPACITY: usize = 64;
 
+/// Mock connection that answers HTTP/1.1 requests with the mock cases of a [`crate::Connector`]
+///
+/// Requests written to the stream are parsed and matched against the mock cases, and the
+/// responses can be read back from it. This is the transport returned when connecting through the
+/// connector, and [`crate::Connector::stream`] opens one directly, for example to test custom
+/// connection pools.
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::Uri;
+/// # use mock_http_connector::{Connector, Error};
+/// # use tokio::io::{AsyncReadExt, AsyncWriteExt};
+/// # async {
+/// let mut builder = Connector::builder();
+/// builder
+///     .expect()
+///     .with_uri("http://test.example/")
+///     .returning("OK")?;
+/// let connector = builder.build();
+///
+/// let mut stream = connector.stream(Uri::from_static("http://test.example"))?;
+/// stream
+///     .write_all(b"GET / HTTP/1.1\r\nhost: test.example\r\nconnection: close\r\n\r\n")
+///     .await?;
+/// let mut res = String::new();
+/// stream.read_to_string(&mut res).await?;
+/// assert!(res.starts_with("HTTP/1.1 200"));
+/// # Ok::<_, Error>(())
+/// # };
+/// ```
 pub struct MockStream {
     res: ResponseState,
+    /// Data for the current request, followed by any request sent after it on the connection
     req_data: Vec<u8>,
+    /// Length of the current request in `req_data`, once it is complete
+    req_len: Option<usize>,
+    /// The current request asked to close the connection after its response
+    req_close: bool,
+    /// Number of headers that can be parsed, grown for requests with more headers
+    header_capacity: usize,
     waker: Option<Waker>,
+    /// Interim response to send before the final response
+    interim: Vec<u8>,
+    /// How the `expect: 100-continue` header was handled, if the request had one
+    expect_continue: Option<ExpectContinue>,
+    /// Number of responses sent on this connection
+    responses: usize,
+    /// Matchers consuming the payload of the current request, when request bodies are streamed
+    body: Option<StreamingBody>,
+    /// Chunks of the current request received so far, when it uses chunked transfer-encoding
+    chunked: ChunkedDecoder,
 
     uri: Uri,
 
@@ -31,16 +94,169 @@ impl MockStream {
         Self {
             res: ResponseState::New,
             req_data: Vec::new(),
+            req_len: None,
+            req_close: false,
+            header_capacity: DEFAULT_HEADER_CAPACITY,
             waker: None,
+            interim: Vec::new(),
+            expect_continue: None,
+            responses: 0,
+            body: None,
+            chunked: ChunkedDecoder::default(),
             uri,
             connector,
         }
     }
 }
 
+impl MockStream {
+    /// Match the current request against the mock cases, once it is complete
+    ///
+    /// This is called each time the client sends more data, until the request head and payload
+    /// are fully received, based on the `content-length` or `transfer-encoding` headers.
+    fn parse_request(&mut self) -> io::Result<()> {
+        let mut headers = vec![httparse::EMPTY_HEADER; self.header_capacity];
+        let mut req = Request::new(&mut headers);
+
+        let status = match req.parse(&self.req_data) {
+            Ok(status) => status,
+            // Grow the header storage until the whole head fits
+            Err(httparse::Error::TooManyHeaders) => {
+                self.header_capacity *= 2;
+                return self.parse_request();
+            }
+            Err(err) => return Err(into_connect_error(err.into())),
+        };
+
+        // Wait for the rest of the request head
+        let Status::Complete(head_len) = status else {
+            return Ok(());
+        };
+
+        let header = |name: &HeaderName, value: &[u8]| {
+            req.headers.iter().any(|header| {
+                header.name.eq_ignore_ascii_case(name.as_str())
+                    && header.value.eq_ignore_ascii_case(value)
+            })
+        };
+        // HTTP/1.0 connections are closed after each response, unless the client asks otherwise
+        self.req_close = header(&CONNECTION, b"close")
+            || (req.version == Some(0) && !header(&CONNECTION, b"keep-alive"));
+
+        // Answer the expectation as soon as the head is received, as the client might wait for it
+        // before sending the payload
+        if self.expect_continue.is_none() && header(&EXPECT, b"100-continue") {
+            let expect_continue = self.connector.expect_continue;
+            self.expect_continue = Some(expect_continue);
+            match expect_continue {
+                ExpectContinue::Continue => {
+                    self.interim = b"HTTP/1.1 100 Continue\r\n\r\n".to_vec();
+                }
+                ExpectContinue::Reject(status) => {
+                    self.res = ResponseState::Fut(reject(status));
+                    return Ok(());
+                }
+            }
+        }
+
+        if self.connector.stream_bodies && !is_chunked(req.headers) {
+            // Pass the payload to the matchers as it arrives, and discard it
+            let body = self.body.get_or_insert_with(|| StreamingBody {
+                streams: self.connector.body_streams(self.uri.host()),
+                remaining: content_length(req.headers).unwrap_or(0),
+            });
+            let size = min(body.remaining, self.req_data.len() - head_len);
+            if size > 0 {
+                body.streams
+                    .update(&self.req_data[head_len..head_len + size]);
+                body.remaining -= size;
+                self.req_data.drain(head_len..head_len + size);
+                return self.parse_request();
+            }
+            self.req_len = (body.remaining == 0).then_some(head_len);
+        } else {
+            self.req_len = request_len(req.headers, head_len, &self.req_data, &mut self.chunked)
+                .map_err(into_connect_error)?;
+        }
+
+        // Wait for the rest of the payload
+        let Some(req_len) = self.req_len else {
+            return Ok(());
+        };
+
+        let streamed = self.body.take().map(|body| body.streams.finish());
+        // Hand over the chunks decoded while waiting for the payload, instead of decoding it again
+        let chunks = is_chunked(req.headers).then(|| std::mem::take(&mut self.chunked).finish());
+        self.res = ResponseState::Fut(
+            self.connector
+                .matches(
+                    req,
+                    &self.req_data[head_len..req_len],
+                    chunks,
+                    streamed,
+                    &self.uri,
+                )
+                .map_err(into_connect_error)?,
+        );
+
+        Ok(())
+    }
+
+    /// Move on to the next request, once the response for the current one is sent
+    ///
+    /// This returns `false` if the connection should be closed instead.
+    fn next_request(&mut self) -> io::Result<bool> {
+        match &self.res {
+            ResponseState::Data(data) if data.is_done() && !data.close => (),
+            _ => return Ok(false),
+        }
+
+        match self.req_len.take() {
+            Some(len) => drop(self.req_data.drain(..len)),
+            // The response was sent before the end of the request, so the rest of the data
+            // can't be told apart from the next request
+            None => self.req_data.clear(),
+        }
+        self.res = ResponseState::New;
+        self.req_close = false;
+        self.interim.clear();
+        self.expect_continue = None;
+        self.body = None;
+        self.chunked = ChunkedDecoder::default();
+
+        // The client already sent the next request
+        if !self.req_data.is_empty() {
+            self.parse_request()?;
+        }
+
+        Ok(true)
+    }
+
+    /// Return the upgraded connection, once the response head for an upgrade was sent
+    fn upgraded(&mut self) -> Option<&mut UpgradedStream> {
+        if let ResponseState::Data(data) = &mut self.res {
+            if let Some(upgrade) = data.take_upgrade() {
+                self.res = ResponseState::Upgraded(upgrade.start());
+            }
+        }
+
+        match &mut self.res {
+            ResponseState::Upgraded(upgraded) => Some(upgraded),
+            _ => None,
+        }
+    }
+}
+
 impl Connection for MockStream {
     fn connected(&self) -> Connected {
-        Connected::new()
+        let connected = match (self.uri.scheme_str(), self.uri.host()) {
+            (Some("https"), Some(host)) => Connected::new().extra(TlsInfo::new(host.to_string())),
+            _ => Connected::new(),
+        };
+        self.connector
+            .on_connect
+            .iter()
+            .fold(connected, |connected, hook| hook(&self.uri, connected))
     }
 }
 
@@ -50,24 +266,48 @@ impl AsyncRead for MockStream {
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
-        let (data, mut pos) = match &mut self.res {
-            ResponseState::New => {
-                self.waker = Some(cx.waker().clone());
-                return Poll::Pending;
-            }
-            ResponseState::Fut(fut) => {
-                let res = ready!(Pin::new(fut).poll(cx))
-                    .map_err(|err| into_connect_error(Error::Runtime(err)))?;
-                (into_data(res)?, 0)
-            }
-            ResponseState::Data(data, pos) => (data.clone(), *pos),
-        };
-
-        let size = min(buf.remaining(), data.len() - pos);
-        buf.put_slice(&data[pos..pos + size]);
-        pos += size;
+        if !self.interim.is_empty() {
+            let size = min(buf.remaining(), self.interim.len());
+            buf.put_slice(&self.interim[..size]);
+            self.interim.drain(..size);
+            return Poll::Ready(Ok(()));
+        }
 
-        self.res = ResponseState::Data(data, pos);
+        loop {
+            match &mut self.res {
+                ResponseState::New => {
+                    self.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+                ResponseState::Fut(fut) => {
+                    let res = ready!(Pin::new(fut).poll(cx))
+                        .map_err(|err| into_connect_error(Error::Runtime(err)))?;
+                    self.responses += 1;
+                    let close = self.req_close
+                        || self
+                            .connector
+                            .max_requests
+                            .is_some_and(|max| self.responses >= max);
+                    self.res = ResponseState::Data(into_data(res, close, &self.connector.clock));
+                }
+                ResponseState::Data(data) => {
+                    if let Some(upgrade) = data.take_upgrade() {
+                        self.res = ResponseState::Upgraded(upgrade.start());
+                        continue;
+                    }
+                    if data.is_done() {
+                        if self.next_request()? {
+                            continue;
+                        }
+                        // The connection is closed
+                        break;
+                    }
+                    ready!(data.poll_read(cx, buf))?;
+                    break;
+                }
+                ResponseState::Upgraded(upgraded) => return upgraded.poll_read(cx, buf),
+            }
+        }
 
         self.waker = Some(cx.waker().clone());
 
@@ -76,39 +316,49 @@ impl AsyncRead for MockStream {
 }
 
 impl AsyncWrite for MockStream {
-    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
-        Poll::Ready(Ok(()))
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        match self.upgraded() {
+            Some(upgraded) => upgraded.poll_flush(cx),
+            None => Poll::Ready(Ok(())),
+        }
     }
 
-    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
-        Poll::Ready(Ok(()))
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), io::Error>> {
+        match self.upgraded() {
+            Some(upgraded) => upgraded.poll_shutdown(cx),
+            None => Poll::Ready(Ok(())),
+        }
     }
 
     fn poll_write(
         mut self: Pin<&mut Self>,
-        _cx: &mut Context<'_>,
+        cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<Result<usize, io::Error>> {
-        let mut headers = [httparse::EMPTY_HEADER; 64];
-        let mut req = Request::new(&mut headers);
-        self.req_data.extend(buf);
+        // Once the connection is upgraded, the client sends raw bytes instead of requests
+        if let Some(upgraded) = self.upgraded() {
+            return upgraded.poll_write(cx, buf);
+        }
 
-        let status = req
-            .parse(&self.req_data)
-            .map_err(|err| into_connect_error(err.into()))?;
+        // The request was already rejected, so the payload is discarded
+        if let Some(ExpectContinue::Reject(_)) = self.expect_continue {
+            return Poll::Ready(Ok(buf.len()));
+        }
 
-        let body = match status {
-            Status::Complete(body_pos) => &self.req_data[body_pos..],
-            Status::Partial => &[],
-        };
+        let this = self.get_mut();
+        // The response for the previous request was sent, so this is a new request
+        this.next_request()?;
 
-        self.res = ResponseState::Fut(
-            self.connector
-                .matches(req, body, &self.uri)
-                .map_err(into_connect_error)?,
-        );
+        // Data sent while a response is pending is kept for the next request
+        this.req_data.extend(buf);
+        if matches!(this.res, ResponseState::New) {
+            this.parse_request()?;
+        }
 
-        if let Some(w) = self.waker.take() {
+        if let Some(w) = this.waker.take() {
             w.wake()
         }
 
@@ -116,36 +366,202 @@ impl AsyncWrite for MockStream {
     }
 }
 
+/// Payload of a request, while it is streamed to the matchers
+struct StreamingBody {
+    streams: BodyStreams,
+    /// Number of bytes left to receive
+    remaining: usize,
+}
+
 #[derive(Default)]
 enum ResponseState {
     #[default]
     New,
     Fut(ResponseFuture),
-    Data(Vec<u8>, usize),
+    Data(ResponseData),
+    Upgraded(UpgradedStream),
 }
 
-fn into_data(res: Response<String>) -> Result<Vec<u8>, io::Error> {
-    let mut data = String::new();
+/// Response data to send back to the client
+///
+/// Payloads are stored as [`Bytes`] and consumed in place, so reads don't copy the remaining
+/// data.
+struct ResponseData {
+    /// Data left to send from the current segment
+    data: Bytes,
+    /// Segments to send once `data` is consumed, with an optional delay before each of them
+    segments: VecDeque<(Option<Duration>, Bytes)>,
+    sleep: Option<SleepFuture>,
+    /// Clock for the delays between segments
+    clock: SharedClock,
+    /// Never signal the end of the data, to keep the connection open
+    keep_open: bool,
+    /// Handler for the connection once all the data is sent
+    upgrade: Option<OnUpgrade>,
+    /// Number of bytes sent so far, across `data` and `segments`
+    sent: usize,
+    /// Abort the connection once this many bytes are sent
+    abort_at: Option<usize>,
+    /// Close the connection after the response
+    close: bool,
+    /// Request tracked by a mock case with a concurrency limit, until the data is sent
+    in_flight: Option<InFlight>,
+}
+
+impl ResponseData {
+    /// Returns `true` if all the data was sent
+    fn is_sent(&self) -> bool {
+        self.sleep.is_none() && self.data.is_empty() && self.segments.is_empty()
+    }
+
+    /// Returns `true` if the response is complete, and the connection can be used for another
+    /// request or closed
+    fn is_done(&self) -> bool {
+        self.is_sent() && !self.keep_open && self.upgrade.is_none()
+    }
+
+    /// Return the upgrade handler if the response is fully sent
+    fn take_upgrade(&mut self) -> Option<OnUpgrade> {
+        self.is_sent().then(|| self.upgrade.take()).flatten()
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        loop {
+            if let Some(sleep) = &mut self.sleep {
+                ready!(sleep.as_mut().poll(cx));
+                self.sleep = None;
+            }
+
+            if !self.data.is_empty() {
+                let mut size = min(buf.remaining(), self.data.len());
+                if let Some(abort_at) = self.abort_at {
+                    if self.sent >= abort_at {
+                        return Poll::Ready(Err(io::Error::new(
+                            io::ErrorKind::ConnectionReset,
+                            "connection aborted by the mock case",
+                        )));
+                    }
+                    size = min(size, abort_at - self.sent);
+                }
+                buf.put_slice(&self.data.split_to(size));
+                self.sent += size;
+                if self.is_sent() {
+                    self.in_flight = None;
+                }
+                return Poll::Ready(Ok(()));
+            }
+
+            match self.segments.pop_front() {
+                Some((delay, data)) => {
+                    self.data = data;
+                    self.sleep = delay.map(|delay| self.clock.sleep(delay));
+                }
+                // The connection stays open until the client drops it
+                None if self.keep_open => return Poll::Pending,
+                // Nothing else to send
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+/// Connection options for a response, set by the mock case that returned it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ConnectionOptions {
+    /// Send `connection: close` with the response
+    pub close: bool,
+    /// Abort the connection after sending this many bytes of the response body
+    pub abort_after: Option<usize>,
+    /// Abort the connection before sending the response
+    pub reset: bool,
+    /// Abort the connection after sending half of the response body
+    pub truncate: bool,
+}
+
+fn into_data(mut res: Response<Bytes>, close: bool, clock: &SharedClock) -> ResponseData {
+    let options = res
+        .extensions_mut()
+        .remove::<ConnectionOptions>()
+        .unwrap_or_default();
+    let body = res.extensions_mut().remove::<ChunkedBody>();
+    let keep_open = body.as_ref().is_some_and(|body| body.keep_open);
+    let mut segments: VecDeque<_> = body
+        .map(|body| {
+            body.encode()
+                .map(|(delay, data)| (delay, Bytes::from(data)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let upgrade = res.extensions_mut().remove::<OnUpgrade>();
+    let in_flight = res.extensions_mut().remove::<InFlight>();
+
+    let mut data = Vec::new();
     let status = res.status();
-    data.push_str(&format!(
-        "HTTP/1.1 {} {}\r\n",
-        status.as_u16(),
-        status.as_str()
-    ));
+    data.extend(format!("HTTP/1.1 {} {}\r\n", status.as_u16(), status.as_str()).as_bytes());
 
     for (name, value) in res.headers() {
-        data.push_str(&format!(
-            "{name}: {}\r\n",
-            value
-                .to_str()
-                .map_err(|err| io::Error::new(io::ErrorKind::BrokenPipe, err))?
-        ));
+        data.extend(name.as_str().as_bytes());
+        data.extend(b": ");
+        data.extend(value.as_bytes());
+        data.extend(b"\r\n");
+    }
+
+    // Add framing information if the case didn't set any, so clients know where the body ends.
+    // Upgraded connections don't have a body.
+    if upgrade.is_none()
+        && !res.headers().contains_key(CONTENT_LENGTH)
+        && !res.headers().contains_key(TRANSFER_ENCODING)
+    {
+        data.extend(format!("{CONTENT_LENGTH}: {}\r\n", res.body().len()).as_bytes());
+    }
+
+    let close = close
+        || options.close
+        || res
+            .headers()
+            .get(CONNECTION)
+            .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"close"));
+    if close && !res.headers().contains_key(CONNECTION) {
+        data.extend(format!("{CONNECTION}: close\r\n").as_bytes());
     }
 
-    data.push_str("\r\n");
-    data.push_str(res.body());
+    data.extend(b"\r\n");
+    let abort_at = if options.reset {
+        Some(0)
+    } else if options.truncate {
+        Some(data.len() + res.body().len() / 2)
+    } else {
+        options.abort_after.map(|size| data.len() + size)
+    };
+
+    // The body is sent right after the head, without copying it
+    let body = res.into_body();
+    if !body.is_empty() {
+        segments.push_front((None, body));
+    }
+
+    ResponseData {
+        data: data.into(),
+        segments,
+        sleep: None,
+        clock: clock.clone(),
+        keep_open,
+        upgrade,
+        sent: 0,
+        abort_at,
+        close,
+        in_flight,
+    }
+}
+
+/// Final response for requests rejected before they send their payload
+fn reject(status: StatusCode) -> ResponseFuture {
+    let res = Response::builder()
+        .status(status)
+        .header(CONNECTION, "close")
+        .body(Bytes::new());
 
-    Ok(data.into_bytes())
+    Box::pin(async move { Ok(res?) })
 }
 
 fn into_connect_error(err: Error) -> io::Error {
@@ -0,0 +1,73 @@
+/// Simulated TLS behavior for `https` URIs
+///
+/// The connector never performs a real TLS handshake: with [`Tls::Accept`], requests to `https`
+/// URIs behave like any other request, and responses carry a [`TlsInfo`] extension. With
+/// [`Tls::Fail`], the connection fails as if the server certificate was rejected.
+///
+/// ## Default
+///
+/// [`Tls`] implements [`Default`], which will return `Tls::Accept`.
+///
+/// ```rust
+/// use mock_http_connector::Tls;
+///
+/// assert_eq!(Tls::default(), Tls::Accept);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Tls {
+    /// Accept connections to `https` URIs
+    #[default]
+    Accept,
+    /// Fail connections to `https` URIs with the given certificate error message
+    ///
+    /// This only affects `https` URIs: requests to `http` URIs still go through.
+    Fail(String),
+}
+
+/// Information about the simulated TLS session for `https` URIs
+///
+/// This is added to the extensions of responses for requests sent to `https` URIs, so code that
+/// inspects the connection can be tested.
+///
+/// ## Example
+///
+/// ```rust
+/// # use hyper::{Body, Request};
+/// # use mock_http_connector::{Connector, Error, TlsInfo};
+/// # tokio_test::block_on(async move {
+/// let mut builder = Connector::builder();
+/// builder.expect().returning("OK")?;
+/// let connector = builder.build();
+/// let client = hyper::Client::builder().build::<_, Body>(connector);
+///
+/// let res = client
+///     .request(Request::builder().uri("https://test.example/").body(Body::empty())?)
+///     .await?;
+///
+/// let tls = res.extensions().get::<TlsInfo>().unwrap();
+/// assert_eq!(tls.server_name(), "test.example");
+/// # Ok::<_, Error>(())
+/// # });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsInfo {
+    server_name: String,
+}
+
+impl TlsInfo {
+    pub(crate) fn new(server_name: String) -> Self {
+        Self { server_name }
+    }
+
+    /// Server name sent by the client, based on the host of the URI
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    /// Protocol negotiated through ALPN
+    ///
+    /// This is always `http/1.1`, as the connector doesn't support other protocols.
+    pub fn alpn_protocol(&self) -> &[u8] {
+        b"http/1.1"
+    }
+}
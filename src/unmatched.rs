@@ -0,0 +1,31 @@
+use hyper::StatusCode;
+
+/// Behavior of the connector when an incoming request doesn't match any mock case
+///
+/// ## Default
+///
+/// [`OnUnmatched`] implements [`Default`], which will return `OnUnmatched::Error`.
+///
+/// ```rust
+/// use mock_http_connector::OnUnmatched;
+///
+/// assert_eq!(OnUnmatched::default(), OnUnmatched::Error);
+/// ```
+///
+/// ## Remark
+///
+/// A fallback set with [`crate::Builder::fallback`] takes precedence over this behavior.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnUnmatched {
+    /// Return a connection error to the client
+    #[default]
+    Error,
+    /// Return an empty response with the given status code
+    Status(StatusCode),
+    /// Panic with the diagnostic report as the panic message
+    ///
+    /// The request fails with [`crate::Error::Unmatched`], which contains the diagnostic report,
+    /// and the test panics on the next call to [`crate::Connector::checkpoint`], or when the last
+    /// clone of the connector is dropped. See [`crate::Level::Strict`].
+    Panic,
+}
@@ -0,0 +1,384 @@
+use hyper::{
+    body::Bytes,
+    http::{HeaderName, HeaderValue},
+    Request, Response, StatusCode,
+};
+use serde_json::Value;
+use std::{convert::Infallible, fs, path::Path};
+
+use crate::{
+    handler::{JsonEq, Returning, Sealed},
+    response::ResponseFuture,
+    Error, UriParts, WithHandler,
+};
+
+/// Mock case parsed from a WireMock stub mapping
+pub(crate) struct Stub {
+    pub name: Option<String>,
+    pub priority: Option<i32>,
+    pub with: WithHandler,
+    pub response: StubResponse,
+}
+
+/// Response definition from a WireMock stub mapping
+#[derive(Debug, Clone)]
+pub(crate) struct StubResponse {
+    status: StatusCode,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: Bytes,
+}
+
+impl Returning for StubResponse {
+    fn returning(&self, _req: Request<Bytes>) -> ResponseFuture {
+        let mut res = Response::builder().status(self.status);
+        for (key, value) in &self.headers {
+            res = res.header(key, value);
+        }
+        let res = res.body(self.body.clone());
+
+        Box::pin(async move { Ok(res?) })
+    }
+}
+
+impl Sealed for StubResponse {}
+
+/// Load all stub mappings from a file, or from all the `.json` files in a directory
+///
+/// Files are loaded in alphabetical order, so cases with the same priority are registered in a
+/// predictable order.
+pub(crate) fn load_stubs(path: &Path) -> Result<Vec<Stub>, Error> {
+    let mut files = if path.is_dir() {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+            })
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    files.sort();
+
+    let mut stubs = Vec::new();
+    for file in files {
+        let value: Value = serde_json::from_str(&fs::read_to_string(&file)?)?;
+        stubs.extend(parse_mappings(&value)?);
+    }
+
+    Ok(stubs)
+}
+
+/// Parse either a single stub mapping, or an object with a `mappings` array
+pub(crate) fn parse_mappings(value: &Value) -> Result<Vec<Stub>, Error> {
+    match value.get("mappings") {
+        Some(mappings) => mappings
+            .as_array()
+            .ok_or_else(|| invalid("`mappings` must be an array"))?
+            .iter()
+            .map(parse_stub)
+            .collect(),
+        None => Ok(vec![parse_stub(value)?]),
+    }
+}
+
+fn parse_stub(value: &Value) -> Result<Stub, Error> {
+    let request = value
+        .get("request")
+        .ok_or_else(|| invalid("missing `request`"))?;
+    let response = value
+        .get("response")
+        .ok_or_else(|| invalid("missing `response`"))?;
+
+    Ok(Stub {
+        name: value.get("name").and_then(Value::as_str).map(Into::into),
+        // WireMock uses 1 for the highest priority
+        priority: value
+            .get("priority")
+            .and_then(Value::as_i64)
+            .and_then(|priority| i32::try_from(priority).ok())
+            .map(|priority| -priority),
+        with: parse_request(request)?,
+        response: parse_response(response)?,
+    })
+}
+
+fn parse_request(value: &Value) -> Result<WithHandler, Error> {
+    let mut with = WithHandler::default();
+
+    if let Some(method) = value.get("method").and_then(Value::as_str) {
+        if method != "ANY" {
+            with = with.with_method(method)?;
+        }
+    }
+
+    if let Some(url) = value.get("url").and_then(Value::as_str) {
+        let url = url.to_string();
+        with = with.and_with(move |req: &Request<Bytes>| {
+            let path = req.uri().path_and_query().map(|pq| pq.as_str());
+            Ok::<_, Infallible>(path == Some(url.as_str()))
+        });
+    }
+    if let Some(path) = value.get("urlPath").and_then(Value::as_str) {
+        with = with.with_uri_parts(UriParts::new().path(path));
+    }
+    for key in ["urlPattern", "urlPathPattern"] {
+        if value.get(key).is_some() {
+            return Err(unsupported(key));
+        }
+    }
+
+    if let Some(params) = value.get("queryParameters") {
+        for (name, pattern) in as_object(params, "queryParameters")? {
+            let name = name.clone();
+            let check = parse_pattern(pattern)?;
+            with = with.and_with(move |req: &Request<Bytes>| {
+                let values = req
+                    .uri()
+                    .query()
+                    .unwrap_or_default()
+                    .split('&')
+                    .filter_map(|pair| {
+                        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                        (key == name).then_some(value)
+                    })
+                    .collect::<Vec<_>>();
+                Ok::<_, Infallible>(check.matches(&values))
+            });
+        }
+    }
+
+    if let Some(headers) = value.get("headers") {
+        for (name, pattern) in as_object(headers, "headers")? {
+            with = match parse_pattern(pattern)? {
+                Pattern::Absent => with.with_header_missing(name.as_str())?,
+                Pattern::EqualTo(expected) => with.with_header(name.as_str(), expected)?,
+                check => {
+                    with.with_header_fn(name.as_str(), move |value| check.matches(&[value]))?
+                }
+            };
+        }
+    }
+
+    let mut has_body = false;
+    for pattern in value
+        .get("bodyPatterns")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if let Some(expected) = pattern.get("equalToJson") {
+            // The expected payload can be either a JSON value, or a string containing JSON
+            let expected: Value = match expected {
+                Value::String(expected) => serde_json::from_str(expected)?,
+                expected => expected.clone(),
+            };
+            if std::mem::replace(&mut has_body, true) {
+                return Err(invalid("only one exact body pattern is supported"));
+            }
+            // Other stubs can receive payloads that are not JSON, so those are mismatches instead
+            // of errors
+            let partial = pattern.get("ignoreExtraElements").and_then(Value::as_bool) == Some(true);
+            with = with.and_with(move |req: &Request<Bytes>| {
+                let found = serde_json::from_slice::<Value>(req.body())
+                    .ok()
+                    .is_some_and(|payload| match partial {
+                        true => expected.json_eq(&payload),
+                        false => expected == payload,
+                    });
+                Ok::<_, Infallible>(found)
+            });
+        } else if let Some(expected) = pattern.get("equalTo").and_then(Value::as_str) {
+            if std::mem::replace(&mut has_body, true) {
+                return Err(invalid("only one exact body pattern is supported"));
+            }
+            with = with.with_body(expected);
+        } else if let Some(expected) = pattern.get("contains").and_then(Value::as_str) {
+            let expected = expected.to_string();
+            with = with.and_with(move |req: &Request<Bytes>| {
+                Ok::<_, Infallible>(String::from_utf8_lossy(req.body()).contains(&expected))
+            });
+        } else {
+            return Err(unsupported(&format!("body pattern {pattern}")));
+        }
+    }
+
+    Ok(with)
+}
+
+fn parse_response(value: &Value) -> Result<StubResponse, Error> {
+    let status = value
+        .get("status")
+        .and_then(Value::as_u64)
+        .and_then(|status| u16::try_from(status).ok())
+        .unwrap_or(200);
+    let status = StatusCode::from_u16(status).map_err(hyper::http::Error::from)?;
+
+    let mut headers = Vec::new();
+    if let Some(map) = value.get("headers") {
+        for (name, values) in as_object(map, "response headers")? {
+            let values = match values {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            for value in values {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| invalid("header values must be strings"))?;
+                headers.push((
+                    HeaderName::try_from(name.as_str()).map_err(hyper::http::Error::from)?,
+                    HeaderValue::try_from(value).map_err(hyper::http::Error::from)?,
+                ));
+            }
+        }
+    }
+
+    let body = match (value.get("body"), value.get("jsonBody")) {
+        (Some(Value::String(body)), _) => Bytes::from(body.clone()),
+        (_, Some(body)) => Bytes::from(body.to_string()),
+        (None, None) if value.get("base64Body").is_some() => return Err(unsupported("base64Body")),
+        _ => Bytes::new(),
+    };
+
+    Ok(StubResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Value pattern for headers and query parameters
+enum Pattern {
+    EqualTo(String),
+    EqualToIgnoreCase(String),
+    Contains(String),
+    Absent,
+}
+
+impl Pattern {
+    fn matches(&self, values: &[&str]) -> bool {
+        match self {
+            Self::EqualTo(expected) => values.iter().any(|value| value == expected),
+            Self::EqualToIgnoreCase(expected) => values
+                .iter()
+                .any(|value| value.eq_ignore_ascii_case(expected)),
+            Self::Contains(expected) => values.iter().any(|value| value.contains(expected)),
+            Self::Absent => values.is_empty(),
+        }
+    }
+}
+
+fn parse_pattern(value: &Value) -> Result<Pattern, Error> {
+    let ignore_case = value.get("caseInsensitive").and_then(Value::as_bool) == Some(true);
+
+    if let Some(expected) = value.get("equalTo").and_then(Value::as_str) {
+        Ok(match ignore_case {
+            true => Pattern::EqualToIgnoreCase(expected.to_string()),
+            false => Pattern::EqualTo(expected.to_string()),
+        })
+    } else if let Some(expected) = value.get("contains").and_then(Value::as_str) {
+        Ok(Pattern::Contains(expected.to_string()))
+    } else if value.get("absent").and_then(Value::as_bool) == Some(true) {
+        Ok(Pattern::Absent)
+    } else {
+        Err(unsupported(&format!("pattern {value}")))
+    }
+}
+
+fn as_object<'v>(
+    value: &'v Value,
+    name: &str,
+) -> Result<&'v serde_json::Map<String, Value>, Error> {
+    value
+        .as_object()
+        .ok_or_else(|| invalid(&format!("`{name}` must be an object")))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::WireMock(message.to_string())
+}
+
+fn unsupported(feature: &str) -> Error {
+    Error::WireMock(format!("unsupported {feature}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Report, With};
+    use rstest::*;
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    fn request(uri: &str, header: Option<&str>, body: &str) -> Request<Bytes> {
+        let mut req = Request::builder().method("POST").uri(uri);
+        if let Some(header) = header {
+            req = req.header("content-type", header);
+        }
+        req.body(Bytes::copy_from_slice(body.as_bytes())).unwrap()
+    }
+
+    #[rstest]
+    #[case(json!({"method": "POST", "url": "/items?id=1"}), request("http://test.example/items?id=1", None, ""), true)]
+    #[case(json!({"method": "GET", "url": "/items?id=1"}), request("http://test.example/items?id=1", None, ""), false)]
+    #[case(json!({"method": "ANY", "urlPath": "/items"}), request("http://test.example/items?id=1", None, ""), true)]
+    #[case(json!({"url": "/items"}), request("http://test.example/items?id=1", None, ""), false)]
+    #[case(json!({"queryParameters": {"id": {"equalTo": "1"}}}), request("http://test.example/items?id=1", None, ""), true)]
+    #[case(json!({"queryParameters": {"id": {"absent": true}}}), request("http://test.example/items?id=1", None, ""), false)]
+    #[case(json!({"headers": {"Content-Type": {"equalTo": "text/plain"}}}), request("http://test.example/", Some("text/plain"), ""), true)]
+    #[case(json!({"headers": {"Content-Type": {"equalTo": "TEXT/PLAIN", "caseInsensitive": true}}}), request("http://test.example/", Some("text/plain"), ""), true)]
+    #[case(json!({"headers": {"Content-Type": {"contains": "json"}}}), request("http://test.example/", Some("text/plain"), ""), false)]
+    #[case(json!({"headers": {"Content-Type": {"absent": true}}}), request("http://test.example/", None, ""), true)]
+    #[case(json!({"bodyPatterns": [{"equalToJson": {"id": 1}}]}), request("http://test.example/", None, r#"{"id": 1}"#), true)]
+    #[case(json!({"bodyPatterns": [{"equalToJson": "{\"id\": 1}"}]}), request("http://test.example/", None, r#"{"id": 1, "name": "a"}"#), false)]
+    #[case(json!({"bodyPatterns": [{"equalToJson": {"id": 1}, "ignoreExtraElements": true}]}), request("http://test.example/", None, r#"{"id": 1, "name": "a"}"#), true)]
+    #[case(json!({"bodyPatterns": [{"equalTo": "hello"}]}), request("http://test.example/", None, "hello"), true)]
+    #[case(json!({"bodyPatterns": [{"contains": "ell"}]}), request("http://test.example/", None, "hello"), true)]
+    fn test_parse_request(
+        #[case] value: Value,
+        #[case] req: Request<Bytes>,
+        #[case] expected: bool,
+    ) {
+        let with = parse_request(&value).unwrap();
+
+        assert_that!(with.with(&req).map(|r| matches!(r, Report::Match)))
+            .is_ok()
+            .is_equal_to(expected);
+    }
+
+    #[rstest]
+    #[case(json!({"urlPattern": "/items/.*"}))]
+    #[case(json!({"headers": {"Accept": {"matches": ".*"}}}))]
+    #[case(json!({"bodyPatterns": [{"matchesJsonPath": "$.id"}]}))]
+    #[case(json!({"bodyPatterns": [{"equalTo": "a"}, {"equalTo": "b"}]}))]
+    fn test_parse_request_unsupported(#[case] value: Value) {
+        assert_that!(parse_request(&value)).is_err();
+    }
+
+    #[test]
+    fn test_parse_mappings() {
+        let value = json!({
+            "mappings": [
+                {"request": {"method": "GET"}, "response": {"status": 404}},
+                {
+                    "name": "get item",
+                    "priority": 1,
+                    "request": {"method": "GET", "urlPath": "/item"},
+                    "response": {"jsonBody": {"id": 1}, "headers": {"x-id": ["1", "2"]}}
+                }
+            ]
+        });
+
+        let stubs = parse_mappings(&value).unwrap();
+
+        assert_that!(stubs).has_length(2);
+        assert_that!(stubs[0].response.status).is_equal_to(StatusCode::NOT_FOUND);
+        assert_that!(stubs[1].name.as_deref()).is_equal_to(Some("get item"));
+        assert_that!(stubs[1].priority).is_equal_to(Some(-1));
+        assert_that!(stubs[1].response.status).is_equal_to(StatusCode::OK);
+        assert_that!(stubs[1].response.headers).has_length(2);
+        assert_that!(stubs[1].response.body.as_ref()).is_equal_to(br#"{"id":1}"#.as_ref());
+    }
+}
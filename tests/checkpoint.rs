@@ -0,0 +1,302 @@
+use hyper::{Body, Request, StatusCode};
+use mock_http_connector::{CheckpointKind, Connector, Error, Level, Reason};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[tokio::test]
+async fn test_checkpoint_name() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a named case and an unnamed case
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(3)
+        .name("auth token request")
+        .with_uri("http://test.example/token")
+        .returning("token")?;
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/data")
+        .returning("data")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making fewer requests than expected
+    client
+        .request(
+            Request::builder()
+                .uri("http://test.example/token")
+                .body("".to_string().into())?,
+        )
+        .await?;
+
+    // THEN the checkpoint error identifies the cases
+    let err = connector.checkpoint().unwrap_err().to_string();
+    assert_that!(err).contains("case `auth token request`: expected 3, got 1");
+    assert_that!(err).contains("case `WithHandler`: expected 1, got 0");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_report() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a token refresh case and a case without expectations
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(2)
+        .name("token refresh")
+        .with_uri("http://test.example/token")
+        .returning("token")?;
+    builder
+        .expect()
+        .name("data")
+        .with_uri("http://test.example/data")
+        .returning("data")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a single token request
+    client
+        .request(
+            Request::builder()
+                .uri("http://test.example/token")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the report shows the calls so far
+    let report = connector.report();
+    assert_that!(report).has_length(2);
+
+    assert_that!(report[0].name.as_str()).is_equal_to("token refresh");
+    assert_that!(report[0].times).is_equal_to(Some(2));
+    assert_that!(report[0].seen).is_equal_to(1);
+    assert_that!(report[0].pending()).is_equal_to(Some(1));
+    assert_that!(report[0].is_satisfied()).is_false();
+
+    assert_that!(report[1].name.as_str()).is_equal_to("data");
+    assert_that!(report[1].seen).is_equal_to(0);
+    assert_that!(report[1].pending()).is_none();
+    assert_that!(report[1].is_satisfied()).is_true();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_checkpoint_details() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case expecting two requests
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(2)
+        .with_uri("http://test.example/token")
+        .returning("token")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a single request
+    client
+        .request(
+            Request::builder()
+                .uri("http://test.example/token")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the checkpoint error contains the details of the case
+    let err = connector.checkpoint().unwrap_err();
+    assert_that!(matches!(err, Error::Checkpoint(_))).is_true();
+
+    let checkpoints = err.checkpoints();
+    assert_that!(checkpoints.len()).is_equal_to(1);
+    assert_that!(checkpoints[0].case()).is_equal_to("WithHandler");
+    assert_that!(checkpoints[0].matcher())
+        .is_some()
+        .contains("http://test.example/token");
+    assert_that!(checkpoints[0].kind()).is_equal_to(CheckpointKind::Count {
+        expected: 2,
+        got: 1,
+    });
+    assert_that!(checkpoints[0].expected()).is_equal_to(Some(2));
+    assert_that!(checkpoints[0].got()).is_equal_to(1);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+#[should_panic(expected = "case `token refresh`: expected 2, got 1")]
+async fn test_verify_on_drop() {
+    // GIVEN a connector that verifies its cases on drop
+    let mut builder = Connector::builder();
+    builder.verify_on_drop(true);
+    builder
+        .expect()
+        .times(2)
+        .name("token refresh")
+        .returning("token")
+        .unwrap();
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making fewer requests than expected, and dropping the connector
+    client
+        .request(
+            Request::builder()
+                .uri("http://test.example/token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    drop(client);
+
+    // THEN dropping the last clone panics
+    drop(connector);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_verify_on_drop_ok() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that verifies its cases on drop
+    let mut builder = Connector::builder();
+    builder.verify_on_drop(true);
+    builder.expect().times(1).returning("token")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making the expected requests
+    client
+        .request(
+            Request::builder()
+                .uri("http://test.example/token")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN dropping the connector doesn't panic
+    drop(client);
+    drop(connector);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(true, false)]
+#[case(false, true)]
+#[tokio::test]
+async fn test_strict(
+    #[case] builder_strict: bool,
+    #[case] case_strict: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a strict case expecting a single request
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder.strict(builder_strict);
+    let case = builder.expect().times(1).name("token refresh");
+    let case = if case_strict { case.strict() } else { case };
+    case.returning("token")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    let request = || {
+        client.request(
+            Request::builder()
+                .uri("http://test.example/token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    // WHEN making one more request than expected
+    let first = request().await;
+    let second = request().await;
+
+    // THEN the extra request is rejected
+    assert_that!(first.map(|res| res.status())).is_ok_containing(StatusCode::OK);
+    assert_that!(second).is_err();
+
+    // AND the reports explain why
+    let report = connector.last_report().unwrap();
+    assert_that!(report.cases[0].mismatches[0].reason).is_equal_to(Reason::Times);
+    assert_that!(connector.checkpoint().unwrap_err().to_string())
+        .contains("case `token refresh`: expected 1, got 2");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_strict_fallthrough() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a strict case, followed by a case for the same requests
+    let mut builder = Connector::builder();
+    builder.strict(true);
+    builder.expect().times(1).returning("first")?;
+    builder.expect().returning("second")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making two requests
+    let mut bodies = Vec::new();
+    for _ in 0..2 {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri("http://test.example")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        bodies.push(hyper::body::to_bytes(res.into_body()).await?);
+    }
+
+    // THEN the second request goes to the next case
+    assert_that!(bodies).is_equal_to(vec!["first".into(), "second".into()]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_consumed() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector where the first request fails, and the next ones succeed
+    let mut builder = Connector::builder();
+    builder.expect().times(1).consumed().returning(503)?;
+    builder.expect().times(2).consumed().returning(200)?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making three requests
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri("http://test.example")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        statuses.push(res.status());
+    }
+
+    // THEN each case handles its share of the requests
+    assert_that!(statuses).is_equal_to(vec![
+        StatusCode::SERVICE_UNAVAILABLE,
+        StatusCode::OK,
+        StatusCode::OK,
+    ]);
+    connector.checkpoint()?;
+
+    Ok(())
+}
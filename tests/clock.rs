@@ -0,0 +1,142 @@
+use hyper::{body::to_bytes, service::Service, Body, Request, StatusCode};
+use mock_http_connector::{Chunked, Clock, Connector, FaultProfile, RateLimited, SleepFuture};
+use rstest::*;
+use speculoos::prelude::*;
+use std::{
+    error::Error as StdError,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+fn request() -> Request<Body> {
+    Request::builder()
+        .uri("http://test.example")
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Clock that only moves forward when the test advances it, and never waits
+#[derive(Debug, Clone)]
+struct ManualClock(Arc<Mutex<Instant>>);
+
+impl ManualClock {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.0.lock().unwrap()
+    }
+
+    fn sleep(&self, _duration: Duration) -> SleepFuture {
+        Box::pin(async {})
+    }
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_paused_chunked_delay() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that waits an hour between chunks
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .returning(Chunked::new(["hello", " world"]).delay(Duration::from_secs(3600)))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a paused clock
+    let start = Instant::now();
+    let res = client.request(request()).await?;
+    let body = to_bytes(res.into_body()).await?;
+
+    // THEN the clock auto-advances through the delays
+    assert_that!(body.as_ref()).is_equal_to(b"hello world".as_ref());
+    assert_that!(start.elapsed()).is_greater_than_or_equal_to(Duration::from_secs(7200));
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_paused_timeout() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that delays responses by a minute
+    let mut builder = Connector::builder();
+    builder.faults(FaultProfile::new(1).delay(1.0, Duration::from_secs(60), Duration::ZERO));
+    builder.expect().returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a shorter timeout
+    let res = tokio::time::timeout(Duration::from_secs(5), client.request(request())).await;
+
+    // THEN the request times out without waiting for real time
+    assert_that!(res).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+#[case("http://test.example", Duration::from_secs(1))]
+#[case("http://SLOW.example", Duration::from_secs(30))]
+#[tokio::test(start_paused = true)]
+async fn test_connect_delay(
+    #[case] uri: &str,
+    #[case] delay: Duration,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a connect delay, and a longer one for a slow host
+    let mut builder = Connector::builder();
+    builder.connect_delay(Duration::from_secs(1));
+    builder.connect_delay_for("slow.example", Duration::from_secs(30));
+    builder.expect().returning("OK")?;
+    let mut connector = builder.build();
+
+    // WHEN connecting with a timeout shorter than the delay
+    let res = tokio::time::timeout(delay / 2, connector.call(uri.parse()?)).await;
+
+    // THEN the connection times out
+    assert_that!(res.is_err()).is_true();
+
+    // AND the connection succeeds after the delay
+    let start = Instant::now();
+    connector.call(uri.parse()?).await?;
+    assert_that!(start.elapsed()).is_greater_than_or_equal_to(delay);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_custom_clock() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a manual clock and a rate-limited case
+    let clock = ManualClock::new();
+    let mut builder = Connector::builder();
+    builder.clock(clock.clone());
+    builder
+        .expect()
+        .returning(RateLimited::new(1, Duration::from_secs(10), "slow down"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making requests before and after advancing the clock
+    let first = client.request(request()).await?.status();
+    let second = client.request(request()).await?.status();
+    clock.advance(Duration::from_secs(10));
+    let third = client.request(request()).await?.status();
+
+    // THEN the rate limit window follows the manual clock
+    assert_that!(first).is_equal_to(StatusCode::OK);
+    assert_that!(second).is_equal_to(StatusCode::TOO_MANY_REQUESTS);
+    assert_that!(third).is_equal_to(StatusCode::OK);
+
+    Ok(())
+}
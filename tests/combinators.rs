@@ -0,0 +1,172 @@
+use hyper::{Body, Method, Request};
+use mock_http_connector::{all_of, any_of, not, Connector, Level, Reason, With, WithHandler};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+async fn request(
+    connector: &Connector,
+    method: Method,
+    uri: &str,
+) -> Result<bool, Box<dyn StdError + Send + Sync>> {
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    let res = client
+        .request(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())?,
+        )
+        .await;
+    Ok(res.is_ok())
+}
+
+#[rstest]
+#[case(Method::PUT, true)]
+#[case(Method::PATCH, true)]
+#[case(Method::GET, false)]
+#[tokio::test]
+async fn test_any_of(
+    #[case] method: Method,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting PUT or PATCH requests
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_matcher(any_of([
+            WithHandler::default().with_method("PUT")?,
+            WithHandler::default().with_method("PATCH")?,
+        ]))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN making a request
+    let matched = request(&connector, method, "http://test.example").await?;
+
+    // THEN it only matches one of the methods
+    assert_that!(matched).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_any_of_empty() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with an empty any_of matcher
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_matcher(any_of(Vec::<WithHandler>::new()))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN making a request
+    let matched = request(&connector, Method::GET, "http://test.example").await?;
+
+    // THEN it never matches
+    assert_that!(matched).is_false();
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Method::POST, "http://test.example/hello", true)]
+#[case(Method::GET, "http://test.example/hello", false)]
+#[case(Method::POST, "http://test.example/other", false)]
+#[tokio::test]
+async fn test_all_of(
+    #[case] method: Method,
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting POST requests to a specific URI
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_matcher(all_of([
+            WithHandler::default().with_method("POST")?,
+            WithHandler::default().with_uri("http://test.example/hello")?,
+        ]))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN making a request
+    let matched = request(&connector, method, uri).await?;
+
+    // THEN it only matches if all the matchers match
+    assert_that!(matched).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Method::POST, true)]
+#[case(Method::GET, false)]
+#[tokio::test]
+async fn test_not(
+    #[case] method: Method,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting anything but GET requests
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_matcher(not(WithHandler::default().with_method("GET")?))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN making a request
+    let matched = request(&connector, method, "http://test.example").await?;
+
+    // THEN it only matches other methods
+    assert_that!(matched).is_equal_to(expected);
+
+    // AND the report names the negated matcher
+    if !expected {
+        let report = connector.last_report().unwrap();
+        let reasons = report.cases[0]
+            .mismatches
+            .iter()
+            .map(|mismatch| mismatch.reason.clone())
+            .collect::<Vec<_>>();
+        assert_that!(reasons).is_equal_to(vec![Reason::Custom("not WithHandler".to_string())]);
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_nested() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting requests to a URI, except for GET and HEAD requests
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_matcher(all_of([
+            WithHandler::default()
+                .with_uri("http://test.example/hello")?
+                .boxed(),
+            not(any_of([
+                WithHandler::default().with_method("GET")?,
+                WithHandler::default().with_method("HEAD")?,
+            ]))
+            .boxed(),
+        ]))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN making requests
+    let uri = "http://test.example/hello";
+    let matched = [
+        request(&connector, Method::DELETE, uri).await?,
+        request(&connector, Method::HEAD, uri).await?,
+    ];
+
+    // THEN the combined matchers apply
+    assert_that!(matched).is_equal_to([true, false]);
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use hyper::{body::to_bytes, Body, Request, StatusCode};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::{error::Error as StdError, time::Duration};
+
+#[rstest]
+#[case(3, 0)]
+#[case(2, 1)]
+#[case(1, 2)]
+#[tokio::test]
+async fn test_max_concurrent(
+    #[case] max: usize,
+    #[case] expected: usize,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a slow case that limits concurrent requests
+    let mut builder = Connector::builder();
+    builder.expect().max_concurrent(max).returning(|_| async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        "OK"
+    })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    let request = || {
+        client.request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    // WHEN making 3 requests at the same time
+    let (a, b, c) = tokio::join!(request(), request(), request());
+
+    // THEN the requests over the limit are rejected
+    let rejected = [a?, b?, c?]
+        .iter()
+        .filter(|res| res.status() == StatusCode::TOO_MANY_REQUESTS)
+        .count();
+    assert_that!(rejected).is_equal_to(expected);
+
+    // AND the checkpoint reports the violation
+    let checkpoint = connector.checkpoint();
+    if expected == 0 {
+        assert_that!(checkpoint).is_ok();
+    } else {
+        assert_that!(checkpoint.unwrap_err().to_string())
+            .contains(format!("expected at most {max} concurrent requests"));
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_max_concurrent_sequential() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that only allows one request at a time
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(3)
+        .max_concurrent(1)
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making requests one after the other
+    for _ in 0..3 {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri("http://test.example")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_that!(res.status()).is_equal_to(StatusCode::OK);
+        to_bytes(res.into_body()).await?;
+    }
+
+    // THEN all the requests are accepted
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_max_concurrent_times() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a slow case that expects 2 calls, and allows 2 concurrent requests
+    let mut builder = Connector::builder();
+    builder.metrics(true);
+    builder
+        .expect()
+        .times(2)
+        .max_concurrent(2)
+        .returning(|_| async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            "OK"
+        })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    let request = || {
+        client.request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    // WHEN making 3 requests at the same time
+    let (a, b, c) = tokio::join!(request(), request(), request());
+
+    // THEN the rejected request isn't counted as a call to the case
+    let statuses = [a?, b?, c?].map(|res| res.status());
+    assert_that!(statuses
+        .iter()
+        .filter(|status| **status == StatusCode::OK)
+        .count())
+    .is_equal_to(2);
+    assert_that!(connector.metrics().unwrap().cases[0].requests).is_equal_to(2);
+
+    // AND the checkpoint only reports the concurrency violation
+    let checkpoint = connector.checkpoint().unwrap_err().to_string();
+    assert_that!(checkpoint).contains("expected at most 2 concurrent requests");
+    assert_that!(checkpoint).does_not_contain("expected 2, got 3");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_max_concurrent_fallthrough() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a slow case that expects 1 call, and a fallback case
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .max_concurrent(1)
+        .consumed()
+        .returning(|_| async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            "first"
+        })?;
+    builder.expect().priority(-1).returning("fallback")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    let request = || {
+        client.request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    // WHEN making 2 requests at the same time
+    let (a, b) = tokio::join!(request(), request());
+
+    // THEN the second request falls through to the fallback case
+    let mut bodies = Vec::new();
+    for res in [a?, b?] {
+        assert_that!(res.status()).is_equal_to(StatusCode::OK);
+        bodies.push(to_bytes(res.into_body()).await?);
+    }
+    assert_that!(bodies).contains_all_of(&[&"first".into(), &"fallback".into()]);
+
+    // AND the checkpoint doesn't report a concurrency violation
+    connector.checkpoint()?;
+
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use hyper::{
+    body::{to_bytes, HttpBody},
+    client::connect::{capture_connection, Connected},
+    service::Service,
+    Body, Request, Uri,
+};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::{
+    error::Error as StdError,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Connector that counts how many connections the client opens
+#[derive(Clone)]
+struct CountingConnector {
+    connector: Connector,
+    count: Arc<AtomicUsize>,
+}
+
+impl Service<Uri> for CountingConnector {
+    type Response = <Connector as Service<Uri>>::Response;
+    type Error = <Connector as Service<Uri>>::Error;
+    type Future = <Connector as Service<Uri>>::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.connector.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Uri) -> Self::Future {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.connector.call(req)
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_close_connection() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that closes the connection
+    let mut builder = Connector::builder();
+    builder.expect().close_connection().returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the response asks the client to close the connection
+    assert_that!(res.headers()["connection"].to_str()?).is_equal_to("close");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_max_requests_per_connection() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that closes connections after one request
+    let mut builder = Connector::builder();
+    builder.max_requests_per_connection(1);
+    builder.expect().returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the response asks the client to close the connection
+    assert_that!(res.headers()["connection"].to_str()?).is_equal_to("close");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_abort_after() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that aborts mid-body
+    let mut builder = Connector::builder();
+    builder.expect().abort_after(5).returning("hello world")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let mut res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the client receives the start of the body, then an error
+    let mut body = Vec::new();
+    let mut error = None;
+    while let Some(chunk) = res.body_mut().data().await {
+        match chunk {
+            Ok(chunk) => body.extend(chunk),
+            Err(err) => {
+                error = Some(err);
+                break;
+            }
+        }
+    }
+    assert_that!(body).is_equal_to(b"hello".to_vec());
+    assert_that!(error).is_some();
+
+    Ok(())
+}
+
+#[rstest]
+#[case(None, 1)]
+#[case(Some(1), 3)]
+#[case(Some(2), 2)]
+#[tokio::test]
+async fn test_connection_reuse(
+    #[case] max_requests: Option<usize>,
+    #[case] expected: usize,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with an optional limit of requests per connection
+    let mut builder = Connector::builder();
+    if let Some(max_requests) = max_requests {
+        builder.max_requests_per_connection(max_requests);
+    }
+    builder
+        .expect()
+        .times(3)
+        .with_method("POST")
+        .with_body("hello")
+        .returning("OK")?;
+    let connector = CountingConnector {
+        connector: builder.build(),
+        count: Arc::new(AtomicUsize::new(0)),
+    };
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making several requests one after the other
+    for _ in 0..3 {
+        let res = client
+            .request(
+                Request::builder()
+                    .method("POST")
+                    .uri("http://test.example")
+                    .body("hello".into())?,
+            )
+            .await?;
+        assert_that!(res.status().as_u16()).is_equal_to(200);
+        to_bytes(res.into_body()).await?;
+    }
+
+    // THEN the client reuses connections until they are closed
+    assert_that!(connector.count.load(Ordering::SeqCst)).is_equal_to(expected);
+    connector.connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_pipelined_requests() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with two cases
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/first")
+        .with_body("one")
+        .returning("first")?;
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/second")
+        .returning("second")?;
+    let connector = builder.build();
+
+    // WHEN writing two requests at once on the same connection
+    let mut stream = connector
+        .clone()
+        .call("http://test.example".parse()?)
+        .await?;
+    stream
+        .write_all(
+            b"POST /first HTTP/1.1\r\nhost: test.example\r\ncontent-length: 3\r\n\r\none\
+            GET /second HTTP/1.1\r\nhost: test.example\r\nconnection: close\r\n\r\n",
+        )
+        .await?;
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).await?;
+
+    // THEN it returns both responses in order, and closes the connection
+    let data = String::from_utf8(buf)?;
+    assert_that!(data.matches("HTTP/1.1 200").count()).is_equal_to(2);
+    assert_that!(data.find("first")).is_less_than(data.find("second"));
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+/// Extra connection metadata, attached with `on_connect`
+#[derive(Debug, Clone, PartialEq)]
+struct Region(String);
+
+#[rstest]
+#[tokio::test]
+async fn test_on_connect() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that customizes the connection metadata
+    let mut builder = Connector::builder();
+    builder.on_connect(|uri: &Uri, connected: Connected| {
+        connected
+            .proxy(true)
+            .extra(Region(uri.host().unwrap_or_default().to_string()))
+    });
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let mut req = Request::builder()
+        .uri("http://test.example/hello")
+        .body(Body::empty())?;
+    let connection = capture_connection(&mut req);
+    let res = client.request(req).await?;
+
+    // THEN the client sees the custom metadata
+    assert_that!(res.extensions().get::<Region>())
+        .is_equal_to(Some(&Region("test.example".to_string())));
+    let metadata = connection.connection_metadata();
+    assert_that!(metadata.as_ref().map(Connected::is_proxied)).is_equal_to(Some(true));
+
+    Ok(())
+}
+
+#[rstest]
+#[case("http://api.down.example/hello", true)]
+#[case("http://API.DOWN.EXAMPLE:8080/hello", true)]
+#[case("http://api.example:8443/hello", true)]
+#[case("http://api.example/hello", false)]
+#[tokio::test]
+async fn test_refuse_connection_to(
+    #[case] uri: &str,
+    #[case] refused: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector refusing connections to some authorities
+    let mut builder = Connector::builder();
+    builder.refuse_connection_to("api.down.example");
+    builder.refuse_connection_to("api.example:8443");
+    builder.expect().returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body(Body::empty())?)
+        .await;
+
+    // THEN it fails to connect to the refused authorities
+    match res {
+        Ok(res) => {
+            assert_that!(refused).is_false();
+            assert_that!(res.status().as_u16()).is_equal_to(200);
+        }
+        Err(err) => {
+            assert_that!(refused).is_true();
+            assert_that!(err.is_connect()).is_true();
+        }
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[case("http://primary.example/", true)]
+#[case("http://secondary.example/", false)]
+#[tokio::test]
+async fn test_refuse_unexpected_hosts(
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with cases for a single host, refusing other hosts
+    let mut builder = Connector::builder();
+    builder.refuse_unexpected_hosts(true);
+    builder
+        .for_host("primary.example")
+        .expect()
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN connecting to a host
+    let res = connector.stream(uri.parse()?);
+
+    // THEN only connections to the expected host succeed
+    match res {
+        Ok(_) => assert_that!(expected).is_true(),
+        Err(err) => {
+            assert_that!(expected).is_false();
+            assert_that!(err.kind()).is_equal_to(std::io::ErrorKind::ConnectionRefused);
+        }
+    }
+
+    Ok(())
+}
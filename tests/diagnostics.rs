@@ -0,0 +1,258 @@
+use hyper::{Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::{
+    error::Error as StdError,
+    io,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Clone, Default)]
+struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_diagnostics_writer() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector writing diagnostics into a custom writer
+    let writer = SharedWriter::default();
+    let mut builder = Connector::builder();
+    builder.diagnostics_writer(writer.clone());
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request that doesn't match any case
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/other")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN the report is written into the writer
+    assert_that!(res).is_err();
+    let report = String::from_utf8(writer.0.lock().unwrap().clone())?;
+    assert_that!(report).contains("no matching case for request");
+    assert_that!(report).contains("http://test.example/other");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_diagnostics_header_values() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a header
+    let writer = SharedWriter::default();
+    let mut builder = Connector::builder();
+    builder.diagnostics_writer(writer.clone());
+    builder
+        .expect()
+        .with_header("content-type", "application/json")
+        .with_header("x-request-id", "1234")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a different header value
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("content-type", "text/plain")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN the report shows both the expected and actual values
+    assert_that!(res).is_err();
+    let report = String::from_utf8(writer.0.lock().unwrap().clone())?;
+    assert_that!(report).contains(
+        "header `content-type` (at least once): expected `application/json`, got `text/plain`",
+    );
+    assert_that!(report)
+        .contains("header `x-request-id` (at least once): expected `1234`, got nothing");
+
+    Ok(())
+}
+
+#[rstest]
+#[case(true, "http://test.example/named", Some("token"))]
+#[case(true, "http://test.example/unnamed", Some("1"))]
+#[case(false, "http://test.example/named", None)]
+#[tokio::test]
+async fn test_case_header(
+    #[case] enabled: bool,
+    #[case] uri: &str,
+    #[case] expected: Option<&str>,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a named and an unnamed case
+    let mut builder = Connector::builder();
+    builder.case_header(enabled);
+    builder
+        .expect()
+        .name("token")
+        .with_uri("http://test.example/named")
+        .returning("OK")?;
+    builder
+        .expect()
+        .with_uri("http://test.example/unnamed")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body(Body::empty())?)
+        .await?;
+
+    // THEN the response identifies the case
+    let header = res
+        .headers()
+        .get("x-mock-case")
+        .map(|value| value.to_str().unwrap());
+    assert_that!(header).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_render_report_plain() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that doesn't match on multiple attributes
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .name("create item")
+        .with_method("POST")
+        .with_uri("http://test.example/items")
+        .with_header("x-api-key", "secret")
+        .with_header("accept", "application/json")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request that doesn't match
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/other")
+                .header("accept", "text/plain")
+                .body("hello".into())?,
+        )
+        .await;
+
+    // THEN the plain report is uncolored, with sorted headers and attributes
+    assert_that!(res).is_err();
+    let report = connector.render_report_plain();
+    let expected = concat!(
+        "--> no matching case for request\n",
+        " | \n",
+        " = the incoming request did not match any know cases.\n",
+        " = incoming request:\n",
+        " | \n",
+        " | method:   GET\n",
+        " | uri:      http://test.example/other\n",
+        " | headers:\n",
+        " |   accept        : text/plain\n",
+        " |   content-length: 5\n",
+        " |   host          : test.example\n",
+        " | \n",
+        " | body:\n",
+        " | hello\n",
+        " | \n",
+        "--> case 0 `create item`\n",
+        " | \n",
+        " | method:   POST\n",
+        " |           ^^^^\n",
+        " | uri:      http://test.example/items\n",
+        " |           ^^^^^^^^^^^^^^^^^^^^^^^^^\n",
+        " | headers:\n",
+        " |   accept   : application/json\n",
+        " |              ^^^^^^^^^^^^^^^^\n",
+        " |   x-api-key: secret\n",
+        " |              ^^^^^^\n",
+        " | \n",
+        " = this case doesn't match the request on the following attributes:\n",
+        " | - header `accept` (at least once): expected `application/json`, got `text/plain`\n",
+        " | - header `x-api-key` (at least once): expected `secret`, got nothing\n",
+        " | - method\n",
+        " | - uri path\n",
+        " | \n",
+        "\n",
+    );
+    assert_that!(report.as_deref()).is_equal_to(Some(expected));
+
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    "a=1&b=2&c=3",
+    "a=1&b=5&c=3",
+    concat!(
+        " = body diff (- expected, + actual):\n",
+        " | - a=1&b=2&c=3\n",
+        " | + a=1&b=5&c=3\n",
+        " |         ^\n",
+    ),
+)]
+#[case(
+    "first\nsecond\nthird",
+    "first\nthird\nfourth",
+    concat!(
+        " = body diff (- expected, + actual):\n",
+        " |   first\n",
+        " | - second\n",
+        " |   third\n",
+        " | + fourth\n",
+    ),
+)]
+#[tokio::test]
+async fn test_body_diff(
+    #[case] expected: &str,
+    #[case] actual: &'static str,
+    #[case] diff: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a string body
+    let mut builder = Connector::builder();
+    builder.expect().with_body(expected).returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a different body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(actual.into())?,
+        )
+        .await;
+
+    // THEN the report contains a diff of the bodies
+    assert_that!(res).is_err();
+    let report = connector.render_report_plain().unwrap_or_default();
+    assert_that!(report).contains(diff);
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use hyper::{body::to_bytes, service::Service, Body, Request, StatusCode};
+use mock_http_connector::{Connector, ExpectContinue};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[rstest]
+#[tokio::test]
+async fn test_expect_continue() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that expects an upload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_body("hello")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN writing the request head with `expect: 100-continue`
+    let mut stream = connector
+        .clone()
+        .call("http://test.example".parse()?)
+        .await?;
+    stream
+        .write_all(
+            b"PUT / HTTP/1.1\r\nhost: test.example\r\ncontent-length: 5\r\nexpect: 100-continue\r\n\r\n",
+        )
+        .await?;
+
+    // THEN it sends an interim response
+    let mut buf = vec![0; 1024];
+    let size = stream.read(&mut buf).await?;
+    assert_that!(String::from_utf8_lossy(&buf[..size]).into_owned())
+        .is_equal_to("HTTP/1.1 100 Continue\r\n\r\n".to_string());
+
+    // AND the final response once the payload is sent
+    stream.write_all(b"hello").await?;
+    let size = stream.read(&mut buf).await?;
+    assert_that!(String::from_utf8_lossy(&buf[..size]).into_owned()).starts_with("HTTP/1.1 200");
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_expect_continue_client() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that expects an upload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_body("hello")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN sending a request with `expect: 100-continue`
+    let res = client
+        .request(
+            Request::builder()
+                .method("PUT")
+                .uri("http://test.example")
+                .header("expect", "100-continue")
+                .body("hello".into())?,
+        )
+        .await?;
+
+    // THEN the client skips the interim response
+    assert_that!(res.status()).is_equal_to(StatusCode::OK);
+    assert_that!(to_bytes(res.into_body()).await?).is_equal_to(hyper::body::Bytes::from("OK"));
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_expect_continue_reject() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that rejects `expect: 100-continue`
+    let mut builder = Connector::builder();
+    builder.expect_continue(ExpectContinue::Reject(StatusCode::EXPECTATION_FAILED));
+    builder.expect().times(0).returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN sending a request with `expect: 100-continue`
+    let res = client
+        .request(
+            Request::builder()
+                .method("PUT")
+                .uri("http://test.example")
+                .header("expect", "100-continue")
+                .body("hello".into())?,
+        )
+        .await?;
+
+    // THEN it is rejected without matching any case
+    assert_that!(res.status()).is_equal_to(StatusCode::EXPECTATION_FAILED);
+    connector.checkpoint()?;
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use hyper::{Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case("http://test.example/hello", 200, "hello")]
+#[case("http://test.example/other", 404, "NOT FOUND")]
+#[tokio::test]
+async fn test_fallback(
+    #[case] uri: &str,
+    #[case] status: u16,
+    #[case] body: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a fallback
+    let mut builder = Connector::builder();
+    builder.fallback((404, "NOT FOUND"));
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await?;
+
+    // THEN it only uses the fallback when no case matches
+    assert_that!(res.status().as_u16()).is_equal_to(status);
+    let res_body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(res_body.as_ref()).is_equal_to(body.as_bytes());
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_fallback_checkpoint() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a fallback and a case
+    let mut builder = Connector::builder();
+    builder.fallback((404, "NOT FOUND"));
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making requests that hit the case once and the fallback multiple times
+    for uri in [
+        "http://test.example/other",
+        "http://test.example/hello",
+        "http://test.example/other",
+    ] {
+        client
+            .request(Request::builder().uri(uri).body("".to_string().into())?)
+            .await?;
+    }
+
+    // THEN the checkpoint ignores the fallback
+    assert_that!(connector.checkpoint()).is_ok();
+
+    Ok(())
+}
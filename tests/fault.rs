@@ -0,0 +1,162 @@
+use hyper::{body::to_bytes, Body, Request, StatusCode};
+use mock_http_connector::{Connector, FaultProfile};
+use rstest::*;
+use speculoos::prelude::*;
+use std::{
+    error::Error as StdError,
+    time::{Duration, Instant},
+};
+
+fn request() -> Request<Body> {
+    Request::builder()
+        .uri("http://test.example")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_fault_status() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that always fails with a server error
+    let mut builder = Connector::builder();
+    builder.faults(FaultProfile::new(1).status(1.0, StatusCode::SERVICE_UNAVAILABLE));
+    builder.expect().times(1).returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client.request(request()).await?;
+
+    // THEN it returns the server error instead of the case response
+    assert_that!(res.status()).is_equal_to(StatusCode::SERVICE_UNAVAILABLE);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_fault_drop_connection() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that always drops the connection
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .faults(FaultProfile::new(1).drop_connection(1.0))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client.request(request()).await;
+
+    // THEN the client doesn't receive a response
+    assert_that!(res).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_fault_truncate_body() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that always truncates the body
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .faults(FaultProfile::new(1).truncate_body(1.0))
+        .returning("hello world")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client.request(request()).await?;
+
+    // THEN the body is incomplete
+    assert_that!(res.status()).is_equal_to(StatusCode::OK);
+    assert_that!(to_bytes(res.into_body()).await).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_fault_delay() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that always delays responses
+    let mut builder = Connector::builder();
+    builder.faults(FaultProfile::new(1).delay(
+        1.0,
+        Duration::from_millis(20),
+        Duration::from_millis(10),
+    ));
+    builder.expect().returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let start = Instant::now();
+    let res = client.request(request()).await?;
+
+    // THEN the response is delayed
+    assert_that!(res.status()).is_equal_to(StatusCode::OK);
+    assert_that!(start.elapsed()).is_greater_than_or_equal_to(Duration::from_millis(20));
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_fault_case_override() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that always fails, except for a case without faults
+    let mut builder = Connector::builder();
+    builder.faults(FaultProfile::new(1).status(1.0, StatusCode::BAD_GATEWAY));
+    builder
+        .expect()
+        .faults(FaultProfile::new(1))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client.request(request()).await?;
+
+    // THEN the case profile is used
+    assert_that!(res.status()).is_equal_to(StatusCode::OK);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_fault_seed() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN two connectors with the same fault profile
+    let statuses = || async {
+        let mut builder = Connector::builder();
+        builder.faults(FaultProfile::new(7).status(0.5, StatusCode::INTERNAL_SERVER_ERROR));
+        builder.expect().returning("OK")?;
+        let connector = builder.build();
+        let client = hyper::Client::builder().build::<_, Body>(connector);
+
+        let mut statuses = Vec::new();
+        for _ in 0..20 {
+            let res = client.request(request()).await?;
+            statuses.push(res.status());
+            to_bytes(res.into_body()).await?;
+        }
+        Ok::<_, Box<dyn StdError + Send + Sync>>(statuses)
+    };
+
+    // WHEN making the same requests on both
+    let first = statuses().await?;
+    let second = statuses().await?;
+
+    // THEN they inject the same faults
+    assert_that!(first).is_equal_to(&second);
+    assert_that!(first).contains(StatusCode::OK);
+    assert_that!(first).contains(StatusCode::INTERNAL_SERVER_ERROR);
+
+    Ok(())
+}
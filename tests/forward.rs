@@ -0,0 +1,92 @@
+use hyper::{Body, Request};
+use mock_http_connector::{Connector, Forward};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+/// Connector standing in for a real service
+fn upstream() -> Result<Connector, Box<dyn StdError + Send + Sync>> {
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_method("POST")
+        .with_body("ping")
+        .returning((201, "from upstream"))?;
+    Ok(builder.build())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_passthrough() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector forwarding some requests to another client
+    let upstream = upstream()?;
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_host("real.example")
+        .passthrough(hyper::Client::builder().build(upstream.clone()))?;
+    builder
+        .expect()
+        .with_host("mocked.example")
+        .returning("from mock")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making requests to both hosts
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://real.example/")
+                .body("ping".to_string().into())?,
+        )
+        .await?;
+    let mocked = client
+        .request(
+            Request::builder()
+                .uri("http://mocked.example/")
+                .body("".to_string().into())?,
+        )
+        .await?;
+
+    // THEN only the first request reaches the upstream client
+    assert_that!(res.status().as_u16()).is_equal_to(201);
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"from upstream".as_ref());
+    let body = hyper::body::to_bytes(mocked.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"from mock".as_ref());
+    upstream.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_forward_fallback() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector forwarding unmatched requests
+    let upstream = upstream()?;
+    let mut builder = Connector::builder();
+    builder.fallback(Forward::new(
+        hyper::Client::builder().build(upstream.clone()),
+    ));
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example/")
+                .body("ping".to_string().into())?,
+        )
+        .await?;
+
+    // THEN it is sent to the upstream client
+    assert_that!(res.status().as_u16()).is_equal_to(201);
+    upstream.checkpoint()?;
+
+    Ok(())
+}
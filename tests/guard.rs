@@ -0,0 +1,161 @@
+use hyper::{Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::{error::Error as StdError, panic::AssertUnwindSafe};
+
+#[rstest]
+#[tokio::test]
+async fn test_scope() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a scoped case
+    let connector = Connector::builder().build();
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    {
+        let mut guard = connector.scope();
+        guard
+            .expect()
+            .times(1)
+            .with_uri("http://test.example")
+            .returning("OK")?;
+
+        // WHEN making a request within the scope
+        let res = client
+            .request(
+                Request::builder()
+                    .uri("http://test.example")
+                    .body("".to_string().into())?,
+            )
+            .await;
+
+        // THEN it returns the right payload
+        assert_that!(res).is_ok().matches(|res| res.status() == 200);
+    }
+
+    // WHEN making a request after the end of the scope
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it returns an error
+    assert_that!(res).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+fn test_scope_panics() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a scoped case that is never called
+    let connector = Connector::builder().build();
+    let mut guard = connector.scope();
+    guard
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning("OK")?;
+
+    // WHEN dropping the guard
+    let res = std::panic::catch_unwind(AssertUnwindSafe(move || drop(guard)));
+
+    // THEN it panics
+    assert_that!(res).is_err();
+    assert_that!(connector.checkpoint()).is_ok();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_expect_after_build() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case added after `build()`
+    let connector = Connector::builder().build();
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    connector
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning("OK")?;
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it returns the right payload
+    assert_that!(res).is_ok().matches(|res| res.status() == 200);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_reset() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that was not called
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning("OK")?;
+    let connector = builder.build();
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN resetting the connector
+    connector.reset();
+
+    // THEN the checkpoint succeeds
+    assert_that!(connector.checkpoint()).is_ok();
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it returns an error
+    assert_that!(res).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_scope_priority() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a scoped low-priority fallback registered before a specific case
+    let connector = Connector::builder().build();
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    let mut guard = connector.scope();
+    guard.expect().priority(-1).returning("fallback")?;
+    guard
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+
+    // WHEN making a request matching both cases
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/hello")
+                .body("".to_string().into())?,
+        )
+        .await?;
+
+    // THEN the higher priority case wins
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"hello".as_ref());
+
+    Ok(())
+}
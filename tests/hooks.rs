@@ -0,0 +1,96 @@
+use hyper::{body::Bytes, http::HeaderValue, Body, Request, Response};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::{
+    error::Error as StdError,
+    sync::{Arc, Mutex},
+};
+
+#[rstest]
+#[case("http://test.example/hello", 200)]
+#[case("http://test.example/other", 404)]
+#[tokio::test]
+async fn test_on_request(
+    #[case] uri: &str,
+    #[case] status: u16,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that logs all requests
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut builder = Connector::builder();
+    builder.fallback((404, "NOT FOUND"));
+    builder.on_request({
+        let log = log.clone();
+        move |req: &Request<Bytes>| {
+            log.lock()
+                .unwrap()
+                .push(format!("{} {} {:?}", req.method(), req.uri(), req.body()));
+        }
+    });
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("payload".into())?)
+        .await?;
+
+    // THEN the request is logged, whether it matched a case or not
+    assert_that!(res.status().as_u16()).is_equal_to(status);
+    assert_that!(*log.lock().unwrap()).is_equal_to(vec![format!("GET {uri} b\"payload\"")]);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("http://test.example/hello", 200)]
+#[case("http://test.example/other", 404)]
+#[tokio::test]
+async fn test_on_response(
+    #[case] uri: &str,
+    #[case] status: u16,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that echoes the request ID in all responses
+    let mut builder = Connector::builder();
+    builder.fallback((404, "NOT FOUND"));
+    builder.on_response(|req: &Request<()>, res: &mut Response<Bytes>| {
+        if let Some(id) = req.headers().get("x-request-id") {
+            res.headers_mut().insert("x-request-id", id.clone());
+        }
+    });
+    builder.on_response(|_req: &Request<()>, res: &mut Response<Bytes>| {
+        res.headers_mut()
+            .insert("x-hooks", HeaderValue::from_static("2"));
+    });
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a request ID
+    let res = client
+        .request(
+            Request::builder()
+                .uri(uri)
+                .header("x-request-id", "abc-123")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN all the hooks modified the response, whether it matched a case or not
+    assert_that!(res.status().as_u16()).is_equal_to(status);
+    assert_that!(res.headers().get("x-request-id").map(|v| v.as_bytes()))
+        .is_equal_to(Some(b"abc-123".as_ref()));
+    assert_that!(res.headers().get("x-hooks").map(|v| v.as_bytes()))
+        .is_equal_to(Some(b"2".as_ref()));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use hyper::{Body, Request};
+use mock_http_connector::{Connector, Level};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case("http://dynamodb.test.example/", "dynamodb")]
+#[case("http://SQS.test.example/", "sqs")]
+#[tokio::test]
+async fn test_for_host(
+    #[case] uri: &str,
+    #[case] expected: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with cases grouped by host
+    let mut builder = Connector::builder();
+    builder
+        .for_host("dynamodb.test.example")
+        .expect()
+        .returning("dynamodb")?;
+    builder
+        .for_host("sqs.test.example")
+        .expect()
+        .returning("sqs")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await?;
+
+    // THEN the request is routed to the cases for that host
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(expected.as_bytes());
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_for_host_report() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with cases for multiple hosts
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .for_host("dynamodb.test.example")
+        .expect()
+        .with_method("POST")
+        .returning("dynamodb")?;
+    builder
+        .for_host("sqs.test.example")
+        .expect()
+        .with_method("POST")
+        .returning("sqs")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request that doesn't match
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://sqs.test.example/")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN the report only contains the cases for that host
+    assert_that!(res).is_err();
+    let report = connector.last_report().unwrap();
+    assert_that!(report.cases).has_length(1);
+
+    Ok(())
+}
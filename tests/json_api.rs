@@ -0,0 +1,110 @@
+#![cfg(feature = "json")]
+
+use hyper::{body::to_bytes, Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case(
+    "GET",
+    "https://api.example/v1/users/42?fields=name",
+    None,
+    200,
+    r#"{"id":42}"#
+)]
+#[case(
+    "POST",
+    "https://api.example/v1/users",
+    Some("application/json"),
+    201,
+    r#"{"id":43}"#
+)]
+#[case(
+    "POST",
+    "https://api.example/v1/users",
+    Some("text/plain"),
+    404,
+    r#"{"error":"not found"}"#
+)]
+#[case(
+    "GET",
+    "https://api.example/v2/users/42",
+    None,
+    404,
+    r#"{"error":"not found"}"#
+)]
+#[tokio::test]
+async fn test_json_api(
+    #[case] method: &str,
+    #[case] uri: &str,
+    #[case] content_type: Option<&str>,
+    #[case] status: u16,
+    #[case] body: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector mocking a JSON API
+    let mut builder = Connector::builder();
+    let mut api = builder.expect_json_api("https://api.example/v1/")?;
+    api.expect("GET", "/users/42")
+        .returning_json(serde_json::json!({"id": 42}))?;
+    api.expect("POST", "users")
+        .with_json(serde_json::json!({"name": "new user"}))
+        .returning((201, r#"{"id":43}"#))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request to the API
+    let mut req = Request::builder().method(method).uri(uri);
+    if let Some(content_type) = content_type {
+        req = req.header("content-type", content_type);
+    }
+    let res = client
+        .request(req.body(r#"{"name": "new user"}"#.into())?)
+        .await?;
+
+    // THEN it receives the response of the matching case, or the JSON fallback
+    assert_that!(res.status().as_u16()).is_equal_to(status);
+    let res_body = to_bytes(res.into_body()).await?;
+    assert_that!(std::str::from_utf8(&res_body)?).is_equal_to(body);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_json_api_other_host() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector mocking a JSON API
+    let mut builder = Connector::builder();
+    builder.expect_json_api("https://api.example")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request to another host
+    let res = client
+        .request(
+            Request::builder()
+                .uri("https://other.example/users")
+                .body(Body::empty())?,
+        )
+        .await;
+
+    // THEN the fallback of the API doesn't apply
+    assert_that!(res).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+fn test_json_api_invalid_base_url() {
+    // GIVEN a builder
+    let mut builder = Connector::builder();
+
+    // WHEN creating a JSON API without a host
+    let res = builder.expect_json_api("/v1");
+
+    // THEN it fails
+    assert_that!(res.is_err()).is_true();
+}
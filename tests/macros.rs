@@ -0,0 +1,92 @@
+use hyper::{body::to_bytes, Body, Request};
+use mock_http_connector::mock_connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case("GET", "https://test.example/users", None, 200, "[]")]
+#[case(
+    "POST",
+    "https://test.example/users",
+    Some("application/json"),
+    201,
+    r#"{"id":42}"#
+)]
+#[case("DELETE", "https://test.example/health", None, 204, "")]
+#[tokio::test]
+async fn test_mock_connector(
+    #[case] method: &str,
+    #[case] uri: &str,
+    #[case] content_type: Option<&str>,
+    #[case] status: u16,
+    #[case] body: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector declared with the macro
+    let connector = mock_connector! {
+        GET "https://test.example/users" => (200, "[]");
+        POST "https://test.example/users" {
+            header("content-type", "application/json"),
+            body(r#"{"name":"some user"}"#),
+            name("create user"),
+        } => (201, r#"{"id":42}"#);
+        ANY "https://test.example/health" => 204;
+    }?;
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let mut req = Request::builder().method(method).uri(uri);
+    if let Some(content_type) = content_type {
+        req = req.header("content-type", content_type);
+    }
+    let res = client
+        .request(req.body(r#"{"name":"some user"}"#.into())?)
+        .await?;
+
+    // THEN it receives the response of the matching case
+    assert_that!(res.status().as_u16()).is_equal_to(status);
+    let res_body = to_bytes(res.into_body()).await?;
+    assert_that!(std::str::from_utf8(&res_body)?).is_equal_to(body);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_mock_connector_unmatched() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector declared with the macro, with a header option
+    let connector = mock_connector! {
+        POST "https://test.example/users" {
+            header("content-type", "application/json"),
+        } => 201
+    }?;
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request without the header
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("https://test.example/users")
+                .body(Body::empty())?,
+        )
+        .await;
+
+    // THEN the request fails
+    assert_that!(res).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+fn test_mock_connector_invalid() {
+    // WHEN declaring a case with an invalid URI
+    let res = mock_connector! {
+        GET "not a uri" => 200;
+    };
+
+    // THEN it fails
+    assert_that!(res.is_err()).is_true();
+}
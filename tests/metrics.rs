@@ -0,0 +1,79 @@
+use hyper::{Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[tokio::test]
+async fn test_metrics() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector collecting metrics
+    let mut builder = Connector::builder();
+    builder.metrics(true);
+    builder.fallback((404, "NOT FOUND"));
+    builder
+        .expect()
+        .name("batch")
+        .with_uri("http://test.example/batch")
+        .returning("OK")?;
+    builder
+        .expect()
+        .name("single")
+        .with_uri("http://test.example/single")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN sending batches of different sizes, and a request that doesn't match
+    for (uri, body) in [
+        ("http://test.example/batch", "a,b,c"),
+        ("http://test.example/batch", "d,e"),
+        ("http://test.example/other", ""),
+    ] {
+        client
+            .request(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .body(body.into())?,
+            )
+            .await?;
+    }
+
+    // THEN the metrics contain the requests and body sizes for each case
+    let metrics = connector.metrics().expect("metrics are enabled");
+    assert_that!(metrics.unmatched).is_equal_to(1);
+    assert_that!(metrics.cases).has_length(2);
+
+    let batch = &metrics.cases[0];
+    assert_that!(batch.name.as_str()).is_equal_to("batch");
+    assert_that!(batch.requests).is_equal_to(2);
+    assert_that!(batch.body_size.samples()).is_equal_to([5, 3].as_ref());
+    assert_that!(batch.body_size.max()).is_equal_to(Some(5));
+    assert_that!(batch.body_size.sum()).is_equal_to(8);
+    assert_that!(batch.match_latency.len()).is_equal_to(2);
+
+    let single = &metrics.cases[1];
+    assert_that!(single.requests).is_equal_to(0);
+    assert_that!(single.match_latency.is_empty()).is_true();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_metrics_disabled() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector without metrics
+    let mut builder = Connector::builder();
+    builder.expect().returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN getting the metrics
+    let metrics = connector.metrics();
+
+    // THEN there are none
+    assert_that!(metrics).is_none();
+
+    Ok(())
+}
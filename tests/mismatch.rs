@@ -0,0 +1,328 @@
+use hyper::{body::Bytes, http::HeaderValue, Body, Request};
+use mock_http_connector::{
+    BoxError, Connector, HeaderCheckKind, Level, Mismatch, Multipart, MultipartPart, Reason,
+    Report, UriComponent, UriParts, With, WithPrint,
+};
+use rstest::*;
+use speculoos::prelude::*;
+use std::{collections::HashSet, error::Error as StdError};
+
+#[rstest]
+#[tokio::test]
+async fn test_last_report() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a specific request
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_method("POST")
+        .with_uri("http://test.example/hello")
+        .with_body("hello world")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request that doesn't match
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example/other")
+                .body("hello there".into())?,
+        )
+        .await;
+    assert_that!(res).is_err();
+
+    // THEN the connector exposes a structured report
+    let report = connector.last_report();
+    assert_that!(report).is_some();
+    let report = report.unwrap();
+    assert_that!(report.uri.to_string()).is_equal_to("http://test.example/other".to_string());
+    assert_that!(report.cases).has_length(1);
+    assert_that!(report.cases[0].mismatches).is_equal_to(vec![
+        Mismatch {
+            reason: Reason::Body(Vec::new()),
+            expected: Some("hello world".to_string()),
+            actual: Some("hello there".to_string()),
+            diff_position: Some(6),
+        },
+        Mismatch {
+            reason: Reason::Uri(UriComponent::Path),
+            expected: Some("/hello".to_string()),
+            actual: Some("/other".to_string()),
+            diff_position: Some(1),
+        },
+    ]);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[tokio::test]
+async fn test_non_utf8_body() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a JSON matcher and a binary fallback case
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_json(serde_json::json!({"a": 1}))
+        .returning("json")?;
+    builder
+        .expect()
+        .with_body_bytes(b"\xff\xfe")
+        .returning("binary")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN sending a body that isn't valid UTF-8
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(Body::from(&b"\xff\xfe"[..]))?,
+        )
+        .await?;
+
+    // THEN the JSON case is skipped, and the binary case matches
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"binary".as_ref());
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[tokio::test]
+async fn test_non_utf8_body_report() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a JSON matcher
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_json(serde_json::json!({"a": 1}))
+        .returning("json")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN sending a body that isn't valid UTF-8
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(Body::from(&b"\xff\xfe"[..]))?,
+        )
+        .await;
+    assert_that!(res).is_err();
+
+    // THEN the report explains why the body didn't match
+    let report = connector.last_report().unwrap();
+    let reason = &report.cases[0].mismatches[0].reason;
+    let Reason::Body(diffs) = reason else {
+        panic!("unexpected reason: {reason:?}");
+    };
+    assert_that!(diffs[0].to_string()).contains("body is not valid UTF-8");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_multipart_report() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a multipart part
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_multipart(Multipart::new().part(MultipartPart::new("title").content("hello")))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN sending a part with a different content
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .header("content-type", "multipart/form-data; boundary=b")
+                .body(
+                    "--b\r\ncontent-disposition: form-data; name=title\r\n\r\nhello there\r\n--b--"
+                        .into(),
+                )?,
+        )
+        .await;
+    assert_that!(res).is_err();
+
+    // THEN the mismatch names the part
+    let report = connector.last_report().unwrap();
+    assert_that!(report.cases[0].mismatches).is_equal_to(vec![Mismatch {
+        reason: Reason::MultipartPart("title".to_string()),
+        expected: Some("hello".to_string()),
+        actual: Some("hello there".to_string()),
+        diff_position: Some(5),
+    }]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_uri_parts_report() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting URI components
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_uri_parts(
+            UriParts::new()
+                .scheme("http")
+                .path_prefix("/api/")
+                .query_param("page", "2"),
+        )
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a different query parameter
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/api/items?page=3")
+                .body(Body::empty())?,
+        )
+        .await;
+    assert_that!(res).is_err();
+
+    // THEN the mismatch names the query parameter
+    let report = connector.last_report().unwrap();
+    assert_that!(report.cases[0].mismatches).is_equal_to(vec![Mismatch {
+        reason: Reason::Uri(UriComponent::QueryParam("page".to_string())),
+        expected: Some("2".to_string()),
+        actual: Some("3".to_string()),
+        diff_position: Some(0),
+    }]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_last_report_header() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a header exactly once
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_header_once("content-type", "application/json")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with the header twice
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("content-type", "application/json")
+                .header("content-type", "text/plain")
+                .body(Body::empty())?,
+        )
+        .await;
+    assert_that!(res).is_err();
+
+    // THEN the mismatch contains the kind of check and all the actual values
+    let report = connector.last_report().unwrap();
+    assert_that!(report.cases[0].mismatches).is_equal_to(vec![Mismatch {
+        reason: Reason::Header {
+            name: "content-type".try_into()?,
+            check: HeaderCheckKind::ExactlyOnce,
+            actual: vec![
+                HeaderValue::from_static("application/json"),
+                HeaderValue::from_static("text/plain"),
+            ],
+        },
+        expected: Some("application/json".to_string()),
+        actual: Some("application/json, text/plain".to_string()),
+        diff_position: Some(16),
+    }]);
+
+    Ok(())
+}
+
+/// Matcher for requests with a given `x-api-key` header
+struct ApiKey(&'static str);
+
+impl ApiKey {
+    fn key(req: &Request<Bytes>) -> Option<String> {
+        Some(req.headers().get("x-api-key")?.to_str().ok()?.to_string())
+    }
+}
+
+impl With for ApiKey {
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, BoxError> {
+        Ok((Self::key(req).as_deref() != Some(self.0))
+            .then(|| Reason::Custom("api key".to_string()))
+            .into())
+    }
+
+    fn print_pretty(&self, _report: &HashSet<Reason>) -> WithPrint<'_> {
+        WithPrint {
+            name: "api key".into(),
+            body: None,
+        }
+    }
+
+    fn expected(&self, _reason: &Reason) -> Option<String> {
+        Some(self.0.to_string())
+    }
+
+    fn actual(&self, _reason: &Reason, req: &Request<Bytes>) -> Option<String> {
+        Self::key(req)
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_last_report_custom() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a custom matcher
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_matcher(ApiKey("abcd"))
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with another key
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("x-api-key", "abce")
+                .body(Body::empty())?,
+        )
+        .await;
+    assert_that!(res).is_err();
+
+    // THEN the mismatch contains the custom reason and the values reported by the matcher
+    let report = connector.last_report().unwrap();
+    assert_that!(report.cases[0].mismatches).is_equal_to(vec![Mismatch {
+        reason: Reason::Custom("api key".to_string()),
+        expected: Some("abcd".to_string()),
+        actual: Some("abce".to_string()),
+        diff_position: Some(3),
+    }]);
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use hyper::{Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case("http://test.example/hello", "hello")]
+#[case("http://test.example/other", "fallback")]
+#[tokio::test]
+async fn test_priority(
+    #[case] uri: &str,
+    #[case] expected: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a low-priority fallback registered first
+    let mut builder = Connector::builder();
+    builder.expect().priority(-1).returning("fallback")?;
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await?;
+
+    // THEN the most specific case wins
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(expected.as_bytes());
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_priority_ties() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with multiple catch-all cases with the same priority
+    let mut builder = Connector::builder();
+    builder.expect().returning("first")?;
+    builder.expect().priority(1).returning("second")?;
+    builder.expect().priority(1).returning("third")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await?;
+
+    // THEN the first registered case with the highest priority wins
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"second".as_ref());
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "recording")]
+
+use hyper::{Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case("fixture.json")]
+#[case("fixture.har")]
+#[tokio::test]
+async fn test_record_and_replay(
+    #[case] file_name: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a recording connector
+    let mut builder = Connector::builder();
+    builder.record();
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning((201, "hello"))?;
+    let connector = builder.build();
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example/hello")
+                .body("payload".to_string().into())?,
+        )
+        .await?;
+
+    // WHEN saving the recording and loading it into another connector
+    let fixture = connector.recording().unwrap();
+    assert_that!(fixture.entries).has_length(1);
+    let path = std::env::temp_dir().join(format!("{}-{file_name}", std::process::id()));
+    fixture.save(&path)?;
+
+    let mut builder = Connector::builder();
+    builder.load_fixture(&path)?;
+    std::fs::remove_file(&path)?;
+    let connector = builder.build();
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example/hello")
+                .body("payload".to_string().into())?,
+        )
+        .await?;
+
+    // THEN the recorded response is replayed
+    assert_that!(res.status().as_u16()).is_equal_to(201);
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"hello".as_ref());
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_replay_mismatch() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector loaded from a fixture
+    let path = std::env::temp_dir().join(format!("{}-mismatch.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{"entries": [{
+            "request": {"method": "GET", "uri": "http://test.example/"},
+            "response": {"status": 200, "body": "OK"}
+        }]}"#,
+    )?;
+    let mut builder = Connector::builder();
+    builder.load_fixture(&path)?;
+    std::fs::remove_file(&path)?;
+    let connector = builder.build();
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request that wasn't recorded
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/other")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it doesn't match
+    assert_that!(res).is_err();
+
+    Ok(())
+}
@@ -1,8 +1,18 @@
-use hyper::{body::to_bytes, Body, Request};
-use mock_http_connector::Connector;
+use hyper::{
+    body::{to_bytes, Bytes, HttpBody},
+    header::{CONTENT_TYPE, SET_COOKIE},
+    http::HeaderValue,
+    service::Service,
+    Body, HeaderMap, Request, Response, StatusCode,
+};
+#[cfg(feature = "json")]
+use mock_http_connector::Json;
+use mock_http_connector::{
+    Backoff, CaseContext, Chunked, Connector, MockResponse, RateLimited, SoapFault, Sse, SseEvent,
+};
 use rstest::*;
 use speculoos::prelude::*;
-use std::{error::Error as StdError, str::from_utf8};
+use std::{error::Error as StdError, str::from_utf8, time::Duration};
 
 #[rstest]
 #[tokio::test]
@@ -38,6 +48,7 @@ async fn test_async() -> Result<(), Box<dyn StdError + Send + Sync>> {
     Ok(())
 }
 
+#[cfg(feature = "json")]
 #[rstest]
 #[tokio::test]
 async fn test_json() -> Result<(), Box<dyn StdError + Send + Sync>> {
@@ -72,3 +83,824 @@ async fn test_json() -> Result<(), Box<dyn StdError + Send + Sync>> {
 
     Ok(())
 }
+
+#[rstest]
+#[tokio::test]
+async fn test_returning_tuples() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning responses with status, headers, and bodies
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri("http://test.example/map")
+        .returning((StatusCode::CREATED, headers, "map"))?;
+    builder
+        .expect()
+        .with_uri("http://test.example/list")
+        .returning((
+            201,
+            vec![(CONTENT_TYPE, HeaderValue::from_static("text/plain"))],
+            "list",
+        ))?;
+    builder
+        .expect()
+        .with_uri("http://test.example/closure")
+        .returning(|_req| async {
+            (
+                201,
+                vec![(CONTENT_TYPE, HeaderValue::from_static("text/plain"))],
+                "closure",
+            )
+        })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    for path in ["map", "list", "closure"] {
+        // WHEN making a request
+        let res = client
+            .request(
+                Request::builder()
+                    .uri(format!("http://test.example/{path}"))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        // THEN it returns the status, headers, and body
+        assert_that!(res.status()).is_equal_to(StatusCode::CREATED);
+        assert_that!(res.headers().get(CONTENT_TYPE))
+            .is_equal_to(Some(&HeaderValue::from_static("text/plain")));
+        let body = to_bytes(res.into_body()).await?;
+        assert_that!(from_utf8(&body)?).is_equal_to(path);
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_returning_empty() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning empty and binary responses
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri("http://test.example/unit")
+        .returning(())?;
+    builder
+        .expect()
+        .with_uri("http://test.example/closure")
+        .returning(|_req| async {})?;
+    builder
+        .expect()
+        .with_uri("http://test.example/bytes")
+        .returning(|_req| async { Bytes::from_static(b"\x00\x01") })?;
+    builder
+        .expect()
+        .with_uri("http://test.example/vec")
+        .returning(|_req| async { vec![0u8, 1] })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    for (path, expected) in [
+        ("unit", &b""[..]),
+        ("closure", b""),
+        ("bytes", b"\x00\x01"),
+        ("vec", b"\x00\x01"),
+    ] {
+        // WHEN making a request
+        let res = client
+            .request(
+                Request::builder()
+                    .uri(format!("http://test.example/{path}"))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        // THEN it returns a 200 response with the payload
+        assert_that!(res.status()).is_equal_to(StatusCode::OK);
+        let body = to_bytes(res.into_body()).await?;
+        assert_that!(body.as_ref()).is_equal_to(expected);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_mock_response() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a response built with MockResponse
+    let mut builder = Connector::builder();
+    builder.expect().returning(
+        MockResponse::created()
+            .header("x-request-id", "some-id")?
+            .json(serde_json::json!({"id": 42}))?
+            .delay(Duration::from_secs(5)),
+    )?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let start = tokio::time::Instant::now();
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN it returns the response after the delay
+    assert_that!(start.elapsed()).is_greater_than_or_equal_to(Duration::from_secs(5));
+    assert_that!(res.status()).is_equal_to(StatusCode::CREATED);
+    assert_that!(res.headers().get("x-request-id"))
+        .is_equal_to(Some(&HeaderValue::from_static("some-id")));
+    assert_that!(res.headers().get(CONTENT_TYPE))
+        .is_equal_to(Some(&HeaderValue::from_static("application/json")));
+    let body: serde_json::Value = serde_json::from_slice(&to_bytes(res.into_body()).await?)?;
+    assert_that!(body).is_equal_to(serde_json::json!({"id": 42}));
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[derive(serde::Serialize)]
+struct Item {
+    id: u64,
+    name: &'static str,
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[tokio::test]
+async fn test_returning_json() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning serialized structs
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_method("GET")
+        .returning_json(Item { id: 1, name: "a" })?;
+    builder
+        .expect()
+        .with_method("POST")
+        .returning((StatusCode::CREATED, Json(Item { id: 2, name: "b" })))?;
+    builder
+        .expect()
+        .with_method("PUT")
+        .returning(|_req| async { (202, Json(Item { id: 3, name: "c" })) })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    for (method, status, expected) in [
+        (
+            "GET",
+            StatusCode::OK,
+            serde_json::json!({"id": 1, "name": "a"}),
+        ),
+        (
+            "POST",
+            StatusCode::CREATED,
+            serde_json::json!({"id": 2, "name": "b"}),
+        ),
+        (
+            "PUT",
+            StatusCode::ACCEPTED,
+            serde_json::json!({"id": 3, "name": "c"}),
+        ),
+    ] {
+        // WHEN making a request
+        let res = client
+            .request(
+                Request::builder()
+                    .method(method)
+                    .uri("http://test.example")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        // THEN it returns the serialized payload
+        assert_that!(res.status()).is_equal_to(status);
+        assert_that!(res.headers().get("content-type").map(|v| v.as_bytes()))
+            .is_equal_to(Some(b"application/json".as_ref()));
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(res.into_body()).await?)?;
+        assert_that!(body).is_equal_to(expected);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[derive(serde::Deserialize)]
+struct Payload {
+    name: String,
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[tokio::test]
+async fn test_json_de() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a response based on the deserialized payload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning_json_de(|payload: Payload| async move { format!("hello {}", payload.name) })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(r#"{"name": "world"}"#.into())?,
+        )
+        .await;
+
+    // THEN it returns the right payload
+    assert_that!(res).is_ok();
+
+    let body = to_bytes(res?.body_mut()).await?;
+    let body = from_utf8(&body)?;
+
+    assert_that!(body).is_equal_to("hello world");
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_returning_with_ctx() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a response based on the call position
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(3)
+        .name("flaky")
+        .with_uri("http://test.example")
+        .returning_with_ctx(|ctx: CaseContext, _req: Request<Bytes>| async move {
+            let status = if ctx.call() == 0 { 503 } else { 200 };
+            (
+                status,
+                format!("{} {}/{}", ctx.name(), ctx.call(), ctx.seen()),
+            )
+        })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making three requests
+    let mut responses = Vec::new();
+    for _ in 0..3 {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri("http://test.example")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        let status = res.status();
+        let body = to_bytes(res.into_body()).await?;
+        responses.push((status, from_utf8(&body)?.to_string()));
+    }
+
+    // THEN only the first call fails, and each response has the context of its call
+    assert_that!(responses).is_equal_to(vec![
+        (StatusCode::SERVICE_UNAVAILABLE, "flaky 0/1".to_string()),
+        (StatusCode::OK, "flaky 1/2".to_string()),
+        (StatusCode::OK, "flaky 2/3".to_string()),
+    ]);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_bytes() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning non-UTF-8 bytes
+    let payload = vec![0x1f, 0x8b, 0x00, 0xff, 0xfe];
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning(payload.clone())?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it returns the payload unmodified
+    assert_that!(res).is_ok();
+
+    let body = to_bytes(res?.body_mut()).await?;
+
+    assert_that!(body.to_vec()).is_equal_to(payload);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[case("", "0")]
+#[case("hello", "5")]
+#[tokio::test]
+async fn test_content_length(
+    #[case] payload: &'static str,
+    #[case] expected: &'static str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a payload without a content-length header
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning(payload)?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN the response contains a content-length header
+    assert_that!(res)
+        .is_ok()
+        .matches(|res| res.headers()["content-length"] == expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_chunked() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a chunked payload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning(
+            Chunked::new(["hello ", "world", "!"]).delay(std::time::Duration::from_millis(5)),
+        )?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it returns the right payload, chunk by chunk
+    assert_that!(res)
+        .is_ok()
+        .matches(|res| res.headers()["transfer-encoding"] == "chunked");
+
+    let mut res = res?;
+    let mut chunks = Vec::new();
+    while let Some(chunk) = res.body_mut().data().await {
+        chunks.push(chunk?);
+    }
+
+    assert_that!(chunks.concat()).is_equal_to(b"hello world!".to_vec());
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_large_body() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a large payload
+    let payload = Bytes::from(vec![b'a'; 16 * 1024 * 1024]);
+    let mut builder = Connector::builder();
+    let body = payload.clone();
+    builder.expect().times(1).returning(move |_req| {
+        let body = body.clone();
+        async move { hyper::Response::new(body) }
+    })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN it returns the full payload
+    assert_that!(to_bytes(res.into_body()).await?).is_equal_to(payload);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_chunked_trailers() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a chunked payload with trailers
+    let mut builder = Connector::builder();
+    builder.expect().times(1).returning(
+        Chunked::new(["hello ", "world!"])
+            .trailer("grpc-status", "0")?
+            .trailer("grpc-message", "OK")?,
+    )?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await?;
+
+    // THEN it announces the trailers
+    assert_that!(res.headers()["trailer"].to_str()?).is_equal_to("grpc-status, grpc-message");
+
+    // AND the payload is still framed correctly
+    assert_that!(to_bytes(res.into_body()).await?).is_equal_to(Bytes::from("hello world!"));
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[rstest]
+#[tokio::test]
+async fn test_gzip() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a compressed payload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example")
+        .returning_gzip("hello world")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it returns a gzip payload
+    assert_that!(res)
+        .is_ok()
+        .matches(|res| res.headers()["content-encoding"] == "gzip");
+
+    let body = hyper::body::to_bytes(res?.into_body()).await?;
+    assert_that!(body[..3].to_vec()).is_equal_to(vec![0x1f, 0x8b, 0x08]);
+    // CRC32 and size of the uncompressed payload
+    assert_that!(body[body.len() - 8..].to_vec())
+        .is_equal_to(vec![0x85, 0x11, 0x4a, 0x0d, 0x0b, 0x00, 0x00, 0x00]);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_sse() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning Server-Sent Events
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .returning(Sse::new(["first", "second"]).delay(std::time::Duration::from_millis(5)))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let mut res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await?;
+
+    // THEN it returns the events, one per chunk
+    assert_that!(res.headers()["content-type"].to_str()?).is_equal_to("text/event-stream");
+    let mut chunks = Vec::new();
+    while let Some(chunk) = res.body_mut().data().await {
+        chunks.push(from_utf8(&chunk?)?.to_string());
+    }
+    assert_that!(chunks).is_equal_to(vec![
+        "data: first\n\n".to_string(),
+        "data: second\n\n".to_string(),
+    ]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_sse_keep_open() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning Server-Sent Events and keeping the connection open
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .returning(Sse::new([SseEvent::new("first").event("message")]).keep_open())?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN reading the events
+    let mut res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await?;
+    let first = res.body_mut().data().await.transpose()?;
+    let next =
+        tokio::time::timeout(std::time::Duration::from_millis(20), res.body_mut().data()).await;
+
+    // THEN the first event is received, and the body never ends
+    assert_that!(first).is_equal_to(Some("event: message\ndata: first\n\n".into()));
+    assert_that!(next).is_err();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_rate_limited() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that allows 2 requests per hour
+    let mut builder = Connector::builder();
+    builder.expect().times(3).returning(
+        RateLimited::new(2, Duration::from_secs(3600), "slow down").within_limit("OK"),
+    )?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making 3 requests
+    let mut responses = Vec::new();
+    for _ in 0..3 {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri("http://test.example")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        let status = res.status().as_u16();
+        let retry_after = res
+            .headers()
+            .get("retry-after")
+            .map(|value| value.to_str().map(ToString::to_string))
+            .transpose()?;
+        let body = to_bytes(res.into_body()).await?;
+        responses.push((status, retry_after, String::from_utf8(body.to_vec())?));
+    }
+
+    // THEN the requests over the limit are rejected until the end of the window
+    assert_that!(responses).is_equal_to(vec![
+        (200, None, "OK".to_string()),
+        (200, None, "OK".to_string()),
+        (429, Some("3600".to_string()), "slow down".to_string()),
+    ]);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    SoapFault::new("Client", "Unknown <symbol>"),
+    "soap:Client",
+    "Unknown &lt;symbol&gt;"
+)]
+#[case(
+    SoapFault::new("env:Server", "Unavailable"),
+    "env:Server",
+    "Unavailable"
+)]
+#[tokio::test]
+async fn test_returning_soap_fault(
+    #[case] fault: SoapFault,
+    #[case] code: &str,
+    #[case] string: &str,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector returning a SOAP fault
+    let mut builder = Connector::builder();
+    builder.expect().returning(fault)?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the response is a SOAP envelope with the fault
+    assert_that!(res.status()).is_equal_to(StatusCode::INTERNAL_SERVER_ERROR);
+    assert_that!(res.headers().get(CONTENT_TYPE))
+        .is_equal_to(Some(&HeaderValue::from_static("text/xml; charset=utf-8")));
+    let body = to_bytes(res.into_body()).await?;
+    let body = from_utf8(&body)?;
+    assert_that!(body).contains("<soap:Envelope");
+    assert_that!(body).contains(format!("<faultcode>{code}</faultcode>").as_str());
+    assert_that!(body).contains(format!("<faultstring>{string}</faultstring>").as_str());
+
+    Ok(())
+}
+
+#[rstest]
+#[case(false)]
+#[case(true)]
+#[tokio::test]
+async fn test_multi_value_headers(
+    #[case] service: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with cases returning multiple values for the same header
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri("http://test.example/mock")
+        .returning(
+            MockResponse::ok()
+                .header(SET_COOKIE, "session=1234; HttpOnly")?
+                .header(SET_COOKIE, "theme=dark")?,
+        )?;
+    builder
+        .expect()
+        .with_uri("http://test.example/response")
+        .returning(|_req: Request<Bytes>| async {
+            Response::builder()
+                .header(SET_COOKIE, "session=1234; HttpOnly")
+                .header(SET_COOKIE, "theme=dark")
+                .body(Bytes::new())
+        })?;
+    let connector = builder.build();
+
+    for uri in ["http://test.example/mock", "http://test.example/response"] {
+        // WHEN making a request through the connector or the service
+        let req = Request::builder().uri(uri).body(Body::empty())?;
+        let res = if service {
+            connector.service().call(req).await?
+        } else {
+            let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+            client.request(req).await?
+        };
+
+        // THEN the client receives all the values, in order
+        let cookies = res
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .map(HeaderValue::to_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_that!(cookies).is_equal_to(vec!["session=1234; HttpOnly", "theme=dark"]);
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_redirect_chain() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a chain of redirects
+    let mut builder = Connector::builder();
+    builder.redirect_chain(
+        [
+            "http://test.example/a",
+            "http://test.example/b",
+            "http://test.example/c",
+        ],
+        "done",
+    )?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN following the redirects
+    let mut uri = "http://test.example/a".to_string();
+    let mut visited = Vec::new();
+    let res = loop {
+        visited.push(uri.clone());
+        let res = client
+            .request(Request::builder().uri(&uri).body(Body::empty())?)
+            .await?;
+        if res.status() != StatusCode::FOUND {
+            break res;
+        }
+        uri = res.headers()["location"].to_str()?.to_string();
+    };
+
+    // THEN each case redirects to the next one, until the final response
+    assert_that!(visited).is_equal_to(vec![
+        "http://test.example/a".to_string(),
+        "http://test.example/b".to_string(),
+        "http://test.example/c".to_string(),
+    ]);
+    assert_that!(res.status()).is_equal_to(StatusCode::OK);
+    assert_that!(to_bytes(res.into_body()).await?).is_equal_to(Bytes::from("done"));
+
+    Ok(())
+}
+
+#[rstest]
+#[case(4, true)]
+#[case(3, false)]
+#[case(5, false)]
+#[tokio::test]
+async fn test_backoff(
+    #[case] attempts: usize,
+    #[case] checkpoint: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that fails the first three requests
+    let mut builder = Connector::builder();
+    builder.expect().returning_backoff(
+        Backoff::new(3)
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .retry_after_each([Duration::from_secs(1), Duration::from_millis(1500)])
+            .success("done"),
+    )?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making some attempts
+    let mut responses = Vec::new();
+    for _ in 0..attempts {
+        let res = client
+            .request(
+                Request::builder()
+                    .uri("http://test.example")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        let retry_after = res
+            .headers()
+            .get("retry-after")
+            .map(|value| value.to_str().map(ToString::to_string))
+            .transpose()?;
+        responses.push((res.status().as_u16(), retry_after));
+    }
+
+    // THEN the first requests fail with the retry-after delays, then succeed
+    let expected = [
+        (429, Some("1".to_string())),
+        (429, Some("2".to_string())),
+        (429, Some("2".to_string())),
+        (200, None),
+        (200, None),
+    ];
+    assert_that!(responses.as_slice()).is_equal_to(&expected[..attempts]);
+
+    // AND the checkpoint verifies the number of attempts
+    assert_that!(connector.checkpoint().is_ok()).is_equal_to(checkpoint);
+
+    Ok(())
+}
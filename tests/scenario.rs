@@ -0,0 +1,133 @@
+use hyper::{body::to_bytes, Body, Request};
+use mock_http_connector::{Connector, Error, Level, Mismatch, Reason, Scenario};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[tokio::test]
+async fn test_scenario() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector modeling a job that completes after being polled once
+    let job = Scenario::new("started");
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_method("POST")
+        .with_uri("http://test.example/jobs")
+        .in_scenario(&job)
+        .in_state("started")
+        .will_set_state("created")
+        .returning((201, "created"))?;
+    builder
+        .expect()
+        .with_uri("http://test.example/jobs/1")
+        .in_scenario(&job)
+        .in_state("created")
+        .will_set_state("completed")
+        .returning("running")?;
+    builder
+        .expect()
+        .with_uri("http://test.example/jobs/1")
+        .in_scenario(&job)
+        .in_state("completed")
+        .returning("completed")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN creating the job, then polling it until it completes
+    let mut bodies = Vec::new();
+    for (method, uri) in [
+        ("POST", "http://test.example/jobs"),
+        ("GET", "http://test.example/jobs/1"),
+        ("GET", "http://test.example/jobs/1"),
+        ("GET", "http://test.example/jobs/1"),
+    ] {
+        let res = client
+            .request(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        bodies.push(String::from_utf8(
+            to_bytes(res.into_body()).await?.to_vec(),
+        )?);
+    }
+
+    // THEN each step moves the scenario to the next state
+    assert_that!(bodies).is_equal_to(vec![
+        "created".to_string(),
+        "running".to_string(),
+        "completed".to_string(),
+        "completed".to_string(),
+    ]);
+    assert_that!(job.state().as_str()).is_equal_to("completed");
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_scenario_mismatch() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case that requires an authorized scenario
+    let login = Scenario::new("anonymous");
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_uri("http://test.example/data")
+        .in_scenario(&login)
+        .in_state("authorized")
+        .returning("data")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request before the scenario is authorized
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/data")
+                .body(Body::empty())?,
+        )
+        .await;
+
+    // THEN the request doesn't match, and the report shows the states
+    assert_that!(res).is_err();
+    let report = connector.last_report().unwrap();
+    assert_that!(report.cases[0].mismatches).is_equal_to(vec![Mismatch {
+        reason: Reason::Scenario,
+        expected: Some("authorized".to_string()),
+        actual: Some("anonymous".to_string()),
+        diff_position: Some(1),
+    }]);
+
+    // AND the case matches once the scenario is in the right state
+    login.set_state("authorized");
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/data")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_that!(res.status().as_u16()).is_equal_to(200);
+
+    Ok(())
+}
+
+#[rstest]
+fn test_scenario_missing() {
+    // GIVEN a case with a state but no scenario
+    let mut builder = Connector::builder();
+
+    // WHEN registering the case
+    let res = builder.expect().in_state("authorized").returning("data");
+
+    // THEN it fails
+    assert_that!(res)
+        .is_err()
+        .matches(|err| matches!(err, Error::Scenario(_)));
+}
@@ -0,0 +1,64 @@
+use hyper::{Body, Request};
+use mock_http_connector::{Connector, Sequence};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+fn sequence_connector() -> Result<Connector, Box<dyn StdError + Send + Sync>> {
+    let mut seq = Sequence::new();
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/auth")
+        .in_sequence(&mut seq)
+        .returning("token")?;
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/data")
+        .in_sequence(&mut seq)
+        .returning("data")?;
+
+    Ok(builder.build())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_sequence_in_order() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with two cases in a sequence
+    let connector = sequence_connector()?;
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making requests in order
+    for uri in ["http://test.example/auth", "http://test.example/data"] {
+        client
+            .request(Request::builder().uri(uri).body("".to_string().into())?)
+            .await?;
+    }
+
+    // THEN the checkpoint succeeds
+    assert_that!(connector.checkpoint()).is_ok();
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_sequence_out_of_order() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with two cases in a sequence
+    let connector = sequence_connector()?;
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making requests out of order
+    for uri in ["http://test.example/data", "http://test.example/auth"] {
+        client
+            .request(Request::builder().uri(uri).body("".to_string().into())?)
+            .await?;
+    }
+
+    // THEN the checkpoint fails
+    assert_that!(connector.checkpoint()).is_err();
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use hyper::{body::to_bytes, service::Service, Body, Request, StatusCode};
+use mock_http_connector::{Connector, Error};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[tokio::test]
+async fn test_service() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case matching the request body
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/items")
+        .with_body("some payload")
+        .returning((StatusCode::CREATED, "created"))?;
+    let connector = builder.build();
+
+    // WHEN sending a request through the service
+    let mut service = connector.service();
+    let res = service
+        .call(Request::post("http://test.example/items").body(Body::from("some payload"))?)
+        .await?;
+
+    // THEN it returns the response of the case
+    assert_that!(res.status()).is_equal_to(StatusCode::CREATED);
+    let body = to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"created".as_ref());
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_service_chunked() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a chunked response
+    let mut builder = Connector::builder();
+    builder.expect().returning_chunked(["hello", " world"])?;
+    let connector = builder.build();
+
+    // WHEN sending a request through the service
+    let res = connector
+        .service()
+        .call(Request::get("http://test.example").body(Body::empty())?)
+        .await?;
+
+    // THEN the chunks are returned as a single body
+    assert_that!(res.headers().contains_key("transfer-encoding")).is_false();
+    let body = to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"hello world".as_ref());
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_service_not_found() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector without cases for the request
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri("http://test.example/other")
+        .returning("OK")?;
+    let connector = builder.build();
+
+    // WHEN sending a request through the service
+    let res = connector
+        .service()
+        .call(Request::get("http://test.example").body(Body::empty())?)
+        .await;
+
+    // THEN it returns a not found error
+    assert_that!(matches!(res, Err(Error::NotFound(_)))).is_true();
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "sigv4")]
+
+use hyper::{Body, Request};
+use mock_http_connector::{Connector, Level, Reason, SigV4Credentials};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+/// Signature of the example request from the AWS documentation
+const SIGNATURE: &str = "5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7";
+
+#[rstest]
+#[case("Action=ListUsers&Version=2010-05-08", SIGNATURE, None)]
+#[case(
+    "Action=ListRoles&Version=2010-05-08",
+    SIGNATURE,
+    Some("signature doesn't match the request")
+)]
+#[case(
+    "Action=ListUsers&Version=2010-05-08",
+    "0000000000000000000000000000000000000000000000000000000000000000",
+    Some("signature doesn't match the request")
+)]
+#[tokio::test]
+async fn test_sigv4(
+    #[case] query: &str,
+    #[case] signature: &str,
+    #[case] expected: Option<&str>,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting requests signed with SigV4
+    let mut builder = Connector::builder();
+    builder.level(Level::None);
+    builder
+        .expect()
+        .with_sigv4(
+            SigV4Credentials::new("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+            "us-east-1",
+            "iam",
+        )
+        .returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a signed request
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("https://iam.amazonaws.com/?{query}"))
+                .header(
+                    "content-type",
+                    "application/x-www-form-urlencoded; charset=utf-8",
+                )
+                .header("x-amz-date", "20150830T123600Z")
+                .header(
+                    "authorization",
+                    format!(
+                        "AWS4-HMAC-SHA256 \
+                         Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+                         SignedHeaders=content-type;host;x-amz-date, Signature={signature}"
+                    ),
+                )
+                .body(Body::empty())?,
+        )
+        .await;
+
+    // THEN it only matches if the signature is valid for the request
+    match expected {
+        None => {
+            assert_that!(res).is_ok();
+        }
+        Some(message) => {
+            assert_that!(res).is_err();
+            let report = connector.last_report().unwrap();
+            assert_that!(report.cases[0].mismatches[0].reason)
+                .is_equal_to(Reason::Signature(message.to_string()));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use hyper::{body::Bytes, Body, Request};
+use mock_http_connector::{Connector, State};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[tokio::test]
+async fn test_state() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN
+    // * a case that captures a token from the request
+    // * a case that only matches requests with the captured token
+    let token = State::new(None::<String>);
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/login")
+        .returning_state(&token, |token: &mut Option<String>, req: Request<Bytes>| {
+            *token = Some(String::from_utf8_lossy(req.body()).into_owned());
+            "logged in"
+        })?;
+    builder
+        .expect()
+        .times(1)
+        .with_state(
+            &token,
+            |token: &mut Option<String>, req: &Request<Bytes>| {
+                let header = req
+                    .headers()
+                    .get("authorization")
+                    .and_then(|value| value.to_str().ok());
+                token.is_some() && token.as_deref() == header
+            },
+        )
+        .returning("data")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN logging in, then making a request with the token
+    client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example/login")
+                .body("some-token".into())?,
+        )
+        .await?;
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/data")
+                .header("authorization", "some-token")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN the second case matches with the captured token
+    assert_that!(res).is_ok();
+    assert_that!(token.get()).is_equal_to(Some("some-token".to_string()));
+    connector.checkpoint()?;
+
+    Ok(())
+}
@@ -92,7 +92,7 @@ async fn test_status_tuple(
 async fn test_status_fn(#[case] status: StatusCode) -> Result<(), Box<dyn StdError + Send + Sync>> {
     // GIVEN a client that returns `status` through a closure
     let mut builder = Connector::builder();
-    let moved_status = status.clone();
+    let moved_status = status;
     builder
         .expect()
         .times(1)
@@ -1,21 +1,28 @@
 use hyper::{
     body::{Bytes, HttpBody},
-    Body, Request,
+    service::Service,
+    Body, Request, StatusCode,
 };
-use mock_http_connector::Connector;
+use mock_http_connector::{BodyStream, Connector, OnUnmatched};
 use rstest::*;
 use speculoos::prelude::*;
 use std::{
     convert::Infallible,
     error::Error as StdError,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
+    time::Duration,
 };
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[rstest]
 #[tokio::test]
 async fn test_body_stream() -> Result<(), Box<dyn StdError>> {
     // GIVEN
-    // * a connector that expects a chunk-encoded payload
+    // * a connector that expects a decoded chunk-encoded payload
     // * a Body wrapping a stream that splits the payload into 2 str
     let mut builder = Connector::builder();
     builder
@@ -23,7 +30,7 @@ async fn test_body_stream() -> Result<(), Box<dyn StdError>> {
         .times(1)
         .with_method("POST")
         .with_header("transfer-encoding", "chunked")
-        .with_body("6\r\nhello \r\n6\r\nworld!\r\n0\r\n\r\n")
+        .with_body("hello world!")
         .returning((202, "OK"))?;
 
     let connector = builder.build();
@@ -51,7 +58,7 @@ async fn test_body_stream() -> Result<(), Box<dyn StdError>> {
 #[tokio::test]
 async fn test_stream() -> Result<(), Box<dyn StdError>> {
     // GIVEN
-    // * a connector that expects a chunk-encoded payload
+    // * a connector that expects a raw chunk-encoded payload
     // * a custom HttpBody implementation that returns 2 payloads
     let mut builder = Connector::builder();
     builder
@@ -59,7 +66,7 @@ async fn test_stream() -> Result<(), Box<dyn StdError>> {
         .times(1)
         .with_method("POST")
         .with_header("transfer-encoding", "chunked")
-        .with_body("6\r\nworld!\r\n6\r\nhello \r\n0\r\n\r\n")
+        .with_raw_body("6\r\nworld!\r\n6\r\nhello \r\n0\r\n\r\n")
         .returning((202, "OK"))?;
 
     let connector = builder.build();
@@ -83,6 +90,237 @@ async fn test_stream() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+#[rstest]
+#[case("0", true)]
+#[case("2", false)]
+#[tokio::test]
+async fn test_trailers(
+    #[case] status: &str,
+    #[case] matches: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that expects a trailer
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_body("hello")
+        .with_trailer("grpc-status", "0")
+        .returning((202, "OK"))?;
+    builder.on_unmatched(OnUnmatched::Status(StatusCode::NOT_FOUND));
+    let connector = builder.build();
+
+    // WHEN writing a chunked request with trailers
+    let mut stream = connector
+        .clone()
+        .call("http://test.example".parse()?)
+        .await?;
+    stream
+        .write_all(
+            format!(
+                "POST / HTTP/1.1\r\nhost: test.example\r\ntransfer-encoding: chunked\r\n\r\n\
+                5\r\nhello\r\n0\r\ngrpc-status: {status}\r\n\r\n"
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let mut buf = vec![0; 1024];
+    let size = stream.read(&mut buf).await?;
+
+    // THEN it only matches with the right trailer
+    let expected = if matches {
+        "HTTP/1.1 202"
+    } else {
+        "HTTP/1.1 404"
+    };
+    assert_that!(String::from_utf8_lossy(&buf[..size]).into_owned()).starts_with(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("5\r\nhello!\r\n0\r\n\r\n")]
+#[case("zz\r\nhello\r\n0\r\n\r\n")]
+#[case("ffffffffffffffff\r\nhello\r\n")]
+#[tokio::test]
+async fn test_malformed_chunked(#[case] body: &str) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector
+    let mut builder = Connector::builder();
+    builder.expect().returning((202, "OK"))?;
+    let connector = builder.build();
+
+    // WHEN writing a request with invalid chunked framing
+    let mut stream = connector
+        .clone()
+        .call("http://test.example".parse()?)
+        .await?;
+    let res = stream
+        .write_all(
+            format!(
+                "POST / HTTP/1.1\r\nhost: test.example\r\ntransfer-encoding: chunked\r\n\r\n{body}"
+            )
+            .as_bytes(),
+        )
+        .await;
+
+    // THEN the write fails instead of waiting for more data
+    assert_that!(res.map_err(|err| err.to_string()))
+        .is_err()
+        .contains("invalid chunked transfer-encoding");
+
+    Ok(())
+}
+
+#[rstest]
+#[case(1)]
+#[case(7)]
+#[case(4096)]
+#[tokio::test]
+async fn test_split_writes(#[case] size: usize) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that expects a large payload
+    let payload = "0123456789abcdef".repeat(1024);
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_body(&payload)
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    // WHEN writing the request in small pieces
+    let mut stream = connector
+        .clone()
+        .call("http://test.example".parse()?)
+        .await?;
+    let request = format!(
+        "POST / HTTP/1.1\r\nhost: test.example\r\ncontent-length: {}\r\n\r\n{payload}",
+        payload.len()
+    );
+    let (head, last) = request.as_bytes().split_at(request.len() - 1);
+    for chunk in head.chunks(size) {
+        stream.write_all(chunk).await?;
+    }
+
+    // THEN it waits for the full payload before responding
+    let mut buf = vec![0; 1024];
+    let read = tokio::time::timeout(Duration::from_millis(10), stream.read(&mut buf)).await;
+    assert_that!(read).is_err();
+
+    // AND it matches the full payload once it is received
+    stream.write_all(last).await?;
+    let size = stream.read(&mut buf).await?;
+    assert_that!(String::from_utf8_lossy(&buf[..size]).into_owned()).starts_with("HTTP/1.1 202");
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_many_headers() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that expects a header
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_header("x-header-199", "199")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a lot of headers
+    let mut req = Request::builder().uri("http://test.example");
+    for i in 0..200 {
+        req = req.header(format!("x-header-{i}"), i.to_string());
+    }
+    let res = client.request(req.body(Body::empty())?).await;
+
+    // THEN it parses all the headers
+    assert_that!(res).is_ok().matches(|res| res.status() == 202);
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_streamed_body() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that streams request bodies to a matcher counting the chunks, and a case
+    // for another request
+    let chunks = Arc::new(AtomicUsize::new(0));
+    let mut builder = Connector::builder();
+    builder.stream_request_bodies();
+    builder
+        .expect()
+        .times(1)
+        .with_method("PUT")
+        .with_body_stream({
+            let chunks = chunks.clone();
+            move || Length {
+                chunks: chunks.clone(),
+                len: 0,
+                expected: 3 * 4096,
+            }
+        })
+        .returning(|req: Request<Bytes>| {
+            let len = req.body().len();
+            async move { format!("received {len} bytes") }
+        })?;
+    builder
+        .expect()
+        .times(1)
+        .with_method("GET")
+        .returning("second")?;
+    let connector = builder.build();
+
+    // WHEN writing the payload in several pieces, followed by another request
+    let mut stream = connector
+        .clone()
+        .call("http://test.example".parse()?)
+        .await?;
+    stream
+        .write_all(b"PUT / HTTP/1.1\r\nhost: test.example\r\ncontent-length: 12288\r\n\r\n")
+        .await?;
+    for _ in 0..3 {
+        stream.write_all(&[b'a'; 4096]).await?;
+    }
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nhost: test.example\r\nconnection: close\r\n\r\n")
+        .await?;
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).await?;
+
+    // THEN the matcher received the payload incrementally
+    assert_that!(chunks.load(Ordering::SeqCst)).is_equal_to(3);
+
+    // AND the payload was not passed to the handler
+    let data = String::from_utf8(buf)?;
+    assert_that!(data).contains("received 0 bytes");
+
+    // AND the following request was handled separately
+    assert_that!(data).contains("second");
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+/// Body stream checking the length of the payload
+struct Length {
+    chunks: Arc<AtomicUsize>,
+    len: usize,
+    expected: usize,
+}
+
+impl BodyStream for Length {
+    fn update(&mut self, chunk: &[u8]) {
+        self.chunks.fetch_add(1, Ordering::SeqCst);
+        self.len += chunk.len();
+    }
+
+    fn finish(&mut self) -> bool {
+        self.len == self.expected
+    }
+}
+
 struct CustomBody {
     data: Vec<&'static str>,
 }
@@ -118,3 +356,36 @@ impl HttpBody for CustomBody {
         Poll::Ready(Ok(None))
     }
 }
+
+#[rstest]
+#[tokio::test]
+async fn test_standalone_stream() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a case
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_uri("http://test.example/hello")
+        .returning((202, "hello"))?;
+    let connector = builder.build();
+
+    // WHEN sending a request over a stream opened without the client
+    let stream = connector.stream("http://test.example".parse()?)?;
+    let (mut sender, connection) = hyper::client::conn::handshake(stream).await?;
+    tokio::spawn(connection);
+    let res = sender
+        .send_request(
+            Request::builder()
+                .uri("/hello")
+                .header("host", "test.example")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN it receives the response from the case
+    assert_that!(res.status()).is_equal_to(StatusCode::ACCEPTED);
+    assert_that!(hyper::body::to_bytes(res.into_body()).await?).is_equal_to(Bytes::from("hello"));
+    connector.checkpoint()?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use hyper::{Body, Request};
+use mock_http_connector::{Connector, Tls, TlsInfo};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case("https://test.example/", true)]
+#[case("http://test.example/", false)]
+#[tokio::test]
+async fn test_tls_info(
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a catch-all case
+    let mut builder = Connector::builder();
+    builder.expect().returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await?;
+
+    // THEN only https responses contain TLS information
+    let tls = res.extensions().get::<TlsInfo>();
+    assert_that!(tls.is_some()).is_equal_to(expected);
+    if let Some(tls) = tls {
+        assert_that!(tls.server_name()).is_equal_to("test.example");
+        assert_that!(tls.alpn_protocol()).is_equal_to(b"http/1.1".as_ref());
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[case("https://test.example/", false)]
+#[case("http://test.example/", true)]
+#[tokio::test]
+async fn test_tls_fail(
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector rejecting TLS connections
+    let mut builder = Connector::builder();
+    builder.tls(Tls::Fail("UnknownIssuer".to_string()));
+    builder.expect().returning("OK")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await;
+
+    // THEN only https requests fail, with a connection error
+    assert_that!(res.is_ok()).is_equal_to(expected);
+    if let Err(err) = res {
+        assert_that!(err.is_connect()).is_true();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "tracing")]
+
+use hyper::{Body, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::{
+    error::Error as StdError,
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Subscriber that stores the messages of all events
+#[derive(Clone, Default)]
+struct Messages(Arc<Mutex<Vec<String>>>);
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl Subscriber for Messages {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "mock_http_connector"
+    }
+
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        self.0.lock().unwrap().push(message);
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_tracing() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with two cases, and a subscriber collecting events
+    let messages = Messages::default();
+    let _guard = tracing::subscriber::set_default(messages.clone());
+
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri("http://test.example/other")
+        .returning("other")?;
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning((201, "hello"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request matching the second case
+    client
+        .request(
+            Request::builder()
+                .uri("http://test.example/hello")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN events are emitted for the evaluated cases, the chosen case, and the response
+    assert_that!(*messages.0.lock().unwrap()).is_equal_to(vec![
+        "mock case didn't match".to_string(),
+        "matched mock case".to_string(),
+        "returning response".to_string(),
+    ]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_diagnostics_tracing() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector emitting diagnostic reports as events
+    let messages = Messages::default();
+    let _guard = tracing::subscriber::set_default(messages.clone());
+
+    let mut builder = Connector::builder();
+    builder.diagnostics_tracing();
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request that doesn't match any case
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/other")
+                .body(Body::empty())?,
+        )
+        .await;
+
+    // THEN the diagnostic report is emitted as an event
+    assert_that!(res).is_err();
+    assert_that!(messages
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|message| message.contains("no matching case for request")))
+    .is_true();
+
+    Ok(())
+}
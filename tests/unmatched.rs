@@ -0,0 +1,122 @@
+use hyper::{Body, Request, StatusCode};
+use mock_http_connector::{Connector, Level, OnUnmatched};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case(OnUnmatched::Status(StatusCode::NOT_FOUND), Some(404))]
+#[case(OnUnmatched::Status(StatusCode::NOT_IMPLEMENTED), Some(501))]
+#[case(OnUnmatched::Error, None)]
+#[tokio::test]
+async fn test_on_unmatched(
+    #[case] on_unmatched: OnUnmatched,
+    #[case] expected: Option<u16>,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a behavior for unmatched requests
+    let mut builder = Connector::builder();
+    builder.on_unmatched(on_unmatched);
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request that doesn't match any case
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example/other")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it follows the configured behavior
+    assert_that!(res.ok().map(|res| res.status().as_u16())).is_equal_to(expected);
+
+    Ok(())
+}
+
+fn strict_connector(strict: &str) -> Result<Connector, Box<dyn StdError + Send + Sync>> {
+    let mut builder = Connector::builder();
+    match strict {
+        "level" => builder.level(Level::Strict),
+        _ => builder.on_unmatched(OnUnmatched::Panic),
+    };
+    builder
+        .expect()
+        .with_uri("http://test.example/hello")
+        .returning("hello")?;
+    Ok(builder.build())
+}
+
+async fn unmatched_request(connector: &Connector) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+    client
+        .request(
+            Request::builder()
+                .uri("http://test.example/other")
+                .body("".to_string().into())?,
+        )
+        .await?;
+    Ok(())
+}
+
+#[rstest]
+#[case("level")]
+#[case("on_unmatched")]
+#[tokio::test]
+#[should_panic(expected = "no matching case for request")]
+async fn test_strict(#[case] strict: &str) {
+    // GIVEN a strict connector
+    let connector = strict_connector(strict).unwrap();
+
+    // WHEN making a request that doesn't match any case
+    let err = unmatched_request(&connector).await.unwrap_err();
+
+    // THEN the client error contains the diagnostic report
+    let mut source: Option<&(dyn StdError + 'static)> = Some(err.as_ref());
+    let mut messages = Vec::new();
+    while let Some(err) = source {
+        messages.push(err.to_string());
+        source = err.source();
+    }
+    assert_that!(messages.join("\n")).contains("no matching case for request");
+
+    // AND the checkpoint panics with the diagnostic report
+    let _ = connector.checkpoint();
+}
+
+#[rstest]
+#[tokio::test]
+#[should_panic(expected = "no matching case for request")]
+async fn test_strict_drop() {
+    // GIVEN a strict connector
+    let connector = strict_connector("level").unwrap();
+
+    // WHEN making a request that doesn't match any case
+    let _ = unmatched_request(&connector).await;
+
+    // THEN dropping the connector panics with the diagnostic report
+    drop(connector);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_strict_reset() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a strict connector that received an unmatched request
+    let connector = strict_connector("level")?;
+    let _ = unmatched_request(&connector).await;
+
+    // WHEN resetting the connector and adding a new case
+    connector.reset();
+    connector.expect().returning("OK")?;
+
+    // THEN the unmatched request is forgotten
+    assert_that!(connector.last_report()).is_none();
+    assert_that!(connector.checkpoint()).is_ok();
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use hyper::{Body, Method, Request, StatusCode};
+use mock_http_connector::{Connector, Upgrade};
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[rstest]
+#[tokio::test]
+async fn test_upgrade() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that upgrades to an echo protocol
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_header("upgrade", "echo")
+        .returning_upgrade(|mut io| async move {
+            let mut buf = [0; 128];
+            while let Ok(size @ 1..) = io.read(&mut buf).await {
+                if io.write_all(&buf[..size]).await.is_err() {
+                    break;
+                }
+            }
+        })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making an upgrade request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("connection", "upgrade")
+                .header("upgrade", "echo")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the connection is upgraded
+    assert_that!(res.status()).is_equal_to(StatusCode::SWITCHING_PROTOCOLS);
+    assert_that!(res.headers()["upgrade"].to_str()?).is_equal_to("echo");
+
+    // AND the raw bytes are sent to the handler
+    let mut io = hyper::upgrade::on(res).await?;
+    io.write_all(b"ping").await?;
+    let mut buf = [0; 4];
+    io.read_exact(&mut buf).await?;
+    assert_that!(&buf).is_equal_to(b"ping");
+
+    connector.checkpoint()?;
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_upgrade_connect() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector that accepts a CONNECT tunnel
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .times(1)
+        .with_method("CONNECT")
+        .returning(Upgrade::new(|mut io| async move {
+            let _ = io.write_all(b"tunnel open").await;
+        }))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a CONNECT request
+    let res = client
+        .request(
+            Request::builder()
+                .method(Method::CONNECT)
+                .uri("http://proxy.example:443")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    // THEN the tunnel is established
+    assert_that!(res.status()).is_equal_to(StatusCode::OK);
+
+    // AND the handler closes the connection when it returns
+    let mut io = hyper::upgrade::on(res).await?;
+    let mut data = Vec::new();
+    io.read_to_end(&mut data).await?;
+    assert_that!(data).is_equal_to(b"tunnel open".to_vec());
+
+    connector.checkpoint()?;
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "json")]
+
+use hyper::{Body, Method, Request};
+use mock_http_connector::Connector;
+use rstest::*;
+use speculoos::prelude::*;
+use std::error::Error as StdError;
+
+#[rstest]
+#[case(Method::GET, "http://test.example/items/1", None, "", 200)]
+#[case(
+    Method::POST,
+    "http://test.example/items",
+    Some("application/json"),
+    r#"{"name": "second", "tags": []}"#,
+    201
+)]
+#[case(
+    Method::POST,
+    "http://test.example/items",
+    Some("text/plain"),
+    r#"{"name": "second"}"#,
+    404
+)]
+#[case(Method::GET, "http://test.example/items/2", None, "", 404)]
+#[tokio::test]
+async fn test_wiremock_stubs(
+    #[case] method: Method,
+    #[case] uri: &str,
+    #[case] content_type: Option<&str>,
+    #[case] body: &str,
+    #[case] expected: u16,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector loaded from WireMock stubs
+    let mut builder = Connector::builder();
+    builder.load_wiremock_stubs(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/wiremock"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let mut req = Request::builder().method(method).uri(uri);
+    if let Some(content_type) = content_type {
+        req = req.header("content-type", content_type);
+    }
+    let res = client.request(req.body(body.to_string().into())?).await?;
+
+    // THEN the matching stub is used, with the catch-all stub last
+    assert_that!(res.status().as_u16()).is_equal_to(expected);
+
+    Ok(())
+}
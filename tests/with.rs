@@ -1,8 +1,24 @@
-use hyper::{http::HeaderName, Body, Method, Request};
-use mock_http_connector::Connector;
+use hyper::{
+    body::Bytes,
+    http::{Extensions, HeaderName},
+    Body, Method, Request,
+};
+#[cfg(feature = "json")]
+use mock_http_connector::ArrayMatching;
+use mock_http_connector::{
+    any_of, BodyStream, Connector, Multipart, MultipartPart, PathParams, Reason, Report,
+    UriNormalization, UriParts, With, WithFuture, WithHandler, WithPrint,
+};
 use rstest::*;
 use speculoos::prelude::*;
-use std::error::Error as StdError;
+use std::{
+    collections::HashSet,
+    error::Error as StdError,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 #[rstest]
 #[case(Method::POST)] // Remark: hyper defaults to GET
@@ -203,3 +219,1350 @@ async fn test_uri(#[case] uri: &'static str) -> Result<(), Box<dyn StdError + Se
 
     Ok(())
 }
+
+#[rstest]
+#[case("some-signature", true)]
+#[case("invalid-signature", false)]
+#[tokio::test]
+async fn test_and_with(
+    #[case] signature: &'static str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting an URI and validating a signature through a closure
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri("http://test.example")
+        .and_with(|req: &Request<Bytes>| {
+            Ok::<_, std::convert::Infallible>(
+                req.headers()
+                    .get("x-signature")
+                    .map(|v| v == "some-signature")
+                    .unwrap_or(false),
+            )
+        })
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a signature
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("x-signature", signature)
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches if the closure returns true
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[case(r#"{"Item": {"Host": {"S": "some_host"}}}"#, true)]
+#[case(r#"{"Item": {"Host": {"S": "other_host"}}, "Extra": true}"#, false)]
+#[case(r#"{"Item": {}}"#, false)]
+#[case("not json", false)]
+#[tokio::test]
+async fn test_json_path(
+    #[case] body: &'static str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a value at a JSON path
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_json_path("$.Item.Host.S", "some_host")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a JSON body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the value at the path is the expected one
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[case(
+    r#"{"name": "some event", "timestamp": 1700000000, "request_id": "abc"}"#,
+    true
+)]
+#[case(r#"{"name": "some event", "timestamp": "2024-01-01T00:00:00Z"}"#, true)]
+#[case(r#"{"name": "some event"}"#, true)]
+#[case(r#"{"name": "other event", "timestamp": 1700000000}"#, false)]
+#[case(
+    r#"{"name": "some event", "timestamp": 1700000000, "extra": true}"#,
+    false
+)]
+#[tokio::test]
+async fn test_json_ignoring(
+    #[case] body: &'static str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a JSON payload, ignoring generated fields
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_json_ignoring(
+            serde_json::json!({"name": "some event", "timestamp": 0}),
+            ["$.timestamp", "/request_id"],
+        )
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a JSON body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it matches if the payload only differs on the ignored fields
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[case(ArrayMatching::Exact, r#"{"steps": ["build", "test"], "id": 1}"#, true)]
+#[case(ArrayMatching::Exact, r#"{"steps": ["test", "build"]}"#, false)]
+#[case(ArrayMatching::AnyOrder, r#"{"steps": ["test", "build"]}"#, true)]
+#[case(
+    ArrayMatching::AnyOrder,
+    r#"{"steps": ["test", "build", "deploy"]}"#,
+    false
+)]
+#[case(
+    ArrayMatching::Subset,
+    r#"{"steps": ["deploy", "test", "build"]}"#,
+    true
+)]
+#[case(ArrayMatching::Subset, r#"{"steps": ["build", "build"]}"#, false)]
+#[case(
+    ArrayMatching::Prefix,
+    r#"{"steps": ["build", "test", "deploy"]}"#,
+    true
+)]
+#[case(
+    ArrayMatching::Prefix,
+    r#"{"steps": ["lint", "build", "test"]}"#,
+    false
+)]
+#[tokio::test]
+async fn test_json_partial_arrays(
+    #[case] arrays: ArrayMatching,
+    #[case] body: &'static str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a partial JSON payload with an array matching mode
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_json_partial_arrays(serde_json::json!({"steps": ["build", "test"]}), arrays)
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a JSON body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it matches if the arrays match according to the mode
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[derive(serde::Deserialize)]
+struct Payload {
+    id: u32,
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[case(r#"{"id": 42}"#, true)]
+#[case(r#"{"id": 43}"#, false)]
+#[case(r#"{"name": "some name"}"#, false)]
+#[tokio::test]
+async fn test_json_de(
+    #[case] body: &'static str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a deserializable payload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_json_de(|payload: Payload| payload.id == 42)
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a JSON body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the closure returns true
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(vec![0x08, 0x96, 0x01], true)]
+#[case(vec![0x08, 0x96, 0x02], false)]
+#[tokio::test]
+async fn test_body_bytes(
+    #[case] body: Vec<u8>,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a binary payload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_body_bytes([0x08, 0x96, 0x01])
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a binary body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the body is the expected one
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("hello world", true)]
+#[case("goodbye world", false)]
+#[tokio::test]
+async fn test_body_stream(
+    #[case] body: &'static str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a payload with a given prefix
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_body_stream(|| Prefix {
+            prefix: b"hello",
+            seen: Vec::new(),
+        })
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the body starts with the prefix
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("hello", false, true)]
+#[case("hello world", false, false)]
+#[case("hello", true, true)]
+#[case("hello world", true, false)]
+#[tokio::test]
+async fn test_body_len(
+    #[case] body: &'static str,
+    #[case] streamed: bool,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a payload of a given length
+    let mut builder = Connector::builder();
+    if streamed {
+        builder.stream_request_bodies();
+    }
+    builder.expect().with_body_len(5).returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a body
+    let res = client
+        .request(
+            Request::builder()
+                .method("PUT")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the body has the expected length
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("hello world", false, true)]
+#[case("hello world!", false, false)]
+#[case("hello world", true, true)]
+#[case("hello world!", true, false)]
+#[tokio::test]
+async fn test_body_sha256(
+    #[case] body: &'static str,
+    #[case] streamed: bool,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a payload with a given digest
+    let mut builder = Connector::builder();
+    if streamed {
+        builder.stream_request_bodies();
+    }
+    builder
+        .expect()
+        .with_body_sha256("B94D27B9934D3E08A52E52D7DA7DABFAC484EFE37A5380EE9088F7ACE2EFCDE9")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a body
+    let res = client
+        .request(
+            Request::builder()
+                .method("PUT")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the body has the expected digest
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_body_sha256_invalid() {
+    // GIVEN a connector builder
+    let mut builder = Connector::builder();
+
+    // WHEN adding a case with an invalid digest
+    let res = builder
+        .expect()
+        .with_body_sha256("not a digest")
+        .returning("OK");
+
+    // THEN it returns an error
+    assert_that!(res).is_err();
+}
+
+/// Body stream checking that the payload starts with a prefix
+struct Prefix {
+    prefix: &'static [u8],
+    seen: Vec<u8>,
+}
+
+impl BodyStream for Prefix {
+    fn update(&mut self, chunk: &[u8]) {
+        let missing = self.prefix.len().saturating_sub(self.seen.len());
+        self.seen.extend(&chunk[..missing.min(chunk.len())]);
+    }
+
+    fn finish(&mut self) -> bool {
+        self.seen == self.prefix
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[rstest]
+#[case("gzip", vec![
+    0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xab, 0x56, 0xca, 0x4c, 0x51,
+    0xb2, 0x52, 0x30, 0x31, 0xaa, 0x05, 0x00, 0xc2, 0xd0, 0x18, 0x10, 0x0a, 0x00, 0x00, 0x00,
+], true)]
+#[case("deflate", vec![0x78, 0x9c, 0xab, 0x56, 0xca, 0x4c, 0x51, 0xb2, 0x52, 0x30, 0x31, 0xaa, 0x05, 0x00, 0x0f, 0xf9, 0x02, 0xca], true)]
+#[case("identity", br#"{"id": 41}"#.to_vec(), false)]
+#[tokio::test]
+async fn test_compressed_json(
+    #[case] encoding: &str,
+    #[case] body: Vec<u8>,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a JSON payload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_json(serde_json::json!({"id": 42}))
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a compressed body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .header("content-encoding", encoding)
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it matches against the decompressed payload
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("http://test.example/allowed", true)]
+#[case("http://test.example/denied", false)]
+#[tokio::test]
+async fn test_with_async(
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with an async matcher reading shared state
+    let allowed = std::sync::Arc::new(tokio::sync::RwLock::new(vec!["/allowed".to_string()]));
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_async(move |req: Request<Bytes>| {
+            let allowed = allowed.clone();
+            async move {
+                let path = req.uri().path().to_string();
+                Ok::<_, std::convert::Infallible>(allowed.read().await.contains(&path))
+            }
+        })
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await;
+
+    // THEN it only matches if the future resolves to true
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(None, true)]
+#[case(Some("Bearer some-token"), false)]
+#[tokio::test]
+async fn test_header_missing(
+    #[case] authorization: Option<&str>,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a request without an authorization header
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_header_missing("authorization")
+        .returning((401, "UNAUTHORIZED"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let mut req = Request::builder().uri("http://test.example");
+    if let Some(authorization) = authorization {
+        req = req.header("authorization", authorization);
+    }
+    let res = client.request(req.body("".to_string().into())?).await;
+
+    // THEN it only matches if the header is absent
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(vec!["application/json"], true)]
+#[case(vec!["application/json", "application/json"], false)]
+#[case(vec![], false)]
+#[tokio::test]
+async fn test_header_count(
+    #[case] content_types: Vec<&str>,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a single content-type header
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_header_count("content-type", 1)
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with some content-type headers
+    let mut req = Request::builder().uri("http://test.example");
+    for content_type in content_types {
+        req = req.header("content-type", content_type);
+    }
+    let res = client.request(req.body(Body::empty())?).await;
+
+    // THEN it only matches if there is exactly one header
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("Bearer some-token", true)]
+#[case("Basic dXNlcjpwYXNz", false)]
+#[tokio::test]
+async fn test_header_fn(
+    #[case] authorization: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a bearer token
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_header_fn("authorization", |value| value.starts_with("Bearer "))
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("authorization", authorization)
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches if the predicate returns true
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("Basic dXNlcjpwYXNz", true)]
+#[case("Basic dXNlcjpvdGhlcg==", false)]
+#[case("Bearer dXNlcjpwYXNz", false)]
+#[tokio::test]
+async fn test_basic_auth(
+    #[case] authorization: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting basic credentials
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_basic_auth("user", "pass")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("authorization", authorization)
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches with the right credentials
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("Bearer some-token", true)]
+#[case("Bearer other-token", false)]
+#[case("some-token", false)]
+#[tokio::test]
+async fn test_bearer_token(
+    #[case] authorization: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a bearer token
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_bearer_token("some-token")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("authorization", authorization)
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches with the right token
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("Bearer some-token", true)]
+#[case("bearer some-token", true)]
+#[case("Bearer other-token", false)]
+#[case("Basic some-token", false)]
+#[tokio::test]
+async fn test_bearer_token_matching(
+    #[case] authorization: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a bearer token with a prefix
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_bearer_token_matching(|token| token.starts_with("some-"))
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("authorization", authorization)
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches if the predicate returns true for the token
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("application/json;charset=utf-8", true)]
+#[case("Application/JSON; Charset=UTF-8", true)]
+#[case("application/json", false)]
+#[tokio::test]
+async fn test_header_loose(
+    #[case] content_type: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a content type
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_header_loose("content-type", "application/json; charset=UTF-8")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("content-type", content_type)
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it matches regardless of case and whitespace
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Method::PUT, true)]
+#[case(Method::PATCH, true)]
+#[case(Method::DELETE, false)]
+#[tokio::test]
+async fn test_method_in(
+    #[case] method: Method,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting PUT or PATCH requests
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_method_in([Method::PUT, Method::PATCH])
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .method(method)
+                .uri("http://test.example")
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches one of the expected methods
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+fn test_method_in_empty() {
+    // GIVEN a connector builder
+    let mut builder = Connector::builder();
+
+    // WHEN registering a case without any methods to match
+    let res = builder
+        .expect()
+        .with_method_in(Vec::<Method>::new())
+        .returning("OK");
+
+    // THEN it fails instead of matching all requests
+    assert_that!(res.map_err(|err| err.to_string()))
+        .is_err_containing("invalid method matcher: no methods to match".to_string());
+}
+
+#[rstest]
+#[case("http://api.example.com/some/path", true)]
+#[case("https://API.example.com/other", true)]
+#[case("http://auth.example.com/some/path", false)]
+#[tokio::test]
+async fn test_host(
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a host
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_host("api.example.com")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await;
+
+    // THEN it matches any path on that host
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("https://test.example", true)]
+#[case("http://test.example", false)]
+#[tokio::test]
+async fn test_scheme(
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting HTTPS requests
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_scheme("https")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body("".to_string().into())?)
+        .await;
+
+    // THEN it only matches the expected scheme
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+#[case(r#"{"id": 1, "name": "first"}"#, true)]
+#[case(r#"{"id": 1, "name": "first", "tags": []}"#, true)]
+#[case(r#"{"id": "1", "name": "first"}"#, false)]
+#[case(r#"{"name": "first"}"#, false)]
+#[case("not json", false)]
+#[tokio::test]
+async fn test_json_schema(
+    #[case] body: &'static str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a payload matching a JSON schema
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_json_schema(serde_json::json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": {"type": "integer"},
+                "name": {"type": "string"},
+            },
+        }))
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a JSON body
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the payload is valid against the schema
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[rstest]
+fn test_json_schema_unsupported() {
+    // GIVEN a schema with an unsupported keyword
+    let mut builder = Connector::builder();
+
+    // WHEN registering a case with that schema
+    let res = builder
+        .expect()
+        .with_json_schema(serde_json::json!({"type": "string", "pattern": "^a"}))
+        .returning("OK");
+
+    // THEN it fails
+    assert_that!(res).is_err();
+}
+
+#[rstest]
+#[case("report.csv", "text/csv", "a,b\r\n1,2", true)]
+#[case("report.csv", "TEXT/CSV", "a,b\r\n1,2", true)]
+#[case("other.csv", "text/csv", "a,b\r\n1,2", false)]
+#[case("report.csv", "text/plain", "a,b\r\n1,2", false)]
+#[case("report.csv", "text/csv", "a,b", false)]
+#[tokio::test]
+async fn test_multipart(
+    #[case] filename: &str,
+    #[case] content_type: &str,
+    #[case] content: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a file upload
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_multipart(
+            Multipart::new()
+                .part(MultipartPart::new("title").content("Quarterly report"))
+                .part(
+                    MultipartPart::new("file")
+                        .filename("report.csv")
+                        .content_type("text/csv")
+                        .content("a,b\r\n1,2"),
+                ),
+        )
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request with a multipart body
+    let body = format!(
+        "--XyZ\r\n\
+        Content-Disposition: form-data; name=\"title\"\r\n\
+        \r\n\
+        Quarterly report\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+        Content-Type: {content_type}\r\n\
+        \r\n\
+        {content}\r\n\
+        --XyZ--\r\n"
+    );
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .header("content-type", "multipart/form-data; boundary=XyZ")
+                .body(body.into())?,
+        )
+        .await;
+
+    // THEN it only matches if the parts match
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("multipart/form-data; boundary=XyZ", "--XyZ\r\n\r\nvalue", false)]
+#[case("text/plain", "--XyZ--", false)]
+#[case("multipart/form-data; boundary=XyZ", "--XyZ--", true)]
+#[tokio::test]
+async fn test_multipart_invalid(
+    #[case] content_type: &str,
+    #[case] body: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a multipart body
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_multipart(Multipart::new())
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .method("POST")
+                .uri("http://test.example")
+                .header("content-type", content_type)
+                .body(body.to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches valid multipart bodies
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Some("\"http://example.com/GetStockPrice\""), "text/xml", true)]
+#[case(Some("http://example.com/GetStockPrice"), "text/xml", true)]
+#[case(Some("\"http://example.com/Other\""), "text/xml", false)]
+#[case(
+    None,
+    "application/soap+xml; charset=utf-8; action=\"http://example.com/GetStockPrice\"",
+    true
+)]
+#[case(None, "application/soap+xml; charset=utf-8", false)]
+#[tokio::test]
+async fn test_soap_action(
+    #[case] soap_action: Option<&str>,
+    #[case] content_type: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a SOAP action
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_soap_action("http://example.com/GetStockPrice")
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("http://test.example")
+        .header("content-type", content_type);
+    if let Some(soap_action) = soap_action {
+        req = req.header("soapaction", soap_action);
+    }
+    let res = client.request(req.body("<soap:Envelope/>".into())?).await;
+
+    // THEN it only matches the expected action
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("https://api.example/v1/items?page=2&q=hello+world", true)]
+#[case(
+    "https://API.example/v1/items/42?q=hello%20world&page=2&sort=asc",
+    true
+)]
+#[case("http://api.example/v1/items?page=2&q=hello+world", false)]
+#[case("https://other.example/v1/items?page=2&q=hello+world", false)]
+#[case("https://api.example/v2/items?page=2&q=hello+world", false)]
+#[case("https://api.example/v1/items?page=3&q=hello+world", false)]
+#[case("https://api.example/v1/items?page=2&q=hello+world&debug", false)]
+#[tokio::test]
+async fn test_uri_parts(
+    #[case] uri: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting URI components
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri_parts(
+            UriParts::new()
+                .scheme("HTTPS")
+                .authority("api.example")
+                .path_prefix("/v1/")
+                .query_param("page", "2")
+                .query_param("q", "hello world")
+                .query_param_missing("debug"),
+        )
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body(Body::empty())?)
+        .await;
+
+    // THEN it only matches if all the components match
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    "https://api.example/v1/items/?b=2&a=hello%20world",
+    UriNormalization::all(),
+    true
+)]
+#[case(
+    "https://api.example:443/v1/items?a=hello+world&b=2",
+    UriNormalization::all(),
+    true
+)]
+#[case("https://api.example/v1/items/?a=hello+world&b=2", UriNormalization::new().ignore_trailing_slash(), true)]
+#[case(
+    "https://api.example/v1/items/?a=hello+world&b=2",
+    UriNormalization::new(),
+    false
+)]
+#[case("https://api.example/v1/items?b=2&a=hello+world", UriNormalization::new().ignore_trailing_slash(), false)]
+#[case(
+    "https://api.example:8443/v1/items?a=hello+world&b=2",
+    UriNormalization::all(),
+    false
+)]
+#[tokio::test]
+async fn test_uri_normalized(
+    #[case] uri: &str,
+    #[case] normalization: UriNormalization,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a URI, ignoring some differences
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_uri_normalized(
+            "https://api.example/v1/items?a=hello+world&b=2",
+            normalization,
+        )
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(Request::builder().uri(uri).body(Body::empty())?)
+        .await;
+
+    // THEN it only matches if the URIs are equal once normalized
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/users/42/orders/7", Some("user 42, order 7"))]
+#[case("/users/42/orders", None)]
+#[case("/users//orders/7", None)]
+#[tokio::test]
+async fn test_path_template(
+    #[case] path: &str,
+    #[case] expected: Option<&str>,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a path template that echoes the captured parameters
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_path_template("/users/{id}/orders/{order_id}")
+        .returning(|req: Request<Bytes>| async move {
+            let params = req
+                .extensions()
+                .get::<PathParams>()
+                .cloned()
+                .unwrap_or_default();
+            format!(
+                "user {}, order {}",
+                params.get("id").unwrap_or_default(),
+                params.get("order_id").unwrap_or_default(),
+            )
+        })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri(format!("http://test.example{path}"))
+                .body(Body::empty())?,
+        )
+        .await;
+
+    // THEN the response contains the captured parameters if the path matches
+    match expected {
+        Some(expected) => {
+            let body = hyper::body::to_bytes(res?.into_body()).await?;
+            assert_that!(body.as_ref()).is_equal_to(expected.as_bytes());
+        }
+        None => {
+            assert_that!(res).is_err();
+        }
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_path_template_invalid() -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector builder
+    let mut builder = Connector::builder();
+
+    // WHEN adding a case with an invalid template
+    let res = builder
+        .expect()
+        .with_path_template("/users/{id")
+        .returning("OK");
+
+    // THEN it returns an error
+    assert_that!(res).is_err();
+
+    Ok(())
+}
+
+/// Body of the requests for [`test_with_extensions`]
+#[derive(Clone)]
+struct Greeting(String);
+
+/// Matcher that parses the body into a [`Greeting`], and counts how many times it did
+struct ParseGreeting(Arc<AtomicUsize>);
+
+impl ParseGreeting {
+    fn parse(&self, req: &Request<Bytes>) -> Option<Greeting> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let body = std::str::from_utf8(req.body()).ok()?;
+        body.strip_prefix("hello ")
+            .map(|name| Greeting(name.to_string()))
+    }
+}
+
+impl With for ParseGreeting {
+    fn with(&self, req: &Request<Bytes>) -> Result<Report, Box<dyn StdError + Send + Sync>> {
+        Ok(self.parse(req).is_some().into())
+    }
+
+    fn with_extensions<'w>(
+        &'w self,
+        req: &'w Request<Bytes>,
+        extensions: &'w mut Extensions,
+    ) -> WithFuture<'w> {
+        let greeting = self.parse(req);
+        let matched = greeting.is_some();
+        if let Some(greeting) = greeting {
+            extensions.insert(greeting);
+        }
+        Box::pin(async move { Ok(matched.into()) })
+    }
+
+    fn print_pretty(&self, _report: &HashSet<Reason>) -> WithPrint<'_> {
+        WithPrint {
+            name: "greeting".into(),
+            body: None,
+        }
+    }
+}
+
+#[rstest]
+#[case(false)]
+#[case(true)]
+#[tokio::test]
+async fn test_with_extensions(
+    #[case] combined: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector with a matcher that stores the parsed body in the request extensions
+    let parses = Arc::new(AtomicUsize::new(0));
+    let matcher = if combined {
+        any_of([
+            WithHandler::default()
+                .with_uri("http://other.example")?
+                .boxed(),
+            ParseGreeting(parses.clone()).boxed(),
+        ])
+        .boxed()
+    } else {
+        ParseGreeting(parses.clone()).boxed()
+    };
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_matcher(matcher)
+        .returning(|req: Request<Bytes>| async move {
+            let greeting = req.extensions().get::<Greeting>().cloned();
+            format!("hi {}", greeting.map(|g| g.0).unwrap_or_default())
+        })?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .method(Method::POST)
+                .uri("http://test.example")
+                .body("hello world".into())?,
+        )
+        .await?;
+
+    // THEN the response uses the parsed body
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    assert_that!(body.as_ref()).is_equal_to(b"hi world".as_ref());
+
+    // AND the body was only parsed once
+    assert_that!(parses.load(Ordering::SeqCst)).is_equal_to(1);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("0b9f1c2e-5d3a-4f6b-8c7d-9e0f1a2b3c4d", true)]
+#[case("0b9f1c2e", false)]
+#[tokio::test]
+async fn test_header_matches(
+    #[case] request_id: &str,
+    #[case] expected: bool,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    // GIVEN a connector expecting a UUID request ID
+    let mut builder = Connector::builder();
+    builder
+        .expect()
+        .with_header_matches(
+            "x-request-id",
+            "^[0-9a-f]{8}(-[0-9a-f]{4}){3}-[0-9a-f]{12}$",
+        )
+        .returning((202, "OK"))?;
+    let connector = builder.build();
+
+    let client = hyper::Client::builder().build::<_, Body>(connector.clone());
+
+    // WHEN making a request
+    let res = client
+        .request(
+            Request::builder()
+                .uri("http://test.example")
+                .header("x-request-id", request_id)
+                .body("".to_string().into())?,
+        )
+        .await;
+
+    // THEN it only matches if the header value matches the pattern
+    assert_that!(res.is_ok()).is_equal_to(expected);
+
+    Ok(())
+}
+
+#[rstest]
+fn test_header_matches_invalid() {
+    // GIVEN a connector builder
+    let mut builder = Connector::builder();
+
+    // WHEN adding a case with an invalid pattern
+    let res = builder
+        .expect()
+        .with_header_matches("x-request-id", "[0-9a-f")
+        .returning("OK");
+
+    // THEN it returns an error
+    assert_that!(res).is_err();
+}